@@ -0,0 +1,226 @@
+use crate::metadata::load_workspace_root;
+use seeker_editor::buffer::Position;
+use seeker_editor::diagnostic::{Diagnostic, Severity};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// `cargo --message-format=json` 输出的一行
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<CompilerMessage>,
+}
+
+#[derive(Deserialize)]
+struct CompilerMessage {
+    message: String,
+    level: String,
+    code: Option<DiagnosticCode>,
+    spans: Vec<DiagnosticSpan>,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+/// 行列都从 1 开始，列以字符为单位
+#[derive(Deserialize)]
+struct DiagnosticSpan {
+    file_name: String,
+    line_start: usize,
+    line_end: usize,
+    column_start: usize,
+    column_end: usize,
+    is_primary: bool,
+}
+
+/// 编译器报告的一个问题
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub path: PathBuf,
+    pub diagnostic: Diagnostic,
+}
+
+/// cargo check 过程中的事件
+#[derive(Clone, Debug)]
+pub enum CheckEvent {
+    Problem(Problem),
+    /// error 为 cargo 本身的错误（例如 Cargo.toml 有误、没有工具链），编译错误不在其中
+    Finished {
+        success: bool,
+        error: Option<String>,
+    },
+}
+
+/// 解析一行 JSON 输出，不是编译器信息或者没有主位置时返回 None，
+/// root 为工作区根目录
+pub fn parse_line(line: &str, root: &Path) -> Option<Problem> {
+    let message = serde_json::from_str::<CargoMessage>(line).ok()?;
+    if message.reason != "compiler-message" {
+        return None;
+    }
+    let message = message.message?;
+    let severity = match message.level.as_str() {
+        "error" | "error: internal compiler error" => Severity::Error,
+        "warning" => Severity::Warning,
+        "note" => Severity::Information,
+        "help" => Severity::Hint,
+        _ => return None,
+    };
+    let span = message.spans.iter().find(|span| span.is_primary)?;
+    let text = match &message.code {
+        Some(code) => format!("{} [{}]", message.message, code.code),
+        None => message.message,
+    };
+    Some(Problem {
+        path: root.join(&span.file_name),
+        diagnostic: Diagnostic {
+            start: Position::new(span.line_start - 1, span.column_start - 1),
+            end: Position::new(span.line_end - 1, span.column_end - 1),
+            severity,
+            message: text,
+        },
+    })
+}
+
+/// 读取整个输出流，同一个问题只保留一次（例如 lib 和 test 目标重复报告）
+pub fn read_problems(reader: impl BufRead, root: &Path) -> Vec<Problem> {
+    let mut problems: Vec<Problem> = vec![];
+    for line in reader.lines().map_while(Result::ok) {
+        if let Some(problem) = parse_line(&line, root)
+            && !problems.contains(&problem)
+        {
+            problems.push(problem);
+        }
+    }
+    problems
+}
+
+/// 按文件分组
+pub fn group_by_path(problems: &[Problem]) -> HashMap<PathBuf, Vec<Diagnostic>> {
+    let mut files: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+    for problem in problems {
+        let diagnostics = files.entry(problem.path.clone()).or_default();
+        if !diagnostics.contains(&problem.diagnostic) {
+            diagnostics.push(problem.diagnostic.clone());
+        }
+    }
+    files
+}
+
+/// cargo 错误输出中从第一个 `error` 开始的部分，编译进度等其他输出忽略
+pub fn stderr_error(stderr: &str) -> Option<String> {
+    let lines: Vec<&str> = stderr.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("error"))
+        .or_else(|| lines.iter().rposition(|line| !line.trim().is_empty()))?;
+    Some(lines[start..].join("\n").trim_end().to_string())
+}
+
+/// 在 root 下运行 `cargo <command> --message-format=json`，逐个发送问题，阻塞直到结束
+pub fn run_check(
+    root: &Path,
+    command: &str,
+    args: &[String],
+    mut send: impl FnMut(CheckEvent),
+) -> std::io::Result<()> {
+    // 打开的项目可能是工作区的成员，cargo 报告的路径相对于工作区根目录
+    let workspace = load_workspace_root(root).unwrap_or_else(|_| root.to_path_buf());
+    let mut child = Command::new("cargo")
+        .arg(command)
+        .arg("--message-format=json")
+        .args(args)
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // 同时读取两个管道，避免 cargo 写满其中一个时阻塞
+    let stderr = child.stderr.take().map(|mut stderr| {
+        std::thread::spawn(move || {
+            let mut text = String::new();
+            let _ = stderr.read_to_string(&mut text);
+            text
+        })
+    });
+    let mut errors = 0;
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if let Some(problem) = parse_line(&line, &workspace) {
+                errors += usize::from(problem.diagnostic.severity == Severity::Error);
+                send(CheckEvent::Problem(problem));
+            }
+        }
+    }
+    let status = child.wait()?;
+    let stderr = stderr
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+    let error = (!status.success() && errors == 0)
+        .then(|| stderr_error(&stderr))
+        .flatten();
+    send(CheckEvent::Finished {
+        success: status.success(),
+        error,
+    });
+    Ok(())
+}
+
+#[test]
+fn test_read_problems() {
+    let fixture = include_str!("../tests/fixtures/check.json");
+    let root = Path::new("/home/seeker/demo");
+    let problems = read_problems(fixture.as_bytes(), root);
+    let summary = problems
+        .iter()
+        .map(|problem| {
+            (
+                problem.path.strip_prefix(root).unwrap().to_str().unwrap(),
+                problem.diagnostic.severity,
+                problem.diagnostic.start,
+                problem.diagnostic.end,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "src/lib.rs",
+                Severity::Warning,
+                Position::new(1, 8),
+                Position::new(1, 14)
+            ),
+            (
+                "src/main.rs",
+                Severity::Error,
+                Position::new(1, 18),
+                Position::new(1, 24)
+            ),
+            (
+                "src/main.rs",
+                Severity::Error,
+                Position::new(2, 4),
+                Position::new(2, 11)
+            ),
+        ]
+    );
+    assert_eq!(
+        problems[0].diagnostic.message,
+        "unused variable: `unused` [unused_variables]"
+    );
+    let files = group_by_path(&problems);
+    assert_eq!(files[&root.join("src/main.rs")].len(), 2);
+
+    let stderr = "    Checking demo v0.1.0\nerror: failed to parse manifest at `/demo/Cargo.toml`\n\nCaused by:\n  missing field `name`\n";
+    assert_eq!(
+        stderr_error(stderr).unwrap(),
+        "error: failed to parse manifest at `/demo/Cargo.toml`\n\nCaused by:\n  missing field `name`"
+    );
+    assert_eq!(stderr_error("\n"), None);
+}
@@ -0,0 +1,7 @@
+pub mod check;
+pub mod metadata;
+pub mod output;
+pub mod plugin;
+pub mod task;
+
+pub use plugin::SeekerCargoPlugin;
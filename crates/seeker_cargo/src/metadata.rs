@@ -0,0 +1,231 @@
+use serde::Deserialize;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// `cargo metadata --format-version 1` 的输出，只保留需要的字段
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+    workspace_members: Vec<String>,
+    workspace_root: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    id: String,
+    name: String,
+    manifest_path: PathBuf,
+    targets: Vec<Target>,
+}
+
+#[derive(Deserialize)]
+struct Target {
+    name: String,
+    kind: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TargetKind {
+    Lib,
+    Bin,
+    Example,
+    Test,
+    Bench,
+    Other,
+}
+
+impl TargetKind {
+    fn from_kind(kind: &str) -> Self {
+        match kind {
+            "lib" | "rlib" | "dylib" | "cdylib" | "staticlib" | "proc-macro" => Self::Lib,
+            "bin" => Self::Bin,
+            "example" => Self::Example,
+            "test" => Self::Test,
+            "bench" => Self::Bench,
+            _ => Self::Other,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Lib => "lib",
+            Self::Bin => "bin",
+            Self::Example => "example",
+            Self::Test => "test",
+            Self::Bench => "bench",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CargoTarget {
+    pub name: String,
+    pub kind: TargetKind,
+}
+
+/// 工作区中的一个成员
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CargoPackage {
+    pub name: String,
+    pub manifest_path: PathBuf,
+    pub targets: Vec<CargoTarget>,
+}
+
+/// 可以运行的 cargo 命令，label 显示在按钮上
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CargoTask {
+    pub label: &'static str,
+    pub args: Vec<String>,
+}
+
+impl CargoTask {
+    fn new(label: &'static str, args: &[&str]) -> Self {
+        Self {
+            label,
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    /// 完整的命令，例如 `cargo run -p demo --bin demo`
+    pub fn command(&self) -> String {
+        format!("cargo {}", self.args.join(" "))
+    }
+}
+
+impl CargoPackage {
+    /// 整个包的 Build/Test/Bench
+    pub fn tasks(&self) -> Vec<CargoTask> {
+        let name = self.name.as_str();
+        vec![
+            CargoTask::new("Build", &["build", "-p", name]),
+            CargoTask::new("Test", &["test", "-p", name]),
+            CargoTask::new("Bench", &["bench", "-p", name]),
+        ]
+    }
+
+    /// 单个目标可以运行的命令，lib 和构建脚本没有
+    pub fn target_tasks(&self, target: &CargoTarget) -> Vec<CargoTask> {
+        let package = self.name.as_str();
+        let name = target.name.as_str();
+        match target.kind {
+            TargetKind::Bin => vec![
+                CargoTask::new("Run", &["run", "-p", package, "--bin", name]),
+                CargoTask::new("Build", &["build", "-p", package, "--bin", name]),
+            ],
+            TargetKind::Example => vec![
+                CargoTask::new("Run", &["run", "-p", package, "--example", name]),
+                CargoTask::new("Build", &["build", "-p", package, "--example", name]),
+            ],
+            TargetKind::Test => vec![CargoTask::new(
+                "Test",
+                &["test", "-p", package, "--test", name],
+            )],
+            TargetKind::Bench => vec![CargoTask::new(
+                "Bench",
+                &["bench", "-p", package, "--bench", name],
+            )],
+            TargetKind::Lib | TargetKind::Other => vec![],
+        }
+    }
+}
+
+/// 解析 cargo metadata 的输出，只保留工作区成员，按名称排序
+pub fn parse_metadata(json: &str) -> serde_json::Result<Vec<CargoPackage>> {
+    let metadata = serde_json::from_str::<Metadata>(json)?;
+    let mut packages = metadata
+        .packages
+        .into_iter()
+        .filter(|package| metadata.workspace_members.contains(&package.id))
+        .map(|package| CargoPackage {
+            name: package.name,
+            manifest_path: package.manifest_path,
+            targets: package
+                .targets
+                .into_iter()
+                .map(|target| CargoTarget {
+                    kind: target
+                        .kind
+                        .first()
+                        .map(|kind| TargetKind::from_kind(kind))
+                        .unwrap_or(TargetKind::Other),
+                    name: target.name,
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(packages)
+}
+
+/// 工作区根目录，cargo 输出中的相对路径都相对于它
+pub fn parse_workspace_root(json: &str) -> serde_json::Result<PathBuf> {
+    serde_json::from_str::<Metadata>(json).map(|metadata| metadata.workspace_root)
+}
+
+/// 在 root 下运行 `cargo metadata`，阻塞直到结束，失败时返回 cargo 的错误输出
+fn run_metadata(root: &Path) -> io::Result<String> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "cargo metadata exited with {}: {}",
+            output.status,
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn load_metadata(root: &Path) -> io::Result<Vec<CargoPackage>> {
+    parse_metadata(&run_metadata(root)?).map_err(io::Error::other)
+}
+
+pub fn load_workspace_root(root: &Path) -> io::Result<PathBuf> {
+    parse_workspace_root(&run_metadata(root)?).map_err(io::Error::other)
+}
+
+#[test]
+fn test_parse_metadata() {
+    let fixture = include_str!("../tests/fixtures/metadata.json");
+    let packages = parse_metadata(fixture).unwrap();
+    assert_eq!(packages.len(), 1);
+    let package = &packages[0];
+    assert_eq!(package.name, "demo");
+    assert_eq!(
+        package.manifest_path,
+        Path::new("/home/seeker/demo/Cargo.toml")
+    );
+    let targets = package
+        .targets
+        .iter()
+        .map(|target| (target.name.as_str(), target.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        targets,
+        vec![
+            ("demo", TargetKind::Lib),
+            ("demo", TargetKind::Bin),
+            ("hello", TargetKind::Example),
+            ("smoke", TargetKind::Test),
+            ("speed", TargetKind::Bench),
+        ]
+    );
+    assert!(package.target_tasks(&package.targets[0]).is_empty());
+    let run = &package.target_tasks(&package.targets[2])[0];
+    assert_eq!(run.label, "Run");
+    assert_eq!(run.command(), "cargo run -p demo --example hello");
+    assert_eq!(
+        package.target_tasks(&package.targets[3])[0].command(),
+        "cargo test -p demo --test smoke"
+    );
+    assert_eq!(
+        parse_workspace_root(fixture).unwrap(),
+        Path::new("/home/seeker/demo")
+    );
+}
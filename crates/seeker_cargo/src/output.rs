@@ -0,0 +1,224 @@
+use seeker_editor::buffer::Position;
+use std::path::{Path, PathBuf};
+
+/// SGR 设置的前景色
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnsiColor {
+    #[default]
+    Default,
+    /// 256 色表中的序号
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// 样式相同的一段文本
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputSpan {
+    pub text: String,
+    pub color: AnsiColor,
+    pub bold: bool,
+}
+
+/// 输出中的 `path:line:col`，位置从 0 开始
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputLocation {
+    pub path: PathBuf,
+    pub position: Position,
+}
+
+/// 输出的一行
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputLine {
+    pub spans: Vec<OutputSpan>,
+    pub location: Option<OutputLocation>,
+}
+
+impl OutputLine {
+    /// 解析带颜色的一行，相对路径基于 root
+    pub fn parse(line: &str, root: &Path) -> Self {
+        let spans = parse_ansi(line);
+        let text = spans
+            .iter()
+            .map(|span| span.text.as_str())
+            .collect::<String>();
+        Self {
+            location: find_location(&text, root),
+            spans,
+        }
+    }
+}
+
+/// 按 SGR 颜色拆分，其他控制序列直接丢弃。`\r` 之前的内容会被覆盖，只保留最后一段
+pub fn parse_ansi(line: &str) -> Vec<OutputSpan> {
+    let line = line.rsplit('\r').next().unwrap_or_default();
+    let mut spans: Vec<OutputSpan> = vec![];
+    let mut color = AnsiColor::Default;
+    let mut bold = false;
+    let mut chars = line.chars().peekable();
+    let mut text = String::new();
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            if !ch.is_control() || ch == '\t' {
+                text.push(ch);
+            }
+            continue;
+        }
+        if chars.next_if_eq(&'[').is_none() {
+            continue;
+        }
+        let mut params = String::new();
+        let mut last = None;
+        for ch in chars.by_ref() {
+            if ('\x40'..='\x7e').contains(&ch) {
+                last = Some(ch);
+                break;
+            }
+            params.push(ch);
+        }
+        if last != Some('m') {
+            continue;
+        }
+        if !text.is_empty() {
+            push_span(&mut spans, std::mem::take(&mut text), color, bold);
+        }
+        apply_sgr(&params, &mut color, &mut bold);
+    }
+    if !text.is_empty() {
+        push_span(&mut spans, text, color, bold);
+    }
+    spans
+}
+
+fn push_span(spans: &mut Vec<OutputSpan>, text: String, color: AnsiColor, bold: bool) {
+    match spans.last_mut() {
+        Some(last) if last.color == color && last.bold == bold => last.text.push_str(&text),
+        _ => spans.push(OutputSpan { text, color, bold }),
+    }
+}
+
+fn apply_sgr(params: &str, color: &mut AnsiColor, bold: &mut bool) {
+    let mut codes = params
+        .split(';')
+        .map(|code| code.parse::<u8>().unwrap_or(0));
+    while let Some(code) = codes.next() {
+        match code {
+            0 => {
+                *color = AnsiColor::Default;
+                *bold = false;
+            }
+            1 => *bold = true,
+            22 => *bold = false,
+            30..=37 => *color = AnsiColor::Indexed(code - 30),
+            90..=97 => *color = AnsiColor::Indexed(code - 90 + 8),
+            39 => *color = AnsiColor::Default,
+            38 => match codes.next() {
+                Some(5) => *color = AnsiColor::Indexed(codes.next().unwrap_or(0)),
+                Some(2) => {
+                    let mut next = || codes.next().unwrap_or(0);
+                    *color = AnsiColor::Rgb(next(), next(), next());
+                }
+                _ => {}
+            },
+            // 背景色中 48 的参数也要跳过
+            48 => match codes.next() {
+                Some(5) => {
+                    codes.next();
+                }
+                Some(2) => {
+                    codes.nth(2);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// 找到第一个 `path:line:col` 或者 `path:line`，例如 `--> src/main.rs:2:5`
+pub fn find_location(text: &str, root: &Path) -> Option<OutputLocation> {
+    text.split(|ch: char| ch.is_whitespace() || ch == '(' || ch == ')' || ch == '\'')
+        .find_map(|word| parse_location(word.trim_end_matches([':', ',', '.']), root))
+}
+
+fn parse_location(word: &str, root: &Path) -> Option<OutputLocation> {
+    if word.contains("://") {
+        return None;
+    }
+    let mut parts = word.rsplitn(3, ':');
+    let last = parts.next()?.parse::<usize>().ok()?;
+    let second = parts.next()?;
+    let (path, line, column) = match second.parse::<usize>() {
+        Ok(line) => (parts.next()?, line, last),
+        // 只有行号时 second 就是路径
+        Err(_) => (second, last, 1),
+    };
+    let name = Path::new(path).file_name()?.to_str()?;
+    if line == 0 || !name.contains('.') {
+        return None;
+    }
+    Some(OutputLocation {
+        path: root.join(path),
+        position: Position::new(line - 1, column.saturating_sub(1)),
+    })
+}
+
+#[test]
+fn test_parse_ansi() {
+    let spans = parse_ansi("\x1b[0m\x1b[1m\x1b[38;5;9merror\x1b[0m\x1b[1m: oops\x1b[0m done\x1b[K");
+    assert_eq!(
+        spans,
+        vec![
+            OutputSpan {
+                text: "error".to_string(),
+                color: AnsiColor::Indexed(9),
+                bold: true,
+            },
+            OutputSpan {
+                text: ": oops".to_string(),
+                color: AnsiColor::Default,
+                bold: true,
+            },
+            OutputSpan {
+                text: " done".to_string(),
+                color: AnsiColor::Default,
+                bold: false,
+            },
+        ]
+    );
+    assert_eq!(
+        parse_ansi("\x1b[32mok\x1b[39m")[0].color,
+        AnsiColor::Indexed(2)
+    );
+    assert_eq!(parse_ansi("progress\rdone")[0].text, "done");
+}
+
+#[test]
+fn test_find_location() {
+    let root = Path::new("/home/seeker/demo");
+    let location = |text: &str| {
+        find_location(text, root).map(|location| {
+            (
+                location.path.strip_prefix(root).unwrap().to_path_buf(),
+                location.position,
+            )
+        })
+    };
+    assert_eq!(
+        location("  --> src/main.rs:2:5"),
+        Some((PathBuf::from("src/main.rs"), Position::new(1, 4)))
+    );
+    assert_eq!(
+        location("thread 'main' panicked at src/lib.rs:10:3:"),
+        Some((PathBuf::from("src/lib.rs"), Position::new(9, 2)))
+    );
+    assert_eq!(
+        location("error: see tests/smoke.rs:7"),
+        Some((PathBuf::from("tests/smoke.rs"), Position::new(6, 0)))
+    );
+    assert_eq!(
+        find_location("/tmp/other.rs:1:1", root).unwrap().path,
+        Path::new("/tmp/other.rs")
+    );
+    assert_eq!(location("Finished `dev` profile in 0.50s"), None);
+    assert_eq!(location("Listening on http://127.0.0.1:8080"), None);
+}
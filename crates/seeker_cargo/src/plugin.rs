@@ -0,0 +1,339 @@
+use crate::check::{group_by_path, run_check, CheckEvent, Problem};
+use crate::metadata::{load_metadata, CargoPackage, CargoTask};
+use crate::output::OutputLine;
+use crate::task::TaskProcess;
+use bevy::prelude::*;
+use seeker_config::SEEKER_CONFIG;
+use seeker_editor::diagnostic::Diagnostics;
+use seeker_editor::document::DocumentSaved;
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake};
+use seeker_resource::project_list::CurrentProject;
+use seeker_state::SeekerState;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::mpsc::TryRecvError;
+
+pub const CARGO_DIAGNOSTIC_SOURCE: &str = "cargo";
+
+/// 任务面板保留的输出行数
+pub const MAX_OUTPUT_LINES: usize = 5000;
+
+#[derive(Component)]
+pub struct SeekerCargoPlugin;
+
+/// 手动运行一次 cargo 检查
+#[derive(Message, Clone, Debug, Default)]
+pub struct RunCargoCheck;
+
+/// 后台运行的 cargo check
+#[derive(Resource, Default)]
+pub struct CargoCheck {
+    pub running: bool,
+    /// 上一次检查是否通过
+    pub success: Option<bool>,
+    /// 上一次检查失败时 cargo 本身的错误
+    pub error: Option<String>,
+    /// 检查过程中又保存了文件，结束后重新检查
+    pending: bool,
+    root: Option<PathBuf>,
+    problems: Vec<Problem>,
+    receiver: Option<BackgroundReceiver<CheckEvent>>,
+}
+
+impl CargoCheck {
+    fn start(&mut self, root: PathBuf, wake: &BackgroundWake) {
+        if self.running {
+            self.pending = true;
+            return;
+        }
+        self.root = Some(root.clone());
+        let (sender, receiver) = wake.channel();
+        let config = SEEKER_CONFIG.cargo_check.clone();
+        std::thread::spawn(move || {
+            let send = |event: CheckEvent| {
+                sender.send(event);
+            };
+            if let Err(err) = run_check(&root, &config.command, &config.args, &send) {
+                error!("cargo {}: {err}", config.command);
+                send(CheckEvent::Finished {
+                    success: false,
+                    error: Some(err.to_string()),
+                });
+            }
+        });
+        self.running = true;
+        self.pending = false;
+        self.error = None;
+        self.problems.clear();
+        self.receiver = Some(receiver);
+    }
+}
+
+/// 运行一个 cargo 任务，已有任务在运行时先停止它
+#[derive(Message, Clone, Debug)]
+pub struct RunCargoTask(pub CargoTask);
+
+/// 停止正在运行的任务
+#[derive(Message, Clone, Debug, Default)]
+pub struct StopCargoTask;
+
+/// 项目中的 cargo 包和正在运行的任务
+#[derive(Resource, Default)]
+pub struct CargoTasks {
+    pub packages: Vec<CargoPackage>,
+    /// 正在运行或者上一次运行的任务
+    pub task: Option<CargoTask>,
+    pub running: bool,
+    pub success: Option<bool>,
+    pub output: VecDeque<OutputLine>,
+    /// 超过 MAX_OUTPUT_LINES 后从前面丢弃的行数，output[0] 是第 dropped 行
+    pub dropped: usize,
+    /// 每次运行加一，界面据此清空输出
+    pub runs: usize,
+    root: Option<PathBuf>,
+    process: Option<TaskProcess>,
+    metadata: Option<BackgroundReceiver<Option<Vec<CargoPackage>>>>,
+}
+
+impl CargoTasks {
+    /// 在后台读取工作区的包
+    fn load(&mut self, root: PathBuf, wake: &BackgroundWake) {
+        self.metadata = Some(wake.spawn(move || {
+            load_metadata(&root)
+                .inspect_err(|err| error!("cargo metadata {}: {err}", root.display()))
+                .ok()
+        }));
+    }
+
+    fn run(&mut self, task: CargoTask, wake: &BackgroundWake) {
+        let Some(root) = self.root.clone() else {
+            return;
+        };
+        self.stop();
+        self.output.clear();
+        self.dropped = 0;
+        self.runs += 1;
+        self.success = None;
+        match TaskProcess::spawn(&root, &task.args, wake) {
+            Ok(process) => {
+                self.process = Some(process);
+                self.running = true;
+            }
+            Err(err) => {
+                error!("{}: {err}", task.command());
+                self.push_line(&format!("{}: {err}", task.command()));
+                self.success = Some(false);
+            }
+        }
+        self.task = Some(task);
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            process.kill();
+            self.running = false;
+            self.success = Some(false);
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        let root = self.root.clone().unwrap_or_default();
+        self.output.push_back(OutputLine::parse(line, &root));
+        if self.output.len() > MAX_OUTPUT_LINES {
+            self.output.pop_front();
+            self.dropped += 1;
+        }
+    }
+}
+
+impl Plugin for SeekerCargoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CargoCheck>()
+            .init_resource::<CargoTasks>()
+            .init_resource::<Diagnostics>()
+            .init_resource::<BackgroundWake>()
+            .add_message::<RunCargoCheck>()
+            .add_message::<RunCargoTask>()
+            .add_message::<StopCargoTask>()
+            .add_message::<DocumentSaved>()
+            .add_systems(OnEnter(SeekerState::Edit), Self::load_packages)
+            .add_systems(
+                Update,
+                (
+                    (Self::request_check, Self::receive_events).chain(),
+                    Self::reload_packages,
+                    Self::receive_packages,
+                    (Self::run_task, Self::receive_output).chain(),
+                )
+                    .run_if(in_state(SeekerState::Edit)),
+            )
+            .add_systems(OnExit(SeekerState::Edit), (Self::exit, Self::exit_tasks));
+    }
+}
+
+impl SeekerCargoPlugin {
+    /// 保存文件或者手动触发时检查，只在 cargo 项目中运行
+    fn request_check(
+        mut saved: MessageReader<DocumentSaved>,
+        mut run: MessageReader<RunCargoCheck>,
+        current_project: Res<CurrentProject>,
+        mut check: ResMut<CargoCheck>,
+        wake: Res<BackgroundWake>,
+    ) {
+        let on_save = saved.read().count() > 0 && SEEKER_CONFIG.cargo_check.on_save;
+        let manual = run.read().count() > 0;
+        if !on_save && !manual {
+            return;
+        }
+        let Some(project) = current_project.project.as_ref() else {
+            return;
+        };
+        let root = PathBuf::from(&project.path);
+        if root.join("Cargo.toml").exists() {
+            check.start(root, &wake);
+        }
+    }
+
+    fn receive_events(
+        mut check: ResMut<CargoCheck>,
+        mut diagnostics: ResMut<Diagnostics>,
+        wake: Res<BackgroundWake>,
+    ) {
+        let Some(receiver) = check.receiver.as_ref() else {
+            return;
+        };
+        let mut finished = None;
+        let mut problems = vec![];
+        let (events, disconnected) = receiver.drain();
+        for event in events {
+            match event {
+                CheckEvent::Problem(problem) => problems.push(problem),
+                CheckEvent::Finished { success, error } => finished = Some((success, error)),
+            }
+        }
+        if disconnected {
+            finished.get_or_insert((false, None));
+        }
+        if !problems.is_empty() {
+            check.bypass_change_detection().problems.extend(problems);
+        }
+        let Some((success, error)) = finished else {
+            return;
+        };
+        diagnostics.clear_source(CARGO_DIAGNOSTIC_SOURCE);
+        for (path, files) in group_by_path(&check.problems) {
+            diagnostics.set(CARGO_DIAGNOSTIC_SOURCE, path, files);
+        }
+        check.running = false;
+        check.success = Some(success);
+        if let Some(error) = &error {
+            error!("cargo check: {error}");
+        }
+        check.error = error;
+        check.receiver = None;
+        info!("cargo check finished: {} problems", check.problems.len());
+        if check.pending
+            && let Some(root) = check.root.clone()
+        {
+            check.start(root, &wake);
+        }
+    }
+
+    fn exit(mut check: ResMut<CargoCheck>, mut diagnostics: ResMut<Diagnostics>) {
+        check.running = false;
+        check.pending = false;
+        check.success = None;
+        check.error = None;
+        check.problems.clear();
+        check.receiver = None;
+        diagnostics.clear_source(CARGO_DIAGNOSTIC_SOURCE);
+    }
+
+    /// 打开 cargo 项目时读取工作区成员和目标
+    fn load_packages(
+        current_project: Res<CurrentProject>,
+        mut tasks: ResMut<CargoTasks>,
+        wake: Res<BackgroundWake>,
+    ) {
+        let Some(project) = current_project.project.as_ref() else {
+            return;
+        };
+        let root = PathBuf::from(&project.path);
+        if root.join("Cargo.toml").exists() {
+            tasks.root = Some(root.clone());
+            tasks.load(root, &wake);
+        }
+    }
+
+    /// 保存 Cargo.toml 后重新读取
+    fn reload_packages(
+        mut saved: MessageReader<DocumentSaved>,
+        mut tasks: ResMut<CargoTasks>,
+        wake: Res<BackgroundWake>,
+    ) {
+        let manifest = saved.read().any(|saved| {
+            saved
+                .path
+                .file_name()
+                .is_some_and(|name| name == "Cargo.toml")
+        });
+        if manifest && let Some(root) = tasks.root.clone() {
+            tasks.load(root, &wake);
+        }
+    }
+
+    fn receive_packages(mut tasks: ResMut<CargoTasks>) {
+        let Some(receiver) = tasks.metadata.as_ref() else {
+            return;
+        };
+        let received = receiver.try_recv();
+        match received {
+            Ok(packages) => {
+                if let Some(packages) = packages {
+                    tasks.packages = packages;
+                }
+                tasks.metadata = None;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => tasks.metadata = None,
+        }
+    }
+
+    fn run_task(
+        mut run: MessageReader<RunCargoTask>,
+        mut stop: MessageReader<StopCargoTask>,
+        mut tasks: ResMut<CargoTasks>,
+        wake: Res<BackgroundWake>,
+    ) {
+        if let Some(RunCargoTask(task)) = run.read().last() {
+            tasks.run(task.clone(), &wake);
+        }
+        if stop.read().count() > 0 {
+            tasks.stop();
+        }
+    }
+
+    fn receive_output(mut tasks: ResMut<CargoTasks>) {
+        let Some(process) = tasks.bypass_change_detection().process.as_mut() else {
+            return;
+        };
+        let mut lines = vec![];
+        let finished = process.poll(|line| lines.push(line));
+        if lines.is_empty() && finished.is_none() {
+            return;
+        }
+        for line in &lines {
+            tasks.push_line(line);
+        }
+        if let Some(success) = finished {
+            tasks.process = None;
+            tasks.running = false;
+            tasks.success = Some(success);
+        }
+    }
+
+    fn exit_tasks(mut tasks: ResMut<CargoTasks>) {
+        tasks.stop();
+        *tasks = CargoTasks::default();
+    }
+}
@@ -0,0 +1,109 @@
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake, WakeSender};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::TryRecvError;
+
+/// 后台运行的 cargo 命令，stdout 和 stderr 按行合并
+pub struct TaskProcess {
+    child: Child,
+    receiver: BackgroundReceiver<String>,
+}
+
+impl TaskProcess {
+    /// 在 root 下运行 `cargo <args>`，输出保留颜色
+    pub fn spawn(root: &Path, args: &[String], wake: &BackgroundWake) -> io::Result<Self> {
+        let mut command = Command::new("cargo");
+        command
+            .args(args)
+            .current_dir(root)
+            .env("CARGO_TERM_COLOR", "always")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // 单独的进程组，停止时连同 cargo run 启动的程序一起结束
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let mut child = command.spawn()?;
+        let (sender, receiver) = wake.channel();
+        if let Some(stdout) = child.stdout.take() {
+            read_lines(stdout, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            read_lines(stderr, sender);
+        }
+        Ok(Self { child, receiver })
+    }
+
+    /// 把新的输出行交给 f，输出结束后返回进程是否成功
+    pub fn poll(&mut self, mut f: impl FnMut(String)) -> Option<bool> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(line) => f(line),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    return Some(self.child.wait().is_ok_and(|status| status.success()));
+                }
+            }
+        }
+    }
+
+    pub fn kill(&mut self) {
+        #[cfg(unix)]
+        {
+            let _ = Command::new("kill")
+                .args(["-TERM", "--", &format!("-{}", self.child.id())])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for TaskProcess {
+    fn drop(&mut self) {
+        if matches!(self.child.try_wait(), Ok(None)) {
+            self.kill();
+        }
+    }
+}
+
+/// 读完后 sender 被 drop，唤醒一次让界面发现输出已经结束
+fn read_lines(reader: impl Read + Send + 'static, sender: WakeSender<String>) {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut buf = vec![];
+        // 输出不一定是合法的 UTF-8，不能用 lines()
+        while reader.read_until(b'\n', &mut buf).is_ok_and(|n| n > 0) {
+            let line = String::from_utf8_lossy(&buf);
+            let line = line.trim_end_matches(['\n', '\r']).to_string();
+            buf.clear();
+            if !sender.send(line) {
+                break;
+            }
+        }
+    });
+}
+
+#[test]
+fn test_task_process() {
+    let mut process = TaskProcess::spawn(
+        Path::new("."),
+        &["--version".to_string()],
+        &BackgroundWake::default(),
+    )
+    .unwrap();
+    let mut lines = vec![];
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    let success = loop {
+        if let Some(success) = process.poll(|line| lines.push(line)) {
+            break success;
+        }
+        assert!(std::time::Instant::now() < deadline);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+    assert!(success);
+    assert!(lines[0].starts_with("cargo "));
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use bevy::window::WindowTheme;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::LazyLock;
 
 pub static SEEKER_CONFIG: LazyLock<SeekerConfig> = LazyLock::new(|| {
@@ -15,8 +16,35 @@ pub struct SeekerConfig {
     pub window_theme: WindowTheme,
     #[serde(skip)]
     pub colors: SeekerColors,
-    #[serde(skip)]
+    #[serde(skip, default = "default_font_size")]
     pub font_size: f32,
+    /// 语言服务器配置，key 为 languageId
+    #[serde(default = "default_lsp")]
+    pub lsp: HashMap<String, LspServerConfig>,
+    /// 保存时运行的 cargo 检查
+    #[serde(default)]
+    pub cargo_check: CargoCheckConfig,
+    /// 在光标所在行的行尾显示 git blame
+    #[serde(default)]
+    pub blame: bool,
+    /// 启动时打开上次的项目
+    #[serde(default)]
+    pub restore_last_project: bool,
+    /// 编辑器右侧显示缩略图
+    #[serde(default = "default_minimap")]
+    pub minimap: bool,
+    /// 启动时打开软换行，可以用 Alt+Z 切换
+    #[serde(default)]
+    pub soft_wrap: bool,
+    /// 软换行的列数，不设置时按编辑器的宽度换行
+    #[serde(default)]
+    pub wrap_column: Option<usize>,
+    /// 格式化工具配置，key 为 languageId
+    #[serde(default = "default_formatters")]
+    pub formatters: HashMap<String, FormatterConfig>,
+    /// 保存前先格式化
+    #[serde(default)]
+    pub format_on_save: bool,
 }
 
 impl Default for SeekerConfig {
@@ -24,7 +52,94 @@ impl Default for SeekerConfig {
         Self {
             window_theme: WindowTheme::Dark,
             colors: SeekerColors::from(WindowTheme::Dark),
-            font_size: 14.,
+            font_size: default_font_size(),
+            lsp: default_lsp(),
+            cargo_check: CargoCheckConfig::default(),
+            blame: false,
+            restore_last_project: false,
+            minimap: default_minimap(),
+            soft_wrap: false,
+            wrap_column: None,
+            formatters: default_formatters(),
+            format_on_save: false,
+        }
+    }
+}
+
+fn default_font_size() -> f32 {
+    14.
+}
+
+fn default_minimap() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LspServerConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+fn default_lsp() -> HashMap<String, LspServerConfig> {
+    let mut lsp = HashMap::new();
+    lsp.insert(
+        "rust".to_string(),
+        LspServerConfig {
+            command: "rust-analyzer".to_string(),
+            args: vec![],
+        },
+    );
+    lsp
+}
+
+/// 从标准输入读取、向标准输出写出格式化结果的工具，参数中的 {file} 替换为文件路径，
+/// {edition} 替换为文件所属 crate 的 edition
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FormatterConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+fn default_formatters() -> HashMap<String, FormatterConfig> {
+    let formatter = |command: &str, args: &[&str]| FormatterConfig {
+        command: command.to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+    };
+    let mut formatters = HashMap::new();
+    // 在文件所在目录运行，rustfmt 会读取项目的 rustfmt.toml，edition 和 cargo fmt 一样取自 Cargo.toml
+    formatters.insert(
+        "rust".to_string(),
+        formatter("rustfmt", &["--edition", "{edition}", "--emit", "stdout"]),
+    );
+    formatters.insert("toml".to_string(), formatter("taplo", &["fmt", "-"]));
+    formatters.insert("go".to_string(), formatter("gofmt", &[]));
+    formatters.insert("python".to_string(), formatter("ruff", &["format", "-"]));
+    for language in ["javascript", "typescript", "json", "markdown"] {
+        formatters.insert(
+            language.to_string(),
+            formatter("prettier", &["--stdin-filepath", "{file}"]),
+        );
+    }
+    formatters
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CargoCheckConfig {
+    pub on_save: bool,
+    /// check 或者 clippy
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl Default for CargoCheckConfig {
+    fn default() -> Self {
+        Self {
+            on_save: true,
+            command: "check".to_string(),
+            args: vec!["--workspace".to_string(), "--all-targets".to_string()],
         }
     }
 }
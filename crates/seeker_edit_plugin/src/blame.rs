@@ -0,0 +1,393 @@
+use crate::diff_view::OpenDiff;
+use crate::editor::{cell_width, position_to_point, EditorSystems, FocusedContent, LINE_HEIGHT};
+use crate::source_control::age;
+use crate::{EditorView, FocusedEditor};
+use bevy::input::keyboard::KeyboardInput;
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use seeker_config::SEEKER_CONFIG;
+use seeker_editor::buffer::Position;
+use seeker_editor::document::Documents;
+use seeker_git::blame::FileBlame;
+use seeker_git::plugin::GitStatus;
+use seeker_git::Oid;
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake};
+use seeker_resource::fonts::{MAPLE_MONO_BOLD, MAPLE_MONO_BOLD_ITALIC};
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditSubLoadState, SeekerEditViewState};
+use seeker_trait::SeekerTrait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::TryRecvError;
+
+/// 聚焦文档的 git blame，在后台按编辑中的内容计算
+#[derive(Resource)]
+pub struct Blame {
+    enabled: bool,
+    /// 计算时的文档版本和结果，None 表示文件没有被提交过
+    files: HashMap<PathBuf, (i32, Option<FileBlame>)>,
+    running: Option<BlameTask>,
+}
+
+impl Default for Blame {
+    fn default() -> Self {
+        Self {
+            enabled: SEEKER_CONFIG.blame,
+            files: HashMap::new(),
+            running: None,
+        }
+    }
+}
+
+impl Blame {
+    fn file(&self, path: &Path, version: i32) -> Option<&FileBlame> {
+        self.files
+            .get(path)
+            .filter(|(file_version, _)| *file_version == version)
+            .and_then(|(_, file)| file.as_ref())
+    }
+}
+
+struct BlameTask {
+    path: PathBuf,
+    version: i32,
+    receiver: BackgroundReceiver<Option<FileBlame>>,
+}
+
+/// 光标所在行行尾的注释，悬停时显示提交详情
+#[derive(Component)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct BlameAnnotation {
+    path: PathBuf,
+    /// 还没有提交的行为 None
+    id: Option<Oid>,
+}
+
+#[derive(Component)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct BlameCard;
+
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct OpenCommitDiffButton {
+    pub path: PathBuf,
+    pub id: Oid,
+}
+
+#[derive(Component)]
+pub struct BlamePlugin;
+
+impl SeekerTrait for BlamePlugin {}
+
+impl Plugin for BlamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Blame>()
+            .add_systems(OnExit(SeekerEditSubLoadState::Loaded), Self::exit)
+            .add_systems(
+                Update,
+                (
+                    Self::toggle,
+                    Self::update,
+                    Self::annotate,
+                    Self::hover,
+                    Self::open_diff,
+                )
+                    .chain()
+                    .after(EditorSystems)
+                    .run_if(
+                        in_state(SeekerEditSubLoadState::Loaded)
+                            .and(in_state(SeekerEditViewState::Editor)),
+                    ),
+            )
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, OpenCommitDiffButton>,
+            );
+    }
+}
+
+impl BlamePlugin {
+    fn exit(mut blame: ResMut<Blame>) {
+        blame.files.clear();
+        blame.running = None;
+    }
+
+    /// Ctrl+Alt+B 显示或隐藏 blame
+    fn toggle(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        mut blame: ResMut<Blame>,
+    ) {
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        for event in reader.read() {
+            if !event.state.is_pressed() || !command || !alt {
+                continue;
+            }
+            // Alt 在 macOS 上会改变输入的字符，这里用物理按键
+            if event.key_code == KeyCode::KeyB {
+                blame.enabled = !blame.enabled;
+                if !blame.enabled {
+                    blame.files.clear();
+                    blame.running = None;
+                }
+            }
+        }
+    }
+
+    /// 同一时间只计算一个文档，文档版本变化或者提交后重新计算
+    fn update(
+        mut blame: ResMut<Blame>,
+        git: Res<GitStatus>,
+        wake: Res<BackgroundWake>,
+        documents: Res<Documents>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+    ) {
+        if !blame.enabled {
+            return;
+        }
+        let cache = blame.bypass_change_detection();
+        if git.is_changed() {
+            cache.files.clear();
+        }
+        cache.files.retain(|path, _| documents.get(path).is_some());
+        let received = cache.running.as_ref().map(|task| task.receiver.try_recv());
+        match received {
+            Some(Ok(file)) => {
+                if let Some(task) = cache.running.take() {
+                    cache.files.insert(task.path, (task.version, file));
+                }
+                blame.set_changed();
+            }
+            Some(Err(TryRecvError::Empty)) => return,
+            Some(Err(TryRecvError::Disconnected)) => cache.running = None,
+            None => {}
+        }
+        let Some(doc) = view.path.as_ref().and_then(|path| documents.get(path)) else {
+            return;
+        };
+        let cache = blame.bypass_change_detection();
+        if cache
+            .files
+            .get(&doc.path)
+            .is_some_and(|(version, _)| *version == doc.version)
+        {
+            return;
+        }
+        let (path, text) = (doc.path.clone(), doc.buffer.text());
+        let receiver = wake.spawn({
+            let path = path.clone();
+            move || {
+                FileBlame::open(&path, &text)
+                    .inspect_err(|err| error!("blame {}: {err}", path.display()))
+                    .ok()
+                    .flatten()
+            }
+        });
+        cache.running = Some(BlameTask {
+            path,
+            version: doc.version,
+            receiver,
+        });
+    }
+
+    /// 光标换行、文档变化或者 blame 更新后重新生成注释
+    #[allow(clippy::too_many_arguments)]
+    fn annotate(
+        mut commands: Commands,
+        blame: Res<Blame>,
+        documents: Res<Documents>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        content: FocusedContent,
+        annotations: Query<Entity, With<BlameAnnotation>>,
+        mut last: Local<Option<(Entity, PathBuf, usize, i32)>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let doc = view.path.as_ref().and_then(|path| documents.get(path));
+        let content = content.get();
+        // 焦点移到其他窗格时在新的窗格中显示
+        let key = doc
+            .zip(content)
+            .map(|(doc, content)| (content, doc.path.clone(), view.cursor.line, doc.version));
+        if *last == key && !blame.is_changed() {
+            return;
+        }
+        *last = key;
+        annotations
+            .iter()
+            .for_each(|annotation| commands.entity(annotation).despawn());
+        let (Some(doc), Some(content)) = (doc, content) else {
+            return;
+        };
+        // 编辑后结果还没有更新时不显示，避免对错行
+        let Some(file) = blame.file(&doc.path, doc.version).filter(|_| blame.enabled) else {
+            return;
+        };
+        let line = view.cursor.line;
+        let commit = file.line(line);
+        let text = match commit {
+            Some(commit) => format!(
+                "{}, {} • {}",
+                commit.author,
+                age(commit.time),
+                commit.summary
+            ),
+            None => "Not committed yet".to_string(),
+        };
+        let end = Position::new(line, doc.buffer.line_len(line));
+        let point = position_to_point(doc, &view.wrap, end);
+        commands.entity(content).with_children(|parent| {
+            parent
+                .spawn((
+                    BlameAnnotation {
+                        path: doc.path.clone(),
+                        id: commit.map(|commit| commit.id),
+                    },
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(point.x + 4. * cell_width()),
+                        top: Val::Px(point.y),
+                        height: Val::Px(LINE_HEIGHT),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new(text),
+                        TextFont {
+                            font: assets.load(MAPLE_MONO_BOLD_ITALIC),
+                            font_size: 12.,
+                            ..default()
+                        },
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(res.colors.home_font_grey_color),
+                    ));
+                });
+        });
+    }
+
+    /// 鼠标在注释或者卡片上时显示卡片
+    fn hover(
+        mut commands: Commands,
+        annotations: Query<(Entity, &Interaction, &BlameAnnotation)>,
+        cards: Query<(Entity, &Interaction), With<BlameCard>>,
+        buttons: Query<&Interaction, With<OpenCommitDiffButton>>,
+        blame: Res<Blame>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let hovered = annotations
+            .iter()
+            .any(|(_, interaction, _)| *interaction != Interaction::None)
+            || cards
+                .iter()
+                .any(|(_, interaction)| *interaction != Interaction::None)
+            || buttons
+                .iter()
+                .any(|interaction| *interaction != Interaction::None);
+        if !hovered {
+            cards
+                .iter()
+                .for_each(|(card, _)| commands.entity(card).try_despawn());
+            return;
+        }
+        if !cards.is_empty() {
+            return;
+        }
+        let Some((entity, _, annotation)) = annotations.iter().next() else {
+            return;
+        };
+        let commit = annotation.id.and_then(|id| {
+            let (_, file) = blame.files.get(&annotation.path)?;
+            file.as_ref()?.commits.get(&id)
+        });
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            ..default()
+        };
+        commands.entity(entity).with_children(|parent| {
+            parent
+                .spawn((
+                    BlameCard,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(0.),
+                        top: Val::Px(LINE_HEIGHT),
+                        min_width: Val::Px(300.),
+                        max_width: Val::Px(600.),
+                        padding: UiRect::all(Val::Px(8.)),
+                        border: UiRect::all(Val::Px(1.)),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(6.),
+                        ..default()
+                    },
+                    ZIndex(10),
+                    BorderColor::all(res.colors.button_border),
+                    BackgroundColor(res.colors.home_menu),
+                    BorderRadius::all(Val::Px(3.)),
+                ))
+                .with_children(|parent| {
+                    let Some(commit) = commit else {
+                        parent.spawn((
+                            Text::new("Not committed yet"),
+                            text_font.clone(),
+                            TextColor(res.colors.home_font_color),
+                        ));
+                        return;
+                    };
+                    parent.spawn((
+                        Text::new(commit.short_id()),
+                        text_font.clone(),
+                        TextColor(res.colors.warning),
+                        children![(
+                            TextSpan::new(format!("  {}  {}", commit.author, age(commit.time))),
+                            text_font.clone(),
+                            TextColor(res.colors.home_font_grey_color),
+                        )],
+                    ));
+                    parent.spawn((
+                        Text::new(&commit.summary),
+                        text_font.clone(),
+                        TextColor(res.colors.home_font_color),
+                    ));
+                    Self::ui_button(
+                        parent,
+                        OpenCommitDiffButton {
+                            path: annotation.path.clone(),
+                            id: commit.id,
+                        },
+                        "OpenCommitDiff",
+                        "Open commit diff",
+                        &res,
+                        &assets,
+                    );
+                });
+        });
+    }
+
+    fn open_diff(
+        query: Query<(&Interaction, &OpenCommitDiffButton), Changed<Interaction>>,
+        mut open: MessageWriter<OpenDiff>,
+    ) {
+        for (interaction, button) in query.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            match OpenDiff::commit(&button.path, button.id) {
+                Ok(diff) => {
+                    open.write(diff);
+                }
+                Err(err) => error!("diff {} at {}: {err}", button.path.display(), button.id),
+            }
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use crate::editor::{
+    position_to_point, EditorInputCapture, EditorSystems, FocusedContent, LINE_HEIGHT,
+};
+use crate::snippet::{snippet_candidates, SnippetSession, SnippetSystems, Snippets};
+use crate::{EditorView, FocusedEditor};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use bevy::tasks::futures::check_ready;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use lsp_types::{CompletionItem, CompletionTextEdit, Documentation, InsertTextFormat};
+use seeker_editor::buffer::{is_word_char, Position};
+use seeker_editor::completion::{collect_words, fuzzy_score, index_words};
+use seeker_editor::document::{Document, DocumentSaved, Documents};
+use seeker_lsp::plugin::{LspRequest, LspRequestKind, LspServers};
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::project_list::CurrentProject;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditSubLoadState, SeekerEditViewState};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// 补全列表最多显示的条数
+const COMPLETION_VISIBLE: usize = 10;
+/// 自动弹出补全需要输入的字符数
+const AUTO_TRIGGER_LEN: usize = 2;
+
+/// 补全候选项，来自语言服务器或者文本中的标识符
+#[derive(Clone, Debug, Default)]
+pub struct CompletionCandidate {
+    pub label: String,
+    pub detail: Option<String>,
+    pub documentation: Option<String>,
+    pub filter_text: String,
+    pub insert_text: String,
+    pub snippet: bool,
+}
+
+impl CompletionCandidate {
+    pub fn word(word: &str) -> Self {
+        Self {
+            label: word.to_string(),
+            detail: Some("word".to_string()),
+            filter_text: word.to_string(),
+            insert_text: word.to_string(),
+            ..default()
+        }
+    }
+}
+
+impl From<CompletionItem> for CompletionCandidate {
+    fn from(item: CompletionItem) -> Self {
+        let insert_text = match item.text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => edit.new_text,
+            Some(CompletionTextEdit::InsertAndReplace(edit)) => edit.new_text,
+            None => item.insert_text.unwrap_or_else(|| item.label.clone()),
+        };
+        let documentation = item.documentation.map(|documentation| match documentation {
+            Documentation::String(text) => text,
+            Documentation::MarkupContent(content) => content.value,
+        });
+        Self {
+            filter_text: item.filter_text.unwrap_or_else(|| item.label.clone()),
+            label: item.label,
+            detail: item.detail,
+            documentation,
+            insert_text,
+            snippet: item.insert_text_format == Some(InsertTextFormat::SNIPPET),
+        }
+    }
+}
+
+/// 当前打开的补全列表，start 为正在输入的标识符起点
+#[derive(Resource, Default)]
+pub struct CompletionMenu {
+    pub path: Option<PathBuf>,
+    pub start: Position,
+    pub candidates: Vec<CompletionCandidate>,
+    pub selected: usize,
+}
+
+impl CompletionMenu {
+    pub fn is_open(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn open(&mut self, path: PathBuf, start: Position, candidates: Vec<CompletionCandidate>) {
+        self.path = Some(path);
+        self.start = start;
+        self.candidates = candidates;
+        self.selected = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.path = None;
+        self.candidates.clear();
+        self.selected = 0;
+    }
+
+    /// 按已输入的前缀模糊过滤，得分高的在前
+    pub fn filtered(&self, prefix: &str) -> Vec<&CompletionCandidate> {
+        let mut candidates = self
+            .candidates
+            .iter()
+            .filter_map(|candidate| {
+                fuzzy_score(prefix, &candidate.filter_text).map(|score| (score, candidate))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        candidates
+            .into_iter()
+            .map(|(_, candidate)| candidate)
+            .collect()
+    }
+}
+
+/// 项目中出现过的标识符，没有语言服务器时用于补全
+#[derive(Resource, Default)]
+pub struct ProjectIndex {
+    pub words: HashSet<String>,
+    task: Option<Task<HashSet<String>>>,
+}
+
+#[derive(Component)]
+pub struct CompletionPopup;
+
+#[derive(Component)]
+pub struct CompletionPlugin;
+
+impl Plugin for CompletionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CompletionMenu>()
+            .init_resource::<ProjectIndex>()
+            .add_systems(OnEnter(SeekerEditSubLoadState::Loaded), Self::index_project)
+            .add_systems(OnExit(SeekerEditSubLoadState::Loaded), Self::exit)
+            .add_systems(
+                Update,
+                (
+                    Self::poll_index,
+                    Self::index_saved,
+                    Self::trigger,
+                    Self::input,
+                    Self::render,
+                )
+                    .chain()
+                    .after(EditorSystems)
+                    .after(SnippetSystems)
+                    .run_if(
+                        in_state(SeekerEditSubLoadState::Loaded)
+                            .and(in_state(SeekerEditViewState::Editor)),
+                    ),
+            );
+    }
+}
+
+impl CompletionPlugin {
+    fn index_project(mut index: ResMut<ProjectIndex>, current_project: Res<CurrentProject>) {
+        index.words.clear();
+        index.task = current_project.project.as_ref().map(|project| {
+            let root = PathBuf::from(&project.path);
+            AsyncComputeTaskPool::get().spawn(async move { index_words(&root) })
+        });
+    }
+
+    fn exit(mut menu: ResMut<CompletionMenu>, mut index: ResMut<ProjectIndex>) {
+        menu.close();
+        index.words.clear();
+        index.task = None;
+    }
+
+    fn poll_index(mut index: ResMut<ProjectIndex>) {
+        let Some(task) = index.bypass_change_detection().task.as_mut() else {
+            return;
+        };
+        if let Some(words) = check_ready(task) {
+            index.words = words;
+            index.task = None;
+        }
+    }
+
+    /// 保存后把文件中的新标识符加入索引
+    fn index_saved(
+        mut reader: MessageReader<DocumentSaved>,
+        documents: Res<Documents>,
+        mut index: ResMut<ProjectIndex>,
+    ) {
+        for saved in reader.read() {
+            if let Some(doc) = documents.get(&saved.path) {
+                for word in collect_words(&doc.buffer.text()) {
+                    if !index.words.contains(word) {
+                        index.words.insert(word.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Ctrl+Space 手动触发，输入标识符或者 `.`、`:` 时自动触发
+    #[allow(clippy::too_many_arguments)]
+    fn trigger(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        capture: Res<EditorInputCapture>,
+        documents: Res<Documents>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        servers: Res<LspServers>,
+        index: Res<ProjectIndex>,
+        snippets: Res<Snippets>,
+        mut menu: ResMut<CompletionMenu>,
+        mut requests: MessageWriter<LspRequest>,
+    ) {
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        if capture.text {
+            reader.clear();
+            return;
+        }
+        let mut manual = false;
+        let mut typed = None;
+        for event in reader.read() {
+            if !event.state.is_pressed() {
+                continue;
+            }
+            match (&event.logical_key, &event.text) {
+                (Key::Space, _) if command => manual = true,
+                (_, Some(text)) if !command => typed = text.chars().last(),
+                _ => {}
+            }
+        }
+        let Some(doc) = view.path.as_ref().and_then(|path| documents.get(path)) else {
+            return;
+        };
+        let lsp = servers.is_running(&doc.language_id);
+        let start = doc.buffer.word_start(view.cursor);
+        let auto = match typed {
+            _ if menu.is_open() => false,
+            Some('.' | ':') => lsp,
+            Some(ch) if is_word_char(ch) => view.cursor.column - start.column >= AUTO_TRIGGER_LEN,
+            _ => false,
+        };
+        if !manual && !auto {
+            return;
+        }
+        if lsp {
+            requests.write(LspRequest {
+                path: doc.path.clone(),
+                position: view.cursor,
+                kind: LspRequestKind::Completion,
+            });
+        } else {
+            let mut candidates = word_candidates(&documents, &index, doc, view.cursor);
+            candidates.extend(snippet_candidates(&snippets, doc, view.cursor));
+            if !candidates.is_empty() {
+                menu.open(doc.path.clone(), start, candidates);
+            }
+        }
+    }
+
+    /// 补全列表打开时接管上下、回车、Tab 和 Esc
+    fn input(
+        mut reader: MessageReader<KeyboardInput>,
+        mut menu: ResMut<CompletionMenu>,
+        mut capture: ResMut<EditorInputCapture>,
+        mut documents: ResMut<Documents>,
+        mut view: Single<&mut EditorView, With<FocusedEditor>>,
+        mut session: ResMut<SnippetSession>,
+    ) {
+        if !menu.is_open() {
+            reader.clear();
+            if capture.navigation {
+                capture.navigation = false;
+            }
+            return;
+        }
+        let cursor = view.cursor;
+        let prefix = view
+            .path
+            .as_ref()
+            .and_then(|path| documents.get(path))
+            .map(|doc| doc.buffer.slice(menu.start, cursor))
+            .unwrap_or_default();
+        // 光标离开了正在补全的标识符或者没有匹配项时关闭
+        if view.path != menu.path
+            || cursor.line != menu.start.line
+            || cursor < menu.start
+            || menu.filtered(&prefix).is_empty()
+        {
+            menu.close();
+            capture.navigation = false;
+            reader.clear();
+            return;
+        }
+        let Some(path) = view.path.clone() else {
+            return;
+        };
+        for event in reader.read() {
+            if !event.state.is_pressed() {
+                continue;
+            }
+            let count = menu.filtered(&prefix).len();
+            match event.logical_key {
+                Key::ArrowUp => menu.selected = (menu.selected + count - 1) % count,
+                Key::ArrowDown => menu.selected = (menu.selected + 1) % count,
+                Key::Enter | Key::Tab => {
+                    let candidate = menu.filtered(&prefix).get(menu.selected).cloned().cloned();
+                    if let (Some(candidate), Some(doc)) = (candidate, documents.get_mut(&path)) {
+                        accept(&mut view, doc, &mut session, menu.start, &candidate);
+                    }
+                    menu.close();
+                    break;
+                }
+                Key::Escape => {
+                    menu.close();
+                    break;
+                }
+                _ => {}
+            }
+        }
+        capture.navigation = menu.is_open();
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render(
+        mut commands: Commands,
+        menu: Res<CompletionMenu>,
+        documents: Res<Documents>,
+        view: Single<Ref<EditorView>, With<FocusedEditor>>,
+        content: FocusedContent,
+        popups: Query<Entity, With<CompletionPopup>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        if !menu.is_changed() && !view.is_changed() && !documents.is_changed() {
+            return;
+        }
+        popups
+            .iter()
+            .for_each(|popup| commands.entity(popup).despawn());
+        let Some(doc) = menu.path.as_ref().and_then(|path| documents.get(path)) else {
+            return;
+        };
+        let prefix = doc.buffer.slice(menu.start, view.cursor);
+        let candidates = menu.filtered(&prefix);
+        let Some(selected) = candidates.get(menu.selected) else {
+            return;
+        };
+        let font = assets.load(MAPLE_MONO_BOLD);
+        let text_font = TextFont {
+            font: font.clone(),
+            font_size: 13.,
+            ..default()
+        };
+        let point = position_to_point(doc, &view.wrap, menu.start);
+        // 选中项始终在可见范围内
+        let first = (menu.selected + 1).saturating_sub(COMPLETION_VISIBLE);
+        let Some(content) = content.get() else {
+            return;
+        };
+        commands.entity(content).with_children(|parent| {
+            parent
+                .spawn((
+                    CompletionPopup,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(point.x),
+                        top: Val::Px(point.y + LINE_HEIGHT),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Start,
+                        column_gap: Val::Px(4.),
+                        ..default()
+                    },
+                    ZIndex(10),
+                ))
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            Node {
+                                min_width: Val::Px(200.),
+                                display: Display::Flex,
+                                flex_direction: FlexDirection::Column,
+                                border: UiRect::all(Val::Px(1.)),
+                                ..default()
+                            },
+                            BorderColor::all(res.colors.button_border),
+                            BackgroundColor(res.colors.home_menu),
+                        ))
+                        .with_children(|parent| {
+                            for (index, candidate) in candidates
+                                .iter()
+                                .enumerate()
+                                .skip(first)
+                                .take(COMPLETION_VISIBLE)
+                            {
+                                let background = if index == menu.selected {
+                                    res.colors.editor_selection
+                                } else {
+                                    Color::NONE
+                                };
+                                parent
+                                    .spawn((
+                                        Node {
+                                            padding: UiRect::horizontal(Val::Px(6.)),
+                                            column_gap: Val::Px(12.),
+                                            justify_content: JustifyContent::SpaceBetween,
+                                            ..default()
+                                        },
+                                        BackgroundColor(background),
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn((
+                                            Text::new(&candidate.label),
+                                            text_font.clone(),
+                                            TextLayout::new_with_no_wrap(),
+                                            TextColor(res.colors.home_font_color),
+                                        ));
+                                        if let Some(detail) = &candidate.detail {
+                                            parent.spawn((
+                                                Text::new(
+                                                    detail.lines().next().unwrap_or_default(),
+                                                ),
+                                                text_font.clone(),
+                                                TextLayout::new_with_no_wrap(),
+                                                TextColor(res.colors.home_font_grey_color),
+                                            ));
+                                        }
+                                    });
+                            }
+                        });
+                    // 选中项的文档
+                    if let Some(documentation) = &selected.documentation
+                        && !documentation.trim().is_empty()
+                    {
+                        parent
+                            .spawn((
+                                Node {
+                                    max_width: Val::Px(400.),
+                                    padding: UiRect::all(Val::Px(8.)),
+                                    border: UiRect::all(Val::Px(1.)),
+                                    ..default()
+                                },
+                                BorderColor::all(res.colors.button_border),
+                                BackgroundColor(res.colors.home_menu),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(documentation.trim()),
+                                    text_font.clone(),
+                                    TextColor(res.colors.home_font_color),
+                                ));
+                            });
+                    }
+                });
+        });
+    }
+}
+
+/// 用候选项替换 [start, 光标)，片段展开后用 Tab 在跳转点之间移动
+fn accept(
+    view: &mut EditorView,
+    doc: &mut Document,
+    session: &mut SnippetSession,
+    start: Position,
+    candidate: &CompletionCandidate,
+) {
+    doc.seal_undo();
+    if candidate.snippet {
+        session.expand(view, doc, start, &candidate.insert_text);
+        return;
+    }
+    doc.delete(start, view.cursor);
+    view.cursor = start;
+    view.anchor = None;
+    view.insert(doc, &candidate.insert_text);
+}
+
+/// 打开的文档和项目索引中与前缀匹配的标识符
+pub fn word_candidates(
+    documents: &Documents,
+    index: &ProjectIndex,
+    doc: &Document,
+    cursor: Position,
+) -> Vec<CompletionCandidate> {
+    let prefix = doc.buffer.slice(doc.buffer.word_start(cursor), cursor);
+    let mut words = HashSet::new();
+    for document in &documents.documents {
+        for line in document.buffer.lines() {
+            words.extend(collect_words(line));
+        }
+    }
+    words.extend(index.words.iter().map(String::as_str));
+    let current = current_word(doc, cursor);
+    let mut candidates = words
+        .into_iter()
+        .filter(|word| *word != prefix && Some(*word) != current.as_deref())
+        .filter(|word| fuzzy_score(&prefix, word).is_some())
+        .map(CompletionCandidate::word)
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| a.label.cmp(&b.label));
+    candidates
+}
+
+/// 光标所在的完整标识符（光标后面可能还有字符）
+fn current_word(doc: &Document, cursor: Position) -> Option<String> {
+    let line = doc.buffer.line(cursor.line);
+    let start = doc.buffer.word_start(cursor).column;
+    let word = line
+        .chars()
+        .skip(start)
+        .take_while(|ch| is_word_char(*ch))
+        .collect::<String>();
+    (!word.is_empty()).then_some(word)
+}
@@ -0,0 +1,703 @@
+use crate::editor::{cell_width, LINE_HEIGHT};
+use crate::pane::EditorLayout;
+use crate::{EditorUi, EditorView, FocusedEditor};
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::text::LineHeight;
+use bevy::ui::FocusPolicy;
+use seeker_config::SEEKER_CONFIG;
+use seeker_editor::diff::{
+    next_hunk, prev_hunk, unified_hunks, DiffLine, DiffLineKind, TextComparison, UnifiedLine,
+};
+use seeker_editor::document::Documents;
+use seeker_editor::width::str_display_width;
+use seeker_git::commit::commit_file_texts;
+use seeker_git::diff::{head_text, index_text};
+use seeker_git::{GitError, Oid};
+use seeker_resource::file::{FileDialogPicked, FileDialogPurpose};
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::SeekerResource;
+use seeker_scroll::ScrollSync;
+use seeker_state::{SeekerEditSubLoadState, SeekerEditViewState, SeekerFileDialogFnState};
+use seeker_trait::SeekerTrait;
+use std::path::{Path, PathBuf};
+
+/// 比较视图中左右两侧共用的滚动组
+const DIFF_SCROLL_GROUP: ScrollSync = ScrollSync(1);
+const DIFF_GUTTER_WIDTH: f32 = 50.;
+
+/// 参与比较的一段文本
+#[derive(Clone, Debug, Default)]
+pub struct DiffText {
+    pub label: String,
+    pub text: String,
+}
+
+impl DiffText {
+    /// 已打开的文档取编辑中的内容，否则读取磁盘
+    pub fn file(documents: &Documents, path: &Path) -> std::io::Result<Self> {
+        let text = match documents.get(path) {
+            Some(doc) => doc.buffer.text(),
+            None => String::from_utf8_lossy(&std::fs::read(path)?).into_owned(),
+        };
+        Ok(Self {
+            label: file_name(path),
+            text,
+        })
+    }
+
+    /// 某个版本中的内容，版本名显示在文件名后面，不存在时为空
+    pub fn revision(path: &Path, revision: &str, text: Option<String>) -> Self {
+        Self {
+            label: format!("{} ({revision})", file_name(path)),
+            text: text.unwrap_or_default(),
+        }
+    }
+
+    /// HEAD 中的版本，新文件为空
+    pub fn head(path: &Path) -> Result<Self, GitError> {
+        Ok(Self::revision(path, "HEAD", head_text(path)?))
+    }
+
+    /// 暂存区中的版本，没有被跟踪的文件为空
+    pub fn index(path: &Path) -> Result<Self, GitError> {
+        Ok(Self::revision(path, "Index", index_text(path)?))
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// 打开比较视图
+#[derive(Message, Clone, Debug)]
+pub struct OpenDiff {
+    pub left: DiffText,
+    pub right: DiffText,
+}
+
+impl OpenDiff {
+    /// 文件在提交中的改动：第一个父提交和提交中的内容
+    pub fn commit(path: &Path, id: Oid) -> Result<Self, GitError> {
+        let (before, after) = commit_file_texts(path, id)?;
+        let short: String = id.to_string().chars().take(7).collect();
+        Ok(Self {
+            left: DiffText::revision(path, &format!("{short}^"), before),
+            right: DiffText::revision(path, &short, after),
+        })
+    }
+}
+
+/// 用当前编辑的文档和指定文件比较
+#[derive(Message, Clone, Debug)]
+pub struct CompareWithFocused {
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiffMode {
+    #[default]
+    SideBySide,
+    Unified,
+}
+
+/// 当前的比较内容
+#[derive(Resource, Default)]
+pub struct DiffView {
+    pub left: DiffText,
+    pub right: DiffText,
+    pub mode: DiffMode,
+    comparison: TextComparison,
+    unified: Vec<UnifiedLine>,
+    /// 当前改动在当前模式行列表中的下标
+    current: Option<usize>,
+}
+
+impl DiffView {
+    fn open(&mut self, left: DiffText, right: DiffText) {
+        self.comparison = TextComparison::new(&left.text, &right.text);
+        self.unified = self.comparison.unified();
+        self.left = left;
+        self.right = right;
+        self.current = None;
+    }
+
+    fn row_count(&self) -> usize {
+        match self.mode {
+            DiffMode::SideBySide => self.comparison.rows.len(),
+            DiffMode::Unified => self.unified.len(),
+        }
+    }
+
+    fn hunks(&self) -> Vec<usize> {
+        match self.mode {
+            DiffMode::SideBySide => self.comparison.hunks.clone(),
+            DiffMode::Unified => unified_hunks(&self.unified),
+        }
+    }
+
+    /// 某一侧第 row 行的内容和类型
+    fn line(&self, side: DiffSide, row: usize) -> Option<(DiffLineKind, Option<&DiffLine>)> {
+        match side {
+            DiffSide::Unified => {
+                let line = self.unified.get(row)?;
+                Some((line.kind, Some(&line.line)))
+            }
+            DiffSide::Left | DiffSide::Right => {
+                let row = self.comparison.rows.get(row)?;
+                let (line, kind) = match side {
+                    DiffSide::Left => (row.left.as_ref(), DiffLineKind::Delete),
+                    _ => (row.right.as_ref(), DiffLineKind::Insert),
+                };
+                Some((
+                    if row.is_equal() {
+                        DiffLineKind::Equal
+                    } else {
+                        kind
+                    },
+                    line,
+                ))
+            }
+        }
+    }
+
+    fn max_width(&self) -> usize {
+        let lines = self
+            .comparison
+            .rows
+            .iter()
+            .flat_map(|row| [&row.left, &row.right]);
+        lines
+            .flatten()
+            .map(|line| str_display_width(&line.text()))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffSide {
+    Left,
+    Right,
+    Unified,
+}
+
+/// 比较视图的根节点
+#[derive(Component)]
+pub struct DiffViewUi;
+
+/// 可以滚动的一侧，只渲染可见的行
+#[derive(Component)]
+pub struct DiffPane {
+    side: DiffSide,
+    /// 上次渲染的可见范围
+    rendered: Option<(usize, usize)>,
+}
+
+#[derive(Component)]
+pub struct DiffPaneContent;
+
+#[derive(Component)]
+struct DiffPaneRow;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct DiffModeButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct PrevHunkButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct NextHunkButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct CloseDiffButton;
+
+#[derive(Component)]
+pub struct DiffViewPlugin;
+
+impl SeekerTrait for DiffViewPlugin {}
+
+impl Plugin for DiffViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiffView>()
+            .add_sub_state::<SeekerEditViewState>()
+            .add_message::<OpenDiff>()
+            .add_message::<CompareWithFocused>()
+            .add_message::<FileDialogPicked>()
+            .init_resource::<FileDialogPurpose>()
+            .add_systems(OnEnter(SeekerEditViewState::Diff), Self::hide_editor)
+            .add_systems(OnExit(SeekerEditViewState::Diff), Self::show_editor)
+            .add_systems(
+                Update,
+                (
+                    Self::keybindings,
+                    Self::picked_file,
+                    Self::compare_with_focused,
+                    Self::open,
+                )
+                    .chain()
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_systems(
+                Update,
+                (
+                    Self::buttons,
+                    Self::navigate,
+                    Self::spawn_view.run_if(resource_changed::<DiffView>),
+                    Self::render_panes,
+                )
+                    .chain()
+                    .run_if(in_state(SeekerEditViewState::Diff)),
+            )
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, DiffModeButton>)
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, PrevHunkButton>)
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, NextHunkButton>)
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, CloseDiffButton>,
+            );
+    }
+}
+
+impl DiffViewPlugin {
+    /// Ctrl+Alt+D 用当前文档和 HEAD 比较，Ctrl+Alt+F 在文件对话框中选择比较的文件
+    #[allow(clippy::too_many_arguments)]
+    fn keybindings(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        state: Res<State<SeekerEditViewState>>,
+        documents: Res<Documents>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        mut open: MessageWriter<OpenDiff>,
+        mut purpose: ResMut<FileDialogPurpose>,
+        mut dialog: ResMut<NextState<SeekerFileDialogFnState>>,
+    ) {
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        for event in reader.read() {
+            if !event.state.is_pressed() || !command || !alt {
+                continue;
+            }
+            if *state.get() != SeekerEditViewState::Editor {
+                continue;
+            }
+            let Some(path) = view.path.as_ref() else {
+                continue;
+            };
+            if event.key_code == KeyCode::KeyF {
+                *purpose = FileDialogPurpose::CompareWithFocused;
+                dialog.set(SeekerFileDialogFnState::Open);
+                continue;
+            }
+            if event.key_code != KeyCode::KeyD {
+                continue;
+            }
+            match (DiffText::head(path), DiffText::file(&documents, path)) {
+                (Ok(left), Ok(right)) => {
+                    open.write(OpenDiff { left, right });
+                }
+                (Err(err), _) => error!("read HEAD of {}: {err}", path.display()),
+                (_, Err(err)) => error!("read {}: {err}", path.display()),
+            }
+        }
+    }
+
+    fn picked_file(
+        mut reader: MessageReader<FileDialogPicked>,
+        mut compare: MessageWriter<CompareWithFocused>,
+    ) {
+        for picked in reader.read() {
+            if picked.purpose == FileDialogPurpose::CompareWithFocused {
+                compare.write(CompareWithFocused {
+                    path: picked.path.clone(),
+                });
+            }
+        }
+    }
+
+    fn compare_with_focused(
+        mut reader: MessageReader<CompareWithFocused>,
+        documents: Res<Documents>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        mut open: MessageWriter<OpenDiff>,
+    ) {
+        for CompareWithFocused { path } in reader.read() {
+            let Some(current) = view.path.as_ref() else {
+                continue;
+            };
+            match (
+                DiffText::file(&documents, current),
+                DiffText::file(&documents, path),
+            ) {
+                (Ok(left), Ok(right)) => {
+                    open.write(OpenDiff { left, right });
+                }
+                (Err(err), _) | (_, Err(err)) => error!("compare with {}: {err}", path.display()),
+            }
+        }
+    }
+
+    fn open(
+        mut reader: MessageReader<OpenDiff>,
+        mut diff: ResMut<DiffView>,
+        mut state: ResMut<NextState<SeekerEditViewState>>,
+    ) {
+        for OpenDiff { left, right } in reader.read() {
+            diff.open(left.clone(), right.clone());
+            state.set(SeekerEditViewState::Diff);
+        }
+    }
+
+    pub(crate) fn hide_editor(mut editors: Query<&mut Node, With<EditorLayout>>) {
+        for mut node in editors.iter_mut() {
+            node.display = Display::None;
+        }
+    }
+
+    pub(crate) fn show_editor(mut editors: Query<&mut Node, With<EditorLayout>>) {
+        for mut node in editors.iter_mut() {
+            node.display = Display::Flex;
+        }
+    }
+
+    fn buttons(
+        mode: Query<&Interaction, (Changed<Interaction>, With<DiffModeButton>)>,
+        close: Query<&Interaction, (Changed<Interaction>, With<CloseDiffButton>)>,
+        mut diff: ResMut<DiffView>,
+        mut state: ResMut<NextState<SeekerEditViewState>>,
+    ) {
+        if mode
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            diff.mode = match diff.mode {
+                DiffMode::SideBySide => DiffMode::Unified,
+                DiffMode::Unified => DiffMode::SideBySide,
+            };
+            diff.current = None;
+        }
+        if close
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            state.set(SeekerEditViewState::Editor);
+        }
+    }
+
+    /// F7 / Shift+F7 或者按钮跳到下一处、上一处改动，Esc 关闭
+    #[allow(clippy::type_complexity)]
+    fn navigate(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        prev: Query<&Interaction, (Changed<Interaction>, With<PrevHunkButton>)>,
+        next: Query<&Interaction, (Changed<Interaction>, With<NextHunkButton>)>,
+        mut diff: ResMut<DiffView>,
+        mut panes: Query<&mut ScrollPosition, With<DiffPane>>,
+        mut state: ResMut<NextState<SeekerEditViewState>>,
+    ) {
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let mut forward = next
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+        let mut backward = prev
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+        for event in reader.read() {
+            if !event.state.is_pressed() {
+                continue;
+            }
+            match event.logical_key {
+                Key::F7 if shift => backward = true,
+                Key::F7 => forward = true,
+                Key::Escape => state.set(SeekerEditViewState::Editor),
+                _ => {}
+            }
+        }
+        if !forward && !backward {
+            return;
+        }
+        let hunks = diff.hunks();
+        let current = diff.current;
+        let row = if forward {
+            next_hunk(&hunks, current)
+        } else {
+            prev_hunk(&hunks, current)
+        };
+        let Some(row) = row else {
+            return;
+        };
+        diff.bypass_change_detection().current = Some(row);
+        // 留出几行上下文
+        let top = row.saturating_sub(3) as f32 * LINE_HEIGHT;
+        for mut scroll in panes.iter_mut() {
+            scroll.y = top;
+        }
+    }
+
+    fn spawn_view(
+        mut commands: Commands,
+        diff: Res<DiffView>,
+        editor: Single<Entity, With<EditorUi>>,
+        views: Query<Entity, With<DiffViewUi>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        views
+            .iter()
+            .for_each(|view| commands.entity(view).despawn());
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            ..default()
+        };
+        let sides: &[DiffSide] = match diff.mode {
+            DiffMode::SideBySide => &[DiffSide::Left, DiffSide::Right],
+            DiffMode::Unified => &[DiffSide::Unified],
+        };
+        let content_width = DIFF_GUTTER_WIDTH + (diff.max_width() + 2) as f32 * cell_width();
+        let content_height = diff.row_count() as f32 * LINE_HEIGHT;
+        let mode_text = match diff.mode {
+            DiffMode::SideBySide => "Unified",
+            DiffMode::Unified => "Side by side",
+        };
+        commands.entity(*editor).with_children(|parent| {
+            parent
+                .spawn((
+                    DiffViewUi,
+                    DespawnOnExit(SeekerEditViewState::Diff),
+                    Node {
+                        flex_grow: 1.,
+                        min_width: Val::Px(0.),
+                        height: Val::Percent(100.),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent
+                        .spawn(Node {
+                            width: Val::Percent(100.),
+                            padding: UiRect::horizontal(Val::Px(10.)),
+                            column_gap: Val::Px(10.),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Text::new(format!("{} ↔ {}", diff.left.label, diff.right.label)),
+                                text_font.clone(),
+                                TextColor(res.colors.home_font_color),
+                            ));
+                            parent.spawn((
+                                Text::new(format!("{} changes", diff.comparison.hunks.len())),
+                                text_font.clone(),
+                                TextColor(res.colors.home_font_grey_color),
+                            ));
+                            parent.spawn(Node {
+                                flex_grow: 1.,
+                                ..default()
+                            });
+                            Self::ui_button(parent, PrevHunkButton, "PrevHunk", "↑", &res, &assets);
+                            Self::ui_button(parent, NextHunkButton, "NextHunk", "↓", &res, &assets);
+                            Self::ui_button(
+                                parent,
+                                DiffModeButton,
+                                "DiffMode",
+                                mode_text,
+                                &res,
+                                &assets,
+                            );
+                            Self::ui_button_same(parent, CloseDiffButton, "×", &res, &assets);
+                        });
+                    parent
+                        .spawn(Node {
+                            width: Val::Percent(100.),
+                            flex_grow: 1.,
+                            min_height: Val::Px(0.),
+                            display: Display::Flex,
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(1.),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for side in sides {
+                                parent
+                                    .spawn((
+                                        DiffPane {
+                                            side: *side,
+                                            rendered: None,
+                                        },
+                                        DIFF_SCROLL_GROUP,
+                                        Node {
+                                            flex_grow: 1.,
+                                            flex_basis: Val::Px(0.),
+                                            min_width: Val::Px(0.),
+                                            height: Val::Percent(100.),
+                                            overflow: Overflow::scroll(),
+                                            ..default()
+                                        },
+                                        ScrollPosition::default(),
+                                        BackgroundColor(res.colors.editor_background),
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn((
+                                            DiffPaneContent,
+                                            Node {
+                                                width: Val::Px(content_width),
+                                                min_width: Val::Px(content_width),
+                                                height: Val::Px(content_height),
+                                                min_height: Val::Px(content_height),
+                                                ..default()
+                                            },
+                                        ));
+                                    });
+                            }
+                        });
+                });
+        });
+    }
+
+    /// 和编辑器一样只为可见的行生成节点
+    fn render_panes(
+        mut commands: Commands,
+        diff: Res<DiffView>,
+        mut panes: Query<(&mut DiffPane, &ScrollPosition, &ComputedNode, &Children)>,
+        contents: Query<Option<&Children>, With<DiffPaneContent>>,
+        rows: Query<(), With<DiffPaneRow>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: SEEKER_CONFIG.font_size,
+            line_height: LineHeight::Px(LINE_HEIGHT),
+            ..default()
+        };
+        for (mut pane, scroll, computed, children) in panes.iter_mut() {
+            let Some(content) = children.iter().find(|child| contents.contains(*child)) else {
+                continue;
+            };
+            let height = computed.size().y * computed.inverse_scale_factor();
+            let first = (scroll.y / LINE_HEIGHT).floor().max(0.) as usize;
+            let last = (first + (height / LINE_HEIGHT).ceil() as usize + 1).min(diff.row_count());
+            if pane.rendered == Some((first, last)) {
+                continue;
+            }
+            pane.rendered = Some((first, last));
+            if let Ok(Some(content_children)) = contents.get(content) {
+                for child in content_children {
+                    if rows.contains(*child) {
+                        commands.entity(*child).despawn();
+                    }
+                }
+            }
+            commands.entity(content).with_children(|parent| {
+                for row in first..last {
+                    if let Some((kind, line)) = diff.line(pane.side, row) {
+                        Self::render_row(parent, row, kind, line, pane.side, &text_font, &res);
+                    }
+                }
+            });
+        }
+    }
+
+    fn render_row(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        row: usize,
+        kind: DiffLineKind,
+        line: Option<&DiffLine>,
+        side: DiffSide,
+        text_font: &TextFont,
+        res: &SeekerResource,
+    ) {
+        let tint = match kind {
+            DiffLineKind::Equal => None,
+            DiffLineKind::Delete => Some(res.colors.error),
+            DiffLineKind::Insert => Some(res.colors.git_added),
+        };
+        // 另一侧多出来的行在这一侧留空
+        let background = match (line, tint) {
+            (None, _) => res.colors.home_menu,
+            (Some(_), Some(tint)) => tint.with_alpha(0.12),
+            (Some(_), None) => Color::NONE,
+        };
+        let marker = match (side, kind) {
+            (DiffSide::Unified, DiffLineKind::Delete) => "-",
+            (DiffSide::Unified, DiffLineKind::Insert) => "+",
+            _ => " ",
+        };
+        parent
+            .spawn((
+                DiffPaneRow,
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(row as f32 * LINE_HEIGHT),
+                    left: Val::Px(0.),
+                    min_width: Val::Percent(100.),
+                    height: Val::Px(LINE_HEIGHT),
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+                BackgroundColor(background),
+            ))
+            .with_children(|parent| {
+                let Some(line) = line else {
+                    return;
+                };
+                parent
+                    .spawn(Node {
+                        width: Val::Px(DIFF_GUTTER_WIDTH),
+                        min_width: Val::Px(DIFF_GUTTER_WIDTH),
+                        padding: UiRect::right(Val::Px(8.)),
+                        justify_content: JustifyContent::End,
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(format!("{} {marker}", line.number + 1)),
+                            text_font.clone(),
+                            TextColor(res.colors.home_font_grey_color),
+                        ));
+                    });
+                parent
+                    .spawn((
+                        Text::default(),
+                        text_font.clone(),
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(res.colors.home_font_color),
+                    ))
+                    .with_children(|parent| {
+                        for (text, changed) in &line.spans {
+                            let background = match tint {
+                                Some(tint) if *changed => tint.with_alpha(0.35),
+                                _ => Color::NONE,
+                            };
+                            parent.spawn((
+                                TextSpan::new(text),
+                                text_font.clone(),
+                                TextColor(res.colors.home_font_color),
+                                TextBackgroundColor(background),
+                            ));
+                        }
+                    });
+            });
+    }
+}
@@ -0,0 +1,1176 @@
+use crate::fold::{FoldPlugin, FOLD_GUTTER_RIGHT, FOLD_GUTTER_WIDTH};
+use crate::format::FormatDocument;
+use crate::git_gutter::{GitDiffs, GutterMarker};
+use crate::minimap::overlay_width;
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use bevy::text::LineHeight;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+use seeker_config::SEEKER_CONFIG;
+use seeker_editor::buffer::{ordered, Position};
+use seeker_editor::diagnostic::{Diagnostics, Severity};
+use seeker_editor::document::{Document, DocumentSaved, Documents};
+use seeker_editor::width::{column_at_display_width, column_display_width, str_display_width};
+use seeker_editor::wrap::WrapLayout;
+use seeker_editor::{bracket, indent};
+use seeker_git::diff::{hunk_at_line, Hunk, HunkKind};
+use seeker_resource::clipboard::SeekerClipboard;
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditSubLoadState, SeekerEditViewState, SeekerFileDialogFnState};
+use seeker_trait::text_input::{is_printable_char, SeekerTextInput};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+pub const LINE_HEIGHT: f32 = 20.;
+pub const GUTTER_WIDTH: f32 = 60.;
+/// 诊断波浪线的高度和每段的宽度
+const SQUIGGLE_HEIGHT: f32 = 3.;
+const SQUIGGLE_STEP: f32 = 2.;
+
+/// 等宽字体一个半角字符的宽度
+pub fn cell_width() -> f32 {
+    SEEKER_CONFIG.font_size * 0.6
+}
+
+/// 位置在编辑器内容中的坐标（字符左上角），折叠的行不占高度，软换行后从所在段的开头算起
+pub fn position_to_point(doc: &Document, wrap: &WrapLayout, pos: Position) -> Vec2 {
+    let row = wrap.row(doc, pos);
+    let (line, columns) = wrap.segment(doc, row);
+    let text = doc.buffer.line(pos.line);
+    let start = if line == pos.line {
+        column_display_width(text, columns.start)
+    } else {
+        0
+    };
+    let x = column_display_width(text, pos.column).saturating_sub(start);
+    Vec2::new(
+        GUTTER_WIDTH + x as f32 * cell_width(),
+        row as f32 * LINE_HEIGHT,
+    )
+}
+
+/// 编辑器视图，光标属于视图而不是文档
+#[derive(Component, Default)]
+#[require(Node, FocusPolicy::Block, Interaction, RelativeCursorPosition)]
+pub struct EditorView {
+    pub path: Option<PathBuf>,
+    pub cursor: Position,
+    /// 选区起点
+    pub anchor: Option<Position>,
+    /// 上下移动时保持的列，按显示宽度从显示行的开头算起
+    preferred_column: Option<usize>,
+    /// 切换文档时记住各文档的光标
+    cursors: HashMap<PathBuf, Position>,
+    /// 这个视图的标签，按打开的顺序
+    pub tabs: Vec<PathBuf>,
+    /// 软换行布局，宽度随视图变化
+    pub wrap: WrapLayout,
+}
+
+impl EditorView {
+    /// 打开文档，没有指定位置时恢复上次的光标
+    pub fn open(&mut self, path: PathBuf, position: Option<Position>) {
+        if let Some(current) = self.path.take() {
+            self.cursors.insert(current, self.cursor);
+        }
+        if !self.tabs.contains(&path) {
+            self.tabs.push(path.clone());
+        }
+        self.cursor = position
+            .or_else(|| self.cursors.get(&path).copied())
+            .unwrap_or_default();
+        self.path = Some(path);
+        self.anchor = None;
+        self.preferred_column = None;
+    }
+
+    /// 关闭标签，关闭的是当前文档时切换到相邻的标签
+    pub fn close(&mut self, path: &Path) {
+        self.cursors.remove(path);
+        let Some(index) = self.tabs.iter().position(|tab| tab == path) else {
+            return;
+        };
+        self.tabs.remove(index);
+        if self.path.as_deref() == Some(path) {
+            self.path = None;
+            self.cursor = Position::default();
+            self.anchor = None;
+            if let Some(next) = self.tabs.get(index).or(self.tabs.last()).cloned() {
+                self.open(next, None);
+            }
+        }
+    }
+
+    /// 标签的光标，当前文档用视图的光标
+    pub fn cursor_of(&self, path: &Path) -> Option<Position> {
+        if self.path.as_deref() == Some(path) {
+            return Some(self.cursor);
+        }
+        self.cursors.get(path).copied()
+    }
+
+    /// 文档从磁盘重新读取后，把光标限制在新的内容中
+    pub fn reloaded(&mut self, doc: &Document) {
+        if let Some(cursor) = self.cursors.get_mut(&doc.path) {
+            *cursor = doc.buffer.clamp(*cursor);
+        }
+        if self.path.as_ref() == Some(&doc.path) {
+            self.cursor = doc.buffer.clamp(self.cursor);
+            self.anchor = None;
+            self.preferred_column = None;
+        }
+    }
+
+    pub fn selection(&self) -> Option<(Position, Position)> {
+        self.anchor
+            .filter(|anchor| *anchor != self.cursor)
+            .map(|anchor| ordered(anchor, self.cursor))
+    }
+
+    /// 选中 start 到 end，两者相同时只移动光标
+    pub fn select_range(&mut self, start: Position, end: Position) {
+        self.anchor = (start != end).then_some(start);
+        self.cursor = end;
+        self.preferred_column = None;
+    }
+
+    fn move_to(&mut self, pos: Position, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = pos;
+    }
+
+    /// 插入文本，有选区时先删除选区
+    pub fn insert(&mut self, doc: &mut Document, text: &str) {
+        self.delete_selection(doc);
+        self.cursor = doc.insert(self.cursor, text);
+        self.preferred_column = None;
+    }
+
+    /// 输入字符：补全括号和引号、跳过已有的右括号、把选区用括号包起来，
+    /// 在空白行输入右括号时对齐到左括号的缩进
+    pub fn type_text(&mut self, doc: &mut Document, text: &str) {
+        let mut chars = text.chars();
+        let (Some(ch), None) = (chars.next(), chars.next()) else {
+            self.insert(doc, text);
+            return;
+        };
+        let pair = bracket::auto_pair(ch, &doc.language_id);
+        if let (Some(close), Some((start, end))) = (pair, self.selection()) {
+            doc.insert(end, &close.to_string());
+            doc.insert(start, &ch.to_string());
+            let shift = |pos: Position| {
+                let column = pos.column + usize::from(pos.line == start.line);
+                Position::new(pos.line, column)
+            };
+            self.anchor = self.anchor.map(shift);
+            self.cursor = shift(self.cursor);
+            return;
+        }
+        let cursor = self.cursor;
+        let next = doc.buffer.line(cursor.line).chars().nth(cursor.column);
+        let closes = bracket::opening(ch).is_some() || pair == Some(ch);
+        if self.selection().is_none() && closes && next == Some(ch) {
+            self.move_to(Position::new(cursor.line, cursor.column + 1), false);
+            self.preferred_column = None;
+            return;
+        }
+        if bracket::opening(ch).is_some()
+            && self.selection().is_none()
+            && let Some(indent) = indent::outdent_for_close(&doc.buffer, cursor, ch)
+        {
+            doc.delete(Position::new(cursor.line, 0), cursor);
+            self.cursor = doc.insert(Position::new(cursor.line, 0), &indent);
+        }
+        match pair {
+            Some(close) if bracket::should_pair(&doc.buffer, self.cursor, ch) => {
+                self.insert(doc, &format!("{ch}{close}"));
+                self.cursor.column -= 1;
+            }
+            _ => self.insert(doc, text),
+        }
+    }
+
+    /// 换行并按语言规则缩进
+    pub fn newline(&mut self, doc: &mut Document) {
+        self.delete_selection(doc);
+        let (before, after) =
+            indent::newline(&doc.buffer, self.cursor, &doc.language_id, doc.indent());
+        let cursor = doc.insert(self.cursor, &before);
+        doc.insert(cursor, &after);
+        self.cursor = cursor;
+        self.preferred_column = None;
+    }
+
+    /// 缩进或减少缩进选区所在的行，没有选区时只处理光标所在的行
+    pub fn indent_lines(&mut self, doc: &mut Document, outdent: bool) {
+        let (start, end) = self.selection().unwrap_or((self.cursor, self.cursor));
+        // 选区结束在行首时不包含这一行
+        let last = if end.line > start.line && end.column == 0 {
+            end.line - 1
+        } else {
+            end.line
+        };
+        let style = doc.indent();
+        let unit = style.unit();
+        let mut shifts = HashMap::new();
+        for line in start.line..=last {
+            let text = doc.buffer.line(line);
+            if outdent {
+                let width = indent::outdent_width(text, style);
+                if width > 0 {
+                    doc.delete(Position::new(line, 0), Position::new(line, width));
+                    shifts.insert(line, -(width as isize));
+                }
+            } else if !text.is_empty() || start.line == last {
+                doc.insert(Position::new(line, 0), &unit);
+                shifts.insert(line, unit.chars().count() as isize);
+            }
+        }
+        let shift = |pos: Position| match shifts.get(&pos.line) {
+            Some(delta) => Position::new(pos.line, pos.column.saturating_add_signed(*delta)),
+            None => pos,
+        };
+        self.anchor = self.anchor.map(shift);
+        self.cursor = shift(self.cursor);
+        self.preferred_column = None;
+    }
+
+    /// 退格，光标在一对空括号或引号中间时一起删除
+    pub fn backspace(&mut self, doc: &mut Document) {
+        self.preferred_column = None;
+        if self.delete_selection(doc) {
+            return;
+        }
+        let cursor = self.cursor;
+        let chars: Vec<char> = doc.buffer.line(cursor.line).chars().collect();
+        let prev = cursor.column.checked_sub(1).and_then(|i| chars.get(i));
+        let next = chars.get(cursor.column);
+        let pair = prev.and_then(|prev| bracket::auto_pair(*prev, &doc.language_id));
+        let end = if pair.is_some() && pair.as_ref() == next {
+            Position::new(cursor.line, cursor.column + 1)
+        } else {
+            cursor
+        };
+        let prev = doc.buffer.prev_position(cursor);
+        doc.delete(prev, end);
+        self.cursor = prev;
+    }
+
+    /// 复制的范围和文本，没有选区时复制光标所在的整行
+    pub fn copy_range(&self, doc: &Document) -> (Position, Position, String) {
+        if let Some((start, end)) = self.selection() {
+            return (start, end, doc.buffer.slice(start, end));
+        }
+        let line = self.cursor.line;
+        let text = format!("{}\n", doc.buffer.line(line));
+        let (start, end) = if line + 1 < doc.buffer.line_count() {
+            (Position::new(line, 0), Position::new(line + 1, 0))
+        } else if line > 0 {
+            let prev = line - 1;
+            (Position::new(prev, doc.buffer.line_len(prev)), doc.buffer.end())
+        } else {
+            (Position::new(line, 0), doc.buffer.end())
+        };
+        (start, end, text)
+    }
+
+    /// 剪切选区或者光标所在的整行
+    pub fn cut(&mut self, doc: &mut Document) -> String {
+        let (start, end, text) = self.copy_range(doc);
+        doc.delete(start, end);
+        self.cursor = start;
+        self.anchor = None;
+        self.preferred_column = None;
+        text
+    }
+
+    pub fn delete_selection(&mut self, doc: &mut Document) -> bool {
+        let Some((start, end)) = self.selection() else {
+            self.anchor = None;
+            return false;
+        };
+        doc.delete(start, end);
+        self.cursor = start;
+        self.anchor = None;
+        true
+    }
+}
+
+#[derive(Component)]
+pub struct FocusedEditor;
+
+/// 所有行的容器，高度等于全部行高，只渲染可见行
+#[derive(Component)]
+pub struct EditorContent;
+
+/// 聚焦编辑器的内容节点，弹出层和注释生成在其中
+#[derive(SystemParam)]
+pub struct FocusedContent<'w, 's> {
+    views: Query<'w, 's, &'static Children, With<FocusedEditor>>,
+    contents: Query<'w, 's, (), With<EditorContent>>,
+}
+
+impl FocusedContent<'_, '_> {
+    pub fn get(&self) -> Option<Entity> {
+        let children = self.views.single().ok()?;
+        children.iter().find(|child| self.contents.contains(*child))
+    }
+}
+
+#[derive(Component)]
+struct EditorRow;
+
+/// 上次渲染时的状态，没有变化就不重建
+#[derive(Component, Default, PartialEq)]
+struct EditorRenderKey {
+    path: Option<PathBuf>,
+    version: i32,
+    folds: u32,
+    wrap: Option<usize>,
+    first: usize,
+    last: usize,
+    cursor: Position,
+    anchor: Option<Position>,
+}
+
+/// 弹出层打开时由弹出层处理上下、回车、Tab 和 Esc
+#[derive(Resource, Default)]
+pub struct EditorInputCapture {
+    pub navigation: bool,
+    /// 面板中的输入框获得焦点时编辑器不处理键盘和输入法
+    pub text: bool,
+    /// 片段展开和跳转时由片段处理 Tab
+    pub tab: bool,
+}
+
+/// 软换行开关，Alt+Z 切换
+#[derive(Resource)]
+pub struct SoftWrap {
+    pub enabled: bool,
+}
+
+impl Default for SoftWrap {
+    fn default() -> Self {
+        Self {
+            enabled: SEEKER_CONFIG.soft_wrap,
+        }
+    }
+}
+
+/// 编辑器输入和渲染，弹出层在它之后处理按键
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EditorSystems;
+
+#[derive(Component)]
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorInputCapture>()
+            .init_resource::<SoftWrap>()
+            .init_resource::<Diagnostics>()
+            .add_message::<DocumentSaved>()
+            .add_systems(OnEnter(SeekerEditSubLoadState::Loaded), Self::enable_ime)
+            .add_systems(PreUpdate, Self::capture_text_input)
+            .add_systems(
+                Update,
+                (
+                    Self::toggle_soft_wrap,
+                    Self::update_wrap,
+                    Self::keyboard_input,
+                    Self::ime_input,
+                    Self::mouse_input,
+                    Self::update_wrap,
+                    Self::scroll_to_cursor,
+                    Self::render,
+                )
+                    .chain()
+                    .in_set(EditorSystems)
+                    // 文件对话框是单独的窗口，打开时按键不输入到编辑器
+                    .run_if(
+                        in_state(SeekerEditSubLoadState::Loaded)
+                            .and(in_state(SeekerEditViewState::Editor))
+                            .and(not(in_state(SeekerFileDialogFnState::Open))),
+                    ),
+            );
+    }
+}
+
+impl EditorPlugin {
+    fn enable_ime(mut window: Single<&mut Window>) {
+        window.ime_enabled = true;
+    }
+
+    /// 在处理这一帧的按键之前确定是否有输入框获得焦点，
+    /// 输入框按 Esc 失去焦点的这一帧编辑器和视图也不处理这个 Esc
+    fn capture_text_input(
+        inputs: Query<&SeekerTextInput>,
+        mut capture: ResMut<EditorInputCapture>,
+    ) {
+        let focused = inputs.iter().any(|input| input.focused);
+        if capture.text != focused {
+            capture.text = focused;
+        }
+    }
+
+    /// 生成编辑器视图和它的内容节点，由分屏放进窗格
+    pub fn spawn_editor(commands: &mut Commands, view: EditorView) -> Entity {
+        commands
+            .spawn((
+                view,
+                EditorRenderKey::default(),
+                Node {
+                    flex_grow: 1.,
+                    min_height: Val::Px(0.),
+                    margin: UiRect::right(Val::Px(overlay_width())),
+                    overflow: Overflow::scroll(),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn((EditorContent, Node::default()));
+            })
+            .id()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn keyboard_input(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        capture: Res<EditorInputCapture>,
+        mut documents: ResMut<Documents>,
+        view: Single<(&mut EditorView, &ComputedNode), With<FocusedEditor>>,
+        mut saved: MessageWriter<DocumentSaved>,
+        mut format: MessageWriter<FormatDocument>,
+        mut clipboard: ResMut<SeekerClipboard>,
+        mut pasted: Local<Option<(PathBuf, Position, Position)>>,
+        mut typed: Local<Option<(PathBuf, Position)>>,
+    ) {
+        if reader.is_empty() {
+            return;
+        }
+        if capture.text {
+            reader.clear();
+            return;
+        }
+        let (mut view, computed) = view.into_inner();
+        let Some(path) = view.path.clone() else {
+            reader.clear();
+            return;
+        };
+        let Some(doc) = documents.get_mut(&path) else {
+            return;
+        };
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        let page = (computed.size().y * computed.inverse_scale_factor() / LINE_HEIGHT) as usize;
+
+        for event in reader.read() {
+            if !event.state.is_pressed() {
+                continue;
+            }
+            if capture.navigation
+                && matches!(
+                    event.logical_key,
+                    Key::ArrowUp | Key::ArrowDown | Key::Enter | Key::Tab | Key::Escape
+                )
+            {
+                continue;
+            }
+            let cursor = view.cursor;
+            // 接着上一次输入的字符一起撤销，其他按键开始新的一组
+            let typing = !command && matches!(event.logical_key, Key::Character(_) | Key::Space);
+            if !typing || typed.as_ref() != Some(&(path.clone(), cursor)) {
+                doc.seal_undo();
+            }
+            match &event.logical_key {
+                // Alt+Z 切换软换行，Shift+Alt+F 格式化
+                Key::Character(_)
+                    if alt && matches!(event.key_code, KeyCode::KeyZ | KeyCode::KeyF) => {}
+                Key::Character(ch) if command => match ch.as_str() {
+                    // 格式化完成后再保存
+                    "s" if SEEKER_CONFIG.format_on_save
+                        && SEEKER_CONFIG.formatters.contains_key(&doc.language_id) =>
+                    {
+                        format.write(FormatDocument {
+                            path: path.clone(),
+                            lines: None,
+                            save: true,
+                        });
+                    }
+                    "s" => match doc.save() {
+                        Ok(()) => {
+                            // 保存时可能去掉了行尾的空白
+                            view.cursor = doc.buffer.clamp(view.cursor);
+                            view.anchor = view.anchor.map(|anchor| doc.buffer.clamp(anchor));
+                            saved.write(DocumentSaved { path: path.clone() });
+                        }
+                        Err(err) => error!("save {}: {err}", path.display()),
+                    },
+                    "z" if !shift => {
+                        if let Some(pos) = doc.undo() {
+                            view.select_range(pos, pos);
+                        }
+                    }
+                    "z" | "Z" | "y" => {
+                        if let Some(pos) = doc.redo() {
+                            view.select_range(pos, pos);
+                        }
+                    }
+                    "a" => {
+                        view.anchor = Some(Position::default());
+                        view.cursor = doc.buffer.end();
+                    }
+                    "c" => clipboard.copy(view.copy_range(doc).2),
+                    "x" => {
+                        let text = view.cut(doc);
+                        clipboard.copy(text);
+                    }
+                    // Ctrl+Shift+V 把刚粘贴的内容换成历史中的下一条
+                    "v" | "V" if shift => {
+                        let Some((_, start, end)) = pasted
+                            .take()
+                            .filter(|(pasted, _, end)| *pasted == path && *end == view.cursor)
+                        else {
+                            continue;
+                        };
+                        let Some(entry) = clipboard.cycle() else {
+                            continue;
+                        };
+                        let text = entry.text.clone();
+                        view.select_range(start, end);
+                        view.insert(doc, &text);
+                        *pasted = Some((path.clone(), start, view.cursor));
+                    }
+                    "v" => {
+                        let Some(text) = clipboard.paste() else {
+                            continue;
+                        };
+                        view.delete_selection(doc);
+                        let start = view.cursor;
+                        view.insert(doc, &text);
+                        *pasted = Some((path.clone(), start, view.cursor));
+                    }
+                    _ => {}
+                },
+                Key::ArrowLeft => {
+                    let pos = doc.buffer.prev_position(cursor);
+                    view.move_to(pos, shift);
+                    view.preferred_column = None;
+                }
+                Key::ArrowRight => {
+                    let pos = doc.buffer.next_position(cursor);
+                    view.move_to(pos, shift);
+                    view.preferred_column = None;
+                }
+                Key::ArrowUp => Self::move_vertical(&mut view, doc, -1, shift),
+                Key::ArrowDown => Self::move_vertical(&mut view, doc, 1, shift),
+                Key::PageUp => Self::move_vertical(&mut view, doc, -(page as isize), shift),
+                Key::PageDown => Self::move_vertical(&mut view, doc, page as isize, shift),
+                Key::Home => {
+                    view.move_to(Position::new(cursor.line, 0), shift);
+                    view.preferred_column = None;
+                }
+                Key::End => {
+                    let end = doc.buffer.line_len(cursor.line);
+                    view.move_to(Position::new(cursor.line, end), shift);
+                    view.preferred_column = None;
+                }
+                Key::Enter => view.newline(doc),
+                Key::Tab if capture.tab => {}
+                Key::Tab if shift => view.indent_lines(doc, true),
+                Key::Tab => match view.selection() {
+                    Some((start, end)) if start.line != end.line => view.indent_lines(doc, false),
+                    _ => {
+                        let unit = doc.indent().unit();
+                        view.insert(doc, &unit);
+                    }
+                },
+                Key::Escape => view.anchor = None,
+                Key::Backspace => view.backspace(doc),
+                Key::Delete => {
+                    if !view.delete_selection(doc) {
+                        let next = doc.buffer.next_position(cursor);
+                        doc.delete(cursor, next);
+                    }
+                }
+                _ => {
+                    if command {
+                        continue;
+                    }
+                    // 控制字符（例如 Esc）不插入
+                    if let Some(text) = &event.text
+                        && text.chars().all(is_printable_char)
+                    {
+                        view.type_text(doc, text);
+                    }
+                }
+            }
+            *typed = typing.then(|| (path.clone(), view.cursor));
+        }
+    }
+
+    /// 按显示的行移动，跳过折叠的行，软换行时在同一行的各段之间移动
+    fn move_vertical(view: &mut EditorView, doc: &Document, delta: isize, select: bool) {
+        // 同一帧中前面的按键可能修改了文档
+        let width = view.wrap.width();
+        view.wrap.update(doc, width);
+        let cursor = view.cursor;
+        let row = view.wrap.row(doc, cursor);
+        let x = position_to_point(doc, &view.wrap, cursor).x - GUTTER_WIDTH;
+        let preferred = *view
+            .preferred_column
+            .get_or_insert((x / cell_width()).round() as usize);
+        let row = row
+            .saturating_add_signed(delta)
+            .min(view.wrap.row_count(doc) - 1);
+        let pos = row_position(doc, &view.wrap, row, preferred as f32);
+        view.move_to(pos, select);
+    }
+
+    fn toggle_soft_wrap(
+        keys: Res<ButtonInput<KeyCode>>,
+        capture: Res<EditorInputCapture>,
+        mut soft_wrap: ResMut<SoftWrap>,
+    ) {
+        let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        if alt && keys.just_pressed(KeyCode::KeyZ) && !capture.text {
+            soft_wrap.enabled = !soft_wrap.enabled;
+        }
+    }
+
+    /// 视图宽度、文档或者折叠变化后重新计算软换行
+    fn update_wrap(
+        soft_wrap: Res<SoftWrap>,
+        documents: Res<Documents>,
+        mut views: Query<(&mut EditorView, &ComputedNode)>,
+    ) {
+        for (mut view, computed) in views.iter_mut() {
+            let Some(doc) = view.path.clone().and_then(|path| documents.get(&path)) else {
+                continue;
+            };
+            let width = soft_wrap.enabled.then(|| wrap_width(computed)).flatten();
+            if view.bypass_change_detection().wrap.update(doc, width) {
+                view.set_changed();
+            }
+        }
+    }
+
+    fn ime_input(
+        mut reader: MessageReader<Ime>,
+        capture: Res<EditorInputCapture>,
+        mut documents: ResMut<Documents>,
+        mut view: Single<&mut EditorView, With<FocusedEditor>>,
+    ) {
+        if capture.text {
+            reader.clear();
+            return;
+        }
+        for ime in reader.read() {
+            if let Ime::Commit { value, .. } = ime {
+                let Some(doc) = view.path.as_ref().and_then(|path| documents.get_mut(path)) else {
+                    continue;
+                };
+                doc.seal_undo();
+                view.insert(doc, value);
+            }
+        }
+    }
+
+    fn mouse_input(
+        keys: Res<ButtonInput<KeyCode>>,
+        documents: Res<Documents>,
+        mut query: Query<
+            (
+                &Interaction,
+                &RelativeCursorPosition,
+                &ComputedNode,
+                &ScrollPosition,
+                &mut EditorView,
+            ),
+            Changed<Interaction>,
+        >,
+    ) {
+        for (interaction, relative, computed, scroll, mut view) in query.iter_mut() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            let Some(normalized) = relative.normalized else {
+                continue;
+            };
+            let Some(doc) = view.path.as_ref().and_then(|path| documents.get(path)) else {
+                continue;
+            };
+            let size = computed.size() * computed.inverse_scale_factor();
+            let point = (normalized + Vec2::splat(0.5)) * size + scroll.0;
+            let pos = point_to_position(doc, &view.wrap, point);
+            let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+            view.move_to(pos, shift);
+            view.preferred_column = None;
+        }
+    }
+
+    /// 光标移出可见区域时滚动，光标移到折叠的行中时展开
+    fn scroll_to_cursor(
+        mut documents: ResMut<Documents>,
+        mut query: Query<(&EditorView, &mut ScrollPosition, &ComputedNode), Changed<EditorView>>,
+    ) {
+        for (view, mut scroll, computed) in query.iter_mut() {
+            let Some(path) = view.path.as_ref() else {
+                continue;
+            };
+            if documents
+                .get(path)
+                .is_some_and(|doc| doc.folds.is_hidden(view.cursor.line))
+                && let Some(doc) = documents.get_mut(path)
+            {
+                doc.folds.reveal(view.cursor.line);
+            }
+            let Some(doc) = documents.get(path) else {
+                continue;
+            };
+            let size = computed.size() * computed.inverse_scale_factor();
+            // 刚生成的视图还没有布局，保留恢复的滚动位置
+            if size.y <= 0. {
+                continue;
+            }
+            let point = position_to_point(doc, &view.wrap, view.cursor);
+            if point.y < scroll.y {
+                scroll.y = point.y;
+            } else if point.y + LINE_HEIGHT > scroll.y + size.y {
+                scroll.y = point.y + LINE_HEIGHT - size.y;
+            }
+            let left = point.x - GUTTER_WIDTH;
+            if left < scroll.x {
+                scroll.x = left;
+            } else if point.x + cell_width() > scroll.x + size.x {
+                scroll.x = point.x + cell_width() - size.x;
+            }
+        }
+    }
+
+    /// 虚拟化渲染：只为可见的行生成节点
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
+    fn render(
+        mut commands: Commands,
+        documents: Res<Documents>,
+        diagnostics: Res<Diagnostics>,
+        diffs: Res<GitDiffs>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+        mut views: Query<(
+            &EditorView,
+            &ScrollPosition,
+            &ComputedNode,
+            &Children,
+            &mut EditorRenderKey,
+        )>,
+        mut contents: Query<(&mut Node, Option<&Children>), With<EditorContent>>,
+        rows: Query<(), With<EditorRow>>,
+    ) {
+        let font = assets.load(MAPLE_MONO_BOLD);
+        for (view, scroll, computed, children, mut key) in views.iter_mut() {
+            let Some(content) = children.iter().find(|child| contents.contains(*child)) else {
+                continue;
+            };
+            let doc = view.path.as_ref().and_then(|path| documents.get(path));
+            let height = computed.size().y * computed.inverse_scale_factor();
+            // first 和 last 是显示的行，折叠的行被跳过
+            let first = (scroll.y / LINE_HEIGHT).floor().max(0.) as usize;
+            let row_count = doc.map(|doc| view.wrap.row_count(doc)).unwrap_or_default();
+            let last = (first + (height / LINE_HEIGHT).ceil() as usize + 1).min(row_count);
+            let new_key = EditorRenderKey {
+                path: view.path.clone(),
+                version: doc.map(|doc| doc.version).unwrap_or_default(),
+                folds: doc.map(|doc| doc.folds.revision()).unwrap_or_default(),
+                wrap: view.wrap.width(),
+                first,
+                last,
+                cursor: view.cursor,
+                anchor: view.anchor,
+            };
+            if *key == new_key && !diagnostics.is_changed() && !diffs.is_changed() {
+                continue;
+            }
+            *key = new_key;
+
+            let Ok((mut node, content_children)) = contents.get_mut(content) else {
+                continue;
+            };
+            for child in content_children.into_iter().flatten() {
+                if rows.contains(*child) {
+                    commands.entity(*child).despawn();
+                }
+            }
+            let Some(doc) = doc else {
+                node.width = Val::Px(0.);
+                node.height = Val::Px(0.);
+                continue;
+            };
+            let max_width = match view.wrap.width() {
+                Some(width) => width,
+                None => doc.buffer.lines().map(str_display_width).max().unwrap_or(0),
+            };
+            node.width = Val::Px(GUTTER_WIDTH + (max_width + 2) as f32 * cell_width());
+            node.height = Val::Px(row_count as f32 * LINE_HEIGHT);
+
+            let selection = view.selection();
+            let brackets = bracket::matching_bracket(&doc.buffer, view.cursor);
+            let hunks = diffs.hunks(&doc.path);
+            commands.entity(content).with_children(|parent| {
+                for row in first..last {
+                    let (line, columns) = view.wrap.segment(doc, row);
+                    Self::render_row(
+                        parent,
+                        doc,
+                        line,
+                        columns,
+                        view.wrap.continues(doc, row),
+                        row,
+                        view,
+                        selection,
+                        brackets,
+                        &diagnostics,
+                        hunks,
+                        &res,
+                        &font,
+                    );
+                }
+            });
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_row(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        doc: &Document,
+        line: usize,
+        columns: Range<usize>,
+        continues: bool,
+        row: usize,
+        view: &EditorView,
+        selection: Option<(Position, Position)>,
+        brackets: Option<(Position, Position)>,
+        diagnostics: &Diagnostics,
+        hunks: &[Hunk],
+        res: &SeekerResource,
+        font: &Handle<Font>,
+    ) {
+        let text_font = TextFont {
+            font: font.clone(),
+            font_size: SEEKER_CONFIG.font_size,
+            line_height: LineHeight::Px(LINE_HEIGHT),
+            ..default()
+        };
+        let worst = diagnostics.worst_on_line(&doc.path, line);
+        let gutter_color = match worst.map(|diagnostic| diagnostic.severity) {
+            Some(Severity::Error) => res.colors.error,
+            Some(Severity::Warning) => res.colors.warning,
+            Some(_) => res.colors.info,
+            None => res.colors.home_font_grey_color,
+        };
+        let background = if line == view.cursor.line {
+            res.colors.editor_current_line
+        } else {
+            Color::NONE
+        };
+
+        parent
+            .spawn((
+                EditorRow,
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(row as f32 * LINE_HEIGHT),
+                    left: Val::Px(0.),
+                    min_width: Val::Percent(100.),
+                    height: Val::Px(LINE_HEIGHT),
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+                BackgroundColor(background),
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn(Node {
+                        width: Val::Px(GUTTER_WIDTH),
+                        min_width: Val::Px(GUTTER_WIDTH),
+                        padding: UiRect::right(Val::Px(FOLD_GUTTER_WIDTH + FOLD_GUTTER_RIGHT + 2.)),
+                        justify_content: JustifyContent::End,
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        // 软换行接续的段用箭头代替行号
+                        let (number, color) = if columns.start == 0 {
+                            ((line + 1).to_string(), gutter_color)
+                        } else {
+                            ("↪".to_string(), res.colors.home_font_grey_color)
+                        };
+                        parent.spawn((Text::new(number), text_font.clone(), TextColor(color)));
+                    });
+                if columns.start == 0 && doc.folds.range_at(line).is_some() {
+                    FoldPlugin::render_toggle(parent, doc, line, res, font);
+                }
+                if let Some(index) = hunk_at_line(hunks, line) {
+                    Self::render_diff_marker(parent, doc, line, hunks[index].kind(), res);
+                }
+                parent
+                    .spawn((
+                        Text::default(),
+                        text_font.clone(),
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(res.colors.home_font_color),
+                    ))
+                    .with_children(|parent| {
+                        for (text, color, background) in line_spans(
+                            doc,
+                            line,
+                            &columns,
+                            continues,
+                            view.cursor,
+                            selection,
+                            brackets,
+                            res,
+                        ) {
+                            parent.spawn((
+                                TextSpan::new(text),
+                                text_font.clone(),
+                                TextColor(color),
+                                TextBackgroundColor(background),
+                            ));
+                        }
+                        // 折叠的区域用省略号表示
+                        if !continues && doc.folds.is_folded(line) {
+                            parent.spawn((
+                                TextSpan::new(" ⋯ "),
+                                text_font.clone(),
+                                TextColor(res.colors.home_font_grey_color),
+                                TextBackgroundColor(res.colors.editor_selection),
+                            ));
+                        }
+                    });
+                for (left, width, color) in squiggles(doc, line, &columns, diagnostics, res) {
+                    Self::render_squiggle(parent, left, width, color);
+                }
+            });
+    }
+
+    /// 行号右侧的改动标记，删除的行画在下一行的上边缘
+    fn render_diff_marker(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        doc: &Document,
+        line: usize,
+        kind: HunkKind,
+        res: &SeekerResource,
+    ) {
+        let (color, top, width, height) = match kind {
+            HunkKind::Added => (res.colors.git_added, 0., 3., LINE_HEIGHT),
+            HunkKind::Modified => (res.colors.git_modified, 0., 3., LINE_HEIGHT),
+            HunkKind::Deleted => (res.colors.error, -2., 8., 4.),
+        };
+        parent.spawn((
+            GutterMarker {
+                path: doc.path.clone(),
+                line,
+            },
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(GUTTER_WIDTH - 10.),
+                top: Val::Px(top),
+                width: Val::Px(width),
+                height: Val::Px(height),
+                ..default()
+            },
+            ZIndex(1),
+            BackgroundColor(color),
+        ));
+    }
+
+    /// 用上下交替的短线段画波浪线
+    fn render_squiggle(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        left: f32,
+        width: f32,
+        color: Color,
+    ) {
+        parent
+            .spawn(Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(left),
+                top: Val::Px(LINE_HEIGHT - SQUIGGLE_HEIGHT),
+                width: Val::Px(width),
+                height: Val::Px(SQUIGGLE_HEIGHT),
+                overflow: Overflow::clip(),
+                ..default()
+            })
+            .with_children(|parent| {
+                let steps = (width / SQUIGGLE_STEP).ceil() as usize;
+                for step in 0..steps {
+                    parent.spawn((
+                        Node {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(step as f32 * SQUIGGLE_STEP),
+                            top: Val::Px(if step % 2 == 0 {
+                                0.
+                            } else {
+                                SQUIGGLE_HEIGHT - 1.
+                            }),
+                            width: Val::Px(SQUIGGLE_STEP),
+                            height: Val::Px(1.),
+                            ..default()
+                        },
+                        BackgroundColor(color),
+                    ));
+                }
+            });
+    }
+}
+
+/// 把一行（软换行时是其中的一段）按颜色切分成若干段
+#[allow(clippy::too_many_arguments)]
+fn line_spans(
+    doc: &Document,
+    line: usize,
+    columns: &Range<usize>,
+    continues: bool,
+    cursor: Position,
+    selection: Option<(Position, Position)>,
+    brackets: Option<(Position, Position)>,
+    res: &SeekerResource,
+) -> Vec<(String, Color, Color)> {
+    let mut spans: Vec<(String, Color, Color)> = vec![];
+    let text = doc.buffer.line(line);
+    // 光标在行尾时画在最后一段的末尾
+    let end = (cursor.line == line && !continues).then_some((columns.end, ' '));
+    let chars = text
+        .chars()
+        .enumerate()
+        .skip(columns.start)
+        .take(columns.len())
+        .chain(end);
+    for (column, ch) in chars {
+        let pos = Position::new(line, column);
+        let mut color = res.colors.home_font_color;
+        let mut background = Color::NONE;
+        if selection.is_some_and(|(start, end)| start <= pos && pos < end) {
+            background = res.colors.editor_selection;
+        }
+        if brackets.is_some_and(|(a, b)| pos == a || pos == b) {
+            background = res.colors.editor_bracket;
+        }
+        if pos == cursor {
+            color = res.colors.editor_background;
+            background = res.colors.editor_cursor;
+        }
+        match spans.last_mut() {
+            Some((text, last_color, last_background))
+                if *last_color == color && *last_background == background =>
+            {
+                text.push(ch)
+            }
+            _ => spans.push((ch.to_string(), color, background)),
+        }
+    }
+    spans
+}
+
+/// 诊断在一行（软换行时是其中的一段）中的波浪线，返回 (左侧坐标, 宽度, 颜色)
+fn squiggles(
+    doc: &Document,
+    line: usize,
+    columns: &Range<usize>,
+    diagnostics: &Diagnostics,
+    res: &SeekerResource,
+) -> Vec<(f32, f32, Color)> {
+    let text = doc.buffer.line(line);
+    let line_len = doc.buffer.line_len(line);
+    let offset = column_display_width(text, columns.start);
+    diagnostics
+        .for_path(&doc.path)
+        .filter(|diagnostic| diagnostic.start.line <= line && line <= diagnostic.end.line)
+        .filter_map(|diagnostic| {
+            let start = if diagnostic.start.line == line {
+                diagnostic.start.column.min(line_len)
+            } else {
+                0
+            };
+            let end = if diagnostic.end.line == line {
+                diagnostic.end.column.min(line_len)
+            } else {
+                line_len
+            };
+            // 只画落在这一段中的部分，段末尾的位置属于下一段
+            let last = columns.end == line_len;
+            if end < columns.start || start > columns.end || (!last && start == columns.end) {
+                return None;
+            }
+            let (start, end) = (start.max(columns.start), end.min(columns.end));
+            let left = column_display_width(text, start) - offset;
+            // 空范围至少画一个字符宽
+            let width = (column_display_width(text, end) - offset)
+                .saturating_sub(left)
+                .max(1);
+            let color = match diagnostic.severity {
+                Severity::Error => res.colors.error,
+                Severity::Warning => res.colors.warning,
+                _ => res.colors.info,
+            };
+            Some((
+                GUTTER_WIDTH + left as f32 * cell_width(),
+                width as f32 * cell_width(),
+                color,
+            ))
+        })
+        .collect()
+}
+
+/// 编辑器内容坐标转换为文本位置
+pub fn point_to_position(doc: &Document, wrap: &WrapLayout, point: Vec2) -> Position {
+    let row = ((point.y / LINE_HEIGHT).max(0.) as usize).min(wrap.row_count(doc) - 1);
+    let width = (point.x - GUTTER_WIDTH).max(0.) / cell_width();
+    row_position(doc, wrap, row, width)
+}
+
+/// 显示行中显示宽度 width 处的位置，软换行的段不会停在段末尾（那是下一段的开头）
+fn row_position(doc: &Document, wrap: &WrapLayout, row: usize, width: f32) -> Position {
+    let (line, columns) = wrap.segment(doc, row);
+    let text: String = doc
+        .buffer
+        .line(line)
+        .chars()
+        .skip(columns.start)
+        .take(columns.len())
+        .collect();
+    let mut column = columns.start + column_at_display_width(&text, width);
+    if wrap.continues(doc, row) {
+        column = column.min(columns.end.saturating_sub(1));
+    }
+    Position::new(line, column)
+}
+
+/// 按视图宽度换行时的列数，配置了列数时使用配置
+fn wrap_width(computed: &ComputedNode) -> Option<usize> {
+    if let Some(column) = SEEKER_CONFIG.wrap_column {
+        return Some(column.max(1));
+    }
+    let width = computed.size().x * computed.inverse_scale_factor() - GUTTER_WIDTH;
+    // 视图还没有布局时不换行
+    if width <= 0. {
+        return None;
+    }
+    Some(((width / cell_width()) as usize).saturating_sub(2).max(10))
+}
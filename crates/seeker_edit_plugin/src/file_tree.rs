@@ -0,0 +1,206 @@
+use crate::{FileTreeUi, OpenLocation};
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use seeker_git::plugin::{GitStatus, ProjectFilesChanged};
+use seeker_git::status::FileStatus;
+use seeker_resource::file::{get_files, Level};
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::project_list::CurrentProject;
+use seeker_resource::SeekerResource;
+use seeker_state::SeekerEditSubLoadState;
+use seeker_trait::SeekerTrait;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Resource, Default)]
+pub struct FileTree {
+    pub root: Option<PathBuf>,
+    pub expanded: HashSet<PathBuf>,
+}
+
+#[derive(Component, Clone)]
+pub struct FileTreeItem {
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct FileTreeButton;
+
+#[derive(Component)]
+pub struct FileTreePlugin;
+
+impl SeekerTrait for FileTreePlugin {}
+
+impl Plugin for FileTreePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FileTree>()
+            .add_systems(OnEnter(SeekerEditSubLoadState::Loaded), Self::enter)
+            .add_systems(
+                Update,
+                (
+                    Self::click,
+                    Self::files_changed,
+                    Self::render
+                        .run_if(resource_changed::<FileTree>.or(resource_changed::<GitStatus>)),
+                )
+                    .chain()
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, FileTreeButton>);
+    }
+}
+
+impl FileTreePlugin {
+    /// 展开的目录由 SessionPlugin 恢复
+    fn enter(mut tree: ResMut<FileTree>, current_project: Res<CurrentProject>) {
+        tree.root = current_project
+            .project
+            .as_ref()
+            .map(|project| PathBuf::from(&project.path));
+    }
+
+    fn click(
+        mut tree: ResMut<FileTree>,
+        query: Query<(&Interaction, &FileTreeItem), Changed<Interaction>>,
+        mut open: MessageWriter<OpenLocation>,
+    ) {
+        for (interaction, item) in query.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            if item.is_dir {
+                if !tree.expanded.remove(&item.path) {
+                    tree.expanded.insert(item.path.clone());
+                }
+            } else {
+                open.write(OpenLocation {
+                    path: item.path.clone(),
+                    position: None,
+                });
+            }
+        }
+    }
+
+    /// 文件被创建或者删除后重新读取目录
+    fn files_changed(mut reader: MessageReader<ProjectFilesChanged>, mut tree: ResMut<FileTree>) {
+        if reader.read().count() > 0 {
+            tree.set_changed();
+        }
+    }
+
+    fn render(
+        mut commands: Commands,
+        tree: Res<FileTree>,
+        git: Res<GitStatus>,
+        query: Query<Entity, With<FileTreeUi>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let Some(root) = tree.root.as_ref() else {
+            return;
+        };
+        let font = assets.load(MAPLE_MONO_BOLD);
+        for entity in query.iter() {
+            commands
+                .entity(entity)
+                .despawn_children()
+                .with_children(|parent| {
+                    Self::render_dir(parent, &tree, &git, root, 0, &res, &font);
+                });
+        }
+    }
+
+    fn render_dir(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        tree: &FileTree,
+        git: &GitStatus,
+        dir: &Path,
+        depth: usize,
+        res: &SeekerResource,
+        font: &Handle<Font>,
+    ) {
+        let mut files = get_files(dir.to_path_buf(), Entity::PLACEHOLDER, Level::new(depth));
+        files.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then(a.filename.cmp(&b.filename)));
+        for file in files {
+            let expanded = tree.expanded.contains(&file.path);
+            let icon = match (file.is_dir, expanded) {
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+                _ => "  ",
+            };
+            let status = git
+                .status
+                .as_ref()
+                .and_then(|status| status.status_of(&file.path));
+            let color = status_color(status, res);
+            parent
+                .spawn((
+                    FileTreeButton,
+                    FileTreeItem {
+                        path: file.path.clone(),
+                        is_dir: file.is_dir,
+                    },
+                    Hovered::default(),
+                    Node {
+                        width: Val::Percent(100.),
+                        min_height: Val::Px(22.),
+                        padding: UiRect::left(Val::Px(8. + depth as f32 * 14.)),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new(format!("{icon}{}", file.filename)),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 14.,
+                            ..default()
+                        },
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(color),
+                    ));
+                    // 目录只用颜色表示，文件在右侧显示状态字母
+                    if let Some(status) = status.filter(|_| !file.is_dir) {
+                        parent.spawn(Node {
+                            flex_grow: 1.,
+                            ..default()
+                        });
+                        parent.spawn((
+                            Text::new(status.letter()),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: 12.,
+                                ..default()
+                            },
+                            TextColor(color),
+                            Node {
+                                margin: UiRect::right(Val::Px(10.)),
+                                ..default()
+                            },
+                        ));
+                    }
+                });
+            if file.is_dir && expanded {
+                Self::render_dir(parent, tree, git, &file.path, depth + 1, res, font);
+            }
+        }
+    }
+}
+
+/// git 状态对应的文字颜色
+pub fn status_color(status: Option<FileStatus>, res: &SeekerResource) -> Color {
+    match status {
+        Some(FileStatus::Added) => res.colors.git_added,
+        Some(FileStatus::Modified | FileStatus::Renamed) => res.colors.git_modified,
+        Some(FileStatus::Untracked) => res.colors.git_untracked,
+        Some(FileStatus::Deleted | FileStatus::Conflicted) => res.colors.error,
+        Some(FileStatus::Ignored) => res.colors.home_font_grey_color,
+        None => res.colors.home_font_color,
+    }
+}
@@ -0,0 +1,256 @@
+use crate::editor::{EditorInputCapture, EditorSystems, GUTTER_WIDTH, LINE_HEIGHT};
+use crate::{EditorView, FocusedEditor};
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use bevy::window::RequestRedraw;
+use seeker_editor::buffer::Position;
+use seeker_editor::document::{Document, Documents};
+use seeker_lsp::plugin::{LspRequest, LspRequestKind, LspResponse, LspServers};
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditSubLoadState, SeekerEditViewState};
+use seeker_trait::SeekerTrait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 停止输入这么久之后重新计算折叠区域
+const FOLD_REFRESH_DELAY: Duration = Duration::from_millis(300);
+
+/// 行号右侧折叠按钮的宽度，按钮和正文之间留出改动标记的位置
+pub const FOLD_GUTTER_WIDTH: f32 = 12.;
+pub const FOLD_GUTTER_RIGHT: f32 = 10.;
+
+/// 行号旁边的折叠按钮
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct FoldToggle {
+    pub path: PathBuf,
+    pub line: usize,
+}
+
+#[derive(Component)]
+pub struct FoldPlugin;
+
+impl SeekerTrait for FoldPlugin {}
+
+impl Plugin for FoldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                Self::refresh,
+                Self::request_syntax_folds,
+                Self::receive_syntax_folds,
+                Self::keybindings,
+                Self::click,
+            )
+                .chain()
+                .before(EditorSystems)
+                .run_if(
+                    in_state(SeekerEditSubLoadState::Loaded)
+                        .and(in_state(SeekerEditViewState::Editor)),
+                ),
+        );
+    }
+}
+
+impl FoldPlugin {
+    /// 编辑后延迟重新计算折叠区域，等待期间继续请求重绘以便到时计算
+    fn refresh(mut documents: ResMut<Documents>, mut redraw: MessageWriter<RequestRedraw>) {
+        let mut waiting = false;
+        // 折叠的变化通过 revision 判断，不触发文档的变化检测
+        for doc in documents.bypass_change_detection().documents.iter_mut() {
+            waiting |= doc.folds.refresh_after(&doc.buffer, FOLD_REFRESH_DELAY);
+        }
+        if waiting {
+            redraw.write(RequestRedraw);
+        }
+    }
+
+    /// 打开文档、服务器启动或者停止输入后请求按语法结构的折叠区域，
+    /// 没有语言服务器时保留按缩进计算的区域
+    fn request_syntax_folds(
+        documents: Res<Documents>,
+        servers: Res<LspServers>,
+        mut requests: MessageWriter<LspRequest>,
+        mut requested: Local<HashMap<PathBuf, i32>>,
+    ) {
+        requested.retain(|path, _| documents.get(path).is_some());
+        for doc in &documents.documents {
+            if doc.folds.is_editing()
+                || requested.get(&doc.path) == Some(&doc.version)
+                || !servers.is_running(&doc.language_id)
+            {
+                continue;
+            }
+            requested.insert(doc.path.clone(), doc.version);
+            requests.write(LspRequest {
+                path: doc.path.clone(),
+                position: Position::default(),
+                kind: LspRequestKind::FoldingRange,
+            });
+        }
+    }
+
+    fn receive_syntax_folds(
+        mut responses: MessageReader<LspResponse>,
+        mut documents: ResMut<Documents>,
+    ) {
+        for response in responses.read() {
+            let LspResponse::FoldingRanges {
+                path,
+                version,
+                ranges,
+            } = response
+            else {
+                continue;
+            };
+            // 折叠的变化通过 revision 判断
+            if let Some(doc) = documents.bypass_change_detection().get_mut(path)
+                && doc.version == *version
+            {
+                doc.folds.set_syntax_ranges(ranges.clone());
+            }
+        }
+    }
+
+    /// Ctrl+Shift+[ 折叠，Ctrl+Shift+] 展开；
+    /// Ctrl+K 之后 Ctrl+0 全部折叠，Ctrl+J 全部展开，Ctrl+1..9 折叠对应层级
+    fn keybindings(
+        keys: Res<ButtonInput<KeyCode>>,
+        capture: Res<EditorInputCapture>,
+        mut documents: ResMut<Documents>,
+        mut view: Single<&mut EditorView, With<FocusedEditor>>,
+        mut chord: Local<bool>,
+    ) {
+        // 单独按下修饰键不打断 Ctrl+K 组合键
+        let modifiers = [
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+            KeyCode::ShiftLeft,
+            KeyCode::ShiftRight,
+        ];
+        if capture.text || keys.get_just_pressed().all(|key| modifiers.contains(key)) {
+            return;
+        }
+        let command = keys.any_pressed(modifiers[..4].iter().copied());
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let pending = std::mem::take(&mut *chord);
+        if !command {
+            return;
+        }
+        let Some(doc) = view.path.clone().and_then(|path| documents.get_mut(&path)) else {
+            return;
+        };
+        let line = view.cursor.line;
+        if pending {
+            let level = (1..=9).find(|level| keys.just_pressed(digit(*level)));
+            if keys.just_pressed(KeyCode::Digit0) {
+                doc.folds.fold_all();
+            } else if keys.just_pressed(KeyCode::KeyJ) {
+                doc.folds.unfold_all();
+            } else if let Some(level) = level {
+                doc.folds.fold_level(level);
+            } else {
+                return;
+            }
+        } else if keys.just_pressed(KeyCode::KeyK) && !shift {
+            *chord = true;
+            return;
+        } else if shift && keys.just_pressed(KeyCode::BracketLeft) {
+            doc.folds.fold(line);
+        } else if shift && keys.just_pressed(KeyCode::BracketRight) {
+            doc.folds.unfold(line);
+        } else {
+            return;
+        }
+        Self::move_out_of_fold(&mut view, doc);
+    }
+
+    fn click(
+        query: Query<(&Interaction, &FoldToggle), Changed<Interaction>>,
+        mut documents: ResMut<Documents>,
+        mut views: Query<&mut EditorView>,
+    ) {
+        for (interaction, toggle) in query.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            let Some(doc) = documents.get_mut(&toggle.path) else {
+                continue;
+            };
+            doc.folds.toggle(toggle.line);
+            for mut view in views.iter_mut() {
+                if view.path.as_ref() == Some(&toggle.path) {
+                    Self::move_out_of_fold(&mut view, doc);
+                }
+            }
+        }
+    }
+
+    /// 光标所在的行被折叠后移到折叠的起始行
+    fn move_out_of_fold(view: &mut EditorView, doc: &Document) {
+        let line = view.cursor.line;
+        if !doc.folds.is_hidden(line) {
+            return;
+        }
+        let start = doc.folds.buffer_line(doc.folds.display_row(line));
+        view.cursor = doc.buffer.clamp(Position::new(start, view.cursor.column));
+        view.anchor = None;
+    }
+
+    pub fn render_toggle(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        doc: &Document,
+        line: usize,
+        res: &SeekerResource,
+        font: &Handle<Font>,
+    ) {
+        let folded = doc.folds.is_folded(line);
+        parent.spawn((
+            FoldToggle {
+                path: doc.path.clone(),
+                line,
+            },
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(GUTTER_WIDTH - FOLD_GUTTER_WIDTH - FOLD_GUTTER_RIGHT),
+                width: Val::Px(FOLD_GUTTER_WIDTH),
+                height: Val::Px(LINE_HEIGHT),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            children![(
+                Text::new(if folded { "▸" } else { "▾" }),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 12.,
+                    ..default()
+                },
+                TextColor(if folded {
+                    res.colors.home_font_color
+                } else {
+                    res.colors.home_font_grey_color
+                }),
+            )],
+        ));
+    }
+}
+
+fn digit(level: usize) -> KeyCode {
+    [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ][level - 1]
+}
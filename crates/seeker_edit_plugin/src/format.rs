@@ -0,0 +1,204 @@
+use crate::editor::EditorSystems;
+use crate::{EditorView, FocusedEditor};
+use bevy::input::keyboard::KeyboardInput;
+use bevy::prelude::*;
+use seeker_config::SEEKER_CONFIG;
+use seeker_editor::document::{DocumentSaved, Documents};
+use seeker_editor::format::{apply_formatted, run_formatter};
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake};
+use seeker_state::SeekerEditSubLoadState;
+use seeker_trait::SeekerTrait;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::TryRecvError;
+
+/// 格式化文档，lines 为选中的行时只应用和这些行相交的修改，save 为 true 时格式化后保存
+#[derive(Message, Clone, Debug)]
+pub struct FormatDocument {
+    pub path: PathBuf,
+    pub lines: Option<Range<usize>>,
+    pub save: bool,
+}
+
+/// 后台运行的格式化
+#[derive(Resource, Default)]
+pub struct Formatting {
+    running: Vec<FormatTask>,
+}
+
+struct FormatTask {
+    path: PathBuf,
+    /// 开始格式化时的文档版本，结果回来前文档被修改时丢弃结果
+    version: i32,
+    lines: Option<Range<usize>>,
+    save: bool,
+    receiver: BackgroundReceiver<std::io::Result<String>>,
+}
+
+#[derive(Component)]
+pub struct FormatPlugin;
+
+impl SeekerTrait for FormatPlugin {}
+
+impl Plugin for FormatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Formatting>()
+            .add_message::<FormatDocument>()
+            .add_systems(OnExit(SeekerEditSubLoadState::Loaded), Self::exit)
+            .add_systems(
+                Update,
+                (Self::keybindings, Self::start, Self::finish)
+                    .chain()
+                    .after(EditorSystems)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            );
+    }
+}
+
+impl FormatPlugin {
+    fn exit(mut formatting: ResMut<Formatting>) {
+        formatting.running.clear();
+    }
+
+    /// Shift+Alt+F 格式化文档，有选区时只格式化选中的行
+    fn keybindings(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        mut format: MessageWriter<FormatDocument>,
+    ) {
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        for event in reader.read() {
+            // Alt 在 macOS 上会改变输入的字符，这里用物理按键
+            if !event.state.is_pressed() || !shift || !alt || event.key_code != KeyCode::KeyF {
+                continue;
+            }
+            let Some(path) = view.path.clone() else {
+                continue;
+            };
+            let lines = view
+                .selection()
+                .map(|(start, end)| start.line..end.line + 1);
+            format.write(FormatDocument {
+                path,
+                lines,
+                save: false,
+            });
+        }
+    }
+
+    fn start(
+        mut reader: MessageReader<FormatDocument>,
+        mut formatting: ResMut<Formatting>,
+        mut documents: ResMut<Documents>,
+        mut views: Query<&mut EditorView>,
+        wake: Res<BackgroundWake>,
+        mut saved: MessageWriter<DocumentSaved>,
+    ) {
+        for FormatDocument { path, lines, save } in reader.read() {
+            let Some(doc) = documents.get(path) else {
+                continue;
+            };
+            let Some(config) = SEEKER_CONFIG.formatters.get(&doc.language_id) else {
+                if *save {
+                    Self::save(&mut documents, &mut views, path, &mut saved);
+                } else {
+                    warn!("no formatter for {}", doc.language_id);
+                }
+                continue;
+            };
+            let (text, version) = (doc.buffer.text(), doc.version);
+            let receiver = wake.spawn({
+                let (path, config) = (path.clone(), config.clone());
+                move || run_formatter(&config.command, &config.args, &path, &text)
+            });
+            formatting.running.retain(|task| task.path != *path);
+            formatting.running.push(FormatTask {
+                path: path.clone(),
+                version,
+                lines: lines.clone(),
+                save: *save,
+                receiver,
+            });
+        }
+    }
+
+    /// 应用格式化结果，格式化失败时保存也照常进行
+    fn finish(
+        mut formatting: ResMut<Formatting>,
+        mut documents: ResMut<Documents>,
+        mut views: Query<&mut EditorView>,
+        mut saved: MessageWriter<DocumentSaved>,
+    ) {
+        if formatting.running.is_empty() {
+            return;
+        }
+        let mut done = vec![];
+        for task in std::mem::take(&mut formatting.running) {
+            let received = task.receiver.try_recv();
+            match received {
+                Ok(result) => done.push((task, result)),
+                Err(TryRecvError::Empty) => formatting.running.push(task),
+                Err(TryRecvError::Disconnected) => {}
+            }
+        }
+        for (task, result) in done {
+            let Some(doc) = documents.get_mut(&task.path) else {
+                continue;
+            };
+            match result {
+                Ok(_) if doc.version != task.version => {
+                    warn!("{} changed while formatting", task.path.display());
+                }
+                Ok(formatted) => {
+                    let mut open: Vec<_> = views
+                        .iter_mut()
+                        .filter(|view| view.path.as_ref() == Some(&task.path))
+                        .collect();
+                    // 每个视图的光标和选区起点
+                    let mut positions: Vec<_> = open
+                        .iter()
+                        .flat_map(|view| [view.cursor, view.anchor.unwrap_or(view.cursor)])
+                        .collect();
+                    if apply_formatted(doc, &formatted, task.lines, &mut positions) {
+                        for (view, pos) in open.iter_mut().zip(positions.chunks(2)) {
+                            view.cursor = pos[0];
+                            view.anchor = view.anchor.map(|_| pos[1]);
+                        }
+                    }
+                }
+                Err(err) => error!("format {}: {err}", task.path.display()),
+            }
+            if task.save {
+                Self::save(&mut documents, &mut views, &task.path, &mut saved);
+            }
+        }
+    }
+
+    fn save(
+        documents: &mut Documents,
+        views: &mut Query<&mut EditorView>,
+        path: &Path,
+        saved: &mut MessageWriter<DocumentSaved>,
+    ) {
+        let Some(doc) = documents.get_mut(path) else {
+            return;
+        };
+        match doc.save() {
+            Ok(()) => {
+                // 保存时可能去掉了行尾的空白
+                for mut view in views.iter_mut() {
+                    if view.path.as_deref() == Some(path) {
+                        view.cursor = doc.buffer.clamp(view.cursor);
+                        view.anchor = view.anchor.map(|anchor| doc.buffer.clamp(anchor));
+                    }
+                }
+                saved.write(DocumentSaved {
+                    path: path.to_path_buf(),
+                });
+            }
+            Err(err) => error!("save {}: {err}", path.display()),
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use crate::editor::{EditorContent, EditorSystems, GUTTER_WIDTH, LINE_HEIGHT};
+use crate::{EditorView, FocusedEditor};
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use seeker_editor::buffer::Position;
+use seeker_editor::document::Documents;
+use seeker_git::diff::{diff_lines, hunk_at_line, index_text, lines, stage_hunk, Hunk};
+use seeker_git::plugin::GitStatus;
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake, WakeSender};
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::SeekerResource;
+use seeker_state::SeekerEditSubLoadState;
+use seeker_trait::SeekerTrait;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// 已打开文档相对于暂存区的改动
+#[derive(Resource, Default)]
+pub struct GitDiffs {
+    /// 暂存区中的内容，None 表示文件没有被跟踪
+    bases: HashMap<PathBuf, Option<String>>,
+    /// 计算时的文档版本和改动
+    hunks: HashMap<PathBuf, (i32, Vec<Hunk>)>,
+    /// 正在后台比较的文档
+    running: HashSet<PathBuf>,
+    /// 暂存区变化时自增，之前开始的比较结果被丢弃
+    generation: u64,
+    channel: Option<(WakeSender<DiffResult>, BackgroundReceiver<DiffResult>)>,
+}
+
+/// 后台比较一个文档的结果
+struct DiffResult {
+    path: PathBuf,
+    version: i32,
+    generation: u64,
+    base: Option<String>,
+    hunks: Vec<Hunk>,
+}
+
+impl GitDiffs {
+    pub fn hunks(&self, path: &Path) -> &[Hunk] {
+        self.hunks
+            .get(path)
+            .map(|(_, hunks)| hunks.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// 行号旁边的改动标记，点击后预览改动
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct GutterMarker {
+    pub path: PathBuf,
+    pub line: usize,
+}
+
+#[derive(Component)]
+pub struct HunkPopup {
+    path: PathBuf,
+    version: i32,
+    hunk: Hunk,
+    /// 已经点过一次还原，再点一次才真正还原
+    confirm_revert: bool,
+}
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct RevertHunkButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct StageHunkButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct CloseHunkButton;
+
+#[derive(Component)]
+pub struct GitGutterPlugin;
+
+impl SeekerTrait for GitGutterPlugin {}
+
+impl Plugin for GitGutterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GitDiffs>()
+            .add_systems(OnExit(SeekerEditSubLoadState::Loaded), Self::exit)
+            .add_systems(
+                Update,
+                (
+                    Self::receive_diffs,
+                    Self::update_diffs.run_if(
+                        resource_changed::<Documents>
+                            .or(resource_changed::<GitStatus>)
+                            .or(resource_changed::<GitDiffs>),
+                    ),
+                )
+                    .chain()
+                    .before(EditorSystems)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_systems(
+                Update,
+                (
+                    Self::click_marker,
+                    Self::revert,
+                    Self::stage,
+                    Self::dismiss_popup,
+                )
+                    .chain()
+                    .after(EditorSystems)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, RevertHunkButton>,
+            )
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, StageHunkButton>)
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, CloseHunkButton>,
+            );
+    }
+}
+
+impl GitGutterPlugin {
+    fn exit(mut diffs: ResMut<GitDiffs>) {
+        *diffs = GitDiffs::default();
+    }
+
+    /// 暂存区变化后重新读取基准，版本变化的文档在后台重新比较，
+    /// 每个文档同时只有一个比较，连续输入时比较完再比较最新的版本
+    fn update_diffs(
+        git: Res<GitStatus>,
+        documents: Res<Documents>,
+        mut diffs: ResMut<GitDiffs>,
+        wake: Res<BackgroundWake>,
+    ) {
+        let cache = diffs.bypass_change_detection();
+        let mut changed = false;
+        if git.is_changed() {
+            cache.bases.clear();
+            cache.hunks.clear();
+            cache.running.clear();
+            cache.generation += 1;
+            changed = true;
+        }
+        let open = |path: &Path| documents.get(path).is_some();
+        cache.bases.retain(|path, _| open(path));
+        cache.hunks.retain(|path, _| open(path));
+        let (sender, _) = cache.channel.get_or_insert_with(|| wake.channel());
+        for doc in &documents.documents {
+            if cache.running.contains(&doc.path)
+                || cache
+                    .hunks
+                    .get(&doc.path)
+                    .is_some_and(|(version, _)| *version == doc.version)
+            {
+                continue;
+            }
+            cache.running.insert(doc.path.clone());
+            let base = cache.bases.get(&doc.path).cloned();
+            let (path, version, text) = (doc.path.clone(), doc.version, doc.buffer.text());
+            let (generation, sender) = (cache.generation, sender.clone());
+            std::thread::spawn(move || {
+                let base = base.unwrap_or_else(|| index_text(&path).ok().flatten());
+                let hunks = match &base {
+                    Some(base) => diff_lines(base, &text),
+                    None => vec![],
+                };
+                sender.send(DiffResult {
+                    path,
+                    version,
+                    generation,
+                    base,
+                    hunks,
+                });
+            });
+        }
+        if changed {
+            diffs.set_changed();
+        }
+    }
+
+    fn receive_diffs(documents: Res<Documents>, mut diffs: ResMut<GitDiffs>) {
+        let Some((_, receiver)) = diffs.channel.as_ref() else {
+            return;
+        };
+        let (results, _) = receiver.drain();
+        let cache = diffs.bypass_change_detection();
+        let mut changed = false;
+        for result in results {
+            if result.generation != cache.generation {
+                continue;
+            }
+            cache.running.remove(&result.path);
+            if documents.get(&result.path).is_none() {
+                continue;
+            }
+            cache.bases.insert(result.path.clone(), result.base);
+            cache
+                .hunks
+                .insert(result.path, (result.version, result.hunks));
+            changed = true;
+        }
+        if changed {
+            diffs.set_changed();
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn click_marker(
+        mut commands: Commands,
+        query: Query<(Entity, &Interaction, &GutterMarker), Changed<Interaction>>,
+        popups: Query<Entity, With<HunkPopup>>,
+        diffs: Res<GitDiffs>,
+        documents: Res<Documents>,
+        parents: Query<&ChildOf>,
+        contents: Query<(), With<EditorContent>>,
+        views: Query<&EditorView>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        for (entity, interaction, marker) in query.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            popups
+                .iter()
+                .for_each(|popup| commands.entity(popup).despawn());
+            let hunks = diffs.hunks(&marker.path);
+            let (Some(index), Some(doc), Some(Some(base))) = (
+                hunk_at_line(hunks, marker.line),
+                documents.get(&marker.path),
+                diffs.bases.get(&marker.path),
+            ) else {
+                continue;
+            };
+            // 弹出层放在标记所在窗格的编辑器中
+            let Some(content) = parents
+                .iter_ancestors(entity)
+                .find(|ancestor| contents.contains(*ancestor))
+            else {
+                continue;
+            };
+            let Some(view) = parents
+                .iter_ancestors(content)
+                .find_map(|ancestor| views.get(ancestor).ok())
+            else {
+                continue;
+            };
+            let hunk = hunks[index].clone();
+            let current = doc.buffer.text();
+            let below = Position::new(hunk.new.end.max(hunk.new.start + 1), 0);
+            let top = view.wrap.row(doc, below) as f32 * LINE_HEIGHT;
+            commands.entity(content).with_children(|parent| {
+                parent
+                    .spawn((
+                        HunkPopup {
+                            path: marker.path.clone(),
+                            version: doc.version,
+                            hunk: hunk.clone(),
+                            confirm_revert: false,
+                        },
+                        Node {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(GUTTER_WIDTH),
+                            top: Val::Px(top),
+                            min_width: Val::Px(400.),
+                            max_width: Val::Px(800.),
+                            padding: UiRect::all(Val::Px(8.)),
+                            border: UiRect::all(Val::Px(1.)),
+                            display: Display::Flex,
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(6.),
+                            ..default()
+                        },
+                        ZIndex(10),
+                        BorderColor::all(res.colors.button_border),
+                        BackgroundColor(res.colors.home_menu),
+                        BorderRadius::all(Val::Px(3.)),
+                    ))
+                    .with_children(|parent| {
+                        parent
+                            .spawn(Node {
+                                column_gap: Val::Px(10.),
+                                align_items: AlignItems::Center,
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                Self::ui_button(
+                                    parent,
+                                    RevertHunkButton,
+                                    "RevertHunk",
+                                    "Revert",
+                                    &res,
+                                    &assets,
+                                );
+                                Self::ui_button(
+                                    parent,
+                                    StageHunkButton,
+                                    "StageHunk",
+                                    "Stage",
+                                    &res,
+                                    &assets,
+                                );
+                                parent.spawn(Node {
+                                    flex_grow: 1.,
+                                    ..default()
+                                });
+                                Self::ui_button_same(parent, CloseHunkButton, "×", &res, &assets);
+                            });
+                        let removed = lines(base, hunk.old.clone());
+                        let added = lines(&current, hunk.new.clone());
+                        Self::render_lines(parent, &removed, "- ", res.colors.error, &assets);
+                        Self::render_lines(parent, &added, "+ ", res.colors.git_added, &assets);
+                    });
+            });
+        }
+    }
+
+    fn render_lines(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        text: &str,
+        prefix: &str,
+        color: Color,
+        assets: &AssetServer,
+    ) {
+        for line in text.lines() {
+            parent.spawn((
+                Text::new(format!("{prefix}{line}")),
+                TextFont {
+                    font: assets.load(MAPLE_MONO_BOLD),
+                    font_size: 13.,
+                    ..default()
+                },
+                TextLayout::new_with_no_wrap(),
+                TextColor(color),
+                BackgroundColor(color.with_alpha(0.1)),
+            ));
+        }
+    }
+
+    /// 用暂存区中的内容替换这处改动，第一次点击只要求确认，替换后可以撤销
+    #[allow(clippy::too_many_arguments)]
+    fn revert(
+        mut commands: Commands,
+        query: Query<&Interaction, (Changed<Interaction>, With<RevertHunkButton>)>,
+        buttons: Query<&Children, With<RevertHunkButton>>,
+        mut texts: Query<&mut Text>,
+        popup: Single<(Entity, &mut HunkPopup)>,
+        diffs: Res<GitDiffs>,
+        mut documents: ResMut<Documents>,
+        mut view: Single<&mut EditorView, With<FocusedEditor>>,
+    ) {
+        if !query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            return;
+        }
+        let (entity, mut popup) = popup.into_inner();
+        if !popup.confirm_revert {
+            popup.confirm_revert = true;
+            for child in buttons.iter().flat_map(|children| children.iter()) {
+                if let Ok(mut text) = texts.get_mut(child) {
+                    text.0 = "Discard changes?".to_string();
+                }
+            }
+            return;
+        }
+        commands.entity(entity).despawn();
+        let (Some(doc), Some(Some(base))) =
+            (documents.get_mut(&popup.path), diffs.bases.get(&popup.path))
+        else {
+            return;
+        };
+        let hunk = &popup.hunk;
+        let start = Position::new(hunk.new.start, 0);
+        let last = doc.buffer.line_count() - 1;
+        let end = if hunk.new.end <= last {
+            Position::new(hunk.new.end, 0)
+        } else {
+            Position::new(last, doc.buffer.line_len(last))
+        };
+        doc.seal_undo();
+        if start < end {
+            doc.delete(start, end);
+        }
+        doc.insert(start, &lines(base, hunk.old.clone()));
+        doc.seal_undo();
+        if view.path.as_ref() == Some(&popup.path) {
+            view.cursor = doc.buffer.clamp(Position::new(hunk.new.start, 0));
+            view.anchor = None;
+        }
+    }
+
+    /// 只暂存这处改动，文件中的其他改动保持不变
+    fn stage(
+        mut commands: Commands,
+        query: Query<&Interaction, (Changed<Interaction>, With<StageHunkButton>)>,
+        popup: Single<(Entity, &HunkPopup)>,
+        diffs: Res<GitDiffs>,
+        documents: Res<Documents>,
+        mut git: ResMut<GitStatus>,
+        wake: Res<BackgroundWake>,
+    ) {
+        if !query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            return;
+        }
+        let (entity, popup) = *popup;
+        commands.entity(entity).despawn();
+        let (Some(doc), Some(Some(base))) =
+            (documents.get(&popup.path), diffs.bases.get(&popup.path))
+        else {
+            return;
+        };
+        if let Err(err) = stage_hunk(&popup.path, base, &doc.buffer.text(), &popup.hunk) {
+            error!("stage hunk {}: {err}", popup.path.display());
+            return;
+        }
+        // 刷新后重新读取暂存区
+        git.refresh(&wake);
+    }
+
+    /// 点击关闭、按 Esc、切换或者编辑文档时关闭预览
+    fn dismiss_popup(
+        mut commands: Commands,
+        mut reader: MessageReader<KeyboardInput>,
+        close: Query<&Interaction, (Changed<Interaction>, With<CloseHunkButton>)>,
+        popups: Query<(Entity, &HunkPopup)>,
+        documents: Res<Documents>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+    ) {
+        let escape = reader
+            .read()
+            .any(|event| event.state.is_pressed() && event.logical_key == Key::Escape);
+        let closed = close
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+        for (entity, popup) in popups.iter() {
+            let stale = documents
+                .get(&popup.path)
+                .is_none_or(|doc| doc.version != popup.version);
+            if escape || closed || stale || view.path.as_ref() != Some(&popup.path) {
+                commands.entity(entity).try_despawn();
+            }
+        }
+    }
+}
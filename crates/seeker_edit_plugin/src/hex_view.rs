@@ -0,0 +1,976 @@
+use crate::diff_view::DiffViewPlugin;
+use crate::editor::{cell_width, EditorInputCapture, LINE_HEIGHT};
+use crate::EditorUi;
+use bevy::ecs::query::QueryFilter;
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::text::LineHeight;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+use seeker_config::SEEKER_CONFIG;
+use seeker_editor::hex::{column_byte, offset_digits, parse_offset, parse_pattern, printable};
+use seeker_editor::hex::{HexFile, BYTES_PER_ROW};
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake};
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditSubLoadState, SeekerEditViewState};
+use seeker_trait::text_input::TextInputSystems;
+use seeker_trait::text_input::{SeekerTextInput, TextInputCancel, TextInputSubmit};
+use seeker_trait::SeekerTrait;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const HEX_PADDING: f32 = 10.;
+const HEX_SCROLLBAR_WIDTH: f32 = 10.;
+const HEX_THUMB_MIN_HEIGHT: f32 = 20.;
+
+/// 用十六进制视图打开文件
+#[derive(Message, Clone, Debug)]
+pub struct OpenHex {
+    pub path: PathBuf,
+}
+
+/// 输入框的用途
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HexPrompt {
+    Goto,
+    Find,
+}
+
+/// 后台搜索发回的进度（已经扫描的字节数）和结果
+enum HexSearchEvent {
+    Progress(u64),
+    Done(Option<u64>),
+}
+
+/// 正在进行的后台搜索，drop 时取消
+struct HexSearch {
+    cancelled: Arc<AtomicBool>,
+    receiver: BackgroundReceiver<HexSearchEvent>,
+}
+
+impl Drop for HexSearch {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 十六进制视图的状态，只渲染可见的行，文件再大也只读取这些行的字节
+#[derive(Resource, Default)]
+pub struct HexView {
+    file: Option<HexFile>,
+    /// 光标所在的字节
+    cursor: u64,
+    /// 已经输入了光标处字节的高半字节
+    low_nibble: bool,
+    /// 光标在 ASCII 列，输入字符而不是十六进制
+    ascii: bool,
+    /// 默认只读，Ctrl+E 切换
+    editable: bool,
+    /// 第一个可见的行
+    top: u64,
+    /// 可见的行数，由布局决定
+    rows: u64,
+    prompt: Option<HexPrompt>,
+    /// 上一次搜索的字节和找到的位置
+    pattern: Vec<u8>,
+    found: Option<u64>,
+    /// 大文件的搜索可能需要几秒，在后台进行
+    search: Option<HexSearch>,
+    message: Option<String>,
+    /// 有未保存的修改时第一次 Esc 只提示
+    closing: bool,
+}
+
+impl HexView {
+    fn len(&self) -> u64 {
+        self.file.as_ref().map(HexFile::len).unwrap_or_default()
+    }
+
+    fn row_count(&self) -> u64 {
+        self.len().div_ceil(BYTES_PER_ROW as u64)
+    }
+
+    fn scroll_to(&mut self, top: u64) {
+        self.top = top.min(self.row_count().saturating_sub(self.rows));
+    }
+
+    /// 光标所在的行不可见时滚动到可见
+    fn scroll_into_view(&mut self) {
+        let row = self.cursor / BYTES_PER_ROW as u64;
+        let rows = self.rows.max(1);
+        if row < self.top {
+            self.scroll_to(row);
+        } else if row >= self.top + rows {
+            self.scroll_to(row + 1 - rows);
+        }
+    }
+
+    fn move_to(&mut self, offset: u64) {
+        self.cursor = offset.min(self.len().saturating_sub(1));
+        self.low_nibble = false;
+        self.scroll_into_view();
+    }
+
+    fn move_by(&mut self, delta: i64) {
+        self.move_to(self.cursor.saturating_add_signed(delta));
+    }
+
+    fn open_prompt(&mut self, prompt: HexPrompt) {
+        self.prompt = Some(prompt);
+    }
+
+    fn submit(&mut self, prompt: HexPrompt, input: &str, wake: &BackgroundWake) {
+        match prompt {
+            HexPrompt::Goto => match parse_offset(input) {
+                Some(offset) if offset < self.len() => self.move_to(offset),
+                Some(_) => self.message = Some("Offset out of range".to_string()),
+                None => self.message = Some("Invalid offset".to_string()),
+            },
+            HexPrompt::Find => match parse_pattern(input) {
+                Some(pattern) => {
+                    self.pattern = pattern;
+                    self.found = None;
+                    self.find_next(wake);
+                }
+                None => self.message = Some("Invalid pattern".to_string()),
+            },
+        }
+    }
+
+    /// 从光标开始在后台查找，光标正好在上一次找到的位置时从下一个字节开始。
+    /// 搜索的是开始时文件内容的副本，新的搜索会取消上一次
+    fn find_next(&mut self, wake: &BackgroundWake) {
+        if self.pattern.is_empty() {
+            self.open_prompt(HexPrompt::Find);
+            return;
+        }
+        let Some(file) = self.file.as_ref().map(HexFile::snapshot) else {
+            return;
+        };
+        let from = if self.found == Some(self.cursor) {
+            self.cursor + 1
+        } else {
+            self.cursor
+        };
+        let pattern = self.pattern.clone();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = wake.channel();
+        std::thread::spawn({
+            let cancelled = cancelled.clone();
+            move || {
+                let found = file.find(&pattern, from, &cancelled, |scanned| {
+                    sender.send(HexSearchEvent::Progress(scanned));
+                });
+                if !cancelled.load(Ordering::Relaxed) {
+                    sender.send(HexSearchEvent::Done(found));
+                }
+            }
+        });
+        self.found = None;
+        self.search = Some(HexSearch {
+            cancelled,
+            receiver,
+        });
+        self.message = Some("Searching".to_string());
+    }
+
+    /// 十六进制列输入半个字节，ASCII 列输入一个字符
+    fn type_char(&mut self, ch: char) {
+        let cursor = self.cursor;
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        let Some(byte) = file.byte(cursor) else {
+            return;
+        };
+        if self.ascii {
+            if ch.is_ascii() && !ch.is_ascii_control() {
+                file.set(cursor, ch as u8);
+                self.move_by(1);
+            }
+            return;
+        }
+        let Some(digit) = ch.to_digit(16) else {
+            return;
+        };
+        let digit = digit as u8;
+        if self.low_nibble {
+            file.set(cursor, byte & 0xf0 | digit);
+            self.move_by(1);
+        } else {
+            file.set(cursor, digit << 4 | byte & 0x0f);
+            self.low_nibble = true;
+        }
+    }
+
+    fn undo(&mut self) {
+        match self.file.as_mut().and_then(HexFile::undo) {
+            Some(offset) => self.move_to(offset),
+            None => self.message = Some("Nothing to undo".to_string()),
+        }
+    }
+
+    fn save(&mut self) {
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        self.message = Some(match file.save() {
+            Ok(()) => "Saved".to_string(),
+            Err(err) => {
+                error!("save {}: {err}", file.path.display());
+                format!("Save failed: {err}")
+            }
+        });
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.file.as_ref().is_some_and(HexFile::is_dirty)
+    }
+
+    /// 一个字节在十六进制列或者 ASCII 列中的文字和背景颜色
+    fn style(&self, offset: u64, byte: u8, ascii: bool, res: &SeekerResource) -> (Color, Color) {
+        let file = self.file.as_ref();
+        let color = if file.is_some_and(|file| file.is_edited(offset)) {
+            res.colors.warning
+        } else if byte == 0 {
+            res.colors.home_font_grey_color
+        } else {
+            res.colors.home_font_color
+        };
+        let found = self
+            .found
+            .is_some_and(|found| (found..found + self.pattern.len() as u64).contains(&offset));
+        let background = if offset == self.cursor {
+            // 另一列中对应的字节淡一些
+            if ascii == self.ascii {
+                res.colors.editor_selection
+            } else {
+                res.colors.editor_current_line
+            }
+        } else if found {
+            res.colors.info.with_alpha(0.3)
+        } else {
+            Color::NONE
+        };
+        (color, background)
+    }
+
+    fn title(&self) -> String {
+        let Some(file) = self.file.as_ref() else {
+            return String::new();
+        };
+        let name = file
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let dirty = if file.is_dirty() { " ●" } else { "" };
+        format!("{name}{dirty}  {} bytes", file.len())
+    }
+
+    fn status(&self) -> String {
+        let mode = if self.editable { "EDIT" } else { "READ ONLY" };
+        let value = match self.file.as_ref().and_then(|file| file.byte(self.cursor)) {
+            Some(byte) => format!("0x{byte:02x}  u8 {byte}  i8 {}", byte as i8),
+            None => String::new(),
+        };
+        let mut status = format!(
+            "Offset 0x{:x} ({})  {value}  {mode}",
+            self.cursor, self.cursor
+        );
+        if let Some(message) = &self.message {
+            status.push_str("  ");
+            status.push_str(message);
+        }
+        status
+    }
+}
+
+/// 十六进制视图的根节点
+#[derive(Component)]
+pub struct HexViewUi;
+
+#[derive(Component)]
+struct HexTitle;
+
+#[derive(Component)]
+struct HexStatus;
+
+#[derive(Component)]
+struct HexPromptRow;
+
+#[derive(Component)]
+struct HexPromptText;
+
+#[derive(Component)]
+struct HexPromptInput;
+
+/// 显示行的区域，点击选中字节，滚轮滚动
+#[derive(Component)]
+#[require(Node, FocusPolicy::Block, Interaction, RelativeCursorPosition)]
+struct HexBody;
+
+#[derive(Component)]
+struct HexRow;
+
+#[derive(Component)]
+#[require(Node, FocusPolicy::Block, Interaction, RelativeCursorPosition)]
+struct HexScrollbar;
+
+#[derive(Component)]
+struct HexThumb;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct HexGotoButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct HexFindButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct HexEditButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct HexSaveButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct CloseHexButton;
+
+#[derive(Component)]
+pub struct HexViewPlugin;
+
+impl SeekerTrait for HexViewPlugin {}
+
+impl Plugin for HexViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HexView>()
+            .add_message::<OpenHex>()
+            .add_systems(
+                OnEnter(SeekerEditViewState::Hex),
+                (DiffViewPlugin::hide_editor, Self::spawn_view),
+            )
+            .add_systems(
+                OnExit(SeekerEditViewState::Hex),
+                (DiffViewPlugin::show_editor, Self::close),
+            )
+            .add_systems(
+                Update,
+                Self::open.run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_systems(
+                Update,
+                (
+                    Self::buttons,
+                    Self::prompt_input,
+                    Self::input,
+                    Self::receive_search,
+                    Self::wheel,
+                    Self::click,
+                    Self::drag_scrollbar,
+                    Self::render_rows,
+                    Self::refresh,
+                )
+                    .chain()
+                    .after(TextInputSystems)
+                    .run_if(in_state(SeekerEditViewState::Hex)),
+            )
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, HexGotoButton>)
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, HexFindButton>)
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, HexEditButton>)
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, HexSaveButton>)
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, CloseHexButton>);
+    }
+}
+
+impl HexViewPlugin {
+    fn open(
+        mut reader: MessageReader<OpenHex>,
+        mut hex: ResMut<HexView>,
+        mut state: ResMut<NextState<SeekerEditViewState>>,
+    ) {
+        for OpenHex { path } in reader.read() {
+            match HexFile::open(path) {
+                Ok(file) => {
+                    *hex = HexView {
+                        file: Some(file),
+                        rows: hex.rows,
+                        ..default()
+                    };
+                    state.set(SeekerEditViewState::Hex);
+                }
+                Err(err) => error!("open {}: {err}", path.display()),
+            }
+        }
+    }
+
+    /// 关闭时释放映射
+    fn close(mut hex: ResMut<HexView>) {
+        *hex = HexView::default();
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn buttons(
+        goto: Query<&Interaction, (Changed<Interaction>, With<HexGotoButton>)>,
+        find: Query<&Interaction, (Changed<Interaction>, With<HexFindButton>)>,
+        edit: Query<&Interaction, (Changed<Interaction>, With<HexEditButton>)>,
+        save: Query<&Interaction, (Changed<Interaction>, With<HexSaveButton>)>,
+        close: Query<&Interaction, (Changed<Interaction>, With<CloseHexButton>)>,
+        mut hex: ResMut<HexView>,
+        mut state: ResMut<NextState<SeekerEditViewState>>,
+    ) {
+        if pressed(&goto) {
+            hex.open_prompt(HexPrompt::Goto);
+        }
+        if pressed(&find) {
+            hex.open_prompt(HexPrompt::Find);
+        }
+        if pressed(&edit) {
+            hex.editable = !hex.editable;
+        }
+        if pressed(&save) {
+            hex.save();
+        }
+        if pressed(&close) {
+            state.set(SeekerEditViewState::Editor);
+        }
+    }
+
+    /// 打开输入框时清空并获得焦点，Enter 跳转或者搜索，Esc 关闭
+    fn prompt_input(
+        input: Single<(Entity, &mut SeekerTextInput), With<HexPromptInput>>,
+        mut submit: MessageReader<TextInputSubmit>,
+        mut cancel: MessageReader<TextInputCancel>,
+        wake: Res<BackgroundWake>,
+        mut hex: ResMut<HexView>,
+        mut shown: Local<Option<HexPrompt>>,
+    ) {
+        let (entity, mut input) = input.into_inner();
+        if let Some(submit) = submit.read().find(|submit| submit.entity == entity)
+            && let Some(prompt) = hex.prompt.take()
+        {
+            hex.message = None;
+            hex.submit(prompt, &submit.value, &wake);
+        }
+        if cancel.read().any(|cancel| cancel.entity == entity) {
+            hex.prompt = None;
+        }
+        if *shown != hex.prompt {
+            *shown = hex.prompt;
+            input.set_value("");
+            input.focused = hex.prompt.is_some();
+        }
+    }
+
+    /// 方向键、翻页键移动光标，Tab 切换列，可以修改时输入十六进制或者字符。
+    /// Ctrl+G 跳转，Ctrl+F / F3 搜索，Ctrl+E 切换只读，Ctrl+Z 撤销，Ctrl+S 保存，Esc 关闭
+    fn input(
+        mut keyboard: MessageReader<KeyboardInput>,
+        mut ime: MessageReader<Ime>,
+        keys: Res<ButtonInput<KeyCode>>,
+        wake: Res<BackgroundWake>,
+        mut hex: ResMut<HexView>,
+        capture: Res<EditorInputCapture>,
+        mut state: ResMut<NextState<SeekerEditViewState>>,
+    ) {
+        if keyboard.is_empty() && ime.is_empty() {
+            return;
+        }
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        // 跳转、搜索或者面板中的输入框获得焦点
+        if capture.text {
+            keyboard.clear();
+            ime.clear();
+            return;
+        }
+        ime.clear();
+        let page = (hex.rows.max(1) * BYTES_PER_ROW as u64) as i64;
+        let row = BYTES_PER_ROW as i64;
+        for event in keyboard.read() {
+            if !event.state.is_pressed() {
+                continue;
+            }
+            hex.message = None;
+            if event.logical_key != Key::Escape {
+                hex.closing = false;
+            }
+            match &event.logical_key {
+                Key::Escape if hex.search.is_some() => {
+                    hex.search = None;
+                    hex.message = Some("Search cancelled".to_string());
+                }
+                Key::Escape if hex.is_dirty() && !hex.closing => {
+                    hex.closing = true;
+                    hex.message = Some("Unsaved changes, Esc again to discard".to_string());
+                }
+                Key::Escape => state.set(SeekerEditViewState::Editor),
+                Key::Character(ch) if command => match ch.to_lowercase().as_str() {
+                    "g" => hex.open_prompt(HexPrompt::Goto),
+                    "f" => hex.open_prompt(HexPrompt::Find),
+                    "e" => hex.editable = !hex.editable,
+                    "z" => hex.undo(),
+                    "s" => hex.save(),
+                    _ => {}
+                },
+                Key::F3 => hex.find_next(&wake),
+                Key::ArrowLeft => hex.move_by(-1),
+                Key::ArrowRight => hex.move_by(1),
+                Key::ArrowUp => hex.move_by(-row),
+                Key::ArrowDown => hex.move_by(row),
+                Key::PageUp => hex.move_by(-page),
+                Key::PageDown => hex.move_by(page),
+                Key::Home if command => hex.move_to(0),
+                Key::End if command => hex.move_to(u64::MAX),
+                Key::Home => {
+                    let cursor = hex.cursor;
+                    hex.move_to(cursor - cursor % BYTES_PER_ROW as u64);
+                }
+                Key::End => {
+                    let cursor = hex.cursor;
+                    hex.move_to(cursor - cursor % BYTES_PER_ROW as u64 + row as u64 - 1);
+                }
+                Key::Tab => {
+                    hex.ascii = !hex.ascii;
+                    hex.low_nibble = false;
+                }
+                _ if hex.editable => {
+                    if let Some(text) = event.text.as_deref() {
+                        text.chars().for_each(|ch| hex.type_char(ch));
+                    }
+                }
+                _ => {}
+            }
+            // 剩下的按键交给输入框
+            if hex.prompt.is_some() {
+                break;
+            }
+        }
+    }
+
+    /// 显示搜索进度，找到时移动光标
+    fn receive_search(mut hex: ResMut<HexView>) {
+        let Some(search) = hex.search.as_ref() else {
+            return;
+        };
+        let (events, finished) = search.receiver.drain();
+        let len = hex.len().max(1);
+        for event in events {
+            match event {
+                HexSearchEvent::Progress(scanned) => {
+                    hex.message = Some(format!("Searching {}%", scanned * 100 / len));
+                }
+                HexSearchEvent::Done(found) => {
+                    hex.search = None;
+                    hex.found = found;
+                    match found {
+                        Some(offset) => {
+                            hex.message = None;
+                            hex.move_to(offset);
+                        }
+                        None => hex.message = Some("Not found".to_string()),
+                    }
+                    return;
+                }
+            }
+        }
+        if finished {
+            hex.search = None;
+        }
+    }
+
+    /// 滚轮只滚动视图，不移动光标
+    fn wheel(
+        mut reader: MessageReader<MouseWheel>,
+        body: Single<&RelativeCursorPosition, With<HexBody>>,
+        mut hex: ResMut<HexView>,
+        mut pending: Local<f32>,
+    ) {
+        let mut rows = 0.;
+        for wheel in reader.read() {
+            if !body.cursor_over() {
+                continue;
+            }
+            rows -= match wheel.unit {
+                MouseScrollUnit::Line => wheel.y,
+                MouseScrollUnit::Pixel => wheel.y / LINE_HEIGHT,
+            };
+        }
+        // 触控板按像素滚动，不满一行的部分留到下一次
+        *pending += rows;
+        let delta = pending.trunc();
+        if delta == 0. {
+            return;
+        }
+        *pending -= delta;
+        let top = hex.top.saturating_add_signed(delta as i64);
+        hex.scroll_to(top);
+    }
+
+    /// 点击十六进制列或者 ASCII 列中的字节移动光标
+    fn click(
+        mouse: Res<ButtonInput<MouseButton>>,
+        body: Single<(&RelativeCursorPosition, &ComputedNode), With<HexBody>>,
+        scrollbar: Single<&RelativeCursorPosition, With<HexScrollbar>>,
+        mut hex: ResMut<HexView>,
+    ) {
+        let (relative, computed) = body.into_inner();
+        if !mouse.just_pressed(MouseButton::Left)
+            || !relative.cursor_over()
+            || scrollbar.cursor_over()
+        {
+            return;
+        }
+        let Some(normalized) = relative.normalized else {
+            return;
+        };
+        let point = (normalized + 0.5) * computed.size() * computed.inverse_scale_factor();
+        let row = hex.top + (point.y / LINE_HEIGHT) as u64;
+        let column = ((point.x - HEX_PADDING) / cell_width()).max(0.) as usize;
+        let Some((index, ascii)) = column_byte(offset_digits(hex.len()), column) else {
+            return;
+        };
+        let offset = row * BYTES_PER_ROW as u64 + index as u64;
+        if offset < hex.len() {
+            hex.ascii = ascii;
+            hex.move_to(offset);
+        }
+    }
+
+    /// 按住滚动条时滚动到指针所在的比例
+    fn drag_scrollbar(
+        scrollbar: Single<(&Interaction, &RelativeCursorPosition), With<HexScrollbar>>,
+        mut hex: ResMut<HexView>,
+    ) {
+        let (interaction, relative) = scrollbar.into_inner();
+        if *interaction != Interaction::Pressed {
+            return;
+        }
+        let Some(normalized) = relative.normalized else {
+            return;
+        };
+        let ratio = (normalized.y as f64 + 0.5).clamp(0., 1.);
+        let row = (ratio * hex.row_count() as f64) as u64;
+        let top = row.saturating_sub(hex.rows / 2);
+        if top != hex.top {
+            hex.scroll_to(top);
+        }
+    }
+
+    fn spawn_view(
+        mut commands: Commands,
+        editor: Single<Entity, With<EditorUi>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            ..default()
+        };
+        commands.entity(*editor).with_children(|parent| {
+            parent
+                .spawn((
+                    HexViewUi,
+                    DespawnOnExit(SeekerEditViewState::Hex),
+                    Node {
+                        flex_grow: 1.,
+                        min_width: Val::Px(0.),
+                        height: Val::Percent(100.),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent
+                        .spawn(Node {
+                            width: Val::Percent(100.),
+                            padding: UiRect::horizontal(Val::Px(10.)),
+                            column_gap: Val::Px(10.),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                HexTitle,
+                                Text::default(),
+                                text_font.clone(),
+                                TextColor(res.colors.home_font_color),
+                            ));
+                            parent.spawn(Node {
+                                flex_grow: 1.,
+                                ..default()
+                            });
+                            Self::ui_button(
+                                parent,
+                                HexGotoButton,
+                                "HexGoto",
+                                "Go to",
+                                &res,
+                                &assets,
+                            );
+                            Self::ui_button_same(parent, HexFindButton, "Find", &res, &assets);
+                            Self::ui_button_same(parent, HexEditButton, "Edit", &res, &assets);
+                            Self::ui_button_same(parent, HexSaveButton, "Save", &res, &assets);
+                            Self::ui_button_same(parent, CloseHexButton, "×", &res, &assets);
+                        });
+                    parent
+                        .spawn((
+                            HexPromptRow,
+                            Node {
+                                display: Display::None,
+                                padding: UiRect::axes(Val::Px(10.), Val::Px(4.)),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                HexPromptText,
+                                Text::default(),
+                                text_font.clone(),
+                                TextColor(res.colors.home_font_color),
+                            ));
+                            parent.spawn((
+                                HexPromptInput,
+                                SeekerTextInput::default(),
+                                text_font.clone(),
+                                Node {
+                                    flex_grow: 1.,
+                                    margin: UiRect::left(Val::Px(8.)),
+                                    overflow: Overflow::clip(),
+                                    ..default()
+                                },
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            HexBody,
+                            Node {
+                                width: Val::Percent(100.),
+                                flex_grow: 1.,
+                                min_height: Val::Px(0.),
+                                overflow: Overflow::clip(),
+                                ..default()
+                            },
+                            BackgroundColor(res.colors.editor_background),
+                        ))
+                        .with_children(|parent| {
+                            parent
+                                .spawn((
+                                    HexScrollbar,
+                                    Node {
+                                        position_type: PositionType::Absolute,
+                                        top: Val::Px(0.),
+                                        right: Val::Px(0.),
+                                        width: Val::Px(HEX_SCROLLBAR_WIDTH),
+                                        height: Val::Percent(100.),
+                                        ..default()
+                                    },
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        HexThumb,
+                                        Node {
+                                            position_type: PositionType::Absolute,
+                                            width: Val::Percent(100.),
+                                            ..default()
+                                        },
+                                        BackgroundColor(res.colors.home_hovered),
+                                    ));
+                                });
+                        });
+                    parent
+                        .spawn(Node {
+                            width: Val::Percent(100.),
+                            padding: UiRect::axes(Val::Px(10.), Val::Px(4.)),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                HexStatus,
+                                Text::default(),
+                                text_font.clone(),
+                                TextColor(res.colors.home_font_grey_color),
+                            ));
+                        });
+                });
+        });
+    }
+
+    /// 可见行数或者内容变化时重新生成可见的行
+    #[allow(clippy::type_complexity)]
+    fn render_rows(
+        mut commands: Commands,
+        mut hex: ResMut<HexView>,
+        body: Single<(Entity, &ComputedNode, Option<&Children>), With<HexBody>>,
+        rows: Query<(), With<HexRow>>,
+        mut thumb: Single<&mut Node, With<HexThumb>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let (entity, computed, children) = body.into_inner();
+        let height = computed.size().y * computed.inverse_scale_factor();
+        let visible = (height / LINE_HEIGHT).floor().max(1.) as u64;
+        if hex.rows != visible {
+            hex.rows = visible;
+            hex.scroll_into_view();
+        }
+        if !hex.is_changed() {
+            return;
+        }
+        for child in children.into_iter().flatten() {
+            if rows.contains(*child) {
+                commands.entity(*child).despawn();
+            }
+        }
+        let row_count = hex.row_count();
+        let thumb_height = if row_count <= hex.rows {
+            height
+        } else {
+            (hex.rows as f32 / row_count as f32 * height).max(HEX_THUMB_MIN_HEIGHT)
+        };
+        let max_top = row_count.saturating_sub(hex.rows).max(1);
+        thumb.height = Val::Px(thumb_height);
+        thumb.top = Val::Px((hex.top as f64 / max_top as f64) as f32 * (height - thumb_height));
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: SEEKER_CONFIG.font_size,
+            line_height: LineHeight::Px(LINE_HEIGHT),
+            ..default()
+        };
+        let last = (hex.top + hex.rows).min(row_count);
+        commands.entity(entity).with_children(|parent| {
+            for row in hex.top..last {
+                Self::render_row(parent, &hex, row, &text_font, &res);
+            }
+        });
+    }
+
+    fn render_row(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        hex: &HexView,
+        row: u64,
+        text_font: &TextFont,
+        res: &SeekerResource,
+    ) {
+        let Some(file) = hex.file.as_ref() else {
+            return;
+        };
+        let offset = row * BYTES_PER_ROW as u64;
+        let bytes = file.bytes(offset, BYTES_PER_ROW);
+        let digits = offset_digits(file.len());
+        let plain = (res.colors.home_font_color, Color::NONE);
+        // 相邻样式相同的文字合并成一段
+        let mut spans: Vec<(String, (Color, Color))> = vec![];
+        let mut push = |text: &str, style: (Color, Color)| match spans.last_mut() {
+            Some((last, last_style)) if *last_style == style => last.push_str(text),
+            _ => spans.push((text.to_string(), style)),
+        };
+        push(
+            &format!("{offset:0digits$x}  "),
+            (res.colors.home_font_grey_color, Color::NONE),
+        );
+        for i in 0..BYTES_PER_ROW {
+            if i == BYTES_PER_ROW / 2 {
+                push(" ", plain);
+            }
+            match bytes.get(i) {
+                Some(byte) => push(
+                    &format!("{byte:02x}"),
+                    hex.style(offset + i as u64, *byte, false, res),
+                ),
+                None => push("  ", plain),
+            }
+            push(" ", plain);
+        }
+        push(" |", plain);
+        for (i, byte) in bytes.iter().enumerate() {
+            push(
+                &printable(*byte).to_string(),
+                hex.style(offset + i as u64, *byte, true, res),
+            );
+        }
+        push("|", plain);
+        parent
+            .spawn((
+                HexRow,
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px((row - hex.top) as f32 * LINE_HEIGHT),
+                    left: Val::Px(HEX_PADDING),
+                    height: Val::Px(LINE_HEIGHT),
+                    ..default()
+                },
+                Text::default(),
+                text_font.clone(),
+                TextLayout::new_with_no_wrap(),
+                TextColor(res.colors.home_font_color),
+            ))
+            .with_children(|parent| {
+                for (text, (color, background)) in spans {
+                    parent.spawn((
+                        TextSpan::new(text),
+                        text_font.clone(),
+                        TextColor(color),
+                        TextBackgroundColor(background),
+                    ));
+                }
+            });
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn refresh(
+        hex: Res<HexView>,
+        mut texts: Query<
+            (&mut Text, Has<HexTitle>, Has<HexStatus>),
+            Or<(With<HexTitle>, With<HexStatus>, With<HexPromptText>)>,
+        >,
+        mut prompt_row: Single<&mut Node, With<HexPromptRow>>,
+    ) {
+        if !hex.is_changed() {
+            return;
+        }
+        for (mut text, title, status) in texts.iter_mut() {
+            text.0 = if title {
+                hex.title()
+            } else if status {
+                hex.status()
+            } else {
+                match hex.prompt {
+                    Some(HexPrompt::Goto) => "Go to offset (0x for hex):",
+                    Some(HexPrompt::Find) => "Find bytes (hex or \"text\"):",
+                    None => "",
+                }
+                .to_string()
+            };
+        }
+        prompt_row.display = if hex.prompt.is_some() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+fn pressed<F: QueryFilter>(query: &Query<&Interaction, F>) -> bool {
+    query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+}
@@ -0,0 +1,267 @@
+mod blame;
+mod completion;
+mod diff_view;
+mod editor;
+mod file_tree;
+mod fold;
+mod format;
+mod git_gutter;
+mod hex_view;
+mod lsp_ui;
+mod markdown_preview;
+mod minimap;
+mod outline;
+mod pane;
+mod panel;
+mod problems;
+mod session;
+mod snippet;
+mod source_control;
+mod status_bar;
+mod tab_bar;
+mod tasks;
+mod terminal;
+
+use bevy::prelude::*;
+use panel::{ResizeHandle, SIDEBAR_WIDTH};
+use seeker_editor::buffer::Position;
+use seeker_editor::document::Documents;
+use seeker_editor::hex::is_binary_file;
+use seeker_git::plugin::ProjectFilesChanged;
+use seeker_resource::project_list::CurrentProject;
+use seeker_resource::SeekerResource;
+use seeker_state::{
+    SeekerEditPanelState, SeekerEditSubLoadState, SeekerEditViewState, SeekerState,
+};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+pub use diff_view::{DiffText, OpenDiff};
+pub use editor::{EditorView, FocusedEditor};
+pub use hex_view::OpenHex;
+
+#[derive(Component)]
+pub struct FileTreeUi;
+
+#[derive(Component)]
+pub struct TabBarUi;
+
+#[derive(Component)]
+pub struct EditorUi;
+
+#[derive(Component)]
+pub struct PanelUi;
+
+#[derive(Component)]
+pub struct StatusBarUi;
+
+/// 在聚焦的编辑器中打开文件并跳转到指定位置，没有位置时恢复上次的光标
+#[derive(Message, Clone, Debug)]
+pub struct OpenLocation {
+    pub path: PathBuf,
+    pub position: Option<Position>,
+}
+
+#[derive(Component)]
+pub struct SeekerEditPlugin;
+
+impl Plugin for SeekerEditPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Documents>()
+            .init_resource::<CurrentProject>()
+            .add_sub_state::<SeekerEditSubLoadState>()
+            .add_sub_state::<SeekerEditPanelState>()
+            .add_message::<OpenLocation>()
+            .add_systems(OnEnter(SeekerState::Edit), Self::edit_enter)
+            .add_systems(OnExit(SeekerState::Edit), Self::edit_exit)
+            .add_systems(
+                Update,
+                (Self::open_location, Self::reload_documents)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_plugins(session::SessionPlugin)
+            .add_plugins(file_tree::FileTreePlugin)
+            .add_plugins(pane::PanePlugin)
+            .add_plugins(tab_bar::TabBarPlugin)
+            .add_plugins(editor::EditorPlugin)
+            .add_plugins(fold::FoldPlugin)
+            .add_plugins(minimap::MinimapPlugin)
+            .add_plugins(status_bar::StatusBarPlugin)
+            .add_plugins(git_gutter::GitGutterPlugin)
+            .add_plugins(blame::BlamePlugin)
+            .add_plugins(diff_view::DiffViewPlugin)
+            .add_plugins(hex_view::HexViewPlugin)
+            .add_plugins(panel::PanelPlugin)
+            .add_plugins(problems::ProblemsPlugin)
+            .add_plugins(source_control::SourceControlPlugin)
+            .add_plugins(terminal::TerminalPlugin)
+            .add_plugins(tasks::TasksPlugin)
+            .add_plugins(snippet::SnippetPlugin)
+            .add_plugins(completion::CompletionPlugin)
+            .add_plugins(lsp_ui::LspUiPlugin)
+            .add_plugins(outline::OutlinePlugin)
+            .add_plugins(markdown_preview::MarkdownPreviewPlugin)
+            .add_plugins(format::FormatPlugin);
+    }
+}
+
+impl SeekerEditPlugin {
+    fn edit_enter(
+        mut commands: Commands,
+        res: Res<SeekerResource>,
+        mut state: ResMut<NextState<SeekerEditSubLoadState>>,
+    ) {
+        commands
+            .spawn((
+                DespawnOnExit(SeekerState::Edit),
+                Node {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                BackgroundColor(res.colors.editor_background),
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn(Node {
+                        width: Val::Percent(100.),
+                        flex_grow: 1.,
+                        min_height: Val::Px(0.),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            FileTreeUi,
+                            Node {
+                                width: Val::Px(SIDEBAR_WIDTH),
+                                min_width: Val::Px(SIDEBAR_WIDTH),
+                                height: Val::Percent(100.),
+                                display: Display::Flex,
+                                flex_direction: FlexDirection::Column,
+                                overflow: Overflow::scroll(),
+                                border: UiRect::right(Val::Px(1.)),
+                                ..default()
+                            },
+                            BorderColor::all(res.colors.button_border),
+                            BackgroundColor(res.colors.home_menu),
+                        ));
+                        parent.spawn((
+                            ResizeHandle::Sidebar,
+                            Node {
+                                width: Val::Px(4.),
+                                height: Val::Percent(100.),
+                                flex_shrink: 0.,
+                                ..default()
+                            },
+                        ));
+                        parent
+                            .spawn(Node {
+                                flex_grow: 1.,
+                                min_width: Val::Px(0.),
+                                height: Val::Percent(100.),
+                                display: Display::Flex,
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    EditorUi,
+                                    Node {
+                                        width: Val::Percent(100.),
+                                        flex_grow: 1.,
+                                        min_height: Val::Px(0.),
+                                        display: Display::Flex,
+                                        flex_direction: FlexDirection::Row,
+                                        ..default()
+                                    },
+                                ));
+                                parent.spawn((
+                                    PanelUi,
+                                    Node {
+                                        width: Val::Percent(100.),
+                                        display: Display::Flex,
+                                        flex_direction: FlexDirection::Column,
+                                        border: UiRect::top(Val::Px(1.)),
+                                        ..default()
+                                    },
+                                    BorderColor::all(res.colors.button_border),
+                                ));
+                            });
+                    });
+                parent.spawn((
+                    StatusBarUi,
+                    Node {
+                        width: Val::Percent(100.),
+                        height: Val::Px(24.),
+                        min_height: Val::Px(24.),
+                        padding: UiRect::horizontal(Val::Px(10.)),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(20.),
+                        ..default()
+                    },
+                    BackgroundColor(res.colors.home_menu),
+                ));
+            });
+        state.set(SeekerEditSubLoadState::Loaded);
+    }
+
+    fn edit_exit(mut documents: ResMut<Documents>) {
+        documents.documents.clear();
+    }
+
+    fn open_location(
+        mut reader: MessageReader<OpenLocation>,
+        mut documents: ResMut<Documents>,
+        mut view: Single<&mut EditorView, With<FocusedEditor>>,
+        mut view_state: ResMut<NextState<SeekerEditViewState>>,
+        mut hex: MessageWriter<OpenHex>,
+    ) {
+        for OpenLocation { path, position } in reader.read() {
+            // 二进制文件用十六进制视图打开
+            if is_binary_file(path).unwrap_or_default() {
+                hex.write(OpenHex { path: path.clone() });
+                continue;
+            }
+            let doc = match documents.open(path) {
+                Ok(doc) => doc,
+                Err(err) => {
+                    error!("open {}: {err}", path.display());
+                    continue;
+                }
+            };
+            let position = position.map(|position| doc.buffer.clamp(position));
+            view.open(path.clone(), position);
+            // 比较视图打开时切回编辑器
+            view_state.set(SeekerEditViewState::Editor);
+        }
+    }
+
+    /// 文件在外部被修改（例如切换分支）后重新读取没有未保存修改的文档
+    fn reload_documents(
+        mut reader: MessageReader<ProjectFilesChanged>,
+        mut documents: ResMut<Documents>,
+        mut views: Query<&mut EditorView>,
+    ) {
+        let paths: HashSet<&PathBuf> = reader.read().flat_map(|changed| &changed.paths).collect();
+        if paths.is_empty() {
+            return;
+        }
+        for doc in documents.documents.iter_mut() {
+            if doc.dirty || !paths.contains(&doc.path) {
+                continue;
+            }
+            match doc.reload() {
+                Ok(true) => views.iter_mut().for_each(|mut view| view.reloaded(doc)),
+                Ok(false) => {}
+                // 文件被删除时保留编辑器中的内容
+                Err(err) => warn!("reload {}: {err}", doc.path.display()),
+            }
+        }
+    }
+}
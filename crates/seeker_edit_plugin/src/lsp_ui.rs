@@ -0,0 +1,307 @@
+use crate::completion::{word_candidates, CompletionCandidate, CompletionMenu, ProjectIndex};
+use crate::editor::{position_to_point, EditorSystems, FocusedContent, LINE_HEIGHT};
+use crate::panel::PanelPlugin;
+use crate::snippet::{snippet_candidates, Snippets};
+use crate::{EditorView, FocusedEditor, OpenLocation, PanelUi};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use seeker_editor::buffer::Position;
+use seeker_editor::document::Documents;
+use seeker_lsp::plugin::{LspLocation, LspRequest, LspRequestKind, LspResponse};
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditPanelState, SeekerEditSubLoadState};
+use std::path::PathBuf;
+
+#[derive(Component)]
+pub struct HoverCard {
+    path: PathBuf,
+    position: Position,
+}
+
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct ReferenceItem {
+    pub location: LspLocation,
+}
+
+/// 查找引用的结果
+#[derive(Resource, Default)]
+pub struct ReferenceList {
+    pub locations: Vec<LspLocation>,
+}
+
+#[derive(Component)]
+pub struct LspUiPlugin;
+
+impl Plugin for LspUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReferenceList>()
+            .add_systems(
+                Update,
+                (
+                    Self::keybindings,
+                    Self::receive_responses,
+                    Self::dismiss_hover,
+                    Self::render_references.run_if(resource_changed::<ReferenceList>),
+                    Self::click_reference,
+                )
+                    .chain()
+                    .after(EditorSystems)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_systems(OnExit(SeekerEditSubLoadState::Loaded), Self::exit);
+    }
+}
+
+impl LspUiPlugin {
+    fn exit(mut references: ResMut<ReferenceList>) {
+        references.locations.clear();
+    }
+
+    /// Ctrl+K 悬停，F12 跳转定义，Shift+F12 查找引用
+    fn keybindings(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        mut requests: MessageWriter<LspRequest>,
+    ) {
+        let Some(path) = view.path.clone() else {
+            reader.clear();
+            return;
+        };
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        for event in reader.read() {
+            if !event.state.is_pressed() {
+                continue;
+            }
+            let kind = match &event.logical_key {
+                Key::Character(ch) if command && ch.eq_ignore_ascii_case("k") => {
+                    LspRequestKind::Hover
+                }
+                Key::F12 if shift => LspRequestKind::References,
+                Key::F12 => LspRequestKind::Definition,
+                _ => continue,
+            };
+            requests.write(LspRequest {
+                path: path.clone(),
+                position: view.cursor,
+                kind,
+            });
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn receive_responses(
+        mut commands: Commands,
+        mut reader: MessageReader<LspResponse>,
+        documents: Res<Documents>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        content: FocusedContent,
+        cards: Query<Entity, With<HoverCard>>,
+        mut menu: ResMut<CompletionMenu>,
+        index: Res<ProjectIndex>,
+        snippets: Res<Snippets>,
+        mut references: ResMut<ReferenceList>,
+        mut panel: ResMut<NextState<SeekerEditPanelState>>,
+        mut open: MessageWriter<OpenLocation>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        for response in reader.read() {
+            match response {
+                LspResponse::Hover {
+                    path,
+                    position,
+                    contents,
+                } => {
+                    cards
+                        .iter()
+                        .for_each(|card| commands.entity(card).despawn());
+                    let Some(doc) = documents.get(path) else {
+                        continue;
+                    };
+                    if view.path.as_ref() != Some(path) || contents.trim().is_empty() {
+                        continue;
+                    }
+                    let Some(content) = content.get() else {
+                        continue;
+                    };
+                    let point = position_to_point(doc, &view.wrap, *position);
+                    commands.entity(content).with_children(|parent| {
+                        parent
+                            .spawn((
+                                HoverCard {
+                                    path: path.clone(),
+                                    position: *position,
+                                },
+                                Node {
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Px(point.x),
+                                    top: Val::Px(point.y + LINE_HEIGHT),
+                                    max_width: Val::Px(600.),
+                                    padding: UiRect::all(Val::Px(8.)),
+                                    border: UiRect::all(Val::Px(1.)),
+                                    ..default()
+                                },
+                                ZIndex(10),
+                                BorderColor::all(res.colors.button_border),
+                                BackgroundColor(res.colors.home_menu),
+                                BorderRadius::all(Val::Px(3.)),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(contents.trim()),
+                                    TextFont {
+                                        font: assets.load(MAPLE_MONO_BOLD),
+                                        font_size: 13.,
+                                        ..default()
+                                    },
+                                    TextColor(res.colors.home_font_color),
+                                ));
+                            });
+                    });
+                }
+                LspResponse::Definition(locations) => {
+                    if let Some(location) = locations.first() {
+                        open.write(OpenLocation {
+                            path: location.path.clone(),
+                            position: Some(location.start),
+                        });
+                    }
+                }
+                LspResponse::References(locations) => {
+                    references.locations = locations.clone();
+                    panel.set(SeekerEditPanelState::References);
+                }
+                LspResponse::Completion {
+                    path,
+                    position,
+                    items,
+                } => {
+                    // 光标已经离开请求位置所在的行就丢弃
+                    if view.path.as_ref() != Some(path) || view.cursor.line != position.line {
+                        continue;
+                    }
+                    let Some(doc) = documents.get(path) else {
+                        continue;
+                    };
+                    // 语言服务器没有结果时退回到文本中的标识符
+                    let mut candidates: Vec<_> = if items.is_empty() {
+                        word_candidates(&documents, &index, doc, view.cursor)
+                    } else {
+                        items
+                            .iter()
+                            .cloned()
+                            .map(CompletionCandidate::from)
+                            .collect()
+                    };
+                    candidates.extend(snippet_candidates(&snippets, doc, view.cursor));
+                    if !candidates.is_empty() {
+                        menu.open(path.clone(), doc.buffer.word_start(*position), candidates);
+                    }
+                }
+                // 大纲面板处理
+                LspResponse::DocumentSymbols { .. } | LspResponse::WorkspaceSymbols { .. } => {}
+                // 折叠插件处理
+                LspResponse::FoldingRanges { .. } => {}
+            }
+        }
+    }
+
+    fn dismiss_hover(
+        mut commands: Commands,
+        view: Single<&EditorView, (With<FocusedEditor>, Changed<EditorView>)>,
+        cards: Query<(Entity, &HoverCard)>,
+    ) {
+        for (entity, card) in cards.iter() {
+            if view.path.as_ref() != Some(&card.path) || view.cursor != card.position {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    fn render_references(
+        mut commands: Commands,
+        references: Res<ReferenceList>,
+        documents: Res<Documents>,
+        panel: Single<Entity, With<PanelUi>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            ..default()
+        };
+        let title = format!("References ({})", references.locations.len());
+        PanelPlugin::spawn_panel(
+            &mut commands,
+            *panel,
+            SeekerEditPanelState::References,
+            &res,
+            &assets,
+            |parent| PanelPlugin::panel_title(parent, title, &res, &assets),
+            |parent| {
+                for location in &references.locations {
+                    let line = documents
+                        .get(&location.path)
+                        .map(|doc| doc.buffer.line(location.start.line).to_string())
+                        .or_else(|| {
+                            std::fs::read_to_string(&location.path)
+                                .ok()
+                                .and_then(|text| {
+                                    text.lines().nth(location.start.line).map(str::to_string)
+                                })
+                        })
+                        .unwrap_or_default();
+                    parent
+                        .spawn((
+                            ReferenceItem {
+                                location: location.clone(),
+                            },
+                            Node {
+                                width: Val::Percent(100.),
+                                padding: UiRect::horizontal(Val::Px(20.)),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Text::new(format!(
+                                    "{}:{}  {}",
+                                    location.path.display(),
+                                    location.start.line + 1,
+                                    line.trim()
+                                )),
+                                text_font.clone(),
+                                TextLayout::new_with_no_wrap(),
+                                TextColor(res.colors.home_font_grey_color),
+                            ));
+                        });
+                }
+            },
+        );
+    }
+
+    fn click_reference(
+        query: Query<(&Interaction, &ReferenceItem), Changed<Interaction>>,
+        mut open: MessageWriter<OpenLocation>,
+    ) {
+        for (interaction, item) in query.iter() {
+            if *interaction == Interaction::Pressed {
+                open.write(OpenLocation {
+                    path: item.location.path.clone(),
+                    position: Some(item.location.start),
+                });
+            }
+        }
+    }
+}
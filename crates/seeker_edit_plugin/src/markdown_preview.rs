@@ -0,0 +1,739 @@
+use crate::editor::{EditorSystems, LINE_HEIGHT};
+use crate::{EditorUi, EditorView, FocusedEditor, OpenLocation, StatusBarUi};
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::text::TextLayoutInfo;
+use bevy::ui::{FocusPolicy, UiGlobalTransform};
+use bevy::window::RequestRedraw;
+use seeker_editor::buffer::Position;
+use seeker_editor::document::Documents;
+use seeker_editor::highlight::{highlight, TokenKind};
+use seeker_editor::markdown::{parse, Align, Block, BlockKind, Span};
+use seeker_resource::assets::local_asset_path;
+use seeker_resource::fonts::{MAPLE_MONO_BOLD, MAPLE_MONO_BOLD_ITALIC};
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditSubLoadState, SeekerEditViewState};
+use seeker_trait::SeekerTrait;
+use std::path::{Component as PathComponent, Path, PathBuf};
+use std::process::Command;
+
+/// 预览占编辑区域宽度的百分比
+const PREVIEW_WIDTH: f32 = 45.;
+/// 正文的字号，标题按级别放大
+const FONT_SIZE: f32 = 14.;
+
+/// Markdown 预览的开关，Ctrl+Shift+V 或者状态栏的按钮切换
+#[derive(Resource, Default)]
+pub struct MarkdownPreview {
+    pub enabled: bool,
+}
+
+/// 预览的滚动节点，第一次打开时放到编辑区域的右侧
+#[derive(Component, Default)]
+pub struct PreviewUi {
+    /// 生成内容时的文档和版本
+    key: Option<(PathBuf, i32)>,
+    /// 上次同步后编辑器和预览的滚动位置
+    synced: Option<(f32, f32)>,
+}
+
+/// 预览中的一个块和它在源文件中的起始行
+#[derive(Component)]
+struct PreviewBlock {
+    line: usize,
+}
+
+/// 可能含有链接的文本，点击时按片段的位置查找链接
+#[derive(Component)]
+struct PreviewText;
+
+#[derive(Component)]
+struct PreviewLink {
+    url: String,
+}
+
+/// 状态栏中打开和关闭预览的按钮，只在当前文件是 Markdown 时显示
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct PreviewButton;
+
+#[derive(Component)]
+pub struct MarkdownPreviewPlugin;
+
+impl SeekerTrait for MarkdownPreviewPlugin {}
+
+impl Plugin for MarkdownPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MarkdownPreview>()
+            .add_systems(OnEnter(SeekerEditSubLoadState::Loaded), Self::spawn_button)
+            .add_systems(
+                Update,
+                (
+                    Self::keybindings,
+                    Self::toggle,
+                    Self::show,
+                    Self::rebuild,
+                    Self::click_link,
+                    Self::sync_scroll,
+                )
+                    .chain()
+                    .after(EditorSystems)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, PreviewButton>);
+    }
+}
+
+impl MarkdownPreviewPlugin {
+    fn spawn_button(
+        mut commands: Commands,
+        status_bar: Single<Entity, With<StatusBarUi>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        commands.entity(*status_bar).with_child((
+            PreviewButton,
+            Node {
+                display: Display::None,
+                padding: UiRect::horizontal(Val::Px(6.)),
+                ..default()
+            },
+            BorderRadius::all(Val::Px(3.)),
+            Hovered::default(),
+            children![(
+                Text::new("Preview"),
+                TextFont {
+                    font: assets.load(MAPLE_MONO_BOLD),
+                    font_size: 13.,
+                    ..default()
+                },
+                TextLayout::new_with_no_wrap(),
+                TextColor(res.colors.home_font_color),
+            )],
+        ));
+    }
+
+    fn keybindings(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        mut preview: ResMut<MarkdownPreview>,
+    ) {
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        for event in reader.read() {
+            if event.state.is_pressed() && command && shift && event.key_code == KeyCode::KeyV {
+                preview.enabled = !preview.enabled;
+            }
+        }
+    }
+
+    fn toggle(
+        query: Query<&Interaction, (Changed<Interaction>, With<PreviewButton>)>,
+        mut preview: ResMut<MarkdownPreview>,
+    ) {
+        if query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            preview.enabled = !preview.enabled;
+        }
+    }
+
+    /// 当前文件是 Markdown 时显示按钮，打开预览时显示预览
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
+    fn show(
+        mut commands: Commands,
+        preview: Res<MarkdownPreview>,
+        state: Res<State<SeekerEditViewState>>,
+        documents: Res<Documents>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        editor: Single<Entity, With<EditorUi>>,
+        mut nodes: ParamSet<(
+            Query<&mut Node, With<PreviewUi>>,
+            Query<&mut Node, With<PreviewButton>>,
+        )>,
+        res: Res<SeekerResource>,
+    ) {
+        let markdown = view
+            .path
+            .as_ref()
+            .and_then(|path| documents.get(path))
+            .is_some_and(|doc| doc.language_id == "markdown");
+        let visible = preview.enabled && markdown && *state.get() == SeekerEditViewState::Editor;
+        let display = |shown: bool| if shown { Display::Flex } else { Display::None };
+        for mut node in nodes.p1().iter_mut() {
+            if node.display != display(markdown) {
+                node.display = display(markdown);
+            }
+        }
+        let mut previews = nodes.p0();
+        match previews.single_mut() {
+            Ok(mut node) => {
+                if node.display != display(visible) {
+                    node.display = display(visible);
+                }
+            }
+            Err(_) if visible => {
+                let preview = commands
+                    .spawn((
+                        PreviewUi::default(),
+                        Node {
+                            width: Val::Percent(PREVIEW_WIDTH),
+                            min_width: Val::Px(0.),
+                            flex_shrink: 0.,
+                            height: Val::Percent(100.),
+                            display: Display::Flex,
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(10.),
+                            padding: UiRect::all(Val::Px(16.)),
+                            border: UiRect::left(Val::Px(1.)),
+                            overflow: Overflow::scroll_y(),
+                            ..default()
+                        },
+                        BorderColor::all(res.colors.button_border),
+                        BackgroundColor(res.colors.editor_background),
+                    ))
+                    .id();
+                commands.entity(*editor).add_child(preview);
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// 文档内容变化后重新生成预览
+    fn rebuild(
+        mut commands: Commands,
+        documents: Res<Documents>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        preview: Single<(Entity, &mut PreviewUi, &Node)>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+        mut redraw: MessageWriter<RequestRedraw>,
+    ) {
+        let (entity, mut preview, node) = preview.into_inner();
+        if node.display == Display::None {
+            return;
+        }
+        let Some(doc) = view.path.as_ref().and_then(|path| documents.get(path)) else {
+            return;
+        };
+        let key = Some((doc.path.clone(), doc.version));
+        if preview.key == key {
+            return;
+        }
+        preview.key = key;
+        preview.synced = None;
+        let dir = doc.path.parent().unwrap_or(Path::new(""));
+        let blocks = parse(&doc.buffer.text());
+        commands
+            .entity(entity)
+            .despawn_children()
+            .with_children(|parent| {
+                for block in &blocks {
+                    Self::spawn_block(parent, block, dir, &res, &assets);
+                }
+            });
+        // 布局完成后再同步一次滚动位置
+        redraw.write(RequestRedraw);
+    }
+
+    fn spawn_block(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        block: &Block,
+        dir: &Path,
+        res: &SeekerResource,
+        assets: &AssetServer,
+    ) {
+        let color = res.colors.home_font_color;
+        let mut entity = match &block.kind {
+            BlockKind::Heading { level, spans } => {
+                let size = match level {
+                    1 => 26.,
+                    2 => 22.,
+                    3 => 18.,
+                    4 => 16.,
+                    _ => FONT_SIZE,
+                };
+                let underline = *level <= 2;
+                let mut entity = parent.spawn((
+                    Node {
+                        padding: UiRect::bottom(Val::Px(if underline { 4. } else { 0. })),
+                        border: UiRect::bottom(Val::Px(if underline { 1. } else { 0. })),
+                        ..default()
+                    },
+                    BorderColor::all(res.colors.button_border),
+                ));
+                entity.with_children(|parent| {
+                    Self::spawn_text(parent, spans, size, Color::WHITE, res, assets);
+                });
+                entity
+            }
+            BlockKind::Paragraph(spans) => {
+                Self::spawn_text(parent, spans, FONT_SIZE, color, res, assets)
+            }
+            BlockKind::Code { language, text } => {
+                let mut entity = parent.spawn((
+                    Node {
+                        padding: UiRect::all(Val::Px(10.)),
+                        overflow: Overflow::clip_x(),
+                        ..default()
+                    },
+                    BorderRadius::all(Val::Px(4.)),
+                    BackgroundColor(res.colors.home_menu),
+                ));
+                entity.with_children(|parent| {
+                    parent
+                        .spawn((
+                            Text::default(),
+                            TextFont {
+                                font: assets.load(MAPLE_MONO_BOLD),
+                                font_size: FONT_SIZE - 1.,
+                                ..default()
+                            },
+                            TextLayout::new_with_no_wrap(),
+                            TextColor(color),
+                        ))
+                        .with_children(|parent| {
+                            for (index, line) in text.lines().enumerate() {
+                                let mut tokens = highlight(line, language);
+                                if index > 0 {
+                                    tokens.insert(0, (TokenKind::Plain, "\n".to_string()));
+                                }
+                                for (kind, token) in tokens {
+                                    let color = match kind {
+                                        TokenKind::Plain => color,
+                                        TokenKind::Keyword => res.colors.syntax_keyword,
+                                        TokenKind::String => res.colors.syntax_string,
+                                        TokenKind::Number => res.colors.syntax_number,
+                                        TokenKind::Comment => res.colors.syntax_comment,
+                                    };
+                                    parent.spawn((
+                                        TextSpan::new(token),
+                                        TextFont {
+                                            font: assets.load(MAPLE_MONO_BOLD),
+                                            font_size: FONT_SIZE - 1.,
+                                            ..default()
+                                        },
+                                        TextColor(color),
+                                    ));
+                                }
+                            }
+                        });
+                });
+                entity
+            }
+            BlockKind::Quote(spans) => {
+                let mut entity = parent.spawn((
+                    Node {
+                        padding: UiRect::left(Val::Px(10.)),
+                        border: UiRect::left(Val::Px(3.)),
+                        ..default()
+                    },
+                    BorderColor::all(res.colors.button_border),
+                ));
+                entity.with_children(|parent| {
+                    let grey = res.colors.home_font_grey_color;
+                    Self::spawn_text(parent, spans, FONT_SIZE, grey, res, assets);
+                });
+                entity
+            }
+            BlockKind::ListItem {
+                depth,
+                marker,
+                checked,
+                spans,
+            } => {
+                let marker = match checked {
+                    Some(true) => "[x]",
+                    Some(false) => "[ ]",
+                    None => marker,
+                };
+                let mut entity = parent.spawn(Node {
+                    margin: UiRect::left(Val::Px(*depth as f32 * 20.)),
+                    column_gap: Val::Px(8.),
+                    ..default()
+                });
+                entity.with_children(|parent| {
+                    parent.spawn((
+                        Text::new(marker),
+                        TextFont {
+                            font: assets.load(MAPLE_MONO_BOLD),
+                            font_size: FONT_SIZE,
+                            ..default()
+                        },
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(res.colors.home_font_grey_color),
+                    ));
+                    Self::spawn_text(parent, spans, FONT_SIZE, color, res, assets);
+                });
+                entity
+            }
+            BlockKind::Table {
+                aligns,
+                header,
+                rows,
+            } => {
+                let mut entity = parent.spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    border: UiRect::all(Val::Px(1.)),
+                    ..default()
+                });
+                entity
+                    .insert(BorderColor::all(res.colors.button_border))
+                    .with_children(|parent| {
+                        for (index, row) in std::iter::once(header).chain(rows).enumerate() {
+                            let background = if index == 0 {
+                                res.colors.home_menu
+                            } else {
+                                Color::NONE
+                            };
+                            parent
+                                .spawn((Node::default(), BackgroundColor(background)))
+                                .with_children(|parent| {
+                                    for (column, align) in aligns.iter().enumerate() {
+                                        let spans =
+                                            row.get(column).map(Vec::as_slice).unwrap_or_default();
+                                        Self::spawn_cell(parent, spans, *align, res, assets);
+                                    }
+                                });
+                        }
+                    });
+                entity
+            }
+            BlockKind::Rule => parent.spawn((
+                Node {
+                    width: Val::Percent(100.),
+                    height: Val::Px(1.),
+                    ..default()
+                },
+                BackgroundColor(res.colors.button_border),
+            )),
+            BlockKind::Image { alt, src } if !src.contains("://") => {
+                let path = dir.join(src);
+                parent.spawn((
+                    ImageNode::new(assets.load(local_asset_path(&path))),
+                    Node {
+                        max_width: Val::Percent(100.),
+                        align_self: AlignSelf::FlexStart,
+                        ..default()
+                    },
+                    Name::new(alt.clone()),
+                ))
+            }
+            // 网络图片显示成链接
+            BlockKind::Image { alt, src } => {
+                let text = if alt.is_empty() { src } else { alt };
+                let spans = [Span {
+                    text: text.clone(),
+                    link: Some(src.clone()),
+                    ..default()
+                }];
+                Self::spawn_text(parent, &spans, FONT_SIZE, color, res, assets)
+            }
+        };
+        entity.insert(PreviewBlock { line: block.line });
+    }
+
+    fn spawn_cell(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        spans: &[Span],
+        align: Align,
+        res: &SeekerResource,
+        assets: &AssetServer,
+    ) {
+        let justify = match align {
+            Align::Left => Justify::Left,
+            Align::Center => Justify::Center,
+            Align::Right => Justify::Right,
+        };
+        parent
+            .spawn((
+                Node {
+                    flex_grow: 1.,
+                    flex_basis: Val::Px(0.),
+                    min_width: Val::Px(0.),
+                    padding: UiRect::axes(Val::Px(8.), Val::Px(4.)),
+                    border: UiRect::all(Val::Px(1.)),
+                    ..default()
+                },
+                BorderColor::all(res.colors.button_border),
+            ))
+            .with_children(|parent| {
+                let color = res.colors.home_font_color;
+                Self::spawn_text(parent, spans, FONT_SIZE, color, res, assets).insert((
+                    TextLayout::new_with_justify(justify),
+                    Node {
+                        flex_grow: 1.,
+                        ..default()
+                    },
+                ));
+            });
+    }
+
+    /// 一段行内文本，每个片段是一个 TextSpan，链接的片段带上 PreviewLink
+    fn spawn_text<'a>(
+        parent: &'a mut RelatedSpawnerCommands<ChildOf>,
+        spans: &[Span],
+        size: f32,
+        color: Color,
+        res: &SeekerResource,
+        assets: &AssetServer,
+    ) -> EntityCommands<'a> {
+        let mut entity = parent.spawn((
+            PreviewText,
+            Text::default(),
+            TextFont {
+                font: assets.load(MAPLE_MONO_BOLD),
+                font_size: size,
+                ..default()
+            },
+            TextColor(color),
+        ));
+        entity.with_children(|parent| {
+            for span in spans {
+                let font = if span.italic {
+                    MAPLE_MONO_BOLD_ITALIC
+                } else {
+                    MAPLE_MONO_BOLD
+                };
+                let color = if span.link.is_some() {
+                    res.colors.info
+                } else if span.strike {
+                    res.colors.home_font_grey_color
+                } else if span.bold {
+                    Color::WHITE
+                } else {
+                    color
+                };
+                let mut text = parent.spawn((
+                    TextSpan::new(span.text.clone()),
+                    TextFont {
+                        font: assets.load(font),
+                        font_size: size,
+                        ..default()
+                    },
+                    TextColor(color),
+                ));
+                if span.code {
+                    text.insert(TextBackgroundColor(res.colors.home_menu));
+                }
+                if let Some(url) = &span.link {
+                    text.insert(PreviewLink { url: url.clone() });
+                }
+            }
+        });
+        entity
+    }
+
+    /// 点击链接：网址交给系统打开，相对路径在编辑器中打开
+    fn click_link(
+        mouse: Res<ButtonInput<MouseButton>>,
+        window: Single<&Window>,
+        preview: Single<(&PreviewUi, &Node, &ComputedNode, &UiGlobalTransform)>,
+        texts: Query<(&TextLayoutInfo, &ComputedNode, &UiGlobalTransform), With<PreviewText>>,
+        links: Query<&PreviewLink>,
+        mut open: MessageWriter<OpenLocation>,
+    ) {
+        if !mouse.just_pressed(MouseButton::Left) {
+            return;
+        }
+        let (preview, node, computed, transform) = preview.into_inner();
+        let Some((path, _)) = preview
+            .key
+            .as_ref()
+            .filter(|_| node.display != Display::None)
+        else {
+            return;
+        };
+        let Some(cursor) = window.physical_cursor_position() else {
+            return;
+        };
+        let rect = |computed: &ComputedNode, transform: &UiGlobalTransform| {
+            Rect::from_center_size(transform.translation, computed.size())
+        };
+        if !rect(computed, transform).contains(cursor) {
+            return;
+        }
+        for (layout, computed, transform) in texts.iter() {
+            let rect = rect(computed, transform);
+            if !rect.contains(cursor) {
+                continue;
+            }
+            let local = cursor - rect.min;
+            let link = layout
+                .section_rects
+                .iter()
+                .filter(|(_, section)| section.contains(local))
+                .find_map(|(entity, _)| links.get(*entity).ok());
+            if let Some(link) = link {
+                follow(&link.url, path, &mut open);
+                return;
+            }
+        }
+    }
+
+    /// 按块的起始行在编辑器和预览之间同步滚动位置，哪边的滚动位置变了就以哪边为准
+    #[allow(clippy::type_complexity)]
+    fn sync_scroll(
+        documents: Res<Documents>,
+        view: Single<(&EditorView, &mut ScrollPosition, &ComputedNode), With<FocusedEditor>>,
+        preview: Single<
+            (
+                &mut PreviewUi,
+                &mut ScrollPosition,
+                &Node,
+                &ComputedNode,
+                &UiGlobalTransform,
+                &Children,
+            ),
+            Without<EditorView>,
+        >,
+        blocks: Query<(&PreviewBlock, &ComputedNode, &UiGlobalTransform)>,
+        mut redraw: MessageWriter<RequestRedraw>,
+    ) {
+        let (view, mut editor_scroll, editor_computed) = view.into_inner();
+        let (mut preview, mut preview_scroll, node, computed, transform, children) =
+            preview.into_inner();
+        if node.display == Display::None {
+            return;
+        }
+        let Some(doc) = view.path.as_ref().and_then(|path| documents.get(path)) else {
+            return;
+        };
+        if preview
+            .key
+            .as_ref()
+            .is_none_or(|(path, _)| *path != doc.path)
+        {
+            return;
+        }
+        // 每个块的起始行和它在预览内容中的位置
+        let scale = computed.inverse_scale_factor();
+        let top = transform.translation.y - computed.size().y / 2. + computed.scroll_position.y;
+        let mut anchors = vec![(0., 0.)];
+        for (block, computed, transform) in
+            children.iter().filter_map(|child| blocks.get(child).ok())
+        {
+            // 刚生成的内容还没有布局
+            if computed.size().y <= 0. {
+                continue;
+            }
+            let y = (transform.translation.y - computed.size().y / 2. - top) * scale;
+            if anchors.last().is_some_and(|(_, last)| y >= *last) {
+                anchors.push((block.line as f32, y));
+            }
+        }
+        if anchors.len() == 1 {
+            return;
+        }
+        let content = computed.content_size().y * scale;
+        anchors.push((doc.buffer.line_count() as f32, content));
+
+        let current = (editor_scroll.y, preview_scroll.y);
+        if preview.synced == Some(current) {
+            return;
+        }
+        let preview_moved = preview
+            .synced
+            .is_some_and(|(editor, preview)| editor == current.0 && preview != current.1);
+        if preview_moved {
+            let inverse: Vec<(f32, f32)> = anchors.iter().map(|(line, y)| (*y, *line)).collect();
+            let line = interpolate(&inverse, preview_scroll.y);
+            let last = doc.buffer.line_count().saturating_sub(1);
+            let row = view
+                .wrap
+                .row(doc, Position::new((line as usize).min(last), 0));
+            let max = (editor_computed.content_size().y - editor_computed.size().y)
+                * editor_computed.inverse_scale_factor();
+            editor_scroll.y = ((row as f32 + line.fract()) * LINE_HEIGHT).clamp(0., max.max(0.));
+            // 编辑器这一帧已经渲染过了
+            redraw.write(RequestRedraw);
+        } else {
+            let row = editor_scroll.y / LINE_HEIGHT;
+            let (line, columns) = view.wrap.segment(doc, row as usize);
+            let fract = if columns.start == 0 { row.fract() } else { 0. };
+            let max = (computed.content_size().y - computed.size().y) * scale;
+            preview_scroll.y = interpolate(&anchors, line as f32 + fract).clamp(0., max.max(0.));
+        }
+        preview.synced = Some((editor_scroll.y, preview_scroll.y));
+    }
+}
+
+/// 在按第一个值排序的点之间线性插值
+fn interpolate(points: &[(f32, f32)], x: f32) -> f32 {
+    let next = points
+        .iter()
+        .position(|(px, _)| *px > x)
+        .unwrap_or(points.len() - 1)
+        .max(1);
+    let (x0, y0) = points[next - 1];
+    let (x1, y1) = points[next];
+    if x1 <= x0 {
+        return y0;
+    }
+    y0 + (y1 - y0) * ((x - x0) / (x1 - x0)).clamp(0., 1.)
+}
+
+/// 去掉路径中的 . 和 ..，文档按路径查找
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            PathComponent::CurDir => {}
+            PathComponent::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn follow(url: &str, doc: &Path, open: &mut MessageWriter<OpenLocation>) {
+    if url.contains("://") || url.starts_with("mailto:") {
+        open_external(url);
+        return;
+    }
+    // 文档内的锚点不处理
+    let path = url.split('#').next().unwrap_or_default();
+    if path.is_empty() {
+        return;
+    }
+    let target = normalize(&doc.parent().unwrap_or(Path::new("")).join(path));
+    if target.is_file() {
+        open.write(OpenLocation {
+            path: target,
+            position: None,
+        });
+    } else {
+        warn!("link target not found: {}", target.display());
+    }
+}
+
+/// 用系统默认的程序打开网址
+fn open_external(url: &str) {
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = Command::new("xdg-open");
+    match command.arg(url).spawn() {
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(err) => error!("open {url}: {err}"),
+    }
+}
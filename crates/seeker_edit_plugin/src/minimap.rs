@@ -0,0 +1,509 @@
+use crate::editor::{EditorSystems, LINE_HEIGHT};
+use crate::git_gutter::GitDiffs;
+use crate::pane::TAB_BAR_HEIGHT;
+use crate::EditorView;
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+use seeker_config::SEEKER_CONFIG;
+use seeker_editor::buffer::Position;
+use seeker_editor::diagnostic::{Diagnostics, Severity};
+use seeker_editor::document::{Document, Documents};
+use seeker_editor::width::char_display_width;
+use seeker_git::diff::HunkKind;
+use seeker_resource::SeekerResource;
+use seeker_scroll::{MarkerLane, ScrollProxy, Scrollbar, ScrollbarMarker, ScrollbarMarkers};
+use seeker_state::{SeekerEditSubLoadState, SeekerEditViewState};
+use seeker_trait::SeekerTrait;
+use std::path::PathBuf;
+
+/// 缩略图的宽度，一个半角字符占一个像素
+pub const MINIMAP_WIDTH: f32 = 80.;
+/// 缩略图中一行的高度，其中两个像素画字符
+const MINIMAP_ROW: f32 = 3.;
+pub const SCROLLBAR_WIDTH: f32 = 14.;
+/// 选中文本出现的位置最多标记的数量
+const MAX_MATCH_MARKERS: usize = 1000;
+
+/// 编辑器右侧缩略图和滚动条占的宽度
+pub fn overlay_width() -> f32 {
+    if SEEKER_CONFIG.minimap {
+        MINIMAP_WIDTH + SCROLLBAR_WIDTH
+    } else {
+        SCROLLBAR_WIDTH
+    }
+}
+
+/// 编辑器的缩略图，按比例缩小显示文档并标出可见区域
+#[derive(Component)]
+#[require(Node, FocusPolicy::Block, Interaction, RelativeCursorPosition)]
+pub struct Minimap {
+    pub view: Entity,
+    /// 缩略图中第一行对应的显示行
+    first: usize,
+}
+
+#[derive(Component)]
+pub struct MinimapImage;
+
+/// 缩略图中表示可见区域的滑块
+#[derive(Component)]
+pub struct MinimapSlider;
+
+/// 上次绘制时的状态，没有变化就不重绘
+#[derive(Component, Default, PartialEq)]
+struct MinimapKey {
+    path: Option<PathBuf>,
+    version: i32,
+    folds: u32,
+    wrap: Option<usize>,
+    first: usize,
+    rows: usize,
+    cursor: usize,
+}
+
+/// 滚动条标记对应的状态
+#[derive(Component, Default, PartialEq)]
+struct MarkerKey {
+    path: Option<PathBuf>,
+    version: i32,
+    folds: u32,
+    wrap: Option<usize>,
+    cursor: usize,
+    selection: Option<(Position, Position)>,
+}
+
+#[derive(Component)]
+pub struct MinimapPlugin;
+
+impl SeekerTrait for MinimapPlugin {}
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (Self::input, Self::render, Self::markers)
+                .chain()
+                .after(EditorSystems)
+                .run_if(
+                    in_state(SeekerEditSubLoadState::Loaded)
+                        .and(in_state(SeekerEditViewState::Editor)),
+                ),
+        );
+    }
+}
+
+impl MinimapPlugin {
+    /// 生成视图右侧的缩略图和滚动条，由窗格放在标签栏下面
+    pub fn spawn(commands: &mut Commands, view: Entity, res: &SeekerResource) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        if SEEKER_CONFIG.minimap {
+            let minimap = commands
+                .spawn((
+                    Minimap { view, first: 0 },
+                    MinimapKey::default(),
+                    ScrollProxy(view),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(TAB_BAR_HEIGHT),
+                        bottom: Val::Px(0.),
+                        right: Val::Px(SCROLLBAR_WIDTH),
+                        width: Val::Px(MINIMAP_WIDTH),
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    BackgroundColor(res.colors.editor_background),
+                    children![
+                        (
+                            MinimapImage,
+                            ImageNode::default(),
+                            Node {
+                                position_type: PositionType::Absolute,
+                                width: Val::Px(MINIMAP_WIDTH),
+                                ..default()
+                            },
+                        ),
+                        (
+                            MinimapSlider,
+                            Node {
+                                position_type: PositionType::Absolute,
+                                width: Val::Percent(100.),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgba(1., 1., 1., 0.08)),
+                        ),
+                    ],
+                ))
+                .id();
+            entities.push(minimap);
+        }
+        let scrollbar = commands
+            .spawn((
+                Scrollbar { target: view },
+                ScrollbarMarkers::default(),
+                MarkerKey::default(),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(TAB_BAR_HEIGHT),
+                    bottom: Val::Px(0.),
+                    right: Val::Px(0.),
+                    width: Val::Px(SCROLLBAR_WIDTH),
+                    border: UiRect::left(Val::Px(1.)),
+                    ..default()
+                },
+                BorderColor::all(res.colors.home_menu),
+                BackgroundColor(res.colors.editor_background),
+            ))
+            .id();
+        entities.push(scrollbar);
+        entities
+    }
+
+    /// 点击滑块以外的地方把那一行滚动到中间，按住后拖动滑块
+    fn input(
+        minimaps: Query<(
+            Entity,
+            &Minimap,
+            &Interaction,
+            &RelativeCursorPosition,
+            &ComputedNode,
+        )>,
+        slider_nodes: Query<(&ChildOf, &Node), With<MinimapSlider>>,
+        documents: Res<Documents>,
+        mut views: Query<(&EditorView, &mut ScrollPosition, &ComputedNode), Without<Minimap>>,
+        mut dragging: Local<Option<(Entity, f32, f32)>>,
+    ) {
+        if dragging.is_some_and(|(entity, _, _)| {
+            !minimaps
+                .get(entity)
+                .is_ok_and(|(_, _, interaction, _, _)| *interaction == Interaction::Pressed)
+        }) {
+            *dragging = None;
+        }
+        for (entity, minimap, interaction, relative, computed) in minimaps.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            let (Some(normalized), Ok((view, mut scroll, view_computed))) =
+                (relative.normalized, views.get_mut(minimap.view))
+            else {
+                continue;
+            };
+            let Some(doc) = view.path.as_ref().and_then(|path| documents.get(path)) else {
+                continue;
+            };
+            let height = computed.size().y * computed.inverse_scale_factor();
+            let viewport = view_computed.size().y * view_computed.inverse_scale_factor();
+            let pointer = (normalized.y + 0.5) * height;
+            let row_count = view.wrap.row_count(doc);
+            let max_offset = (row_count as f32 * LINE_HEIGHT - viewport).max(0.);
+            let Some((slider_top, slider_height)) = slider_nodes
+                .iter()
+                .find(|(parent, _)| parent.parent() == entity)
+                .map(|(_, node)| (px(node.top), px(node.height)))
+            else {
+                continue;
+            };
+            let (_, start, start_scroll) = *dragging.get_or_insert_with(|| {
+                let mut start_scroll = scroll.y;
+                // 点在滑块以外时先跳过去
+                if pointer < slider_top || pointer > slider_top + slider_height {
+                    let row = minimap.first as f32 + pointer / MINIMAP_ROW;
+                    start_scroll = (row * LINE_HEIGHT - viewport / 2.).clamp(0., max_offset);
+                }
+                (entity, pointer, start_scroll)
+            });
+            let track = (row_count as f32 * MINIMAP_ROW).min(height) - slider_height;
+            let offset = drag_offset(start_scroll, pointer - start, track, max_offset);
+            if scroll.y != offset {
+                scroll.y = offset;
+            }
+        }
+    }
+
+    /// 文档比缩略图长时缩略图跟随滚动比例移动
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
+    fn render(
+        mut minimaps: Query<(&mut Minimap, &mut MinimapKey, &ComputedNode, &Children)>,
+        views: Query<(&EditorView, &ScrollPosition, &ComputedNode), Without<Minimap>>,
+        mut image_nodes: Query<(&mut ImageNode, &mut Node), With<MinimapImage>>,
+        mut sliders: Query<&mut Node, (With<MinimapSlider>, Without<MinimapImage>)>,
+        documents: Res<Documents>,
+        diagnostics: Res<Diagnostics>,
+        mut images: ResMut<Assets<Image>>,
+        res: Res<SeekerResource>,
+    ) {
+        for (mut minimap, mut key, computed, children) in minimaps.iter_mut() {
+            let Ok((view, scroll, view_computed)) = views.get(minimap.view) else {
+                continue;
+            };
+            let doc = view.path.as_ref().and_then(|path| documents.get(path));
+            let height = computed.size().y * computed.inverse_scale_factor();
+            let viewport = view_computed.size().y * view_computed.inverse_scale_factor();
+            let row_count = doc.map(|doc| view.wrap.row_count(doc)).unwrap_or_default();
+            let capacity = (height / MINIMAP_ROW).floor() as usize;
+            let max_offset = (row_count as f32 * LINE_HEIGHT - viewport).max(0.);
+            let first = first_row(row_count, capacity, scroll.y, max_offset);
+            let rows = row_count.saturating_sub(first).min(capacity);
+            minimap.bypass_change_detection().first = first;
+
+            for child in children.iter() {
+                if let Ok(mut node) = sliders.get_mut(child) {
+                    let top = Val::Px((scroll.y / LINE_HEIGHT - first as f32) * MINIMAP_ROW);
+                    let height = Val::Px(viewport / LINE_HEIGHT * MINIMAP_ROW);
+                    if node.top != top || node.height != height {
+                        node.top = top;
+                        node.height = height;
+                    }
+                }
+            }
+
+            let new_key = MinimapKey {
+                path: view.path.clone(),
+                version: doc.map(|doc| doc.version).unwrap_or_default(),
+                folds: doc.map(|doc| doc.folds.revision()).unwrap_or_default(),
+                wrap: view.wrap.width(),
+                first,
+                rows,
+                cursor: view.cursor.line,
+            };
+            if *key == new_key && !diagnostics.is_changed() {
+                continue;
+            }
+            *key = new_key;
+            let image = doc.map(|doc| draw(doc, view, first, rows, &diagnostics, &res));
+            for child in children.iter() {
+                if let Ok((mut image_node, mut node)) = image_nodes.get_mut(child) {
+                    node.height = Val::Px(rows as f32 * MINIMAP_ROW);
+                    image_node.image = image
+                        .clone()
+                        .map(|image| images.add(image))
+                        .unwrap_or_default();
+                }
+            }
+        }
+    }
+
+    /// 滚动条上标出改动、诊断、选中文本出现的位置和光标
+    fn markers(
+        mut scrollbars: Query<(&Scrollbar, &mut ScrollbarMarkers, &mut MarkerKey)>,
+        views: Query<&EditorView>,
+        documents: Res<Documents>,
+        diagnostics: Res<Diagnostics>,
+        diffs: Res<GitDiffs>,
+        res: Res<SeekerResource>,
+    ) {
+        for (scrollbar, mut markers, mut key) in scrollbars.iter_mut() {
+            let Ok(view) = views.get(scrollbar.target) else {
+                continue;
+            };
+            let doc = view.path.as_ref().and_then(|path| documents.get(path));
+            let new_key = MarkerKey {
+                path: view.path.clone(),
+                version: doc.map(|doc| doc.version).unwrap_or_default(),
+                folds: doc.map(|doc| doc.folds.revision()).unwrap_or_default(),
+                wrap: view.wrap.width(),
+                cursor: view.cursor.line,
+                selection: view.selection(),
+            };
+            if *key == new_key && !diagnostics.is_changed() && !diffs.is_changed() {
+                continue;
+            }
+            *key = new_key;
+            let Some(doc) = doc else {
+                markers.set_if_neq(ScrollbarMarkers::default());
+                continue;
+            };
+            let rows = view.wrap.row_count(doc).max(1) as f32;
+            let marker = |start: usize, end: usize, lane, color| ScrollbarMarker {
+                start: view.wrap.row(doc, Position::new(start, 0)) as f32 / rows,
+                end: view.wrap.row(doc, Position::new(end, 0)) as f32 / rows,
+                lane,
+                color,
+            };
+            let mut result = Vec::new();
+            for hunk in diffs.hunks(&doc.path) {
+                let color = match hunk.kind() {
+                    HunkKind::Added => res.colors.git_added,
+                    HunkKind::Modified => res.colors.git_modified,
+                    HunkKind::Deleted => res.colors.error,
+                };
+                result.push(marker(
+                    hunk.new.start,
+                    hunk.new.end,
+                    MarkerLane::Left,
+                    color,
+                ));
+            }
+            for line in selection_matches(doc, view.selection()) {
+                result.push(marker(
+                    line,
+                    line + 1,
+                    MarkerLane::Center,
+                    res.colors.editor_cursor,
+                ));
+            }
+            for diagnostic in diagnostics.for_path(&doc.path) {
+                let color = match diagnostic.severity {
+                    Severity::Error => res.colors.error,
+                    Severity::Warning => res.colors.warning,
+                    _ => res.colors.info,
+                };
+                result.push(marker(
+                    diagnostic.start.line,
+                    diagnostic.end.line + 1,
+                    MarkerLane::Right,
+                    color,
+                ));
+            }
+            let cursor = view.cursor.line;
+            result.push(marker(
+                cursor,
+                cursor,
+                MarkerLane::Full,
+                res.colors.home_font_color,
+            ));
+            markers.set_if_neq(ScrollbarMarkers(result));
+        }
+    }
+}
+
+/// 文档比缩略图长时，缩略图第一行按滚动比例在文档中移动
+fn first_row(row_count: usize, capacity: usize, scroll: f32, max_offset: f32) -> usize {
+    if row_count <= capacity || max_offset <= 0. {
+        return 0;
+    }
+    let fraction = (scroll / max_offset).clamp(0., 1.);
+    (fraction * (row_count - capacity) as f32).round() as usize
+}
+
+/// 拖动滑块 moved 像素后的滚动位置，滑块可以移动的距离 track 对应整个滚动范围
+fn drag_offset(start_scroll: f32, moved: f32, track: f32, max_offset: f32) -> f32 {
+    let offset = if track > 0. {
+        start_scroll + moved * max_offset / track
+    } else {
+        start_scroll
+    };
+    offset.clamp(0., max_offset)
+}
+
+fn px(val: Val) -> f32 {
+    match val {
+        Val::Px(px) => px,
+        _ => 0.,
+    }
+}
+
+/// 单行选区在文档中出现的行
+fn selection_matches(doc: &Document, selection: Option<(Position, Position)>) -> Vec<usize> {
+    let Some((start, end)) = selection.filter(|(start, end)| start.line == end.line) else {
+        return vec![];
+    };
+    let text: String = doc
+        .buffer
+        .line(start.line)
+        .chars()
+        .skip(start.column)
+        .take(end.column - start.column)
+        .collect();
+    if text.trim().is_empty() {
+        return vec![];
+    }
+    doc.buffer
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.contains(&text))
+        .map(|(line, _)| line)
+        .take(MAX_MATCH_MARKERS)
+        .collect()
+}
+
+/// 把显示行 first..first+rows 画成图片，字符画成小方块，诊断范围内的字符用对应的颜色
+fn draw(
+    doc: &Document,
+    view: &EditorView,
+    first: usize,
+    rows: usize,
+    diagnostics: &Diagnostics,
+    res: &SeekerResource,
+) -> Image {
+    let width = MINIMAP_WIDTH as usize;
+    let height = (rows.max(1) as f32 * MINIMAP_ROW) as usize;
+    let mut data = vec![0; width * height * 4];
+    let mut fill = |x: usize, y: usize, w: usize, h: usize, color: Color| {
+        let rgba = color.to_srgba().to_u8_array();
+        for y in y..(y + h).min(height) {
+            for x in x..(x + w).min(width) {
+                let index = (y * width + x) * 4;
+                data[index..index + 4].copy_from_slice(&rgba);
+            }
+        }
+    };
+    let text_color = res.colors.home_font_color.with_alpha(0.5);
+    let row_height = MINIMAP_ROW as usize;
+    for row in first..first + rows {
+        let (line, columns) = view.wrap.segment(doc, row);
+        let y = (row - first) * row_height;
+        if line == view.cursor.line {
+            fill(0, y, width, row_height, res.colors.editor_current_line);
+        }
+        let mut x = 0;
+        let chars = doc.buffer.line(line).chars().enumerate();
+        for (column, ch) in chars.skip(columns.start).take(columns.len()) {
+            let w = if ch == '\t' {
+                4
+            } else {
+                char_display_width(ch)
+            };
+            if x >= width {
+                break;
+            }
+            if !ch.is_whitespace() {
+                let position = Position::new(line, column);
+                let color = diagnostics
+                    .for_path(&doc.path)
+                    .filter(|diagnostic| diagnostic.start <= position && position < diagnostic.end)
+                    .map(|diagnostic| diagnostic.severity)
+                    .min()
+                    .map(|severity| match severity {
+                        Severity::Error => res.colors.error,
+                        Severity::Warning => res.colors.warning,
+                        _ => res.colors.info,
+                    })
+                    .unwrap_or(text_color);
+                fill(x, y, w, row_height - 1, color);
+            }
+            x += w;
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+#[test]
+fn test_minimap_mapping() {
+    // 文档能全部显示时从第一行开始
+    assert_eq!(first_row(50, 100, 300., 400.), 0);
+    assert_eq!(first_row(500, 100, 0., 0.), 0);
+    // 滚动到一半时缩略图显示中间的部分
+    assert_eq!(first_row(500, 100, 0., 9000.), 0);
+    assert_eq!(first_row(500, 100, 4500., 9000.), 200);
+    assert_eq!(first_row(500, 100, 9000., 9000.), 400);
+    assert_eq!(first_row(500, 100, 20000., 9000.), 400);
+
+    // 滑块移动整个轨道对应整个滚动范围
+    assert_eq!(drag_offset(0., 150., 300., 9000.), 4500.);
+    assert_eq!(drag_offset(4500., -600., 300., 9000.), 0.);
+    assert_eq!(drag_offset(4500., 600., 300., 9000.), 9000.);
+    assert_eq!(drag_offset(100., 50., 0., 9000.), 100.);
+}
@@ -0,0 +1,477 @@
+use crate::editor::EditorSystems;
+use crate::panel::PanelPlugin;
+use crate::source_control::project_root;
+use crate::{EditorView, FocusedEditor, OpenLocation, PanelUi};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use lsp_types::SymbolKind;
+use seeker_editor::buffer::Position;
+use seeker_editor::document::Documents;
+use seeker_lsp::plugin::{
+    LspRequest, LspRequestKind, LspResponse, LspServers, LspSymbol, WorkspaceSymbolRequest,
+};
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::project_list::CurrentProject;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditPanelState, SeekerEditSubLoadState};
+use seeker_trait::text_input::{SeekerTextInput, TextInputSubmit, TextInputSystems};
+use seeker_trait::SeekerTrait;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 语言服务器没有响应时重新请求的间隔
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 大纲面板，列出当前文件的符号或者搜索整个工作区的符号
+#[derive(Resource, Default)]
+pub struct Outline {
+    /// 搜索整个工作区而不是当前文件
+    workspace: bool,
+    /// 面板重新生成时从这里恢复输入框
+    filter: SeekerTextInput,
+    /// 当前文件的符号和对应的文档版本
+    path: Option<PathBuf>,
+    version: i32,
+    symbols: Vec<LspSymbol>,
+    /// 还没有响应的请求和发出的时间
+    pending: Option<(PathBuf, Duration)>,
+    /// 工作区符号和对应的搜索内容
+    query: String,
+    workspace_symbols: Vec<LspSymbol>,
+}
+
+impl Outline {
+    /// 按名称过滤的符号和它在 symbols 中的下标，工作区符号没有下标
+    fn filtered(&self) -> Vec<(Option<usize>, &LspSymbol)> {
+        let filter = self.filter.value().trim().to_lowercase();
+        if self.workspace {
+            return self
+                .workspace_symbols
+                .iter()
+                .map(|symbol| (None, symbol))
+                .collect();
+        }
+        self.symbols
+            .iter()
+            .enumerate()
+            .filter(|(_, symbol)| symbol.name.to_lowercase().contains(&filter))
+            .map(|(index, symbol)| (Some(index), symbol))
+            .collect()
+    }
+
+    /// 包含光标的最内层符号
+    fn current(&self, path: &Path, cursor: Position) -> Option<usize> {
+        if self.path.as_deref() != Some(path) {
+            return None;
+        }
+        self.symbols
+            .iter()
+            .enumerate()
+            .filter(|(_, symbol)| symbol.location.start <= cursor && cursor <= symbol.location.end)
+            .max_by_key(|(index, symbol)| (symbol.depth, *index))
+            .map(|(index, _)| index)
+    }
+}
+
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct OutlineItem {
+    pub path: PathBuf,
+    pub position: Position,
+    /// 当前文件符号的下标，用于跟随光标高亮
+    pub index: Option<usize>,
+}
+
+#[derive(Component)]
+pub struct OutlineFilterInput;
+
+/// 在当前文件和整个工作区之间切换
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct OutlineModeButton;
+
+#[derive(Component)]
+pub struct OutlinePlugin;
+
+impl SeekerTrait for OutlinePlugin {}
+
+impl Plugin for OutlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Outline>()
+            .add_systems(OnExit(SeekerEditPanelState::Outline), Self::exit)
+            .add_systems(
+                Update,
+                (
+                    Self::filter_input.run_if(in_state(SeekerEditPanelState::Outline)),
+                    Self::toggle,
+                    Self::switch_mode,
+                    Self::request.run_if(in_state(SeekerEditPanelState::Outline)),
+                    Self::receive,
+                    Self::click,
+                    Self::render.run_if(
+                        in_state(SeekerEditPanelState::Outline).and(resource_changed::<Outline>),
+                    ),
+                    Self::highlight.run_if(in_state(SeekerEditPanelState::Outline)),
+                )
+                    .chain()
+                    .after(EditorSystems)
+                    .after(TextInputSystems)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, OutlineModeButton>,
+            );
+    }
+}
+
+impl OutlinePlugin {
+    fn exit(mut outline: ResMut<Outline>) {
+        *outline = Outline::default();
+    }
+
+    /// Ctrl+Shift+O 打开或关闭当前文件的大纲，Ctrl+T 搜索工作区符号
+    fn toggle(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        state: Res<State<SeekerEditPanelState>>,
+        mut next: ResMut<NextState<SeekerEditPanelState>>,
+        mut outline: ResMut<Outline>,
+    ) {
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        for event in reader.read() {
+            if !event.state.is_pressed() || !command {
+                continue;
+            }
+            let Key::Character(ch) = &event.logical_key else {
+                continue;
+            };
+            let workspace = match ch.to_lowercase().as_str() {
+                "o" if shift => false,
+                "t" if !shift => true,
+                _ => continue,
+            };
+            let open = *state.get() == SeekerEditPanelState::Outline;
+            if open && outline.workspace == workspace {
+                next.set(SeekerEditPanelState::None);
+                continue;
+            }
+            if !open {
+                next.set(SeekerEditPanelState::Outline);
+            }
+            outline.workspace = workspace;
+            outline.filter.set_value("");
+            outline.filter.focused = true;
+        }
+    }
+
+    fn switch_mode(
+        query: Query<&Interaction, (Changed<Interaction>, With<OutlineModeButton>)>,
+        mut outline: ResMut<Outline>,
+    ) {
+        if query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            outline.workspace = !outline.workspace;
+            outline.filter.set_value("");
+        }
+    }
+
+    /// 输入框的内容保存到 Outline，变化时重新过滤，搜索工作区时发出请求。
+    /// 点击其他地方失去焦点，Enter 跳转到第一个结果
+    fn filter_input(
+        input: Single<(Entity, &mut SeekerTextInput, &Interaction), With<OutlineFilterInput>>,
+        mut submit: MessageReader<TextInputSubmit>,
+        mouse: Res<ButtonInput<MouseButton>>,
+        mut outline: ResMut<Outline>,
+        mut symbols: MessageWriter<WorkspaceSymbolRequest>,
+        mut open: MessageWriter<OpenLocation>,
+    ) {
+        let (entity, mut filter, interaction) = input.into_inner();
+        if filter.is_changed() {
+            let changed = filter.value() != outline.filter.value();
+            let query = filter.value().trim().to_string();
+            if outline.workspace && !query.is_empty() && query != outline.filter.value().trim() {
+                symbols.write(WorkspaceSymbolRequest { query });
+            }
+            // 光标和焦点变化时不需要重新生成面板
+            outline.bypass_change_detection().filter = filter.clone();
+            if changed {
+                outline.set_changed();
+            }
+        }
+        if submit.read().any(|submit| submit.entity == entity)
+            && let Some((_, symbol)) = outline.filtered().first()
+        {
+            open.write(OpenLocation {
+                path: symbol.location.path.clone(),
+                position: Some(symbol.selection),
+            });
+            filter.focused = false;
+        } else if mouse.just_pressed(MouseButton::Left)
+            && *interaction != Interaction::Pressed
+            && filter.focused
+        {
+            filter.focused = false;
+        }
+    }
+
+    /// 聚焦的文件或者内容变化后重新请求，同一时间只有一个请求
+    fn request(
+        time: Res<Time>,
+        mut outline: ResMut<Outline>,
+        documents: Res<Documents>,
+        servers: Res<LspServers>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        mut requests: MessageWriter<LspRequest>,
+    ) {
+        if outline.workspace {
+            return;
+        }
+        let Some(doc) = view.path.as_ref().and_then(|path| documents.get(path)) else {
+            if outline.path.is_some() {
+                outline.path = None;
+                outline.symbols.clear();
+            }
+            return;
+        };
+        if outline.path.as_ref() == Some(&doc.path) && outline.version == doc.version {
+            return;
+        }
+        let now = time.elapsed();
+        let outline = outline.bypass_change_detection();
+        if outline
+            .pending
+            .as_ref()
+            .is_some_and(|(_, sent)| now - *sent < REQUEST_TIMEOUT)
+        {
+            return;
+        }
+        // 语言服务器启动后会唤醒事件循环，到时再请求
+        if !servers.is_running(&doc.language_id) {
+            return;
+        }
+        outline.pending = Some((doc.path.clone(), now));
+        requests.write(LspRequest {
+            path: doc.path.clone(),
+            position: Position::default(),
+            kind: LspRequestKind::DocumentSymbol,
+        });
+    }
+
+    fn receive(mut reader: MessageReader<LspResponse>, mut outline: ResMut<Outline>) {
+        for response in reader.read() {
+            match response {
+                LspResponse::DocumentSymbols {
+                    path,
+                    version,
+                    symbols,
+                } => {
+                    // 符号没有变化时不重新生成列表，保留滚动位置
+                    let changed =
+                        outline.path.as_ref() != Some(path) || outline.symbols != *symbols;
+                    let cache = outline.bypass_change_detection();
+                    if cache
+                        .pending
+                        .as_ref()
+                        .is_some_and(|(pending, _)| pending == path)
+                    {
+                        cache.pending = None;
+                    }
+                    cache.path = Some(path.clone());
+                    cache.version = *version;
+                    cache.symbols = symbols.clone();
+                    if changed {
+                        outline.set_changed();
+                    }
+                }
+                LspResponse::WorkspaceSymbols { query, symbols } => {
+                    if *query != outline.filter.value().trim() {
+                        continue;
+                    }
+                    // 每个语言服务器分别返回结果
+                    if outline.query != *query {
+                        outline.query = query.clone();
+                        outline.workspace_symbols.clear();
+                    }
+                    outline.workspace_symbols.extend(symbols.iter().cloned());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn click(
+        query: Query<(&Interaction, &OutlineItem), Changed<Interaction>>,
+        mut open: MessageWriter<OpenLocation>,
+    ) {
+        for (interaction, item) in query.iter() {
+            if *interaction == Interaction::Pressed {
+                open.write(OpenLocation {
+                    path: item.path.clone(),
+                    position: Some(item.position),
+                });
+            }
+        }
+    }
+
+    fn render(
+        mut commands: Commands,
+        outline: Res<Outline>,
+        current_project: Res<CurrentProject>,
+        panel: Single<Entity, With<PanelUi>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            ..default()
+        };
+        let root = project_root(&current_project).unwrap_or_default();
+        let symbols = outline.filtered();
+        let (title, mode) = if outline.workspace {
+            ("Workspace Symbols", "Current File")
+        } else {
+            ("Outline", "Workspace")
+        };
+        let title = format!("{title} ({})", symbols.len());
+        let mut filter = outline.filter.clone();
+        filter.placeholder = if outline.workspace {
+            "Search symbols"
+        } else {
+            "Filter"
+        }
+        .to_string();
+        PanelPlugin::spawn_panel(
+            &mut commands,
+            *panel,
+            SeekerEditPanelState::Outline,
+            &res,
+            &assets,
+            |parent| {
+                PanelPlugin::panel_title(parent, title, &res, &assets);
+                parent.spawn((
+                    OutlineFilterInput,
+                    filter,
+                    text_font.clone(),
+                    Node {
+                        width: Val::Px(240.),
+                        padding: UiRect::horizontal(Val::Px(4.)),
+                        border: UiRect::all(Val::Px(1.)),
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    BorderColor::all(res.colors.button_border),
+                    BackgroundColor(res.colors.editor_background),
+                    BorderRadius::all(Val::Px(3.)),
+                ));
+                Self::ui_button_same(parent, OutlineModeButton, mode, &res, &assets);
+            },
+            |parent| {
+                for (index, symbol) in symbols {
+                    let (icon, color) = kind_icon(symbol.kind, &res);
+                    let detail = if outline.workspace {
+                        let path = &symbol.location.path;
+                        format!(
+                            "{}:{}",
+                            path.strip_prefix(&root).unwrap_or(path).display(),
+                            symbol.location.start.line + 1
+                        )
+                    } else {
+                        symbol.detail.clone().unwrap_or_default()
+                    };
+                    parent
+                        .spawn((
+                            OutlineItem {
+                                path: symbol.location.path.clone(),
+                                position: symbol.selection,
+                                index,
+                            },
+                            Node {
+                                width: Val::Percent(100.),
+                                padding: UiRect::new(
+                                    Val::Px(20. + 16. * symbol.depth as f32),
+                                    Val::Px(20.),
+                                    Val::ZERO,
+                                    Val::ZERO,
+                                ),
+                                column_gap: Val::Px(8.),
+                                flex_shrink: 0.,
+                                ..default()
+                            },
+                            BackgroundColor::DEFAULT,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((Text::new(icon), text_font.clone(), TextColor(color)));
+                            parent.spawn((
+                                Text::new(&symbol.name),
+                                text_font.clone(),
+                                TextLayout::new_with_no_wrap(),
+                                TextColor(res.colors.home_font_color),
+                            ));
+                            parent.spawn((
+                                Text::new(detail.lines().next().unwrap_or_default()),
+                                text_font.clone(),
+                                TextLayout::new_with_no_wrap(),
+                                TextColor(res.colors.home_font_grey_color),
+                            ));
+                        });
+                }
+            },
+        );
+    }
+
+    /// 高亮包含光标的符号
+    fn highlight(
+        outline: Res<Outline>,
+        view: Single<Ref<EditorView>, With<FocusedEditor>>,
+        mut items: Query<(Ref<OutlineItem>, &mut BackgroundColor)>,
+        res: Res<SeekerResource>,
+    ) {
+        let added = items.iter().any(|(item, _)| item.is_added());
+        if !added && !view.is_changed() && !outline.is_changed() {
+            return;
+        }
+        let current = view
+            .path
+            .as_ref()
+            .and_then(|path| outline.current(path, view.cursor));
+        for (item, mut background) in items.iter_mut() {
+            let color = if item.index.is_some() && item.index == current {
+                res.colors.editor_current_line
+            } else {
+                Color::NONE
+            };
+            background.set_if_neq(BackgroundColor(color));
+        }
+    }
+}
+
+fn kind_icon(kind: SymbolKind, res: &SeekerResource) -> (&'static str, Color) {
+    match kind {
+        SymbolKind::FUNCTION | SymbolKind::METHOD | SymbolKind::CONSTRUCTOR => {
+            ("ƒ", res.colors.info)
+        }
+        SymbolKind::STRUCT | SymbolKind::CLASS => ("S", res.colors.warning),
+        SymbolKind::ENUM => ("E", res.colors.warning),
+        SymbolKind::ENUM_MEMBER => ("e", res.colors.warning),
+        SymbolKind::INTERFACE => ("T", res.colors.git_added),
+        // rust-analyzer 把 impl 报告为 OBJECT
+        SymbolKind::OBJECT => ("I", res.colors.git_added),
+        SymbolKind::MODULE | SymbolKind::NAMESPACE | SymbolKind::PACKAGE => {
+            ("M", res.colors.git_modified)
+        }
+        SymbolKind::CONSTANT => ("C", res.colors.home_font_grey_color),
+        _ => ("•", res.colors.home_font_grey_color),
+    }
+}
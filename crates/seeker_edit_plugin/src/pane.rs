@@ -0,0 +1,824 @@
+use crate::editor::{EditorInputCapture, EditorPlugin, EditorSystems};
+use crate::minimap::MinimapPlugin;
+use crate::{EditorUi, EditorView, FocusedEditor, TabBarUi};
+use bevy::app::AppExit;
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::prelude::*;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition, UiGlobalTransform};
+use seeker_editor::document::Documents;
+use seeker_editor::layout::{LayoutNode, PaneState, SplitDirection};
+use seeker_resource::project_list::CurrentProject;
+use seeker_resource::SeekerResource;
+use seeker_sqlite::seeker::pane_layout::PaneLayout;
+use seeker_state::SeekerEditSubLoadState;
+use seeker_trait::SeekerTrait;
+use std::path::PathBuf;
+
+/// 分隔条的宽度
+const SPLITTER_SIZE: f32 = 4.;
+/// 标签栏的高度
+pub const TAB_BAR_HEIGHT: f32 = 30.;
+
+/// 分屏的根节点，比较视图打开时整体隐藏
+#[derive(Component)]
+pub struct EditorLayout;
+
+/// 一个窗格：上面是标签栏，下面是编辑器视图
+#[derive(Component)]
+pub struct EditorPane {
+    pub view: Entity,
+    pub tab_bar: Entity,
+}
+
+/// 分屏，子节点依次为 first、分隔条、second
+#[derive(Component)]
+#[require(Node, RelativeCursorPosition)]
+pub struct EditorSplit {
+    pub direction: SplitDirection,
+    /// first 占的比例
+    pub ratio: f32,
+}
+
+/// 拖动调整分屏比例
+#[derive(Component)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct Splitter;
+
+/// 改变分屏结构的系统，标签栏和编辑器在它之后运行
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PaneSystems;
+
+#[derive(Component)]
+pub struct PanePlugin;
+
+impl SeekerTrait for PanePlugin {}
+
+impl Plugin for PanePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(SeekerEditSubLoadState::Loaded), Self::spawn_layout)
+            .add_systems(OnExit(SeekerEditSubLoadState::Loaded), Self::flush_layout)
+            .add_systems(
+                Last,
+                Self::flush_layout
+                    .run_if(on_message::<AppExit>.and(in_state(SeekerEditSubLoadState::Loaded))),
+            )
+            .add_systems(
+                Update,
+                (
+                    Self::keybindings,
+                    Self::focus_on_click,
+                    Self::drag_splitter,
+                    Self::apply_ratio,
+                    Self::close_empty_panes,
+                    Self::save_layout,
+                )
+                    .chain()
+                    .in_set(PaneSystems)
+                    .before(EditorSystems)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            );
+    }
+}
+
+impl PanePlugin {
+    /// 恢复项目上次的分屏、光标和滚动位置，没有记录时只有一个空窗格
+    fn spawn_layout(
+        mut commands: Commands,
+        editor: Single<Entity, With<EditorUi>>,
+        current_project: Res<CurrentProject>,
+        mut documents: ResMut<Documents>,
+        res: Res<SeekerResource>,
+    ) {
+        let layout = current_project
+            .project
+            .as_ref()
+            .and_then(|project| match PaneLayout::load(&project.path) {
+                Ok(layout) => layout,
+                Err(err) => {
+                    error!("load pane layout {}: {err}", project.path);
+                    None
+                }
+            })
+            .and_then(|layout| LayoutNode::from_json(&layout.layout).ok())
+            .and_then(|layout| layout.retain_tabs(&mut |path| documents.open(path).is_ok()))
+            .unwrap_or_default();
+        let mut views = vec![];
+        let root = Self::spawn_node(&mut commands, layout, &mut views, &documents, &res);
+        if let Some((view, _)) = views.iter().find(|(_, focused)| *focused).or(views.first()) {
+            commands.entity(*view).insert(FocusedEditor);
+        }
+        commands.entity(*editor).with_children(|parent| {
+            parent
+                .spawn((
+                    EditorLayout,
+                    Node {
+                        flex_grow: 1.,
+                        min_width: Val::Px(0.),
+                        height: Val::Percent(100.),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                ))
+                .add_child(root);
+        });
+    }
+
+    /// 生成布局中的节点，按顺序记录视图和它是否聚焦
+    fn spawn_node(
+        commands: &mut Commands,
+        node: LayoutNode,
+        views: &mut Vec<(Entity, bool)>,
+        documents: &Documents,
+        res: &SeekerResource,
+    ) -> Entity {
+        match node {
+            LayoutNode::Pane(state) => {
+                let mut view = EditorView::default();
+                // 文件可能在外部被修改过，光标限制在现在的内容中
+                let cursor = |tab: &PathBuf| {
+                    let cursor = state.cursors.get(tab)?;
+                    Some(documents.get(tab)?.buffer.clamp(*cursor))
+                };
+                for tab in &state.tabs {
+                    view.open(tab.clone(), cursor(tab));
+                }
+                if let Some(active) = &state.active {
+                    view.open(active.clone(), cursor(active));
+                }
+                let (pane, view) = Self::spawn_pane(commands, view, 1., res);
+                let (x, y) = state.scroll;
+                commands
+                    .entity(view)
+                    .insert(ScrollPosition(Vec2::new(x, y)));
+                views.push((view, state.focused));
+                pane
+            }
+            LayoutNode::Split {
+                direction,
+                ratio,
+                first,
+                second,
+            } => {
+                let first = Self::spawn_node(commands, *first, views, documents, res);
+                let second = Self::spawn_node(commands, *second, views, documents, res);
+                Self::spawn_split(commands, direction, ratio, 1., [first, second], res)
+            }
+        }
+    }
+
+    /// 生成窗格，返回（窗格，视图）
+    fn spawn_pane(
+        commands: &mut Commands,
+        view: EditorView,
+        grow: f32,
+        res: &SeekerResource,
+    ) -> (Entity, Entity) {
+        let view = EditorPlugin::spawn_editor(commands, view);
+        let tab_bar = commands
+            .spawn((
+                TabBarUi,
+                Node {
+                    width: Val::Percent(100.),
+                    height: Val::Px(TAB_BAR_HEIGHT),
+                    min_height: Val::Px(TAB_BAR_HEIGHT),
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Row,
+                    overflow: Overflow::scroll_x(),
+                    ..default()
+                },
+                BackgroundColor(res.colors.home_menu),
+            ))
+            .id();
+        let pane = commands
+            .spawn((
+                EditorPane { view, tab_bar },
+                Node {
+                    flex_grow: grow,
+                    flex_basis: Val::Px(0.),
+                    min_width: Val::Px(0.),
+                    min_height: Val::Px(0.),
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+            ))
+            .add_children(&[tab_bar, view])
+            .id();
+        let overlay = MinimapPlugin::spawn(commands, view, res);
+        commands.entity(pane).add_children(&overlay);
+        (pane, view)
+    }
+
+    fn spawn_split(
+        commands: &mut Commands,
+        direction: SplitDirection,
+        ratio: f32,
+        grow: f32,
+        [first, second]: [Entity; 2],
+        res: &SeekerResource,
+    ) -> Entity {
+        let (flex_direction, width, height) = match direction {
+            SplitDirection::Horizontal => (
+                FlexDirection::Row,
+                Val::Px(SPLITTER_SIZE),
+                Val::Percent(100.),
+            ),
+            SplitDirection::Vertical => (
+                FlexDirection::Column,
+                Val::Percent(100.),
+                Val::Px(SPLITTER_SIZE),
+            ),
+        };
+        let splitter = commands
+            .spawn((
+                Splitter,
+                Node {
+                    width,
+                    height,
+                    flex_shrink: 0.,
+                    ..default()
+                },
+                BackgroundColor(res.colors.button_border),
+            ))
+            .id();
+        commands
+            .spawn((
+                EditorSplit { direction, ratio },
+                Node {
+                    flex_grow: grow,
+                    flex_basis: Val::Px(0.),
+                    min_width: Val::Px(0.),
+                    min_height: Val::Px(0.),
+                    display: Display::Flex,
+                    flex_direction,
+                    ..default()
+                },
+            ))
+            .add_children(&[first, splitter, second])
+            .id()
+    }
+
+    /// Ctrl+\ 向右分屏，Ctrl+Shift+\ 向下分屏，Ctrl+Alt+方向键切换窗格
+    #[allow(clippy::too_many_arguments)]
+    fn keybindings(
+        mut commands: Commands,
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        capture: Res<EditorInputCapture>,
+        focused: Single<Entity, With<FocusedEditor>>,
+        mut views: Query<&mut EditorView>,
+        geometry: Query<(Entity, &UiGlobalTransform, &ComputedNode), With<EditorView>>,
+        parents: Query<&ChildOf>,
+        children: Query<&Children>,
+        nodes: Query<&Node>,
+        res: Res<SeekerResource>,
+    ) {
+        if capture.text {
+            reader.clear();
+            return;
+        }
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        for event in reader.read() {
+            if !event.state.is_pressed() || !command {
+                continue;
+            }
+            let direction = match event.key_code {
+                KeyCode::ArrowLeft if alt => Vec2::NEG_X,
+                KeyCode::ArrowRight if alt => Vec2::X,
+                KeyCode::ArrowUp if alt => Vec2::NEG_Y,
+                KeyCode::ArrowDown if alt => Vec2::Y,
+                KeyCode::Backslash if !alt => {
+                    let direction = if shift {
+                        SplitDirection::Vertical
+                    } else {
+                        SplitDirection::Horizontal
+                    };
+                    Self::split(
+                        &mut commands,
+                        *focused,
+                        direction,
+                        &mut views,
+                        &parents,
+                        &children,
+                        &nodes,
+                        &res,
+                    );
+                    return;
+                }
+                _ => continue,
+            };
+            if let Some(target) = Self::neighbor(*focused, direction, &geometry) {
+                focus_view(&mut commands, *focused, target, &mut views);
+            }
+            return;
+        }
+    }
+
+    /// 把聚焦的窗格分成两个，新窗格在右边或下面，打开同一个文档并获得焦点
+    #[allow(clippy::too_many_arguments)]
+    fn split(
+        commands: &mut Commands,
+        focused: Entity,
+        direction: SplitDirection,
+        views: &mut Query<&mut EditorView>,
+        parents: &Query<&ChildOf>,
+        children: &Query<&Children>,
+        nodes: &Query<&Node>,
+        res: &SeekerResource,
+    ) {
+        let Ok(view) = views.get(focused) else {
+            return;
+        };
+        let Some(path) = view.path.clone() else {
+            return;
+        };
+        let Ok(pane) = parents.get(focused).map(ChildOf::parent) else {
+            return;
+        };
+        let Ok(container) = parents.get(pane).map(ChildOf::parent) else {
+            return;
+        };
+        let Some(index) = children
+            .get(container)
+            .ok()
+            .and_then(|children| children.iter().position(|child| child == pane))
+        else {
+            return;
+        };
+        let mut new_view = EditorView::default();
+        new_view.open(path, Some(view.cursor));
+        let (new_pane, new_view) = Self::spawn_pane(commands, new_view, 1., res);
+        let grow = nodes.get(pane).map(|node| node.flex_grow).unwrap_or(1.);
+        let split = Self::spawn_split(commands, direction, 0.5, grow, [pane, new_pane], res);
+        commands.entity(container).insert_children(index, &[split]);
+        commands.entity(focused).remove::<FocusedEditor>();
+        commands.entity(new_view).insert(FocusedEditor);
+    }
+
+    /// direction 方向上最近的视图
+    fn neighbor(
+        focused: Entity,
+        direction: Vec2,
+        geometry: &Query<(Entity, &UiGlobalTransform, &ComputedNode), With<EditorView>>,
+    ) -> Option<Entity> {
+        let (_, transform, _) = geometry.get(focused).ok()?;
+        let center = transform.translation;
+        geometry
+            .iter()
+            .filter(|(entity, _, computed)| *entity != focused && computed.size().x > 0.)
+            .filter_map(|(entity, transform, _)| {
+                let offset = transform.translation - center;
+                let along = offset.dot(direction);
+                let across = offset.perp_dot(direction).abs();
+                (along > 0.).then_some((entity, along + across))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity)
+    }
+
+    /// 点击视图时把焦点移过去
+    #[allow(clippy::type_complexity)]
+    fn focus_on_click(
+        mut commands: Commands,
+        query: Query<(Entity, &Interaction), (Changed<Interaction>, With<EditorView>)>,
+        focused: Single<Entity, With<FocusedEditor>>,
+        mut views: Query<&mut EditorView>,
+    ) {
+        if let Some((entity, _)) = query
+            .iter()
+            .find(|(_, interaction)| **interaction == Interaction::Pressed)
+        {
+            focus_view(&mut commands, *focused, entity, &mut views);
+        }
+    }
+
+    fn drag_splitter(
+        splitters: Query<(&Interaction, &ChildOf), With<Splitter>>,
+        mut splits: Query<(&mut EditorSplit, &RelativeCursorPosition)>,
+        mouse: Res<ButtonInput<MouseButton>>,
+        mut dragging: Local<Option<Entity>>,
+    ) {
+        if !mouse.pressed(MouseButton::Left) {
+            *dragging = None;
+            return;
+        }
+        if dragging.is_none() {
+            *dragging = splitters
+                .iter()
+                .find(|(interaction, _)| **interaction == Interaction::Pressed)
+                .map(|(_, parent)| parent.parent());
+        }
+        let Some((mut split, relative)) = dragging.and_then(|split| splits.get_mut(split).ok())
+        else {
+            return;
+        };
+        let Some(normalized) = relative.normalized else {
+            return;
+        };
+        let ratio = match split.direction {
+            SplitDirection::Horizontal => normalized.x,
+            SplitDirection::Vertical => normalized.y,
+        } + 0.5;
+        let ratio = ratio.clamp(0.1, 0.9);
+        if (split.ratio - ratio).abs() > f32::EPSILON {
+            split.ratio = ratio;
+        }
+    }
+
+    fn apply_ratio(
+        splits: Query<(&EditorSplit, &Children), Changed<EditorSplit>>,
+        mut nodes: Query<&mut Node, Without<Splitter>>,
+    ) {
+        for (split, children) in splits.iter() {
+            if let Some(mut node) = children
+                .first()
+                .and_then(|first| nodes.get_mut(*first).ok())
+            {
+                node.flex_grow = split.ratio;
+            }
+            if let Some(mut node) = children.last().and_then(|last| nodes.get_mut(*last).ok()) {
+                node.flex_grow = 1. - split.ratio;
+            }
+        }
+    }
+
+    /// 窗格的最后一个标签关闭后移除窗格，另一半取代分屏
+    #[allow(clippy::too_many_arguments)]
+    fn close_empty_panes(
+        mut commands: Commands,
+        changed: Query<(Entity, &EditorView, Has<FocusedEditor>), Changed<EditorView>>,
+        panes: Query<&EditorPane>,
+        splits: Query<(), With<EditorSplit>>,
+        parents: Query<&ChildOf>,
+        children: Query<&Children>,
+        mut nodes: Query<&mut Node>,
+    ) {
+        let Some((entity, _, focused)) = changed.iter().find(|(_, view, _)| view.tabs.is_empty())
+        else {
+            return;
+        };
+        let Ok(pane) = parents.get(entity).map(ChildOf::parent) else {
+            return;
+        };
+        // 只剩一个窗格时保留它
+        let Ok(split) = parents.get(pane).map(ChildOf::parent) else {
+            return;
+        };
+        if !splits.contains(split) {
+            return;
+        }
+        let (Ok(split_children), Ok(container)) =
+            (children.get(split), parents.get(split).map(ChildOf::parent))
+        else {
+            return;
+        };
+        let Some(sibling) = [split_children.first(), split_children.last()]
+            .into_iter()
+            .flatten()
+            .copied()
+            .find(|child| *child != pane)
+        else {
+            return;
+        };
+        let Some(index) = children
+            .get(container)
+            .ok()
+            .and_then(|children| children.iter().position(|child| child == split))
+        else {
+            return;
+        };
+        let grow = nodes.get(split).map(|node| node.flex_grow).unwrap_or(1.);
+        if let Ok(mut node) = nodes.get_mut(sibling) {
+            node.flex_grow = grow;
+        }
+        commands
+            .entity(container)
+            .insert_children(index, &[sibling]);
+        commands.entity(split).despawn();
+        if focused && let Some(view) = Self::first_view(sibling, &panes, &children) {
+            commands.entity(view).insert(FocusedEditor);
+        }
+    }
+
+    /// 子树中第一个窗格的视图
+    fn first_view(
+        entity: Entity,
+        panes: &Query<&EditorPane>,
+        children: &Query<&Children>,
+    ) -> Option<Entity> {
+        if let Ok(pane) = panes.get(entity) {
+            return Some(pane.view);
+        }
+        let first = *children.get(entity).ok()?.first()?;
+        Self::first_view(first, panes, children)
+    }
+
+    /// 布局、光标或者滚动位置变化后保存到当前项目，最多每秒保存一次，拖动分隔条时等松开再保存
+    #[allow(clippy::type_complexity)]
+    fn save_layout(
+        tree: PaneTree,
+        changed: Query<
+            (),
+            Or<(
+                Changed<EditorView>,
+                Changed<EditorSplit>,
+                (Changed<ScrollPosition>, With<EditorView>),
+            )>,
+        >,
+        mouse: Res<ButtonInput<MouseButton>>,
+        time: Res<Time>,
+        mut dirty: Local<bool>,
+        mut saved_at: Local<f32>,
+    ) {
+        *dirty |= !changed.is_empty();
+        let now = time.elapsed_secs();
+        if !*dirty || mouse.pressed(MouseButton::Left) || now - *saved_at < 1. {
+            return;
+        }
+        tree.save();
+        *dirty = false;
+        *saved_at = now;
+    }
+
+    /// 离开编辑界面或者退出程序前保存最后的光标和滚动位置
+    fn flush_layout(tree: PaneTree) {
+        tree.save();
+    }
+}
+
+/// 从界面中读取当前的分屏布局
+#[derive(SystemParam)]
+struct PaneTree<'w, 's> {
+    layout: Query<'w, 's, &'static Children, With<EditorLayout>>,
+    views: Query<
+        'w,
+        's,
+        (
+            &'static EditorView,
+            &'static ScrollPosition,
+            Has<FocusedEditor>,
+        ),
+    >,
+    panes: Query<'w, 's, &'static EditorPane>,
+    splits: Query<'w, 's, (&'static EditorSplit, &'static Children)>,
+    current_project: Res<'w, CurrentProject>,
+}
+
+impl PaneTree<'_, '_> {
+    fn save(&self) {
+        let Some(project) = self.current_project.project.as_ref() else {
+            return;
+        };
+        let Some(node) = self
+            .layout
+            .single()
+            .ok()
+            .and_then(|layout| layout.first())
+            .and_then(|root| self.node(*root))
+        else {
+            return;
+        };
+        let data = PaneLayout {
+            project_path: project.path.clone(),
+            layout: node.to_json(),
+        };
+        if let Err(err) = data.save() {
+            error!("save pane layout {}: {err}", project.path);
+        }
+    }
+
+    fn node(&self, entity: Entity) -> Option<LayoutNode> {
+        if let Ok(pane) = self.panes.get(entity) {
+            let (view, scroll, focused) = self.views.get(pane.view).ok()?;
+            return Some(LayoutNode::Pane(PaneState {
+                tabs: view.tabs.clone(),
+                active: view.path.clone(),
+                focused,
+                cursors: view
+                    .tabs
+                    .iter()
+                    .filter_map(|tab| Some((tab.clone(), view.cursor_of(tab)?)))
+                    .collect(),
+                scroll: (scroll.x, scroll.y),
+            }));
+        }
+        let (split, children) = self.splits.get(entity).ok()?;
+        Some(LayoutNode::Split {
+            direction: split.direction,
+            ratio: split.ratio,
+            first: Box::new(self.node(*children.first()?)?),
+            second: Box::new(self.node(*children.last()?)?),
+        })
+    }
+}
+
+/// 把焦点从 focused 移到 target，标记 target 变化让弹出层和标签栏刷新
+pub fn focus_view(
+    commands: &mut Commands,
+    focused: Entity,
+    target: Entity,
+    views: &mut Query<&mut EditorView>,
+) {
+    if focused == target {
+        return;
+    }
+    commands.entity(focused).remove::<FocusedEditor>();
+    commands.entity(target).insert(FocusedEditor);
+    if let Ok(mut view) = views.get_mut(target) {
+        view.set_changed();
+    }
+}
+
+#[test]
+fn test_pane_layout() {
+    use bevy::ecs::system::RunSystemOnce;
+    use seeker_editor::buffer::Position;
+
+    let dir = std::env::temp_dir().join(format!("seeker_pane_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let [a, b] = ["a.rs", "b.rs"].map(|name| dir.join(name));
+    std::fs::write(&a, "fn a() {}\n").unwrap();
+    std::fs::write(&b, "fn b() {}\n").unwrap();
+
+    let mut app = App::new();
+    app.insert_resource(SeekerResource::new())
+        .init_resource::<Documents>()
+        .init_resource::<CurrentProject>();
+    let world = app.world_mut();
+    for path in [&a, &b] {
+        world.resource_mut::<Documents>().open(path).unwrap();
+    }
+    let pane = |tabs: &[&PathBuf], focused: bool, scroll: (f32, f32)| {
+        LayoutNode::Pane(PaneState {
+            tabs: tabs.iter().map(|tab| tab.to_path_buf()).collect(),
+            active: tabs.last().map(|tab| tab.to_path_buf()),
+            focused,
+            cursors: tabs
+                .iter()
+                .map(|tab| (tab.to_path_buf(), Position::new(0, 3)))
+                .collect(),
+            scroll,
+        })
+    };
+    // 和 spawn_layout 一样生成布局，返回按顺序排列的视图
+    let spawn = |world: &mut World, layout: LayoutNode| {
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      documents: Res<Documents>,
+                      res: Res<SeekerResource>| {
+                    let mut views = vec![];
+                    let root = PanePlugin::spawn_node(
+                        &mut commands,
+                        layout.clone(),
+                        &mut views,
+                        &documents,
+                        &res,
+                    );
+                    for (view, focused) in &views {
+                        if *focused {
+                            commands.entity(*view).insert(FocusedEditor);
+                        }
+                    }
+                    commands.spawn(EditorLayout).add_child(root);
+                    views.into_iter().map(|(view, _)| view).collect::<Vec<_>>()
+                },
+            )
+            .unwrap()
+    };
+    let read = |world: &mut World| {
+        world
+            .run_system_once(|tree: PaneTree| {
+                let root = *tree.layout.single().ok()?.first()?;
+                tree.node(root)
+            })
+            .unwrap()
+    };
+
+    // 保存的布局恢复后再读出来不变
+    let layout = LayoutNode::Split {
+        direction: SplitDirection::Horizontal,
+        ratio: 0.3,
+        first: Box::new(pane(&[&a, &b], false, (0., 20.))),
+        second: Box::new(LayoutNode::Split {
+            direction: SplitDirection::Vertical,
+            ratio: 0.6,
+            first: Box::new(pane(&[&b], true, (5., 0.))),
+            second: Box::new(pane(&[&a], false, (0., 0.))),
+        }),
+    };
+    let restored = LayoutNode::from_json(&layout.to_json()).unwrap();
+    let views = spawn(world, restored);
+    assert_eq!(views.len(), 3);
+    assert_eq!(read(world), Some(layout.clone()));
+    let layout = world
+        .run_system_once(|layout: Single<Entity, With<EditorLayout>>| *layout)
+        .unwrap();
+    world.entity_mut(layout).despawn();
+    for view in &views {
+        assert!(world.get_entity(*view).is_err());
+    }
+
+    // 分屏后新窗格在右边，打开同一个文档并获得焦点
+    let view = spawn(world, pane(&[&a, &b], true, (0., 20.)))[0];
+    world
+        .run_system_once(
+            move |mut commands: Commands,
+                  mut views_query: Query<&mut EditorView>,
+                  parents: Query<&ChildOf>,
+                  children: Query<&Children>,
+                  nodes: Query<&Node>,
+                  res: Res<SeekerResource>| {
+                PanePlugin::split(
+                    &mut commands,
+                    view,
+                    SplitDirection::Horizontal,
+                    &mut views_query,
+                    &parents,
+                    &children,
+                    &nodes,
+                    &res,
+                );
+            },
+        )
+        .unwrap();
+    let split = LayoutNode::Split {
+        direction: SplitDirection::Horizontal,
+        ratio: 0.5,
+        first: Box::new(pane(&[&a, &b], false, (0., 20.))),
+        second: Box::new(pane(&[&b], true, (0., 0.))),
+    };
+    assert_eq!(read(world), Some(split));
+
+    // 新窗格的最后一个标签关闭后，另一半取代分屏并获得焦点
+    let focused = world
+        .run_system_once(|focused: Single<Entity, With<FocusedEditor>>| *focused)
+        .unwrap();
+    world.get_mut::<EditorView>(focused).unwrap().close(&b);
+    world
+        .run_system_once(PanePlugin::close_empty_panes)
+        .unwrap();
+    assert_eq!(read(world), Some(pane(&[&a, &b], true, (0., 20.))));
+    assert!(world.get_entity(focused).is_err());
+
+    // 只剩一个窗格时保留它
+    world.get_mut::<EditorView>(view).unwrap().close(&a);
+    world.get_mut::<EditorView>(view).unwrap().close(&b);
+    world
+        .run_system_once(PanePlugin::close_empty_panes)
+        .unwrap();
+    assert!(world.get_entity(view).is_ok());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_neighbor() {
+    use bevy::ecs::system::RunSystemOnce;
+
+    let mut app = App::new();
+    let world = app.world_mut();
+    let mut view = |x: f32, y: f32, width: f32| {
+        world
+            .spawn((
+                EditorView::default(),
+                UiGlobalTransform::from(bevy::math::Affine2::from_translation(Vec2::new(x, y))),
+                ComputedNode {
+                    size: Vec2::new(width, 100.),
+                    ..default()
+                },
+            ))
+            .id()
+    };
+    // 左边一个窗格，右边上下两个，还有一个隐藏的
+    let left = view(100., 200., 200.);
+    let top_right = view(300., 150., 200.);
+    let bottom_right = view(300., 300., 200.);
+    view(150., 200., 0.);
+    let neighbor = |world: &mut World, focused: Entity, direction: Vec2| {
+        world
+            .run_system_once(
+                move |geometry: Query<
+                    (Entity, &UiGlobalTransform, &ComputedNode),
+                    With<EditorView>,
+                >| { PanePlugin::neighbor(focused, direction, &geometry) },
+            )
+            .unwrap()
+    };
+    // 隐藏的视图离得最近也跳过
+    assert_eq!(neighbor(world, left, Vec2::X), Some(top_right));
+    assert_eq!(neighbor(world, left, Vec2::NEG_X), None);
+    assert_eq!(neighbor(world, top_right, Vec2::Y), Some(bottom_right));
+    assert_eq!(neighbor(world, bottom_right, Vec2::NEG_Y), Some(top_right));
+    assert_eq!(neighbor(world, bottom_right, Vec2::NEG_X), Some(left));
+    assert_eq!(neighbor(world, top_right, Vec2::NEG_X), Some(left));
+}
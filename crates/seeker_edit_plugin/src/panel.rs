@@ -0,0 +1,236 @@
+use crate::FileTreeUi;
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::ui::{FocusPolicy, UiSystems};
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditPanelState, SeekerEditSubLoadState};
+use seeker_trait::SeekerTrait;
+
+/// 底部面板内容区默认的高度
+pub const PANEL_HEIGHT: f32 = 200.;
+/// 文件树默认的宽度
+pub const SIDEBAR_WIDTH: f32 = 250.;
+const MIN_SIDEBAR_WIDTH: f32 = 120.;
+const MIN_PANEL_HEIGHT: f32 = 80.;
+
+/// 文件树的宽度和底部面板内容区的高度，拖动边缘调整
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct PanelSizes {
+    pub sidebar: f32,
+    pub panel: f32,
+}
+
+impl Default for PanelSizes {
+    fn default() -> Self {
+        Self {
+            sidebar: SIDEBAR_WIDTH,
+            panel: PANEL_HEIGHT,
+        }
+    }
+}
+
+/// 拖动调整大小的边缘
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub enum ResizeHandle {
+    Sidebar,
+    Panel,
+}
+
+/// 底部面板的内容区，高度跟随 PanelSizes
+#[derive(Component)]
+pub struct PanelBody;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct ClosePanelButton;
+
+#[derive(Component)]
+pub struct PanelPlugin;
+
+impl SeekerTrait for PanelPlugin {}
+
+impl Plugin for PanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PanelSizes>()
+            .add_systems(
+                Update,
+                (Self::close_panel, Self::resize).run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            // 在布局之前应用，新生成的面板当帧就是正确的高度
+            .add_systems(
+                PostUpdate,
+                Self::apply_sizes
+                    .before(UiSystems::Layout)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, ClosePanelButton>,
+            );
+    }
+}
+
+impl PanelPlugin {
+    /// 替换底部面板的内容，离开 state 时自动销毁。header 中生成标题和按钮，body 中生成列表
+    pub fn spawn_panel(
+        commands: &mut Commands,
+        panel: Entity,
+        state: SeekerEditPanelState,
+        res: &Res<SeekerResource>,
+        assets: &Res<AssetServer>,
+        header: impl FnOnce(&mut RelatedSpawnerCommands<ChildOf>),
+        body: impl FnOnce(&mut RelatedSpawnerCommands<ChildOf>),
+    ) {
+        commands
+            .entity(panel)
+            .despawn_children()
+            .with_children(|parent| {
+                parent
+                    .spawn((
+                        DespawnOnExit(state),
+                        Node {
+                            width: Val::Percent(100.),
+                            display: Display::Flex,
+                            flex_direction: FlexDirection::Column,
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            ResizeHandle::Panel,
+                            Node {
+                                width: Val::Percent(100.),
+                                height: Val::Px(4.),
+                                ..default()
+                            },
+                        ));
+                        parent
+                            .spawn(Node {
+                                width: Val::Percent(100.),
+                                padding: UiRect::horizontal(Val::Px(10.)),
+                                column_gap: Val::Px(10.),
+                                align_items: AlignItems::Center,
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                header(parent);
+                                parent.spawn(Node {
+                                    flex_grow: 1.,
+                                    ..default()
+                                });
+                                Self::ui_button_same(parent, ClosePanelButton, "×", res, assets);
+                            });
+                        parent
+                            .spawn((
+                                PanelBody,
+                                Node {
+                                    width: Val::Percent(100.),
+                                    height: Val::Px(PANEL_HEIGHT),
+                                    display: Display::Flex,
+                                    flex_direction: FlexDirection::Column,
+                                    overflow: Overflow::scroll_y(),
+                                    ..default()
+                                },
+                            ))
+                            .with_children(body);
+                    });
+            });
+    }
+
+    /// 面板标题
+    pub fn panel_title(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        title: impl Into<String>,
+        res: &SeekerResource,
+        assets: &AssetServer,
+    ) {
+        parent.spawn((
+            Text::new(title),
+            TextFont {
+                font: assets.load(MAPLE_MONO_BOLD),
+                font_size: 13.,
+                ..default()
+            },
+            TextColor(res.colors.home_font_color),
+        ));
+    }
+
+    fn close_panel(
+        query: Query<&Interaction, (Changed<Interaction>, With<ClosePanelButton>)>,
+        mut panel: ResMut<NextState<SeekerEditPanelState>>,
+    ) {
+        if query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            panel.set(SeekerEditPanelState::None);
+        }
+    }
+
+    /// 拖动文件树的右边缘或者底部面板的上边缘
+    fn resize(
+        handles: Query<(&Interaction, &ResizeHandle)>,
+        mouse: Res<ButtonInput<MouseButton>>,
+        window: Single<&Window>,
+        mut sizes: ResMut<PanelSizes>,
+        mut dragging: Local<Option<(ResizeHandle, Vec2, f32)>>,
+    ) {
+        if !mouse.pressed(MouseButton::Left) {
+            *dragging = None;
+            return;
+        }
+        let Some(cursor) = window.cursor_position() else {
+            return;
+        };
+        if dragging.is_none() {
+            *dragging = handles
+                .iter()
+                .find(|(interaction, _)| **interaction == Interaction::Pressed)
+                .map(|(_, handle)| match handle {
+                    ResizeHandle::Sidebar => (*handle, cursor, sizes.sidebar),
+                    ResizeHandle::Panel => (*handle, cursor, sizes.panel),
+                });
+        }
+        let Some((handle, start, size)) = *dragging else {
+            return;
+        };
+        let new_sizes = match handle {
+            ResizeHandle::Sidebar => PanelSizes {
+                sidebar: (size + cursor.x - start.x).clamp(
+                    MIN_SIDEBAR_WIDTH,
+                    (window.width() * 0.6).max(MIN_SIDEBAR_WIDTH),
+                ),
+                ..*sizes
+            },
+            ResizeHandle::Panel => PanelSizes {
+                panel: (size + start.y - cursor.y).clamp(
+                    MIN_PANEL_HEIGHT,
+                    (window.height() * 0.7).max(MIN_PANEL_HEIGHT),
+                ),
+                ..*sizes
+            },
+        };
+        sizes.set_if_neq(new_sizes);
+    }
+
+    fn apply_sizes(
+        sizes: Res<PanelSizes>,
+        mut sidebars: Query<(&mut Node, Ref<FileTreeUi>), Without<PanelBody>>,
+        mut bodies: Query<(&mut Node, Ref<PanelBody>), Without<FileTreeUi>>,
+    ) {
+        for (mut node, sidebar) in sidebars.iter_mut() {
+            if sizes.is_changed() || sidebar.is_added() {
+                node.width = Val::Px(sizes.sidebar);
+                node.min_width = Val::Px(sizes.sidebar);
+            }
+        }
+        for (mut node, body) in bodies.iter_mut() {
+            if sizes.is_changed() || body.is_added() {
+                node.height = Val::Px(sizes.panel);
+            }
+        }
+    }
+}
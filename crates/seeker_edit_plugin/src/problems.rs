@@ -0,0 +1,224 @@
+use crate::panel::PanelPlugin;
+use crate::{OpenLocation, PanelUi};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use seeker_cargo::plugin::{CargoCheck, RunCargoCheck};
+use seeker_editor::buffer::Position;
+use seeker_editor::diagnostic::{Diagnostics, Severity};
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::project_list::CurrentProject;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditPanelState, SeekerEditSubLoadState};
+use seeker_trait::SeekerTrait;
+use std::path::{Path, PathBuf};
+
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct ProblemItem {
+    pub path: PathBuf,
+    pub position: Position,
+}
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct RunCargoCheckButton;
+
+#[derive(Component)]
+pub struct ProblemsPlugin;
+
+impl SeekerTrait for ProblemsPlugin {}
+
+impl Plugin for ProblemsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(SeekerEditPanelState::Problems), Self::render)
+            .add_systems(
+                Update,
+                (
+                    Self::toggle,
+                    Self::run_check,
+                    Self::click,
+                    Self::render.run_if(
+                        in_state(SeekerEditPanelState::Problems).and(
+                            resource_changed::<Diagnostics>.or(resource_changed::<CargoCheck>),
+                        ),
+                    ),
+                )
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, RunCargoCheckButton>,
+            );
+    }
+}
+
+impl ProblemsPlugin {
+    /// Ctrl+Shift+M 打开或关闭问题面板
+    fn toggle(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        state: Res<State<SeekerEditPanelState>>,
+        mut next: ResMut<NextState<SeekerEditPanelState>>,
+    ) {
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        for event in reader.read() {
+            if !event.state.is_pressed() || !command || !shift {
+                continue;
+            }
+            if let Key::Character(ch) = &event.logical_key
+                && ch.eq_ignore_ascii_case("m")
+            {
+                next.set(if *state.get() == SeekerEditPanelState::Problems {
+                    SeekerEditPanelState::None
+                } else {
+                    SeekerEditPanelState::Problems
+                });
+            }
+        }
+    }
+
+    fn run_check(
+        query: Query<&Interaction, (Changed<Interaction>, With<RunCargoCheckButton>)>,
+        mut run: MessageWriter<RunCargoCheck>,
+    ) {
+        if query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            run.write(RunCargoCheck);
+        }
+    }
+
+    fn click(
+        query: Query<(&Interaction, &ProblemItem), Changed<Interaction>>,
+        mut open: MessageWriter<OpenLocation>,
+    ) {
+        for (interaction, item) in query.iter() {
+            if *interaction == Interaction::Pressed {
+                open.write(OpenLocation {
+                    path: item.path.clone(),
+                    position: Some(item.position),
+                });
+            }
+        }
+    }
+
+    fn render(
+        mut commands: Commands,
+        diagnostics: Res<Diagnostics>,
+        check: Res<CargoCheck>,
+        current_project: Res<CurrentProject>,
+        panel: Single<Entity, With<PanelUi>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            ..default()
+        };
+        let root = current_project
+            .project
+            .as_ref()
+            .map(|project| PathBuf::from(&project.path))
+            .unwrap_or_default();
+        let problems = diagnostics.all();
+        let status = match (check.running, check.success) {
+            (true, _) => "cargo check running…",
+            (false, Some(true)) => "cargo check passed",
+            (false, Some(false)) => "cargo check failed",
+            (false, None) => "",
+        };
+        PanelPlugin::spawn_panel(
+            &mut commands,
+            *panel,
+            SeekerEditPanelState::Problems,
+            &res,
+            &assets,
+            |parent| {
+                PanelPlugin::panel_title(
+                    parent,
+                    format!("Problems ({})", problems.len()),
+                    &res,
+                    &assets,
+                );
+                parent.spawn((
+                    Text::new(status),
+                    text_font.clone(),
+                    TextColor(res.colors.home_font_grey_color),
+                ));
+                // cargo 本身出错时没有任何诊断，显示它的错误输出
+                if let Some(error) = &check.error {
+                    parent.spawn((
+                        Text::new(error.lines().next().unwrap_or_default()),
+                        text_font.clone(),
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(res.colors.error),
+                    ));
+                }
+                Self::ui_button(
+                    parent,
+                    RunCargoCheckButton,
+                    "RunCargoCheck",
+                    "Run cargo check",
+                    &res,
+                    &assets,
+                );
+            },
+            |parent| {
+                for (path, diagnostic) in &problems {
+                    let (icon, color) = match diagnostic.severity {
+                        Severity::Error => ("✖", res.colors.error),
+                        Severity::Warning => ("⚠", res.colors.warning),
+                        _ => ("ℹ", res.colors.info),
+                    };
+                    parent
+                        .spawn((
+                            ProblemItem {
+                                path: path.to_path_buf(),
+                                position: diagnostic.start,
+                            },
+                            Node {
+                                width: Val::Percent(100.),
+                                padding: UiRect::horizontal(Val::Px(20.)),
+                                column_gap: Val::Px(8.),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((Text::new(icon), text_font.clone(), TextColor(color)));
+                            parent.spawn((
+                                Text::new(diagnostic.message.lines().next().unwrap_or_default()),
+                                text_font.clone(),
+                                TextLayout::new_with_no_wrap(),
+                                TextColor(res.colors.home_font_color),
+                            ));
+                            parent.spawn((
+                                Text::new(format!(
+                                    "{}:{}:{}",
+                                    relative_path(path, &root).display(),
+                                    diagnostic.start.line + 1,
+                                    diagnostic.start.column + 1
+                                )),
+                                text_font.clone(),
+                                TextLayout::new_with_no_wrap(),
+                                TextColor(res.colors.home_font_grey_color),
+                            ));
+                        });
+                }
+            },
+        );
+    }
+}
+
+fn relative_path<'a>(path: &'a Path, root: &Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
@@ -0,0 +1,123 @@
+use crate::file_tree::FileTree;
+use crate::panel::PanelSizes;
+use bevy::prelude::*;
+use seeker_config::SEEKER_CONFIG;
+use seeker_resource::project_list::CurrentProject;
+use seeker_sqlite::seeker::project::Project;
+use seeker_sqlite::seeker::session::Session;
+use seeker_state::{SeekerEditSubLoadState, SeekerState};
+use seeker_trait::SeekerTrait;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 恢复和保存项目的会话：展开的目录和面板大小。标签、光标和分屏由 PanePlugin 保存
+#[derive(Component)]
+pub struct SessionPlugin;
+
+impl SeekerTrait for SessionPlugin {}
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, Self::restore_last_project)
+            .add_systems(OnEnter(SeekerState::Edit), Self::load)
+            .add_systems(
+                Update,
+                Self::save.run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            );
+    }
+}
+
+impl SessionPlugin {
+    /// 配置了 restore_last_project 时启动后直接打开上次的项目
+    fn restore_last_project(
+        mut current_project: ResMut<CurrentProject>,
+        mut state: ResMut<NextState<SeekerState>>,
+    ) {
+        if !SEEKER_CONFIG.restore_last_project {
+            return;
+        }
+        let session = match Session::last() {
+            Ok(Some(session)) => session,
+            Ok(None) => return,
+            Err(err) => {
+                error!("load last session: {err}");
+                return;
+            }
+        };
+        // 项目可能已经从列表中移除或者目录被删除
+        let project = Project::select_all()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|project| {
+                project.path == session.project_path && Path::new(&project.path).is_dir()
+            });
+        if let Some(project) = project {
+            current_project.project = Some(project);
+            state.set(SeekerState::Edit);
+        }
+    }
+
+    fn load(
+        current_project: Res<CurrentProject>,
+        mut tree: ResMut<FileTree>,
+        mut sizes: ResMut<PanelSizes>,
+    ) {
+        tree.expanded.clear();
+        *sizes = PanelSizes::default();
+        let Some(project) = current_project.project.as_ref() else {
+            return;
+        };
+        let session = match Session::load(&project.path) {
+            Ok(session) => session,
+            Err(err) => {
+                error!("load session {}: {err}", project.path);
+                None
+            }
+        };
+        if let Some(session) = &session {
+            let expanded: Vec<PathBuf> =
+                serde_json::from_str(&session.expanded).unwrap_or_default();
+            tree.expanded = expanded.into_iter().filter(|dir| dir.is_dir()).collect();
+            sizes.sidebar = session.sidebar_width as f32;
+            sizes.panel = session.panel_height as f32;
+        }
+        // 记录打开时间，下次启动时恢复最近的项目
+        Self::write(&project.path, &tree, &sizes);
+    }
+
+    /// 拖动调整大小时等松开再保存
+    fn save(
+        current_project: Res<CurrentProject>,
+        tree: Res<FileTree>,
+        sizes: Res<PanelSizes>,
+        mouse: Res<ButtonInput<MouseButton>>,
+        mut dirty: Local<bool>,
+    ) {
+        *dirty |= tree.is_changed() || sizes.is_changed();
+        if !*dirty || mouse.pressed(MouseButton::Left) {
+            return;
+        }
+        if let Some(project) = current_project.project.as_ref() {
+            Self::write(&project.path, &tree, &sizes);
+        }
+        *dirty = false;
+    }
+
+    fn write(project_path: &str, tree: &FileTree, sizes: &PanelSizes) {
+        let mut expanded: Vec<&PathBuf> = tree.expanded.iter().collect();
+        expanded.sort();
+        let session = Session {
+            project_path: project_path.to_string(),
+            expanded: serde_json::to_string(&expanded).unwrap_or_default(),
+            sidebar_width: sizes.sidebar as f64,
+            panel_height: sizes.panel as f64,
+            opened_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or_default(),
+        };
+        if let Err(err) = session.save() {
+            error!("save session {project_path}: {err}");
+        }
+    }
+}
@@ -0,0 +1,433 @@
+use crate::completion::{CompletionCandidate, CompletionMenu};
+use crate::editor::{EditorInputCapture, EditorSystems};
+use crate::{EditorView, FocusedEditor};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use seeker_editor::buffer::Position;
+use seeker_editor::completion::fuzzy_score;
+use seeker_editor::document::{Document, DocumentSaved, Documents};
+use seeker_editor::indent::leading_whitespace;
+use seeker_editor::snippet::{time_variables, Snippet, SnippetLibrary, SnippetVariables, TabStop};
+use seeker_state::{SeekerEditSubLoadState, SeekerEditViewState};
+use seeker_trait::SeekerTrait;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 用户片段所在的目录，每种语言一个 `<languageId>.toml`，`global.toml` 对所有语言生效
+fn snippets_dir() -> Option<PathBuf> {
+    std::env::home_dir().map(|home| home.join(".Seeker/snippets"))
+}
+
+/// 内置片段和用户片段
+#[derive(Resource, Default)]
+pub struct Snippets {
+    pub library: SnippetLibrary,
+}
+
+/// 正在编辑的片段，Tab 和 Shift+Tab 在跳转点之间移动，到达 $0 或者光标离开片段时结束
+#[derive(Resource, Default)]
+pub struct SnippetSession {
+    path: Option<PathBuf>,
+    /// 跳转点的序号和在文档中的字符偏移，按跳转顺序排列
+    stops: Vec<(u32, Range<usize>)>,
+    /// 去重后按跳转顺序排列的序号
+    order: Vec<u32>,
+    /// 当前跳转点在 order 中的位置
+    current: usize,
+    choices: HashMap<u32, Vec<String>>,
+    /// 进入可选值的跳转点后，下一帧在这里打开补全列表
+    choice: Option<Position>,
+    /// 上次同步时的文档版本和字符数，用于计算修改的长度
+    version: i32,
+    len: usize,
+}
+
+impl SnippetSession {
+    pub fn is_active(&self, path: Option<&PathBuf>) -> bool {
+        self.path.is_some() && self.path.as_ref() == path
+    }
+
+    pub fn end(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 用片段替换 [start, 光标)，多行片段跟随当前行的缩进，然后选中第一个跳转点
+    pub fn expand(
+        &mut self,
+        view: &mut EditorView,
+        doc: &mut Document,
+        start: Position,
+        source: &str,
+    ) {
+        let variables = variables(view, doc, start);
+        doc.delete(start, view.cursor);
+        view.cursor = start;
+        view.anchor = None;
+        let indent = leading_whitespace(doc.buffer.line(start.line)).to_string();
+        let mut snippet =
+            Snippet::parse_with(source, &variables).indented(&indent, &doc.indent().unit());
+        // 没有 $0 时结束在片段末尾
+        if !snippet.tab_stops.iter().any(|stop| stop.index == 0) {
+            let len = snippet.text.chars().count();
+            snippet.tab_stops.push(TabStop {
+                index: 0,
+                start: len,
+                end: len,
+            });
+        }
+        let base = doc.buffer.offset(start);
+        doc.insert(start, &snippet.text);
+
+        self.end();
+        self.path = Some(doc.path.clone());
+        self.stops = snippet
+            .tab_stops
+            .iter()
+            .map(|stop| (stop.index, base + stop.start..base + stop.end))
+            .collect();
+        for (index, _) in &self.stops {
+            if !self.order.contains(index) {
+                self.order.push(*index);
+            }
+        }
+        self.choices = snippet.choices;
+        self.record(doc);
+        self.select(view, doc);
+    }
+
+    fn record(&mut self, doc: &Document) {
+        self.version = doc.version;
+        self.len = doc.buffer.offset(doc.buffer.end());
+    }
+
+    /// 序号相同的跳转点中第一个是编辑的位置，其余的是镜像
+    fn primary(&self, index: u32) -> Option<usize> {
+        self.stops.iter().position(|(stop, _)| *stop == index)
+    }
+
+    /// 选中当前跳转点，到达 $0 时结束
+    fn select(&mut self, view: &mut EditorView, doc: &mut Document) {
+        let index = self.order[self.current];
+        let Some(primary) = self.primary(index) else {
+            self.end();
+            return;
+        };
+        let range = self.stops[primary].1.clone();
+        let start = doc.buffer.position_at(range.start);
+        let end = doc.buffer.position_at(range.end);
+        view.select_range(start, end);
+        if index == 0 {
+            self.end();
+        } else if self.choices.contains_key(&index) {
+            // 去掉默认值，从补全列表中选择
+            doc.delete(start, end);
+            view.select_range(start, start);
+            self.choice = Some(start);
+        }
+    }
+
+    /// 跳到下一个或上一个跳转点
+    fn jump(&mut self, view: &mut EditorView, doc: &mut Document, back: bool) {
+        if back {
+            if self.current == 0 {
+                return;
+            }
+            self.current -= 1;
+        } else {
+            self.current = (self.current + 1).min(self.order.len() - 1);
+        }
+        self.choice = None;
+        self.select(view, doc);
+    }
+
+    /// 第 i 个跳转点的结束位置改为 end，包含它的跳转点变长，后面的跳转点跟着移动
+    fn resize(&mut self, i: usize, end: usize) {
+        let old = self.stops[i].1.clone();
+        let delta = end as isize - old.end as isize;
+        for (j, (_, range)) in self.stops.iter_mut().enumerate() {
+            if j == i {
+                range.end = end;
+            } else if range.start <= old.start && range.end >= old.end && range.len() > old.len() {
+                range.end = range.end.saturating_add_signed(delta);
+            } else if range.start >= old.end {
+                range.start = range.start.saturating_add_signed(delta);
+                range.end = range.end.saturating_add_signed(delta);
+            } else if range.start >= old.start {
+                // 被修改掉的嵌套跳转点
+                range.start = end;
+                range.end = end;
+            }
+        }
+    }
+
+    /// 文档修改后移动跳转点并同步镜像的内容，修改不在当前跳转点中时结束
+    fn sync(&mut self, view: &mut EditorView, doc: &mut Document) {
+        let mut cursor = doc.buffer.offset(view.cursor);
+        if doc.version == self.version {
+            let start = self.stops.iter().map(|(_, range)| range.start).min();
+            let end = self.stops.iter().map(|(_, range)| range.end).max();
+            if start.is_none_or(|start| cursor < start) || end.is_none_or(|end| cursor > end) {
+                self.end();
+            }
+            return;
+        }
+        let len = doc.buffer.offset(doc.buffer.end());
+        let delta = len as isize - self.len as isize;
+        let index = self.order[self.current];
+        let Some(primary) = self.primary(index) else {
+            self.end();
+            return;
+        };
+        let old = self.stops[primary].1.clone();
+        let end = old.end.saturating_add_signed(delta);
+        if end < old.start || cursor < old.start || cursor > end {
+            self.end();
+            return;
+        }
+        self.resize(primary, end);
+        let text = doc.buffer.slice(
+            doc.buffer.position_at(old.start),
+            doc.buffer.position_at(end),
+        );
+        let count = text.chars().count();
+        let mut anchor = view.anchor.map(|anchor| doc.buffer.offset(anchor));
+        // 从后往前替换镜像，前面的偏移不变
+        for i in (0..self.stops.len()).rev() {
+            let (stop, range) = self.stops[i].clone();
+            if i == primary || stop != index {
+                continue;
+            }
+            let start = doc.buffer.position_at(range.start);
+            let end = doc.buffer.position_at(range.end);
+            if doc.buffer.slice(start, end) == text {
+                continue;
+            }
+            doc.delete(start, end);
+            doc.insert(start, &text);
+            let delta = count as isize - range.len() as isize;
+            let shift = |offset: usize| {
+                if offset >= range.end {
+                    offset.saturating_add_signed(delta)
+                } else {
+                    offset
+                }
+            };
+            (cursor, anchor) = (shift(cursor), anchor.map(shift));
+            self.resize(i, range.start + count);
+        }
+        view.cursor = doc.buffer.position_at(cursor);
+        view.anchor = anchor.map(|anchor| doc.buffer.position_at(anchor));
+        self.record(doc);
+    }
+}
+
+/// 片段进行中的系统，补全在它之后处理 Tab
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SnippetSystems;
+
+#[derive(Component)]
+pub struct SnippetPlugin;
+
+impl SeekerTrait for SnippetPlugin {}
+
+impl Plugin for SnippetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Snippets>()
+            .init_resource::<SnippetSession>()
+            .add_systems(OnEnter(SeekerEditSubLoadState::Loaded), Self::load)
+            .add_systems(OnExit(SeekerEditSubLoadState::Loaded), Self::exit)
+            .add_systems(
+                Update,
+                (
+                    Self::capture_tab.before(EditorSystems),
+                    (Self::reload, Self::input)
+                        .chain()
+                        .in_set(SnippetSystems)
+                        .after(EditorSystems),
+                )
+                    .run_if(
+                        in_state(SeekerEditSubLoadState::Loaded)
+                            .and(in_state(SeekerEditViewState::Editor)),
+                    ),
+            );
+    }
+}
+
+impl SnippetPlugin {
+    fn load(mut snippets: ResMut<Snippets>) {
+        snippets.library = match snippets_dir() {
+            Some(dir) => SnippetLibrary::load(&dir),
+            None => SnippetLibrary::builtin(),
+        };
+    }
+
+    fn exit(mut session: ResMut<SnippetSession>, mut capture: ResMut<EditorInputCapture>) {
+        session.end();
+        capture.tab = false;
+    }
+
+    /// 保存片段文件后重新加载
+    fn reload(mut reader: MessageReader<DocumentSaved>, snippets: ResMut<Snippets>) {
+        let Some(dir) = snippets_dir() else {
+            reader.clear();
+            return;
+        };
+        if reader.read().any(|saved| saved.path.starts_with(&dir)) {
+            Self::load(snippets);
+        }
+    }
+
+    /// 片段进行中或者光标前是片段前缀时由片段处理 Tab，编辑器不再插入缩进
+    fn capture_tab(
+        keys: Res<ButtonInput<KeyCode>>,
+        snippets: Res<Snippets>,
+        session: Res<SnippetSession>,
+        documents: Res<Documents>,
+        view: Single<&EditorView, With<FocusedEditor>>,
+        mut capture: ResMut<EditorInputCapture>,
+    ) {
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let tab = match view.path.as_ref().and_then(|path| documents.get(path)) {
+            Some(_) if session.is_active(view.path.as_ref()) => true,
+            Some(doc) if !shift && view.selection().is_none() => {
+                let prefix = doc
+                    .buffer
+                    .slice(doc.buffer.word_start(view.cursor), view.cursor);
+                !prefix.is_empty() && snippets.library.find(&doc.language_id, &prefix).is_some()
+            }
+            _ => false,
+        };
+        if capture.tab != tab {
+            capture.tab = tab;
+        }
+    }
+
+    /// 前缀加 Tab 展开片段，片段中 Tab 和 Shift+Tab 跳转，Esc 结束
+    #[allow(clippy::too_many_arguments)]
+    fn input(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        capture: Res<EditorInputCapture>,
+        snippets: Res<Snippets>,
+        mut session: ResMut<SnippetSession>,
+        mut documents: ResMut<Documents>,
+        mut view: Single<&mut EditorView, With<FocusedEditor>>,
+        mut menu: ResMut<CompletionMenu>,
+    ) {
+        if capture.text {
+            reader.clear();
+            return;
+        }
+        let Some(doc) = view.path.as_ref().and_then(|path| documents.get_mut(path)) else {
+            session.end();
+            reader.clear();
+            return;
+        };
+        if session.path.is_some() {
+            if session.is_active(view.path.as_ref()) {
+                session.sync(&mut view, doc);
+            } else {
+                session.end();
+            }
+        }
+        if let Some(start) = session.choice.take()
+            && !menu.is_open()
+        {
+            let choices = session.choices[&session.order[session.current]].clone();
+            let candidates = choices
+                .iter()
+                .map(|choice| CompletionCandidate {
+                    detail: Some("choice".to_string()),
+                    ..CompletionCandidate::word(choice)
+                })
+                .collect();
+            menu.open(doc.path.clone(), start, candidates);
+        }
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        for event in reader.read() {
+            if !event.state.is_pressed() || menu.is_open() {
+                continue;
+            }
+            let active = session.path.is_some();
+            match event.logical_key {
+                Key::Tab if active => session.jump(&mut view, doc, shift),
+                Key::Tab if !shift && view.selection().is_none() => {
+                    let start = doc.buffer.word_start(view.cursor);
+                    let prefix = doc.buffer.slice(start, view.cursor);
+                    if let Some(snippet) = snippets.library.find(&doc.language_id, &prefix) {
+                        session.expand(&mut view, doc, start, &snippet.body);
+                    }
+                }
+                Key::Escape if active => session.end(),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// 展开片段时可以使用的变量
+fn variables(view: &EditorView, doc: &Document, start: Position) -> SnippetVariables {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let mut variables = time_variables(seconds);
+    let path = &doc.path;
+    let name = |name: Option<&std::ffi::OsStr>| {
+        name.map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let line = doc.buffer.line(start.line);
+    let word = doc
+        .buffer
+        .slice(doc.buffer.word_start(view.cursor), view.cursor);
+    variables.extend(
+        [
+            ("TM_FILENAME", name(path.file_name())),
+            ("TM_FILENAME_BASE", name(path.file_stem())),
+            (
+                "TM_DIRECTORY",
+                name(path.parent().map(|parent| parent.as_os_str())),
+            ),
+            ("TM_FILEPATH", path.to_string_lossy().to_string()),
+            ("TM_LINE_INDEX", start.line.to_string()),
+            ("TM_LINE_NUMBER", (start.line + 1).to_string()),
+            ("TM_CURRENT_LINE", line.to_string()),
+            ("TM_CURRENT_WORD", word),
+        ]
+        .map(|(name, value)| (name.to_string(), value)),
+    );
+    if let Some((start, end)) = view.selection() {
+        variables.insert("TM_SELECTED_TEXT".to_string(), doc.buffer.slice(start, end));
+    }
+    variables
+}
+
+/// 前缀与正在输入的标识符匹配的片段
+pub fn snippet_candidates(
+    snippets: &Snippets,
+    doc: &Document,
+    cursor: Position,
+) -> Vec<CompletionCandidate> {
+    let prefix = doc.buffer.slice(doc.buffer.word_start(cursor), cursor);
+    snippets
+        .library
+        .for_language(&doc.language_id)
+        .filter(|snippet| fuzzy_score(&prefix, &snippet.prefix).is_some())
+        .map(|snippet| CompletionCandidate {
+            label: snippet.prefix.clone(),
+            detail: Some(
+                snippet
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| "snippet".to_string()),
+            ),
+            documentation: Some(Snippet::parse(&snippet.body).text),
+            filter_text: snippet.prefix.clone(),
+            insert_text: snippet.body.clone(),
+            snippet: true,
+        })
+        .collect()
+}
@@ -0,0 +1,711 @@
+use crate::diff_view::{DiffText, OpenDiff};
+use crate::file_tree::status_color;
+use crate::panel::PanelPlugin;
+use crate::PanelUi;
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use seeker_editor::document::Documents;
+use seeker_git::commit::{
+    commit, commit_files, log, stage, unstage, ChangedFile, Changes, CommitInfo,
+};
+use seeker_git::plugin::GitStatus;
+use seeker_git::Oid;
+use seeker_resource::background::BackgroundWake;
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::project_list::CurrentProject;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditPanelState, SeekerEditSubLoadState};
+use seeker_trait::text_input::{SeekerTextInput, TextInputSubmit, TextInputSystems};
+use seeker_trait::SeekerTrait;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 提交历史每次加载的条数
+const LOG_PAGE: usize = 100;
+
+/// 源代码管理面板的内容，面板打开和 git 状态变化时重新读取
+#[derive(Resource, Default)]
+pub struct SourceControl {
+    changes: Changes,
+    commits: Vec<CommitInfo>,
+    /// 展开的提交和其中改动的文件
+    expanded: Option<(Oid, Vec<ChangedFile>)>,
+    /// 上一次操作失败的原因
+    error: Option<String>,
+}
+
+impl SourceControl {
+    fn load(&mut self, root: &Path) {
+        let count = self.commits.len().max(LOG_PAGE);
+        let result = Changes::open(root).and_then(|changes| Ok((changes, log(root, 0, count)?)));
+        match result {
+            Ok((changes, commits)) => {
+                self.changes = changes;
+                self.commits = commits;
+                self.error = None;
+            }
+            Err(err) => {
+                *self = Self {
+                    error: Some(err.message().to_string()),
+                    ..default()
+                }
+            }
+        }
+        if let Some((id, _)) = &self.expanded
+            && !self.commits.iter().any(|commit| commit.id == *id)
+        {
+            self.expanded = None;
+        }
+    }
+}
+
+/// 提交信息，面板重新生成时从这里恢复输入框
+#[derive(Resource)]
+pub struct CommitMessage(SeekerTextInput);
+
+impl Default for CommitMessage {
+    fn default() -> Self {
+        Self(SeekerTextInput::new("Message (Ctrl+Enter to commit)").with_multiline())
+    }
+}
+
+/// Ctrl+Enter 或者点击提交按钮
+#[derive(Message)]
+struct CommitRequested;
+
+#[derive(Component)]
+pub struct CommitMessageInput;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct CommitButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct LoadMoreCommitsButton;
+
+/// 点击后比较改动
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct ChangeItem {
+    pub path: PathBuf,
+    pub staged: bool,
+}
+
+/// 已暂存的文件取消暂存，未暂存的文件暂存
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct ToggleStageButton {
+    pub path: PathBuf,
+    pub staged: bool,
+}
+
+/// 点击后展开改动的文件
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct CommitItem {
+    pub id: Oid,
+}
+
+/// 点击后比较文件在提交前后的内容
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct CommitFileItem {
+    pub id: Oid,
+    pub path: PathBuf,
+}
+
+#[derive(Component)]
+pub struct SourceControlPlugin;
+
+impl SeekerTrait for SourceControlPlugin {}
+
+impl Plugin for SourceControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SourceControl>()
+            .init_resource::<CommitMessage>()
+            .add_message::<CommitRequested>()
+            .add_systems(OnEnter(SeekerEditPanelState::SourceControl), Self::load)
+            .add_systems(OnExit(SeekerEditPanelState::SourceControl), Self::exit)
+            .add_systems(
+                Update,
+                (
+                    Self::message_input.run_if(in_state(SeekerEditPanelState::SourceControl)),
+                    Self::toggle,
+                    Self::load.run_if(
+                        in_state(SeekerEditPanelState::SourceControl)
+                            .and(resource_changed::<GitStatus>),
+                    ),
+                    Self::toggle_stage,
+                    Self::commit,
+                    Self::click_change,
+                    Self::click_commit,
+                    Self::click_commit_file,
+                    Self::load_more,
+                    Self::render.run_if(
+                        in_state(SeekerEditPanelState::SourceControl)
+                            .and(resource_changed::<SourceControl>),
+                    ),
+                )
+                    .chain()
+                    .after(TextInputSystems)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, CommitButton>)
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, LoadMoreCommitsButton>,
+            )
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, ToggleStageButton>,
+            );
+    }
+}
+
+impl SourceControlPlugin {
+    /// Ctrl+Shift+G 打开或关闭源代码管理面板
+    fn toggle(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        state: Res<State<SeekerEditPanelState>>,
+        mut next: ResMut<NextState<SeekerEditPanelState>>,
+    ) {
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        for event in reader.read() {
+            if !event.state.is_pressed() || !command || !shift {
+                continue;
+            }
+            if let Key::Character(ch) = &event.logical_key
+                && ch.eq_ignore_ascii_case("g")
+            {
+                next.set(if *state.get() == SeekerEditPanelState::SourceControl {
+                    SeekerEditPanelState::None
+                } else {
+                    SeekerEditPanelState::SourceControl
+                });
+            }
+        }
+    }
+
+    fn load(mut source_control: ResMut<SourceControl>, current_project: Res<CurrentProject>) {
+        if let Some(root) = project_root(&current_project) {
+            source_control.load(&root);
+        }
+    }
+
+    fn exit(mut source_control: ResMut<SourceControl>, mut message: ResMut<CommitMessage>) {
+        *source_control = SourceControl::default();
+        message.0.focused = false;
+    }
+
+    /// 输入框的内容保存到 CommitMessage，点击其他地方失去焦点，Ctrl+Enter 提交
+    fn message_input(
+        input: Single<(Entity, &mut SeekerTextInput, &Interaction), With<CommitMessageInput>>,
+        mut submit: MessageReader<TextInputSubmit>,
+        mouse: Res<ButtonInput<MouseButton>>,
+        mut message: ResMut<CommitMessage>,
+        mut commit: MessageWriter<CommitRequested>,
+    ) {
+        let (entity, mut input, interaction) = input.into_inner();
+        if mouse.just_pressed(MouseButton::Left)
+            && *interaction != Interaction::Pressed
+            && input.focused
+        {
+            input.focused = false;
+        }
+        if input.is_changed() {
+            message.0 = input.clone();
+        }
+        if submit.read().any(|submit| submit.entity == entity) {
+            commit.write(CommitRequested);
+        }
+    }
+
+    fn toggle_stage(
+        query: Query<(&Interaction, &ToggleStageButton), Changed<Interaction>>,
+        mut source_control: ResMut<SourceControl>,
+        mut git: ResMut<GitStatus>,
+        wake: Res<BackgroundWake>,
+        current_project: Res<CurrentProject>,
+    ) {
+        let Some(root) = project_root(&current_project) else {
+            return;
+        };
+        for (interaction, button) in query.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            let result = if button.staged {
+                unstage(&button.path)
+            } else {
+                stage(&button.path)
+            };
+            source_control.load(&root);
+            if let Err(err) = result {
+                error!("stage {}: {err}", button.path.display());
+                source_control.error = Some(err.message().to_string());
+            }
+            git.refresh(&wake);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn commit(
+        mut reader: MessageReader<CommitRequested>,
+        query: Query<&Interaction, (Changed<Interaction>, With<CommitButton>)>,
+        mut source_control: ResMut<SourceControl>,
+        mut message: ResMut<CommitMessage>,
+        mut input: Query<&mut SeekerTextInput, With<CommitMessageInput>>,
+        mut git: ResMut<GitStatus>,
+        wake: Res<BackgroundWake>,
+        current_project: Res<CurrentProject>,
+    ) {
+        let pressed = query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+        if reader.read().count() == 0 && !pressed {
+            return;
+        }
+        let Some(root) = project_root(&current_project) else {
+            return;
+        };
+        let text = message.0.value().trim().to_string();
+        if text.is_empty() {
+            source_control.error = Some("Commit message is empty".to_string());
+            return;
+        }
+        if source_control.changes.staged.is_empty() {
+            source_control.error = Some("No staged changes".to_string());
+            return;
+        }
+        match commit(&root, &text) {
+            Ok(_) => {
+                message.0.set_value("");
+                input.iter_mut().for_each(|mut input| input.set_value(""));
+                source_control.load(&root);
+                git.refresh(&wake);
+            }
+            Err(err) => {
+                error!("commit {}: {err}", root.display());
+                source_control.error = Some(err.message().to_string());
+            }
+        }
+    }
+
+    /// 已暂存的改动和 HEAD 比较，未暂存的改动和暂存区比较
+    fn click_change(
+        query: Query<(&Interaction, &ChangeItem), Changed<Interaction>>,
+        documents: Res<Documents>,
+        mut open: MessageWriter<OpenDiff>,
+    ) {
+        for (interaction, item) in query.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            let path = &item.path;
+            let result = if item.staged {
+                DiffText::head(path).and_then(|left| Ok((left, DiffText::index(path)?)))
+            } else {
+                DiffText::index(path).map(|left| {
+                    let right = DiffText::file(&documents, path)
+                        .unwrap_or_else(|_| DiffText::revision(path, "Deleted", None));
+                    (left, right)
+                })
+            };
+            match result {
+                Ok((left, right)) => {
+                    open.write(OpenDiff { left, right });
+                }
+                Err(err) => error!("diff {}: {err}", path.display()),
+            }
+        }
+    }
+
+    fn click_commit(
+        query: Query<(&Interaction, &CommitItem), Changed<Interaction>>,
+        mut source_control: ResMut<SourceControl>,
+        current_project: Res<CurrentProject>,
+    ) {
+        let Some(root) = project_root(&current_project) else {
+            return;
+        };
+        for (interaction, item) in query.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            if source_control
+                .expanded
+                .as_ref()
+                .is_some_and(|(id, _)| *id == item.id)
+            {
+                source_control.expanded = None;
+                continue;
+            }
+            match commit_files(&root, item.id) {
+                Ok(files) => source_control.expanded = Some((item.id, files)),
+                Err(err) => source_control.error = Some(err.message().to_string()),
+            }
+        }
+    }
+
+    fn click_commit_file(
+        query: Query<(&Interaction, &CommitFileItem), Changed<Interaction>>,
+        mut open: MessageWriter<OpenDiff>,
+    ) {
+        for (interaction, item) in query.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            match OpenDiff::commit(&item.path, item.id) {
+                Ok(diff) => {
+                    open.write(diff);
+                }
+                Err(err) => error!("diff {} at {}: {err}", item.path.display(), item.id),
+            }
+        }
+    }
+
+    fn load_more(
+        query: Query<&Interaction, (Changed<Interaction>, With<LoadMoreCommitsButton>)>,
+        mut source_control: ResMut<SourceControl>,
+        current_project: Res<CurrentProject>,
+    ) {
+        if !query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            return;
+        }
+        let Some(root) = project_root(&current_project) else {
+            return;
+        };
+        match log(&root, source_control.commits.len(), LOG_PAGE) {
+            Ok(commits) => source_control.commits.extend(commits),
+            Err(err) => source_control.error = Some(err.message().to_string()),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render(
+        mut commands: Commands,
+        source_control: Res<SourceControl>,
+        message: Res<CommitMessage>,
+        git: Res<GitStatus>,
+        current_project: Res<CurrentProject>,
+        panel: Single<Entity, With<PanelUi>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            ..default()
+        };
+        let root = project_root(&current_project).unwrap_or_default();
+        let branch = git
+            .status
+            .as_ref()
+            .and_then(|status| status.branch.clone())
+            .unwrap_or_default();
+        PanelPlugin::spawn_panel(
+            &mut commands,
+            *panel,
+            SeekerEditPanelState::SourceControl,
+            &res,
+            &assets,
+            |parent| {
+                PanelPlugin::panel_title(parent, "Source Control", &res, &assets);
+                parent.spawn((
+                    Text::new(branch),
+                    text_font.clone(),
+                    TextColor(res.colors.home_font_grey_color),
+                ));
+                if let Some(error) = &source_control.error {
+                    parent.spawn((
+                        Text::new(error),
+                        text_font.clone(),
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(res.colors.error),
+                    ));
+                }
+            },
+            |parent| {
+                parent
+                    .spawn(Node {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        column_gap: Val::Px(10.),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent
+                            .spawn(Node {
+                                width: Val::Percent(40.),
+                                height: Val::Percent(100.),
+                                padding: UiRect::horizontal(Val::Px(10.)),
+                                row_gap: Val::Px(4.),
+                                display: Display::Flex,
+                                flex_direction: FlexDirection::Column,
+                                overflow: Overflow::scroll_y(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    CommitMessageInput,
+                                    message.0.clone(),
+                                    text_font.clone(),
+                                    Node {
+                                        width: Val::Percent(100.),
+                                        min_height: Val::Px(48.),
+                                        padding: UiRect::all(Val::Px(4.)),
+                                        border: UiRect::all(Val::Px(1.)),
+                                        overflow: Overflow::clip(),
+                                        ..default()
+                                    },
+                                    BorderColor::all(res.colors.button_border),
+                                    BackgroundColor(res.colors.editor_background),
+                                    BorderRadius::all(Val::Px(3.)),
+                                ));
+                                Self::ui_button(
+                                    parent,
+                                    CommitButton,
+                                    "Commit",
+                                    "Commit",
+                                    &res,
+                                    &assets,
+                                );
+                                let changes = &source_control.changes;
+                                Self::render_changes(
+                                    parent,
+                                    "Staged Changes",
+                                    &changes.staged,
+                                    true,
+                                    &root,
+                                    &text_font,
+                                    &res,
+                                    &assets,
+                                );
+                                Self::render_changes(
+                                    parent,
+                                    "Changes",
+                                    &changes.unstaged,
+                                    false,
+                                    &root,
+                                    &text_font,
+                                    &res,
+                                    &assets,
+                                );
+                            });
+                        parent
+                            .spawn(Node {
+                                flex_grow: 1.,
+                                height: Val::Percent(100.),
+                                display: Display::Flex,
+                                flex_direction: FlexDirection::Column,
+                                overflow: Overflow::scroll_y(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                PanelPlugin::panel_title(parent, "History", &res, &assets);
+                                Self::render_log(
+                                    parent,
+                                    &source_control,
+                                    &root,
+                                    &text_font,
+                                    &res,
+                                    &assets,
+                                );
+                            });
+                    });
+            },
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_changes(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        title: &str,
+        files: &[ChangedFile],
+        staged: bool,
+        root: &Path,
+        text_font: &TextFont,
+        res: &Res<SeekerResource>,
+        assets: &Res<AssetServer>,
+    ) {
+        PanelPlugin::panel_title(parent, format!("{title} ({})", files.len()), res, assets);
+        for file in files {
+            let color = status_color(Some(file.status), res);
+            parent
+                .spawn((
+                    ChangeItem {
+                        path: file.path.clone(),
+                        staged,
+                    },
+                    Node {
+                        width: Val::Percent(100.),
+                        padding: UiRect::left(Val::Px(10.)),
+                        column_gap: Val::Px(8.),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new(file.status.letter()),
+                        text_font.clone(),
+                        TextColor(color),
+                    ));
+                    parent.spawn((
+                        Text::new(relative_path(&file.path, root).display().to_string()),
+                        text_font.clone(),
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(color),
+                    ));
+                    parent.spawn(Node {
+                        flex_grow: 1.,
+                        ..default()
+                    });
+                    Self::ui_button_same(
+                        parent,
+                        ToggleStageButton {
+                            path: file.path.clone(),
+                            staged,
+                        },
+                        if staged { "−" } else { "+" },
+                        res,
+                        assets,
+                    );
+                });
+        }
+    }
+
+    fn render_log(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        source_control: &SourceControl,
+        root: &Path,
+        text_font: &TextFont,
+        res: &Res<SeekerResource>,
+        assets: &Res<AssetServer>,
+    ) {
+        for commit in &source_control.commits {
+            parent
+                .spawn((
+                    CommitItem { id: commit.id },
+                    Node {
+                        width: Val::Percent(100.),
+                        padding: UiRect::horizontal(Val::Px(10.)),
+                        column_gap: Val::Px(8.),
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new(commit.short_id()),
+                        text_font.clone(),
+                        TextColor(res.colors.warning),
+                    ));
+                    parent.spawn((
+                        Text::new(&commit.summary),
+                        text_font.clone(),
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(res.colors.home_font_color),
+                    ));
+                    parent.spawn(Node {
+                        flex_grow: 1.,
+                        ..default()
+                    });
+                    parent.spawn((
+                        Text::new(format!("{}  {}", commit.author, age(commit.time))),
+                        text_font.clone(),
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(res.colors.home_font_grey_color),
+                    ));
+                });
+            let Some((_, files)) = source_control
+                .expanded
+                .as_ref()
+                .filter(|(id, _)| *id == commit.id)
+            else {
+                continue;
+            };
+            for file in files {
+                let color = status_color(Some(file.status), res);
+                parent
+                    .spawn((
+                        CommitFileItem {
+                            id: commit.id,
+                            path: file.path.clone(),
+                        },
+                        Node {
+                            width: Val::Percent(100.),
+                            padding: UiRect::left(Val::Px(30.)),
+                            column_gap: Val::Px(8.),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(file.status.letter()),
+                            text_font.clone(),
+                            TextColor(color),
+                        ));
+                        parent.spawn((
+                            Text::new(relative_path(&file.path, root).display().to_string()),
+                            text_font.clone(),
+                            TextLayout::new_with_no_wrap(),
+                            TextColor(color),
+                        ));
+                    });
+            }
+        }
+        let commits = source_control.commits.len();
+        if commits > 0 && commits.is_multiple_of(LOG_PAGE) {
+            Self::ui_button(
+                parent,
+                LoadMoreCommitsButton,
+                "LoadMoreCommits",
+                "Load more",
+                res,
+                assets,
+            );
+        }
+    }
+}
+
+pub(crate) fn project_root(current_project: &CurrentProject) -> Option<PathBuf> {
+    current_project
+        .project
+        .as_ref()
+        .map(|project| PathBuf::from(&project.path))
+}
+
+fn relative_path<'a>(path: &'a Path, root: &Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+/// 提交时间距离现在多久
+pub fn age(time: i64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs() as i64)
+        .unwrap_or(time);
+    let seconds = (now - time).max(0);
+    match seconds {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        86400..2592000 => format!("{}d ago", seconds / 86400),
+        2592000..31536000 => format!("{}mo ago", seconds / 2592000),
+        _ => format!("{}y ago", seconds / 31536000),
+    }
+}
@@ -0,0 +1,603 @@
+use crate::source_control::project_root;
+use crate::{EditorView, FocusedEditor, StatusBarUi};
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use seeker_editor::diagnostic::{Diagnostics, Severity};
+use seeker_editor::document::Documents;
+use seeker_git::branch::{
+    branches, checkout_branch, create_branch, stash, stash_count, stash_pop, BranchInfo,
+};
+use seeker_git::plugin::GitStatus;
+use seeker_git::GitError;
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake};
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::project_list::CurrentProject;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditSubLoadState, SeekerState};
+use seeker_trait::text_input::TextInputSystems;
+use seeker_trait::text_input::{SeekerTextInput, TextInputCancel, TextInputSubmit};
+use seeker_trait::SeekerTrait;
+use std::path::Path;
+use std::sync::mpsc::TryRecvError;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct ProjectsButton;
+
+#[derive(Component)]
+pub struct StatusBarText;
+
+/// 显示当前分支，点击后打开分支选择
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct BranchButton;
+
+#[derive(Component)]
+pub struct BranchButtonText;
+
+/// 分支选择弹出层，输入框用于过滤和创建分支
+#[derive(Resource, Default)]
+pub struct BranchPicker {
+    open: bool,
+    branches: Vec<BranchInfo>,
+    stashes: usize,
+    /// 弹出层重新生成时从这里恢复输入框
+    input: SeekerTextInput,
+    /// 上一次操作失败的原因
+    error: Option<String>,
+    /// 正在后台执行的切换分支或者储藏
+    running: Option<BackgroundReceiver<ActionResult>>,
+}
+
+impl BranchPicker {
+    fn load(&mut self, root: &Path) {
+        self.set_branches(read_branches(root));
+    }
+
+    fn set_branches(&mut self, result: Result<(Vec<BranchInfo>, usize), GitError>) {
+        match result {
+            Ok((branches, stashes)) => {
+                self.branches = branches;
+                self.stashes = stashes;
+            }
+            Err(err) => self.error = Some(err.message().to_string()),
+        }
+    }
+
+    /// 按输入过滤的分支
+    fn filtered(&self) -> impl Iterator<Item = &BranchInfo> {
+        let filter = self.input.value().trim().to_lowercase();
+        self.branches
+            .iter()
+            .filter(move |branch| branch.name.to_lowercase().contains(&filter))
+    }
+}
+
+#[derive(Component)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct BranchPickerUi;
+
+#[derive(Component)]
+pub struct BranchPickerInput;
+
+/// 点击后切换到分支，分支不存在时从 HEAD 创建
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct BranchItem {
+    pub name: String,
+}
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct StashButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct StashPopButton;
+
+/// 分支选择中的操作
+enum BranchAction {
+    Checkout(String),
+    Stash,
+    StashPop,
+}
+
+/// 后台执行的操作的结果和之后重新读取的分支
+struct ActionResult {
+    checkout: bool,
+    result: Result<(), GitError>,
+    branches: Result<(Vec<BranchInfo>, usize), GitError>,
+}
+
+fn read_branches(root: &Path) -> Result<(Vec<BranchInfo>, usize), GitError> {
+    Ok((branches(root)?, stash_count(root)?))
+}
+
+#[derive(Component)]
+pub struct StatusBarPlugin;
+
+impl SeekerTrait for StatusBarPlugin {}
+
+impl Plugin for StatusBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BranchPicker>()
+            .add_systems(OnEnter(SeekerEditSubLoadState::Loaded), Self::enter)
+            .add_systems(OnExit(SeekerEditSubLoadState::Loaded), Self::exit)
+            .add_systems(
+                Update,
+                (
+                    Self::picker_input,
+                    Self::back_home,
+                    Self::update,
+                    Self::update_branch.run_if(resource_changed::<GitStatus>),
+                    Self::toggle_picker,
+                    Self::branch_action,
+                    Self::receive_action,
+                    Self::render_picker.run_if(resource_changed::<BranchPicker>),
+                )
+                    .chain()
+                    .after(TextInputSystems)
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, ProjectsButton>)
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, BranchButton>)
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, BranchItem>)
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, StashButton>)
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, StashPopButton>);
+    }
+}
+
+impl StatusBarPlugin {
+    fn enter(
+        mut commands: Commands,
+        query: Query<Entity, With<StatusBarUi>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        for entity in query.iter() {
+            commands.entity(entity).with_children(|parent| {
+                Self::ui_button_same(parent, ProjectsButton, "Projects", &res, &assets);
+                parent.spawn((
+                    BranchButton,
+                    Node {
+                        display: Display::None,
+                        padding: UiRect::horizontal(Val::Px(6.)),
+                        ..default()
+                    },
+                    BorderRadius::all(Val::Px(3.)),
+                    Hovered::default(),
+                    children![(
+                        BranchButtonText,
+                        Text::default(),
+                        TextFont {
+                            font: assets.load(MAPLE_MONO_BOLD),
+                            font_size: 13.,
+                            ..default()
+                        },
+                        TextLayout::new_with_no_wrap(),
+                        TextColor(res.colors.home_font_color),
+                    )],
+                ));
+                parent.spawn((
+                    StatusBarText,
+                    Text::default(),
+                    TextFont {
+                        font: assets.load(MAPLE_MONO_BOLD),
+                        font_size: 13.,
+                        ..default()
+                    },
+                    TextLayout::new_with_no_wrap(),
+                    TextColor(res.colors.home_font_grey_color),
+                ));
+            });
+        }
+    }
+
+    fn exit(mut picker: ResMut<BranchPicker>) {
+        *picker = BranchPicker::default();
+    }
+
+    fn back_home(
+        query: Query<&Interaction, (Changed<Interaction>, With<ProjectsButton>)>,
+        mut state: ResMut<NextState<SeekerState>>,
+    ) {
+        if query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+        {
+            state.set(SeekerState::Home);
+        }
+    }
+
+    fn update(
+        view: Single<Ref<EditorView>, With<FocusedEditor>>,
+        diagnostics: Res<Diagnostics>,
+        current_project: Res<CurrentProject>,
+        mut text: Single<&mut Text, With<StatusBarText>>,
+    ) {
+        if !view.is_changed() && !diagnostics.is_changed() {
+            return;
+        }
+        let mut parts = Vec::new();
+        if let Some(project) = &current_project.project {
+            parts.push(project.project_name.clone());
+        }
+        if let Some(path) = &view.path {
+            parts.push(path.display().to_string());
+            parts.push(format!(
+                "Ln {}, Col {}",
+                view.cursor.line + 1,
+                view.cursor.column + 1
+            ));
+        }
+        parts.push(format!(
+            "✖ {}  ⚠ {}",
+            diagnostics.count(Severity::Error),
+            diagnostics.count(Severity::Warning)
+        ));
+        if let Some(path) = &view.path
+            && let Some(diagnostic) = diagnostics.worst_on_line(path, view.cursor.line)
+        {
+            parts.push(
+                diagnostic
+                    .message
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+        }
+        text.0 = parts.join("    ");
+    }
+
+    /// 不在仓库中时隐藏分支
+    fn update_branch(
+        git: Res<GitStatus>,
+        mut button: Single<&mut Node, With<BranchButton>>,
+        mut text: Single<&mut Text, With<BranchButtonText>>,
+    ) {
+        let branch = git.status.as_ref().map(|status| {
+            status
+                .branch
+                .clone()
+                .unwrap_or_else(|| "(detached)".to_string())
+        });
+        button.display = if branch.is_some() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        text.0 = format!("⎇ {}", branch.unwrap_or_default());
+    }
+
+    /// 点击分支打开或关闭选择，点击其他地方关闭
+    #[allow(clippy::too_many_arguments)]
+    fn toggle_picker(
+        button: Query<&Interaction, (Changed<Interaction>, With<BranchButton>)>,
+        popup: Query<&Interaction, With<BranchPickerUi>>,
+        mouse: Res<ButtonInput<MouseButton>>,
+        mut picker: ResMut<BranchPicker>,
+        current_project: Res<CurrentProject>,
+    ) {
+        let pressed = button
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+        let outside = mouse.just_pressed(MouseButton::Left)
+            && popup
+                .iter()
+                .all(|interaction| *interaction == Interaction::None);
+        if pressed && !picker.open {
+            let Some(root) = project_root(&current_project) else {
+                return;
+            };
+            *picker = BranchPicker {
+                open: true,
+                input: SeekerTextInput::new("Branch name (Enter to checkout or create)")
+                    .with_focus(),
+                ..default()
+            };
+            picker.load(&root);
+        } else if picker.open && (pressed || outside) {
+            picker.open = false;
+        }
+    }
+
+    /// 输入框的内容保存到 BranchPicker，变化时重新过滤。
+    /// Enter 切换到同名分支，没有时创建，Esc 关闭
+    #[allow(clippy::too_many_arguments)]
+    fn picker_input(
+        input: Single<(Entity, Ref<SeekerTextInput>), With<BranchPickerInput>>,
+        mut submit: MessageReader<TextInputSubmit>,
+        mut cancel: MessageReader<TextInputCancel>,
+        mut picker: ResMut<BranchPicker>,
+        wake: Res<BackgroundWake>,
+        documents: Res<Documents>,
+        current_project: Res<CurrentProject>,
+    ) {
+        let (entity, input) = input.into_inner();
+        if input.is_changed() {
+            let changed = input.value() != picker.input.value();
+            // 光标和焦点变化时不需要重新生成弹出层
+            picker.bypass_change_detection().input = input.clone();
+            if changed {
+                picker.set_changed();
+            }
+        }
+        if cancel.read().any(|cancel| cancel.entity == entity) {
+            picker.open = false;
+            return;
+        }
+        let name = picker.input.value().trim().to_string();
+        if submit.read().any(|submit| submit.entity == entity)
+            && !name.is_empty()
+            && let Some(root) = project_root(&current_project)
+        {
+            Self::run_action(
+                BranchAction::Checkout(name),
+                &root,
+                &mut picker,
+                &documents,
+                &wake,
+            );
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn branch_action(
+        items: Query<(&Interaction, &BranchItem), Changed<Interaction>>,
+        stash: Query<&Interaction, (Changed<Interaction>, With<StashButton>)>,
+        pop: Query<&Interaction, (Changed<Interaction>, With<StashPopButton>)>,
+        mut picker: ResMut<BranchPicker>,
+        wake: Res<BackgroundWake>,
+        documents: Res<Documents>,
+        current_project: Res<CurrentProject>,
+    ) {
+        let pressed = |interaction: &Interaction| *interaction == Interaction::Pressed;
+        let action =
+            if let Some((_, item)) = items.iter().find(|(interaction, _)| pressed(interaction)) {
+                BranchAction::Checkout(item.name.clone())
+            } else if stash.iter().any(pressed) {
+                BranchAction::Stash
+            } else if pop.iter().any(pressed) {
+                BranchAction::StashPop
+            } else {
+                return;
+            };
+        let Some(root) = project_root(&current_project) else {
+            return;
+        };
+        Self::run_action(action, &root, &mut picker, &documents, &wake);
+    }
+
+    /// 有未保存的文档时不改动工作区。git 操作在后台执行，同时只执行一个
+    fn run_action(
+        action: BranchAction,
+        root: &Path,
+        picker: &mut BranchPicker,
+        documents: &Documents,
+        wake: &BackgroundWake,
+    ) {
+        if picker.running.is_some() {
+            return;
+        }
+        let dirty: Vec<String> = documents
+            .documents
+            .iter()
+            .filter(|doc| doc.dirty)
+            .map(|doc| {
+                doc.path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        if !dirty.is_empty() {
+            picker.error = Some(format!(
+                "Save or discard changes first: {}",
+                dirty.join(", ")
+            ));
+            return;
+        }
+        let exists = match &action {
+            BranchAction::Checkout(name) => {
+                picker.branches.iter().any(|branch| branch.name == *name)
+            }
+            _ => false,
+        };
+        let root = root.to_path_buf();
+        picker.running = Some(wake.spawn(move || {
+            let result = match &action {
+                BranchAction::Checkout(name) => {
+                    let created = if exists {
+                        Ok(())
+                    } else {
+                        create_branch(&root, name)
+                    };
+                    created.and_then(|_| checkout_branch(&root, name))
+                }
+                BranchAction::Stash => stash(&root),
+                BranchAction::StashPop => stash_pop(&root),
+            };
+            if let Err(err) = &result {
+                error!("git {}: {err}", root.display());
+            }
+            ActionResult {
+                checkout: matches!(action, BranchAction::Checkout(_)),
+                result,
+                branches: read_branches(&root),
+            }
+        }));
+    }
+
+    /// 成功切换分支后关闭选择，打开的文档在文件变化后重新读取
+    fn receive_action(
+        mut picker: ResMut<BranchPicker>,
+        mut git: ResMut<GitStatus>,
+        wake: Res<BackgroundWake>,
+    ) {
+        let Some(running) = &picker.running else {
+            return;
+        };
+        let action = match running.try_recv() {
+            Ok(action) => action,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                picker.running = None;
+                return;
+            }
+        };
+        picker.running = None;
+        match action.result {
+            Ok(()) if action.checkout => picker.open = false,
+            Ok(()) => picker.error = None,
+            Err(err) => picker.error = Some(err.message().to_string()),
+        }
+        picker.set_branches(action.branches);
+        git.refresh(&wake);
+    }
+
+    fn render_picker(
+        mut commands: Commands,
+        picker: Res<BranchPicker>,
+        popups: Query<Entity, With<BranchPickerUi>>,
+        status_bar: Single<Entity, With<StatusBarUi>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        popups
+            .iter()
+            .for_each(|popup| commands.entity(popup).despawn());
+        if !picker.open {
+            return;
+        }
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            ..default()
+        };
+        let name = picker.input.value().trim().to_string();
+        commands.entity(*status_bar).with_children(|parent| {
+            parent
+                .spawn((
+                    BranchPickerUi,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(10.),
+                        bottom: Val::Percent(100.),
+                        width: Val::Px(360.),
+                        max_height: Val::Px(400.),
+                        padding: UiRect::all(Val::Px(8.)),
+                        border: UiRect::all(Val::Px(1.)),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(4.),
+                        ..default()
+                    },
+                    ZIndex(10),
+                    BorderColor::all(res.colors.button_border),
+                    BackgroundColor(res.colors.home_menu),
+                    BorderRadius::all(Val::Px(3.)),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        BranchPickerInput,
+                        picker.input.clone(),
+                        text_font.clone(),
+                        Node {
+                            width: Val::Percent(100.),
+                            padding: UiRect::all(Val::Px(4.)),
+                            border: UiRect::all(Val::Px(1.)),
+                            overflow: Overflow::clip(),
+                            ..default()
+                        },
+                        BorderColor::all(res.colors.button_border),
+                        BackgroundColor(res.colors.editor_background),
+                        BorderRadius::all(Val::Px(3.)),
+                    ));
+                    if let Some(error) = &picker.error {
+                        parent.spawn((
+                            Text::new(error),
+                            text_font.clone(),
+                            TextColor(res.colors.error),
+                        ));
+                    }
+                    parent
+                        .spawn(Node {
+                            width: Val::Percent(100.),
+                            display: Display::Flex,
+                            flex_direction: FlexDirection::Column,
+                            overflow: Overflow::scroll_y(),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            let mut exact = false;
+                            for branch in picker.filtered() {
+                                exact |= branch.name == name;
+                                let label = if branch.is_head {
+                                    format!("● {}", branch.name)
+                                } else {
+                                    format!("  {}", branch.name)
+                                };
+                                Self::branch_item(parent, &branch.name, label, &text_font, &res);
+                            }
+                            if !name.is_empty() && !exact {
+                                let label = format!("+ Create branch '{name}'");
+                                Self::branch_item(parent, &name, label, &text_font, &res);
+                            }
+                        });
+                    parent
+                        .spawn(Node {
+                            column_gap: Val::Px(8.),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            Self::ui_button_same(parent, StashButton, "Stash", &res, &assets);
+                            if picker.stashes > 0 {
+                                let text = format!("Pop stash ({})", picker.stashes);
+                                Self::ui_button(
+                                    parent,
+                                    StashPopButton,
+                                    "StashPop",
+                                    &text,
+                                    &res,
+                                    &assets,
+                                );
+                            }
+                        });
+                });
+        });
+    }
+
+    fn branch_item(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        name: &str,
+        label: String,
+        text_font: &TextFont,
+        res: &Res<SeekerResource>,
+    ) {
+        parent.spawn((
+            BranchItem {
+                name: name.to_string(),
+            },
+            Node {
+                width: Val::Percent(100.),
+                padding: UiRect::horizontal(Val::Px(4.)),
+                ..default()
+            },
+            Hovered::default(),
+            children![(
+                Text::new(label),
+                text_font.clone(),
+                TextLayout::new_with_no_wrap(),
+                TextColor(res.colors.home_font_color),
+            )],
+        ));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::diff_view::CompareWithFocused;
+use crate::pane::{focus_view, EditorPane, PaneSystems};
+use crate::{EditorView, FocusedEditor};
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use seeker_editor::document::Documents;
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::SeekerResource;
+use seeker_state::SeekerEditSubLoadState;
+use std::path::PathBuf;
+
+/// 窗格中的标签，view 为所在窗格的视图
+#[derive(Component, Clone)]
+pub struct DocumentTab {
+    pub path: PathBuf,
+    pub view: Entity,
+}
+
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct TabCloseButton {
+    pub path: PathBuf,
+    pub view: Entity,
+}
+
+#[derive(Component)]
+pub struct TabBarPlugin;
+
+impl Plugin for TabBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (Self::click, Self::close, Self::render)
+                .chain()
+                .after(PaneSystems)
+                .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+        );
+    }
+}
+
+impl TabBarPlugin {
+    /// 点击标签时在它的窗格中切换文档并聚焦窗格，Alt+点击时和当前文档比较
+    fn click(
+        mut commands: Commands,
+        query: Query<(&Interaction, &DocumentTab), Changed<Interaction>>,
+        keys: Res<ButtonInput<KeyCode>>,
+        focused: Single<Entity, With<FocusedEditor>>,
+        mut views: Query<&mut EditorView>,
+        mut compare: MessageWriter<CompareWithFocused>,
+    ) {
+        let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        for (interaction, tab) in query.iter() {
+            if *interaction == Interaction::Pressed && alt {
+                compare.write(CompareWithFocused {
+                    path: tab.path.clone(),
+                });
+            } else if *interaction == Interaction::Pressed {
+                focus_view(&mut commands, *focused, tab.view, &mut views);
+                if let Ok(mut view) = views.get_mut(tab.view) {
+                    view.open(tab.path.clone(), None);
+                }
+            }
+        }
+    }
+
+    /// 其他窗格还打开着同一个文档时只关闭标签
+    fn close(
+        query: Query<(&Interaction, &TabCloseButton), Changed<Interaction>>,
+        mut documents: ResMut<Documents>,
+        mut views: Query<&mut EditorView>,
+    ) {
+        for (interaction, button) in query.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+            let shared = views
+                .iter()
+                .filter(|view| view.tabs.contains(&button.path))
+                .count()
+                > 1;
+            if !shared && documents.get(&button.path).is_some_and(|doc| doc.dirty) {
+                warn!("{} has unsaved changes", button.path.display());
+                continue;
+            }
+            if let Ok(mut view) = views.get_mut(button.view) {
+                view.close(&button.path);
+            }
+            if !shared {
+                documents.close(&button.path);
+            }
+        }
+    }
+
+    /// 每个窗格的标签栏，聚焦窗格的当前标签高亮
+    fn render(
+        mut commands: Commands,
+        documents: Res<Documents>,
+        panes: Query<&EditorPane>,
+        views: Query<(Ref<EditorView>, Has<FocusedEditor>)>,
+        mut last_focused: Local<Option<Entity>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let focused = panes
+            .iter()
+            .find(|pane| views.get(pane.view).is_ok_and(|(_, focused)| focused))
+            .map(|pane| pane.view);
+        let focus_changed = *last_focused != focused;
+        *last_focused = focused;
+        let font = assets.load(MAPLE_MONO_BOLD);
+        let text_font = TextFont {
+            font: font.clone(),
+            font_size: 14.,
+            ..default()
+        };
+        for pane in panes.iter() {
+            let Ok((view, is_focused)) = views.get(pane.view) else {
+                continue;
+            };
+            if !documents.is_changed() && !view.is_changed() && !focus_changed {
+                continue;
+            }
+            let highlight = if is_focused {
+                res.colors.home_hovered
+            } else {
+                res.colors.button_border
+            };
+            commands
+                .entity(pane.tab_bar)
+                .despawn_children()
+                .with_children(|parent| {
+                    for doc in view.tabs.iter().filter_map(|tab| documents.get(tab)) {
+                        let active = view.path.as_ref() == Some(&doc.path);
+                        let filename = doc
+                            .path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        let dirty = if doc.dirty { " ●" } else { "" };
+                        parent
+                            .spawn((
+                                DocumentTab {
+                                    path: doc.path.clone(),
+                                    view: pane.view,
+                                },
+                                Button,
+                                Node {
+                                    height: Val::Percent(100.),
+                                    padding: UiRect::horizontal(Val::Px(12.)),
+                                    column_gap: Val::Px(8.),
+                                    align_items: AlignItems::Center,
+                                    border: UiRect::bottom(Val::Px(2.)),
+                                    ..default()
+                                },
+                                BorderColor::all(if active { highlight } else { Color::NONE }),
+                                BackgroundColor(if active {
+                                    res.colors.editor_background
+                                } else {
+                                    Color::NONE
+                                }),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(format!("{filename}{dirty}")),
+                                    text_font.clone(),
+                                    TextColor(res.colors.home_font_color),
+                                ));
+                                parent
+                                    .spawn(TabCloseButton {
+                                        path: doc.path.clone(),
+                                        view: pane.view,
+                                    })
+                                    .with_children(|parent| {
+                                        parent.spawn((
+                                            Text::new("×"),
+                                            text_font.clone(),
+                                            TextColor(res.colors.home_font_grey_color),
+                                        ));
+                                    });
+                            });
+                    }
+                });
+        }
+    }
+}
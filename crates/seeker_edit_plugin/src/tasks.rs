@@ -0,0 +1,424 @@
+use crate::editor::LINE_HEIGHT;
+use crate::panel::PanelPlugin;
+use crate::{OpenLocation, PanelUi};
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::text::LineHeight;
+use bevy::ui::FocusPolicy;
+use seeker_cargo::metadata::{CargoPackage, CargoTask};
+use seeker_cargo::output::{AnsiColor, OutputLine};
+use seeker_cargo::plugin::{CargoTasks, RunCargoTask, StopCargoTask};
+use seeker_editor::buffer::Position;
+use seeker_resource::fonts::MAPLE_MONO_BOLD;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditPanelState, SeekerEditSubLoadState};
+use seeker_terminal::color::indexed_rgb;
+use seeker_trait::SeekerTrait;
+use std::path::PathBuf;
+
+/// 运行一个 cargo 命令的按钮
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct TaskButton {
+    pub task: CargoTask,
+}
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct StopTaskButton;
+
+#[derive(Component)]
+pub struct TaskStatusText;
+
+/// 输出的滚动容器
+#[derive(Component)]
+pub struct TaskOutput;
+
+/// 输出中的一行，index 从第一次输出开始计算
+#[derive(Component)]
+pub struct TaskOutputLine {
+    pub index: usize,
+}
+
+/// 带有 `path:line:col` 的输出行，点击后打开
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct TaskOutputLink {
+    pub path: PathBuf,
+    pub position: Position,
+}
+
+#[derive(Component)]
+pub struct TasksPlugin;
+
+impl SeekerTrait for TasksPlugin {}
+
+impl Plugin for TasksPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                Self::toggle,
+                Self::click,
+                (Self::render_panel, Self::render_output, Self::update_status)
+                    .chain()
+                    .run_if(in_state(SeekerEditPanelState::Tasks)),
+            )
+                .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+        )
+        .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, TaskButton>)
+        .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, StopTaskButton>)
+        .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, TaskOutputLink>);
+    }
+}
+
+impl TasksPlugin {
+    /// Ctrl+Shift+B 打开或关闭任务面板
+    fn toggle(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        state: Res<State<SeekerEditPanelState>>,
+        mut next: ResMut<NextState<SeekerEditPanelState>>,
+    ) {
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        // Ctrl+Alt+B 是 blame
+        let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        for event in reader.read() {
+            if !event.state.is_pressed() || !command || !shift || alt {
+                continue;
+            }
+            if event.key_code == KeyCode::KeyB {
+                next.set(if *state.get() == SeekerEditPanelState::Tasks {
+                    SeekerEditPanelState::None
+                } else {
+                    SeekerEditPanelState::Tasks
+                });
+            }
+        }
+    }
+
+    fn click(
+        buttons: Query<(&Interaction, &TaskButton), Changed<Interaction>>,
+        stop: Query<&Interaction, (Changed<Interaction>, With<StopTaskButton>)>,
+        links: Query<(&Interaction, &TaskOutputLink), Changed<Interaction>>,
+        mut run: MessageWriter<RunCargoTask>,
+        mut stop_task: MessageWriter<StopCargoTask>,
+        mut open: MessageWriter<OpenLocation>,
+    ) {
+        let pressed = |interaction: &Interaction| *interaction == Interaction::Pressed;
+        if let Some((_, button)) = buttons.iter().find(|(interaction, _)| pressed(interaction)) {
+            run.write(RunCargoTask(button.task.clone()));
+        }
+        if stop.iter().any(pressed) {
+            stop_task.write(StopCargoTask);
+        }
+        if let Some((_, link)) = links.iter().find(|(interaction, _)| pressed(interaction)) {
+            open.write(OpenLocation {
+                path: link.path.clone(),
+                position: Some(link.position),
+            });
+        }
+    }
+
+    /// 打开面板或者包变化时重新生成，左边是包和目标，右边是输出
+    fn render_panel(
+        mut commands: Commands,
+        tasks: Res<CargoTasks>,
+        outputs: Query<(), With<TaskOutput>>,
+        panel: Single<Entity, With<PanelUi>>,
+        mut last: Local<Option<Vec<CargoPackage>>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        if last.as_ref() == Some(&tasks.packages) && !outputs.is_empty() {
+            return;
+        }
+        *last = Some(tasks.packages.clone());
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            ..default()
+        };
+        PanelPlugin::spawn_panel(
+            &mut commands,
+            *panel,
+            SeekerEditPanelState::Tasks,
+            &res,
+            &assets,
+            |parent| {
+                PanelPlugin::panel_title(parent, "Tasks", &res, &assets);
+                parent.spawn((
+                    TaskStatusText,
+                    Text::default(),
+                    text_font.clone(),
+                    TextColor(res.colors.home_font_grey_color),
+                ));
+                Self::ui_button(parent, StopTaskButton, "StopTask", "Stop", &res, &assets);
+            },
+            |parent| {
+                parent
+                    .spawn(Node {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent
+                            .spawn(Node {
+                                width: Val::Px(320.),
+                                flex_shrink: 0.,
+                                display: Display::Flex,
+                                flex_direction: FlexDirection::Column,
+                                overflow: Overflow::scroll_y(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                if tasks.packages.is_empty() {
+                                    parent.spawn((
+                                        Text::new("No cargo packages"),
+                                        text_font.clone(),
+                                        TextColor(res.colors.home_font_grey_color),
+                                        Node {
+                                            padding: UiRect::horizontal(Val::Px(10.)),
+                                            ..default()
+                                        },
+                                    ));
+                                }
+                                for package in &tasks.packages {
+                                    Self::task_row(
+                                        parent,
+                                        &package.name,
+                                        res.colors.home_font_color,
+                                        package.tasks(),
+                                        &text_font,
+                                        &res,
+                                    );
+                                    for target in &package.targets {
+                                        let target_tasks = package.target_tasks(target);
+                                        if target_tasks.is_empty() {
+                                            continue;
+                                        }
+                                        Self::task_row(
+                                            parent,
+                                            &format!("  {} {}", target.kind.label(), target.name),
+                                            res.colors.home_font_grey_color,
+                                            target_tasks,
+                                            &text_font,
+                                            &res,
+                                        );
+                                    }
+                                }
+                            });
+                        parent.spawn((
+                            TaskOutput,
+                            Node {
+                                flex_grow: 1.,
+                                height: Val::Percent(100.),
+                                padding: UiRect::left(Val::Px(10.)),
+                                display: Display::Flex,
+                                flex_direction: FlexDirection::Column,
+                                overflow: Overflow::scroll_y(),
+                                ..default()
+                            },
+                            BackgroundColor(res.colors.editor_background),
+                        ));
+                    });
+            },
+        );
+    }
+
+    fn task_row(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        title: &str,
+        color: Color,
+        tasks: Vec<CargoTask>,
+        text_font: &TextFont,
+        res: &SeekerResource,
+    ) {
+        parent
+            .spawn(Node {
+                width: Val::Percent(100.),
+                padding: UiRect::horizontal(Val::Px(10.)),
+                column_gap: Val::Px(4.),
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(title),
+                    text_font.clone(),
+                    TextLayout::new_with_no_wrap(),
+                    TextColor(color),
+                    Node {
+                        flex_grow: 1.,
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                ));
+                for task in tasks {
+                    parent.spawn((
+                        Node {
+                            padding: UiRect::horizontal(Val::Px(6.)),
+                            flex_shrink: 0.,
+                            ..default()
+                        },
+                        BorderRadius::all(Val::Px(3.)),
+                        Hovered::default(),
+                        children![(
+                            Text::new(task.label),
+                            text_font.clone(),
+                            TextColor(res.colors.info),
+                        )],
+                        TaskButton { task },
+                    ));
+                }
+            });
+    }
+
+    /// 只追加新的输出行，停在最底部时跟随输出
+    #[allow(clippy::too_many_arguments)]
+    fn render_output(
+        mut commands: Commands,
+        tasks: Res<CargoTasks>,
+        output: Single<(Entity, Ref<TaskOutput>, &mut ScrollPosition, &ComputedNode)>,
+        lines: Query<(Entity, &TaskOutputLine)>,
+        mut rendered: Local<(usize, usize)>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let (entity, added, mut scroll, computed) = output.into_inner();
+        if !tasks.is_changed() && !added.is_added() {
+            return;
+        }
+        let (runs, end) = &mut *rendered;
+        if added.is_added() || *runs != tasks.runs {
+            for (line, _) in lines.iter() {
+                commands.entity(line).despawn();
+            }
+            *runs = tasks.runs;
+            *end = tasks.dropped;
+        }
+        for (line, output_line) in lines.iter() {
+            if output_line.index < tasks.dropped {
+                commands.entity(line).despawn();
+            }
+        }
+        let start = (*end).max(tasks.dropped);
+        let total = tasks.dropped + tasks.output.len();
+        if start >= total {
+            return;
+        }
+        let size = computed.size() * computed.inverse_scale_factor();
+        let height = |end: usize| (end - tasks.dropped) as f32 * LINE_HEIGHT;
+        let follow = added.is_added() || scroll.y + size.y + LINE_HEIGHT / 2. >= height(start);
+        commands.entity(entity).with_children(|parent| {
+            for index in start..total {
+                let line = &tasks.output[index - tasks.dropped];
+                Self::output_line(parent, index, line, &res, &assets);
+            }
+        });
+        *end = total;
+        if follow {
+            scroll.y = (height(total) - size.y).max(0.);
+        }
+    }
+
+    fn output_line(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        index: usize,
+        line: &OutputLine,
+        res: &SeekerResource,
+        assets: &AssetServer,
+    ) {
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            line_height: LineHeight::Px(LINE_HEIGHT),
+            ..default()
+        };
+        let mut entity = parent.spawn((
+            TaskOutputLine { index },
+            Node {
+                height: Val::Px(LINE_HEIGHT),
+                flex_shrink: 0.,
+                ..default()
+            },
+            Text::default(),
+            text_font.clone(),
+            TextLayout::new_with_no_wrap(),
+        ));
+        if let Some(location) = &line.location {
+            entity.insert((
+                TaskOutputLink {
+                    path: location.path.clone(),
+                    position: location.position,
+                },
+                Hovered::default(),
+            ));
+        }
+        entity.with_children(|parent| {
+            for span in &line.spans {
+                parent.spawn((
+                    TextSpan::new(span.text.as_str()),
+                    text_font.clone(),
+                    TextColor(output_color(span.color, span.bold, res)),
+                ));
+            }
+        });
+    }
+
+    fn update_status(
+        tasks: Res<CargoTasks>,
+        mut status: Query<&mut Text, With<TaskStatusText>>,
+        mut stop: Query<&mut Node, With<StopTaskButton>>,
+    ) {
+        let command = tasks
+            .task
+            .as_ref()
+            .map(CargoTask::command)
+            .unwrap_or_default();
+        let value = match (tasks.running, tasks.success) {
+            (true, _) => format!("{command} running…"),
+            (false, Some(true)) => format!("{command} finished"),
+            (false, Some(false)) => format!("{command} failed"),
+            (false, None) => command,
+        };
+        for mut text in status.iter_mut() {
+            if text.0 != value {
+                text.0 = value.clone();
+            }
+        }
+        for mut node in stop.iter_mut() {
+            let display = if tasks.running {
+                Display::Flex
+            } else {
+                Display::None
+            };
+            if node.display != display {
+                node.display = display;
+            }
+        }
+    }
+}
+
+/// 粗体的基本色按终端习惯显示为亮色
+fn output_color(color: AnsiColor, bold: bool, res: &SeekerResource) -> Color {
+    match color {
+        AnsiColor::Default => res.colors.home_font_color,
+        AnsiColor::Indexed(index) => {
+            let index = if bold && index < 8 { index + 8 } else { index };
+            let (r, g, b) = indexed_rgb(index);
+            Color::srgb_u8(r, g, b)
+        }
+        AnsiColor::Rgb(r, g, b) => Color::srgb_u8(r, g, b),
+    }
+}
@@ -0,0 +1,588 @@
+use crate::editor::{cell_width, EditorInputCapture, EditorSystems, LINE_HEIGHT};
+use crate::panel::PanelPlugin;
+use crate::source_control::project_root;
+use crate::PanelUi;
+use bevy::ecs::relationship::RelatedSpawnerCommands;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::picking::hover::Hovered;
+use bevy::prelude::*;
+use bevy::text::LineHeight;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+use seeker_config::SEEKER_CONFIG;
+use seeker_resource::background::BackgroundWake;
+use seeker_resource::clipboard::SeekerClipboard;
+use seeker_resource::fonts::{MAPLE_MONO_BOLD, MAPLE_MONO_BOLD_ITALIC};
+use seeker_resource::project_list::CurrentProject;
+use seeker_resource::SeekerResource;
+use seeker_state::{SeekerEditPanelState, SeekerEditSubLoadState};
+use seeker_terminal::color::terminal_color;
+use seeker_terminal::input::{key_to_bytes, Modifiers};
+use seeker_terminal::plugin::Terminals;
+use seeker_terminal::session::TerminalSession;
+use seeker_trait::SeekerTrait;
+
+/// 终端中的位置（行，列），行从最早的历史开始计算
+type TerminalPoint = (usize, u16);
+
+/// 终端面板的焦点和鼠标选区
+#[derive(Resource, Default)]
+pub struct TerminalInput {
+    focused: bool,
+    /// 拖动开始和当前的位置
+    selection: Option<(TerminalPoint, TerminalPoint)>,
+    dragging: bool,
+    /// 输入后滚动到最新的内容
+    follow: bool,
+}
+
+impl TerminalInput {
+    fn selection(&self) -> Option<(TerminalPoint, TerminalPoint)> {
+        self.selection
+            .filter(|(anchor, head)| anchor != head)
+            .map(|(anchor, head)| (anchor.min(head), anchor.max(head)))
+    }
+}
+
+/// 终端的滚动容器，内容高度包含历史，只渲染可见的行
+#[derive(Component, Default)]
+#[require(Node, FocusPolicy::Block, Interaction, RelativeCursorPosition)]
+pub struct TerminalView {
+    /// 上次渲染时历史的行数，用于判断是否停在最底部
+    scrollback: usize,
+}
+
+#[derive(Component)]
+pub struct TerminalContent;
+
+#[derive(Component)]
+pub struct TerminalRow;
+
+#[derive(Component, Clone, Copy)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct TerminalTab {
+    pub index: usize,
+}
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct NewTerminalButton;
+
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct KillTerminalButton;
+
+#[derive(Component)]
+pub struct TerminalPlugin;
+
+impl SeekerTrait for TerminalPlugin {}
+
+impl Plugin for TerminalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerminalInput>()
+            .add_systems(OnEnter(SeekerEditPanelState::Terminal), Self::enter)
+            .add_systems(OnExit(SeekerEditPanelState::Terminal), Self::exit)
+            .add_systems(
+                Update,
+                (Self::focus, Self::input)
+                    .chain()
+                    .before(EditorSystems)
+                    .run_if(in_state(SeekerEditPanelState::Terminal)),
+            )
+            .add_systems(
+                Update,
+                (
+                    Self::toggle,
+                    (Self::tabs, Self::select, Self::render_panel, Self::render)
+                        .chain()
+                        .run_if(in_state(SeekerEditPanelState::Terminal)),
+                )
+                    .chain()
+                    .run_if(in_state(SeekerEditSubLoadState::Loaded)),
+            )
+            .add_observer(Self::button_on_hovered_changed_color::<Insert, Hovered, TerminalTab>)
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, NewTerminalButton>,
+            )
+            .add_observer(
+                Self::button_on_hovered_changed_color::<Insert, Hovered, KillTerminalButton>,
+            );
+    }
+}
+
+impl TerminalPlugin {
+    /// Ctrl+` 打开或关闭终端面板
+    fn toggle(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        state: Res<State<SeekerEditPanelState>>,
+        mut next: ResMut<NextState<SeekerEditPanelState>>,
+    ) {
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        for event in reader.read() {
+            if !event.state.is_pressed() || !command {
+                continue;
+            }
+            if event.key_code == KeyCode::Backquote {
+                next.set(if *state.get() == SeekerEditPanelState::Terminal {
+                    SeekerEditPanelState::None
+                } else {
+                    SeekerEditPanelState::Terminal
+                });
+            }
+        }
+    }
+
+    /// 打开面板时没有终端则在项目目录中新建一个，并获得焦点
+    fn enter(
+        mut terminals: ResMut<Terminals>,
+        mut input: ResMut<TerminalInput>,
+        mut capture: ResMut<EditorInputCapture>,
+        current_project: Res<CurrentProject>,
+        wake: Res<BackgroundWake>,
+        mut next: ResMut<NextState<SeekerEditPanelState>>,
+    ) {
+        if terminals.sessions.is_empty() && !Self::open(&mut terminals, &current_project, &wake) {
+            next.set(SeekerEditPanelState::None);
+            return;
+        }
+        input.focused = true;
+        input.follow = true;
+        capture.text = true;
+    }
+
+    fn exit(mut input: ResMut<TerminalInput>, mut capture: ResMut<EditorInputCapture>) {
+        if input.focused {
+            capture.text = false;
+        }
+        *input = TerminalInput::default();
+    }
+
+    /// 大小在渲染时按面板调整
+    fn open(
+        terminals: &mut Terminals,
+        current_project: &CurrentProject,
+        wake: &BackgroundWake,
+    ) -> bool {
+        let Some(root) = project_root(current_project) else {
+            return false;
+        };
+        match terminals.open(&root, 10, 80, wake) {
+            Ok(()) => true,
+            Err(err) => {
+                error!("terminal {}: {err}", root.display());
+                false
+            }
+        }
+    }
+
+    /// 点击终端获得焦点，点击其他地方失去焦点
+    fn focus(
+        view: Query<&Interaction, With<TerminalView>>,
+        mouse: Res<ButtonInput<MouseButton>>,
+        mut input: ResMut<TerminalInput>,
+        mut capture: ResMut<EditorInputCapture>,
+    ) {
+        if !mouse.just_pressed(MouseButton::Left) {
+            return;
+        }
+        let pressed = view
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+        if pressed && !input.focused {
+            input.focused = true;
+            capture.text = true;
+        } else if !pressed && input.focused {
+            input.focused = false;
+            capture.text = false;
+        }
+    }
+
+    /// 按键转换为终端的输入，Ctrl+Shift+C/V 复制粘贴
+    fn input(
+        mut keyboard: MessageReader<KeyboardInput>,
+        mut ime: MessageReader<Ime>,
+        keys: Res<ButtonInput<KeyCode>>,
+        mut terminals: ResMut<Terminals>,
+        mut input: ResMut<TerminalInput>,
+        mut clipboard: ResMut<SeekerClipboard>,
+    ) {
+        if !input.focused {
+            return;
+        }
+        if keyboard.is_empty() && ime.is_empty() {
+            return;
+        }
+        let modifiers = Modifiers {
+            shift: keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            ctrl: keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+            alt: keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+        };
+        let command = keys.any_pressed([KeyCode::SuperLeft, KeyCode::SuperRight]);
+        let terminals = terminals.bypass_change_detection();
+        let Some(session) = terminals.active_mut() else {
+            keyboard.clear();
+            ime.clear();
+            return;
+        };
+        let mut bytes = vec![];
+        for event in keyboard.read() {
+            if !event.state.is_pressed() {
+                continue;
+            }
+            let shortcut = command || (modifiers.ctrl && modifiers.shift);
+            match &event.logical_key {
+                Key::Character(ch) if shortcut && ch.eq_ignore_ascii_case("c") => {
+                    if let Some((start, end)) = input.selection() {
+                        clipboard.copy(session.text_between(start, end));
+                    }
+                }
+                Key::Character(ch) if shortcut && ch.eq_ignore_ascii_case("v") => {
+                    if let Some(text) = clipboard.paste()
+                        && let Err(err) = session.paste(&text)
+                    {
+                        error!("terminal paste: {err}");
+                    }
+                    input.follow = true;
+                }
+                // 其他 Cmd 组合键留给编辑器的快捷键
+                _ if command => {}
+                key => {
+                    let application = session.screen().application_cursor();
+                    if let Some(key_bytes) =
+                        key_to_bytes(key, event.text.as_deref(), modifiers, application)
+                    {
+                        bytes.extend(key_bytes);
+                    }
+                }
+            }
+        }
+        for ime in ime.read() {
+            if let Ime::Commit { value, .. } = ime {
+                bytes.extend(value.as_bytes());
+            }
+        }
+        if bytes.is_empty() {
+            return;
+        }
+        if let Err(err) = session.write(&bytes) {
+            error!("terminal write: {err}");
+        }
+        input.selection = None;
+        input.follow = true;
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn tabs(
+        tabs: Query<(&Interaction, &TerminalTab), Changed<Interaction>>,
+        new: Query<&Interaction, (Changed<Interaction>, With<NewTerminalButton>)>,
+        kill: Query<&Interaction, (Changed<Interaction>, With<KillTerminalButton>)>,
+        mut terminals: ResMut<Terminals>,
+        mut input: ResMut<TerminalInput>,
+        mut capture: ResMut<EditorInputCapture>,
+        current_project: Res<CurrentProject>,
+        wake: Res<BackgroundWake>,
+    ) {
+        let pressed = |interaction: &Interaction| *interaction == Interaction::Pressed;
+        if let Some((_, tab)) = tabs.iter().find(|(interaction, _)| pressed(interaction)) {
+            terminals.active = tab.index;
+        } else if new.iter().any(pressed) {
+            Self::open(&mut terminals, &current_project, &wake);
+        } else if kill.iter().any(pressed) {
+            let active = terminals.active;
+            terminals.close(active);
+        } else {
+            return;
+        }
+        // 点击标签和按钮后继续在终端中输入
+        input.focused = true;
+        input.selection = None;
+        input.follow = true;
+        capture.text = true;
+    }
+
+    /// 按下开始选择，拖动时更新
+    fn select(
+        view: Single<
+            (
+                Ref<Interaction>,
+                &RelativeCursorPosition,
+                &ComputedNode,
+                &ScrollPosition,
+            ),
+            With<TerminalView>,
+        >,
+        mouse: Res<ButtonInput<MouseButton>>,
+        terminals: Res<Terminals>,
+        mut input: ResMut<TerminalInput>,
+    ) {
+        let (interaction, relative, computed, scroll) = view.into_inner();
+        let point = relative.normalized.map(|normalized| {
+            let size = computed.size() * computed.inverse_scale_factor();
+            let point = (normalized + Vec2::splat(0.5)) * size + scroll.0;
+            let cols = terminals
+                .active()
+                .map(|session| session.screen().size().1)
+                .unwrap_or_default();
+            let line = (point.y / LINE_HEIGHT).max(0.) as usize;
+            let column = ((point.x / cell_width()).round().max(0.) as u16).min(cols);
+            (line, column)
+        });
+        if interaction.is_changed() && *interaction == Interaction::Pressed {
+            input.selection = point.map(|point| (point, point));
+            input.dragging = true;
+        } else if input.dragging && mouse.pressed(MouseButton::Left) {
+            if let (Some(point), Some((anchor, head))) = (point, input.selection)
+                && head != point
+            {
+                input.selection = Some((anchor, point));
+            }
+        } else if input.dragging {
+            input.dragging = false;
+        }
+    }
+
+    /// 标签变化时重新生成面板
+    #[allow(clippy::too_many_arguments)]
+    fn render_panel(
+        mut commands: Commands,
+        terminals: Res<Terminals>,
+        views: Query<(), With<TerminalView>>,
+        panel: Single<Entity, With<PanelUi>>,
+        mut last: Local<Option<(usize, usize)>>,
+        mut next: ResMut<NextState<SeekerEditPanelState>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        if terminals.sessions.is_empty() {
+            next.set(SeekerEditPanelState::None);
+            return;
+        }
+        let key = Some((terminals.sessions.len(), terminals.active));
+        if *last == key && !views.is_empty() {
+            return;
+        }
+        *last = key;
+        let text_font = TextFont {
+            font: assets.load(MAPLE_MONO_BOLD),
+            font_size: 13.,
+            ..default()
+        };
+        PanelPlugin::spawn_panel(
+            &mut commands,
+            *panel,
+            SeekerEditPanelState::Terminal,
+            &res,
+            &assets,
+            |parent| {
+                PanelPlugin::panel_title(parent, "Terminal", &res, &assets);
+                for index in 0..terminals.sessions.len() {
+                    let color = if index == terminals.active {
+                        res.colors.home_font_color
+                    } else {
+                        res.colors.home_font_grey_color
+                    };
+                    parent.spawn((
+                        TerminalTab { index },
+                        Node {
+                            padding: UiRect::horizontal(Val::Px(6.)),
+                            ..default()
+                        },
+                        BorderRadius::all(Val::Px(3.)),
+                        Hovered::default(),
+                        children![(
+                            Text::new(format!("{}: shell", index + 1)),
+                            text_font.clone(),
+                            TextColor(color),
+                        )],
+                    ));
+                }
+                Self::ui_button_same(parent, NewTerminalButton, "+", &res, &assets);
+                Self::ui_button(parent, KillTerminalButton, "Kill", "Kill", &res, &assets);
+            },
+            |parent| {
+                parent
+                    .spawn((
+                        TerminalView::default(),
+                        Node {
+                            width: Val::Percent(100.),
+                            height: Val::Percent(100.),
+                            padding: UiRect::left(Val::Px(10.)),
+                            overflow: Overflow::scroll_y(),
+                            ..default()
+                        },
+                        BackgroundColor(res.colors.editor_background),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((TerminalContent, Node::default()));
+                    });
+            },
+        );
+    }
+
+    /// 虚拟化渲染：按滚动位置显示历史或者最新的屏幕
+    #[allow(clippy::too_many_arguments)]
+    fn render(
+        mut commands: Commands,
+        mut terminals: ResMut<Terminals>,
+        mut input: ResMut<TerminalInput>,
+        view: Single<(&mut TerminalView, &mut ScrollPosition, &ComputedNode)>,
+        content: Single<(Entity, &mut Node, Ref<TerminalContent>)>,
+        rows: Query<Entity, With<TerminalRow>>,
+        mut last: Local<Option<(usize, usize, u16, u16)>>,
+        res: Res<SeekerResource>,
+        assets: Res<AssetServer>,
+    ) {
+        let (mut view, mut scroll, computed) = view.into_inner();
+        let (content, mut node, added) = content.into_inner();
+        let size = computed.size() * computed.inverse_scale_factor();
+        if size.x <= 0. {
+            return;
+        }
+        let changed = terminals.is_changed() || input.is_changed() || added.is_added();
+        let active = terminals.active;
+        let Some(session) = terminals.bypass_change_detection().active_mut() else {
+            return;
+        };
+        let cols = ((size.x - 10.) / cell_width()).floor().max(1.) as u16;
+        let lines = (size.y / LINE_HEIGHT).floor().max(1.) as u16;
+        if let Err(err) = session.resize(lines, cols) {
+            error!("terminal resize: {err}");
+        }
+        let len = session.scrollback_len();
+        // 停在最底部时跟随新的输出
+        let bottom = view.scrollback as f32 * LINE_HEIGHT;
+        let follow = input.follow || scroll.y + LINE_HEIGHT / 2. >= bottom;
+        let y = if follow {
+            len as f32 * LINE_HEIGHT
+        } else {
+            scroll.y
+        };
+        let first = ((y / LINE_HEIGHT).round() as usize).min(len);
+        let key = Some((active, first, lines, cols));
+        if *last == key && !changed && !scroll.is_changed() {
+            return;
+        }
+        *last = key;
+        view.scrollback = len;
+        if input.follow {
+            input.bypass_change_detection().follow = false;
+        }
+        if scroll.y != y {
+            scroll.y = y;
+        }
+        session.scroll_to(len - first);
+        node.width = Val::Px(cols as f32 * cell_width());
+        // 最大滚动距离正好是历史的高度
+        node.height = Val::Px(len as f32 * LINE_HEIGHT + size.y);
+        for row in rows.iter() {
+            commands.entity(row).despawn();
+        }
+        let selection = input.selection();
+        let cursor = (input.focused && first == len && !session.screen().hide_cursor())
+            .then(|| session.screen().cursor_position());
+        commands.entity(content).with_children(|parent| {
+            for row in 0..lines {
+                let line = first + row as usize;
+                Self::render_row(parent, session, row, line, cursor, selection, &res, &assets);
+            }
+        });
+        session.scroll_to(0);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_row(
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        session: &TerminalSession,
+        row: u16,
+        line: usize,
+        cursor: Option<(u16, u16)>,
+        selection: Option<(TerminalPoint, TerminalPoint)>,
+        res: &SeekerResource,
+        assets: &AssetServer,
+    ) {
+        let screen = session.screen();
+        let (_, cols) = screen.size();
+        let mut spans: Vec<(String, Color, Color, bool)> = vec![];
+        for col in 0..cols {
+            let Some(cell) = screen.cell(row, col) else {
+                continue;
+            };
+            if cell.is_wide_continuation() {
+                continue;
+            }
+            let mut fg = terminal_color(cell.fgcolor(), res.colors.home_font_color);
+            let mut bg = terminal_color(cell.bgcolor(), Color::NONE);
+            if cell.inverse() {
+                (fg, bg) = (
+                    if bg == Color::NONE {
+                        res.colors.editor_background
+                    } else {
+                        bg
+                    },
+                    fg,
+                );
+            }
+            if selection.is_some_and(|(start, end)| start <= (line, col) && (line, col) < end) {
+                bg = res.colors.editor_selection;
+            }
+            if cursor == Some((row, col)) {
+                (fg, bg) = (res.colors.editor_background, res.colors.editor_cursor);
+            }
+            let text = if cell.has_contents() {
+                cell.contents()
+            } else {
+                " "
+            };
+            let italic = cell.italic();
+            match spans.last_mut() {
+                Some((span, span_fg, span_bg, span_italic))
+                    if *span_fg == fg && *span_bg == bg && *span_italic == italic =>
+                {
+                    span.push_str(text)
+                }
+                _ => spans.push((text.to_string(), fg, bg, italic)),
+            }
+        }
+        let text_font = |italic: bool| TextFont {
+            font: assets.load(if italic {
+                MAPLE_MONO_BOLD_ITALIC
+            } else {
+                MAPLE_MONO_BOLD
+            }),
+            font_size: SEEKER_CONFIG.font_size,
+            line_height: LineHeight::Px(LINE_HEIGHT),
+            ..default()
+        };
+        parent
+            .spawn((
+                TerminalRow,
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(line as f32 * LINE_HEIGHT),
+                    left: Val::Px(0.),
+                    height: Val::Px(LINE_HEIGHT),
+                    ..default()
+                },
+                Text::default(),
+                text_font(false),
+                TextLayout::new_with_no_wrap(),
+            ))
+            .with_children(|parent| {
+                for (text, fg, bg, italic) in spans {
+                    parent.spawn((
+                        TextSpan::new(text),
+                        text_font(italic),
+                        TextColor(fg),
+                        TextBackgroundColor(bg),
+                    ));
+                }
+            });
+    }
+}
@@ -0,0 +1,141 @@
+use crate::buffer::{Position, TextBuffer};
+
+/// 查找配对括号时最多扫描的行数
+const MAX_SCAN_LINES: usize = 2000;
+
+/// 左括号对应的右括号
+pub fn closing(open: char) -> Option<char> {
+    match open {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+/// 右括号对应的左括号
+pub fn opening(close: char) -> Option<char> {
+    match close {
+        ')' => Some('('),
+        ']' => Some('['),
+        '}' => Some('{'),
+        _ => None,
+    }
+}
+
+/// 输入 ch 时自动补全的右半部分，Rust 的单引号可能是生命周期所以不补全
+pub fn auto_pair(ch: char, language_id: &str) -> Option<char> {
+    match ch {
+        '"' => Some('"'),
+        '\'' if !matches!(language_id, "rust" | "plaintext" | "markdown") => Some('\''),
+        '`' if matches!(
+            language_id,
+            "markdown" | "javascript" | "typescript" | "shellscript"
+        ) =>
+        {
+            Some('`')
+        }
+        _ => closing(ch),
+    }
+}
+
+/// 光标前后的字符决定是否补全：后面是空白、行尾或右括号时才补全，
+/// 引号前面紧挨着标识符时不补全
+pub fn should_pair(buffer: &TextBuffer, pos: Position, ch: char) -> bool {
+    let line: Vec<char> = buffer.line(pos.line).chars().collect();
+    let next = line.get(pos.column).copied();
+    let prev = pos.column.checked_sub(1).and_then(|i| line.get(i)).copied();
+    let next_ok = next.is_none_or(|next| next.is_whitespace() || opening(next).is_some());
+    let quote = closing(ch).is_none();
+    next_ok && !(quote && prev.is_some_and(|prev| prev.is_alphanumeric() || prev == ch))
+}
+
+/// 光标所在或前面的括号和它的配对括号
+pub fn matching_bracket(buffer: &TextBuffer, pos: Position) -> Option<(Position, Position)> {
+    let line: Vec<char> = buffer.line(pos.line).chars().collect();
+    let candidates = [Some(pos.column), pos.column.checked_sub(1)];
+    for column in candidates.into_iter().flatten() {
+        let Some(&ch) = line.get(column) else {
+            continue;
+        };
+        let at = Position::new(pos.line, column);
+        if closing(ch).is_some() {
+            return matching_close(buffer, at, ch).map(|other| (at, other));
+        }
+        if opening(ch).is_some() {
+            return matching_open(buffer, at, ch).map(|other| (at, other));
+        }
+    }
+    None
+}
+
+/// 从 open 位置向后找配对的右括号
+fn matching_close(buffer: &TextBuffer, open: Position, ch: char) -> Option<Position> {
+    let close = closing(ch)?;
+    let mut depth = 0;
+    let last = (open.line + MAX_SCAN_LINES).min(buffer.line_count());
+    for line in open.line..last {
+        let skip = if line == open.line { open.column } else { 0 };
+        for (column, c) in buffer.line(line).chars().enumerate().skip(skip) {
+            if c == ch {
+                depth += 1;
+            } else if c == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(Position::new(line, column));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// 从 close 位置（不包含）向前找配对的左括号
+pub fn matching_open(buffer: &TextBuffer, close: Position, ch: char) -> Option<Position> {
+    let open = opening(ch)?;
+    let mut depth = 0;
+    let first = close.line.saturating_sub(MAX_SCAN_LINES);
+    for line in (first..=close.line).rev() {
+        let chars: Vec<char> = buffer.line(line).chars().collect();
+        let end = if line == close.line {
+            close.column.min(chars.len())
+        } else {
+            chars.len()
+        };
+        for column in (0..end).rev() {
+            if chars[column] == ch {
+                depth += 1;
+            } else if chars[column] == open {
+                if depth == 0 {
+                    return Some(Position::new(line, column));
+                }
+                depth -= 1;
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn test_matching_bracket() {
+    let buffer = TextBuffer::new("fn main() {\n    if (a[0]) {}\n}");
+    assert_eq!(
+        matching_bracket(&buffer, Position::new(0, 10)),
+        Some((Position::new(0, 10), Position::new(2, 0)))
+    );
+    // 光标在右括号后面
+    assert_eq!(
+        matching_bracket(&buffer, Position::new(2, 1)),
+        Some((Position::new(2, 0), Position::new(0, 10)))
+    );
+    assert_eq!(
+        matching_bracket(&buffer, Position::new(1, 12)),
+        Some((Position::new(1, 12), Position::new(1, 7)))
+    );
+    assert_eq!(matching_bracket(&buffer, Position::new(1, 2)), None);
+
+    let buffer = TextBuffer::new("let s = a + ");
+    assert!(should_pair(&buffer, Position::new(0, 12), '('));
+    assert!(!should_pair(&buffer, Position::new(0, 8), '('));
+    assert!(!should_pair(&buffer, Position::new(0, 9), '"'));
+}
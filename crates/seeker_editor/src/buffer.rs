@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+
+/// 文本中的位置，column 以字符（char）为单位
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+/// 按行存储的文本缓冲区
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextBuffer {
+    lines: Vec<String>,
+    crlf: bool,
+}
+
+impl Default for TextBuffer {
+    fn default() -> Self {
+        Self {
+            lines: vec![String::new()],
+            crlf: false,
+        }
+    }
+}
+
+impl TextBuffer {
+    pub fn new(text: &str) -> Self {
+        let crlf = text.contains("\r\n");
+        let lines = text
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+            .collect();
+        Self { lines, crlf }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join(if self.crlf { "\r\n" } else { "\n" })
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn line(&self, line: usize) -> &str {
+        self.lines.get(line).map(String::as_str).unwrap_or_default()
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+
+    /// 行的字符数
+    pub fn line_len(&self, line: usize) -> usize {
+        self.line(line).chars().count()
+    }
+
+    pub fn end(&self) -> Position {
+        let line = self.lines.len() - 1;
+        Position::new(line, self.line_len(line))
+    }
+
+    /// 把位置限制在缓冲区范围内
+    pub fn clamp(&self, pos: Position) -> Position {
+        let line = pos.line.min(self.lines.len() - 1);
+        Position::new(line, pos.column.min(self.line_len(line)))
+    }
+
+    /// 位置之前的字符数，换行算一个字符
+    pub fn offset(&self, pos: Position) -> usize {
+        let pos = self.clamp(pos);
+        let before: usize = (0..pos.line).map(|line| self.line_len(line) + 1).sum();
+        before + pos.column
+    }
+
+    /// offset 的反向转换，超出末尾时为末尾
+    pub fn position_at(&self, offset: usize) -> Position {
+        let mut rest = offset;
+        for line in 0..self.lines.len() {
+            let len = self.line_len(line);
+            if rest <= len {
+                return Position::new(line, rest);
+            }
+            rest -= len + 1;
+        }
+        self.end()
+    }
+
+    /// 插入文本，返回插入后末尾的位置
+    pub fn insert(&mut self, pos: Position, text: &str) -> Position {
+        let pos = self.clamp(pos);
+        let line = &mut self.lines[pos.line];
+        let byte = byte_index(line, pos.column);
+        let tail = line.split_off(byte);
+
+        let mut inserted = text.split('\n').map(|l| l.strip_suffix('\r').unwrap_or(l));
+        line.push_str(inserted.next().unwrap_or_default());
+        let mut end = Position::new(pos.line, line.chars().count());
+        for new_line in inserted {
+            end.line += 1;
+            self.lines.insert(end.line, new_line.to_string());
+            end.column = new_line.chars().count();
+        }
+        self.lines[end.line].push_str(&tail);
+        end
+    }
+
+    /// 删除 [start, end) 之间的文本，返回被删除的内容
+    pub fn delete(&mut self, start: Position, end: Position) -> String {
+        let (start, end) = ordered(self.clamp(start), self.clamp(end));
+        let removed = self.slice(start, end);
+        let tail = {
+            let last = &self.lines[end.line];
+            last[byte_index(last, end.column)..].to_string()
+        };
+        let first = &mut self.lines[start.line];
+        let byte = byte_index(first, start.column);
+        first.truncate(byte);
+        first.push_str(&tail);
+        self.lines.drain(start.line + 1..=end.line);
+        removed
+    }
+
+    /// 获取 [start, end) 之间的文本
+    pub fn slice(&self, start: Position, end: Position) -> String {
+        let (start, end) = ordered(self.clamp(start), self.clamp(end));
+        if start.line == end.line {
+            let line = self.line(start.line);
+            return line[byte_index(line, start.column)..byte_index(line, end.column)].to_string();
+        }
+        let mut text = String::new();
+        let first = self.line(start.line);
+        text.push_str(&first[byte_index(first, start.column)..]);
+        for line in start.line + 1..end.line {
+            text.push('\n');
+            text.push_str(self.line(line));
+        }
+        text.push('\n');
+        let last = self.line(end.line);
+        text.push_str(&last[..byte_index(last, end.column)]);
+        text
+    }
+
+    /// 位置的前一个字符位置（跨行）
+    pub fn prev_position(&self, pos: Position) -> Position {
+        let pos = self.clamp(pos);
+        if pos.column > 0 {
+            Position::new(pos.line, pos.column - 1)
+        } else if pos.line > 0 {
+            Position::new(pos.line - 1, self.line_len(pos.line - 1))
+        } else {
+            pos
+        }
+    }
+
+    /// 位置的后一个字符位置（跨行）
+    pub fn next_position(&self, pos: Position) -> Position {
+        let pos = self.clamp(pos);
+        if pos.column < self.line_len(pos.line) {
+            Position::new(pos.line, pos.column + 1)
+        } else if pos.line + 1 < self.lines.len() {
+            Position::new(pos.line + 1, 0)
+        } else {
+            pos
+        }
+    }
+
+    /// 光标前面标识符的起始位置
+    pub fn word_start(&self, pos: Position) -> Position {
+        let pos = self.clamp(pos);
+        let chars = self.line(pos.line).chars().take(pos.column).collect::<Vec<_>>();
+        let prefix = chars.iter().rev().take_while(|ch| is_word_char(**ch)).count();
+        Position::new(pos.line, pos.column - prefix)
+    }
+}
+
+/// 标识符字符
+pub fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// 字符下标转换为字节下标
+pub fn byte_index(line: &str, column: usize) -> usize {
+    line.char_indices()
+        .nth(column)
+        .map(|(i, _)| i)
+        .unwrap_or(line.len())
+}
+
+/// 在 start 处插入 text 后，text 中第 offset 个字符所在的位置
+pub fn advance(start: Position, text: &str, offset: usize) -> Position {
+    let mut pos = start;
+    for ch in text.chars().take(offset) {
+        if ch == '\n' {
+            pos.line += 1;
+            pos.column = 0;
+        } else {
+            pos.column += 1;
+        }
+    }
+    pos
+}
+
+pub fn ordered(a: Position, b: Position) -> (Position, Position) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+#[test]
+fn test_buffer_edit() {
+    let mut buffer = TextBuffer::new("fn main() {\r\n}\r\n");
+    assert_eq!(buffer.line_count(), 3);
+    let end = buffer.insert(Position::new(0, 11), "\n    println!(\"你好\");");
+    assert_eq!(end, Position::new(1, 19));
+    assert_eq!(buffer.line(1), "    println!(\"你好\");");
+    let removed = buffer.delete(Position::new(0, 11), Position::new(1, 19));
+    assert_eq!(removed, "\n    println!(\"你好\");");
+    assert_eq!(buffer.text(), "fn main() {\r\n}\r\n");
+}
@@ -0,0 +1,103 @@
+use crate::buffer::is_word_char;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// 参与补全的标识符最短长度
+const MIN_WORD_LEN: usize = 3;
+/// 建立项目索引时跳过的大文件
+const MAX_INDEX_FILE_SIZE: u64 = 512 * 1024;
+/// 建立项目索引时最多读取的文件数
+const MAX_INDEX_FILES: usize = 5000;
+
+/// 模糊匹配打分，pattern 的字符需要按顺序出现在 candidate 中（忽略大小写）
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i32> {
+    let mut pattern = pattern.chars().peekable();
+    let mut score = 0;
+    let mut prev: Option<char> = None;
+    let mut prev_matched = false;
+    for (index, ch) in candidate.chars().enumerate() {
+        let Some(&expected) = pattern.peek() else {
+            break;
+        };
+        if ch.to_lowercase().eq(expected.to_lowercase()) {
+            pattern.next();
+            score += 1;
+            if ch == expected {
+                score += 1;
+            }
+            if index == 0 {
+                score += 10;
+            } else if prev_matched {
+                score += 5;
+            } else if prev.is_some_and(|prev| !is_word_char(prev) || prev == '_')
+                || prev.is_some_and(|prev| prev.is_lowercase() && ch.is_uppercase())
+            {
+                // 单词边界：snake_case 和 camelCase
+                score += 8;
+            }
+            prev_matched = true;
+        } else {
+            score -= 1;
+            prev_matched = false;
+        }
+        prev = Some(ch);
+    }
+    pattern.peek().is_none().then_some(score)
+}
+
+/// 文本中的标识符
+pub fn collect_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|ch: char| !is_word_char(ch)).filter(|word| {
+        word.chars().count() >= MIN_WORD_LEN && !word.starts_with(|ch: char| ch.is_ascii_digit())
+    })
+}
+
+/// 收集目录下所有文本文件中的标识符，跳过隐藏目录和构建产物
+pub fn index_words(root: &Path) -> HashSet<String> {
+    let mut words = HashSet::new();
+    let mut dirs = vec![root.to_path_buf()];
+    let mut files = 0;
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') || name == "target" || name == "node_modules" {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+                continue;
+            }
+            if metadata.len() > MAX_INDEX_FILE_SIZE {
+                continue;
+            }
+            // 非 UTF-8 文件（二进制）直接跳过
+            if let Ok(text) = fs::read_to_string(entry.path()) {
+                words.extend(collect_words(&text).map(str::to_string));
+            }
+            files += 1;
+            if files >= MAX_INDEX_FILES {
+                return words;
+            }
+        }
+    }
+    words
+}
+
+#[test]
+fn test_fuzzy_score() {
+    assert!(fuzzy_score("psn", "push_str").is_none());
+    assert!(fuzzy_score("pst", "push_str").is_some());
+    assert!(fuzzy_score("", "anything").is_some());
+    assert!(fuzzy_score("gtd", "get_documents") > fuzzy_score("gtd", "bigTextDocument"));
+    assert!(fuzzy_score("doc", "document") > fuzzy_score("doc", "to_document"));
+    assert!(fuzzy_score("TB", "TextBuffer") > fuzzy_score("TB", "tab_bar"));
+    let words = collect_words("let 名字 = value_1 + 12ab + x;").collect::<Vec<_>>();
+    assert_eq!(words, vec!["let", "value_1"]);
+}
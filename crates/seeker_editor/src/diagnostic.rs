@@ -0,0 +1,80 @@
+use crate::buffer::Position;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub start: Position,
+    pub end: Position,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// 所有来源（语言服务器、cargo 等）的诊断信息
+#[derive(Resource, Default)]
+pub struct Diagnostics {
+    /// source -> path -> diagnostics
+    sources: HashMap<String, HashMap<PathBuf, Vec<Diagnostic>>>,
+}
+
+impl Diagnostics {
+    /// 替换某个来源在某个文件上的全部诊断
+    pub fn set(&mut self, source: &str, path: PathBuf, diagnostics: Vec<Diagnostic>) {
+        let files = self.sources.entry(source.to_string()).or_default();
+        if diagnostics.is_empty() {
+            files.remove(&path);
+        } else {
+            files.insert(path, diagnostics);
+        }
+    }
+
+    pub fn clear_source(&mut self, source: &str) {
+        self.sources.remove(source);
+    }
+
+    pub fn for_path<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a Diagnostic> + 'a {
+        self.sources
+            .values()
+            .filter_map(move |files| files.get(path))
+            .flatten()
+    }
+
+    /// 某一行上最严重的诊断
+    pub fn worst_on_line<'a>(&'a self, path: &'a Path, line: usize) -> Option<&'a Diagnostic> {
+        self.for_path(path)
+            .filter(|diagnostic| diagnostic.start.line <= line && line <= diagnostic.end.line)
+            .min_by_key(|diagnostic| diagnostic.severity)
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.sources
+            .values()
+            .flat_map(|files| files.values())
+            .flatten()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    }
+
+    /// 所有来源的诊断，按文件、严重程度和位置排序
+    pub fn all(&self) -> Vec<(&Path, &Diagnostic)> {
+        let mut all = self
+            .sources
+            .values()
+            .flat_map(|files| files.iter())
+            .flat_map(|(path, diagnostics)| {
+                diagnostics.iter().map(move |diagnostic| (path.as_path(), diagnostic))
+            })
+            .collect::<Vec<_>>();
+        all.sort_by_key(|(path, diagnostic)| (*path, diagnostic.severity, diagnostic.start));
+        all
+    }
+}
@@ -0,0 +1,237 @@
+use similar::{ChangeTag, DiffOp, TextDiff};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// 一行中的若干段文本，为 true 的是行内改动
+pub type DiffSpans = Vec<(String, bool)>;
+
+/// 比较结果中一侧的一行
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffLine {
+    /// 从 0 开始的行号
+    pub number: usize,
+    pub spans: DiffSpans,
+}
+
+impl DiffLine {
+    fn plain(number: usize, text: &str) -> Self {
+        Self {
+            number,
+            spans: vec![(text.to_string(), false)],
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|(text, _)| text.as_str()).collect()
+    }
+}
+
+/// 左右对照的一行，修改的行左右配对，多出来的行另一侧为空
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffRow {
+    pub left: Option<DiffLine>,
+    pub right: Option<DiffLine>,
+}
+
+impl DiffRow {
+    pub fn is_equal(&self) -> bool {
+        matches!((&self.left, &self.right), (Some(left), Some(right)) if left.spans == right.spans)
+    }
+}
+
+/// 统一视图中的一行
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnifiedLine {
+    pub kind: DiffLineKind,
+    pub line: DiffLine,
+}
+
+/// 两段文本的比较结果
+#[derive(Clone, Debug, Default)]
+pub struct TextComparison {
+    pub rows: Vec<DiffRow>,
+    /// 每处改动第一行在 rows 中的下标
+    pub hunks: Vec<usize>,
+}
+
+impl TextComparison {
+    pub fn new(left: &str, right: &str) -> Self {
+        let left_lines: Vec<&str> = left.lines().collect();
+        let right_lines: Vec<&str> = right.lines().collect();
+        let diff = TextDiff::from_lines(left, right);
+        let mut comparison = Self::default();
+        for op in diff.ops() {
+            let (old, new) = (op.old_range(), op.new_range());
+            if let DiffOp::Equal { .. } = op {
+                for (old, new) in old.zip(new) {
+                    comparison.rows.push(DiffRow {
+                        left: Some(DiffLine::plain(old, left_lines[old])),
+                        right: Some(DiffLine::plain(new, right_lines[new])),
+                    });
+                }
+                continue;
+            }
+            comparison.hunks.push(comparison.rows.len());
+            for row in 0..old.len().max(new.len()) {
+                let old = old
+                    .clone()
+                    .nth(row)
+                    .map(|number| (number, left_lines[number]));
+                let new = new
+                    .clone()
+                    .nth(row)
+                    .map(|number| (number, right_lines[number]));
+                comparison.rows.push(match (old, new) {
+                    (Some((old, old_text)), Some((new, new_text))) => {
+                        let (left, right) = inline_changes(old_text, new_text);
+                        DiffRow {
+                            left: Some(DiffLine {
+                                number: old,
+                                spans: left,
+                            }),
+                            right: Some(DiffLine {
+                                number: new,
+                                spans: right,
+                            }),
+                        }
+                    }
+                    (old, new) => DiffRow {
+                        left: old.map(|(number, text)| DiffLine::plain(number, text)),
+                        right: new.map(|(number, text)| DiffLine::plain(number, text)),
+                    },
+                });
+            }
+        }
+        comparison
+    }
+
+    /// 统一视图：每处改动先列出删除的行，再列出新增的行
+    pub fn unified(&self) -> Vec<UnifiedLine> {
+        let mut lines = vec![];
+        let mut inserted = vec![];
+        for row in &self.rows {
+            if row.is_equal() {
+                lines.append(&mut inserted);
+                if let Some(line) = &row.right {
+                    lines.push(UnifiedLine {
+                        kind: DiffLineKind::Equal,
+                        line: line.clone(),
+                    });
+                }
+                continue;
+            }
+            if let Some(line) = &row.left {
+                lines.push(UnifiedLine {
+                    kind: DiffLineKind::Delete,
+                    line: line.clone(),
+                });
+            }
+            if let Some(line) = &row.right {
+                inserted.push(UnifiedLine {
+                    kind: DiffLineKind::Insert,
+                    line: line.clone(),
+                });
+            }
+        }
+        lines.append(&mut inserted);
+        lines
+    }
+}
+
+/// 统一视图中每处改动第一行的下标
+pub fn unified_hunks(lines: &[UnifiedLine]) -> Vec<usize> {
+    (0..lines.len())
+        .filter(|index| {
+            lines[*index].kind != DiffLineKind::Equal
+                && (*index == 0 || lines[index - 1].kind == DiffLineKind::Equal)
+        })
+        .collect()
+}
+
+/// row 之后的下一处改动，没有时回到第一处
+pub fn next_hunk(hunks: &[usize], row: Option<usize>) -> Option<usize> {
+    let next = hunks.iter().find(|hunk| row.is_none_or(|row| **hunk > row));
+    next.or(hunks.first()).copied()
+}
+
+/// row 之前的上一处改动，没有时回到最后一处
+pub fn prev_hunk(hunks: &[usize], row: Option<usize>) -> Option<usize> {
+    let prev = hunks
+        .iter()
+        .rev()
+        .find(|hunk| row.is_none_or(|row| **hunk < row));
+    prev.or(hunks.last()).copied()
+}
+
+/// 按字符比较一对修改过的行，连续的相同标记合并成一段
+fn inline_changes(old: &str, new: &str) -> (DiffSpans, DiffSpans) {
+    let diff = TextDiff::from_chars(old, new);
+    let mut left = DiffSpans::new();
+    let mut right = DiffSpans::new();
+    for change in diff.iter_all_changes() {
+        let (side, changed) = match change.tag() {
+            ChangeTag::Equal => {
+                push_span(&mut left, change.value(), false);
+                (&mut right, false)
+            }
+            ChangeTag::Delete => (&mut left, true),
+            ChangeTag::Insert => (&mut right, true),
+        };
+        push_span(side, change.value(), changed);
+    }
+    (left, right)
+}
+
+fn push_span(spans: &mut DiffSpans, text: &str, changed: bool) {
+    match spans.last_mut() {
+        Some((last, last_changed)) if *last_changed == changed => last.push_str(text),
+        _ => spans.push((text.to_string(), changed)),
+    }
+}
+
+#[test]
+fn test_text_comparison() {
+    let comparison = TextComparison::new("a\nlet x = 1;\nc\nd\n", "a\nlet y = 1;\nc\ne\nf\n");
+    assert_eq!(comparison.hunks, vec![1, 3]);
+    let row = &comparison.rows[1];
+    assert_eq!(
+        row.left.as_ref().unwrap().spans,
+        vec![
+            ("let ".to_string(), false),
+            ("x".to_string(), true),
+            (" = 1;".to_string(), false)
+        ]
+    );
+    assert_eq!(row.right.as_ref().unwrap().text(), "let y = 1;");
+    let extra = &comparison.rows[4];
+    assert_eq!(extra.left, None);
+    assert_eq!(extra.right.as_ref().unwrap().number, 4);
+
+    let lines = comparison.unified();
+    assert_eq!(unified_hunks(&lines), vec![1, 4]);
+    let unified: Vec<(DiffLineKind, String)> = lines
+        .into_iter()
+        .map(|line| (line.kind, line.line.text()))
+        .collect();
+    assert_eq!(
+        unified,
+        vec![
+            (DiffLineKind::Equal, "a".to_string()),
+            (DiffLineKind::Delete, "let x = 1;".to_string()),
+            (DiffLineKind::Insert, "let y = 1;".to_string()),
+            (DiffLineKind::Equal, "c".to_string()),
+            (DiffLineKind::Delete, "d".to_string()),
+            (DiffLineKind::Insert, "e".to_string()),
+            (DiffLineKind::Insert, "f".to_string()),
+        ]
+    );
+    assert_eq!(next_hunk(&comparison.hunks, None), Some(1));
+    assert_eq!(next_hunk(&comparison.hunks, Some(1)), Some(3));
+    assert_eq!(next_hunk(&comparison.hunks, Some(3)), Some(1));
+    assert_eq!(prev_hunk(&comparison.hunks, Some(1)), Some(3));
+}
@@ -0,0 +1,248 @@
+use crate::buffer::{ordered, Position, TextBuffer};
+use crate::editorconfig::EditorConfig;
+use crate::fold::Folds;
+use crate::hex::is_binary_file;
+use crate::indent::IndentStyle;
+use crate::undo::{Edit, History};
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// 编辑器中打开的文档
+#[derive(Clone, Debug)]
+pub struct Document {
+    pub path: PathBuf,
+    pub language_id: String,
+    pub buffer: TextBuffer,
+    /// 每次修改自增，用于和语言服务器同步
+    pub version: i32,
+    pub dirty: bool,
+    pub folds: Folds,
+    /// 打开时读取的 .editorconfig 设置
+    pub config: EditorConfig,
+    pub history: History,
+}
+
+impl Document {
+    /// 二进制文件不能作为文本打开，由十六进制视图打开
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        if is_binary_file(path)? {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "binary file",
+            ));
+        }
+        let bytes = std::fs::read(path)?;
+        let language_id = language_id(path);
+        let buffer = TextBuffer::new(&String::from_utf8_lossy(&bytes));
+        Ok(Self {
+            path: path.to_path_buf(),
+            language_id: language_id.to_string(),
+            folds: Folds::new(&buffer),
+            buffer,
+            version: 1,
+            dirty: false,
+            config: EditorConfig::for_path(path),
+            history: History::default(),
+        })
+    }
+
+    /// 保存前按 .editorconfig 去掉行尾空白、处理文件末尾的换行
+    pub fn save(&mut self) -> std::io::Result<()> {
+        let mut text = self.buffer.text();
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        if self.config.trim_trailing_whitespace == Some(true) {
+            let lines: Vec<&str> = self.buffer.lines().map(str::trim_end).collect();
+            text = lines.join(newline);
+        }
+        match self.config.insert_final_newline {
+            Some(true) if !text.is_empty() && !text.ends_with('\n') => text.push_str(newline),
+            Some(false) => text.truncate(text.trim_end_matches(['\r', '\n']).len()),
+            _ => {}
+        }
+        std::fs::write(&self.path, &text)?;
+        if text != self.buffer.text() {
+            self.replace(&text);
+        }
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// 缩进方式，没有 .editorconfig 时使用四个空格
+    pub fn indent(&self) -> IndentStyle {
+        self.config.indent_style.unwrap_or_default()
+    }
+
+    /// 重新读取磁盘上的内容，内容变化时返回 true（未保存的修改会被丢弃）
+    pub fn reload(&mut self) -> std::io::Result<bool> {
+        let bytes = std::fs::read(&self.path)?;
+        let text = String::from_utf8_lossy(&bytes);
+        self.dirty = false;
+        if text == self.buffer.text() {
+            return Ok(false);
+        }
+        self.replace(&text);
+        Ok(true)
+    }
+
+    /// 整体替换内容，替换作为一组修改记录，可以撤销
+    fn replace(&mut self, text: &str) {
+        let old = self.buffer.slice(Position::default(), self.buffer.end());
+        self.buffer = TextBuffer::new(text);
+        self.version += 1;
+        self.folds.refresh(&self.buffer);
+        let new = self.buffer.slice(Position::default(), self.buffer.end());
+        self.history.seal();
+        self.history.record(Edit::Delete {
+            at: Position::default(),
+            text: old,
+        });
+        self.history.record(Edit::Insert {
+            at: Position::default(),
+            text: new,
+        });
+        self.history.seal();
+    }
+
+    pub fn insert(&mut self, pos: Position, text: &str) -> Position {
+        let pos = self.buffer.clamp(pos);
+        let end = self.insert_text(pos, text);
+        self.history.record(Edit::Insert {
+            at: pos,
+            text: self.buffer.slice(pos, end),
+        });
+        end
+    }
+
+    pub fn delete(&mut self, start: Position, end: Position) -> String {
+        let (start, end) = ordered(self.buffer.clamp(start), self.buffer.clamp(end));
+        let text = self.delete_text(start, end);
+        self.history.record(Edit::Delete {
+            at: start,
+            text: text.clone(),
+        });
+        text
+    }
+
+    /// 之后的修改记录为新的一组，一次操作开始前调用
+    pub fn seal_undo(&mut self) {
+        self.history.seal();
+    }
+
+    /// 撤销最近一组修改，返回光标应该在的位置
+    pub fn undo(&mut self) -> Option<Position> {
+        let group = self.history.undo()?;
+        let mut cursor = None;
+        for edit in group.iter().rev() {
+            cursor = Some(self.apply(&edit.inverse()));
+        }
+        cursor
+    }
+
+    /// 重做最近撤销的一组修改，返回光标应该在的位置
+    pub fn redo(&mut self) -> Option<Position> {
+        let group = self.history.redo()?;
+        let mut cursor = None;
+        for edit in &group {
+            cursor = Some(self.apply(edit));
+        }
+        cursor
+    }
+
+    /// 应用撤销记录中的修改，不再记录
+    fn apply(&mut self, edit: &Edit) -> Position {
+        match edit {
+            Edit::Insert { at, text } => self.insert_text(*at, text),
+            Edit::Delete { at, .. } => {
+                self.delete_text(*at, edit.end());
+                *at
+            }
+        }
+    }
+
+    fn insert_text(&mut self, pos: Position, text: &str) -> Position {
+        self.touch();
+        let end = self.buffer.insert(pos, text);
+        // 在行首插入时这一行的折叠也跟着下移
+        let from = if pos.column == 0 {
+            pos.line
+        } else {
+            pos.line + 1
+        };
+        self.folds.shift(from, 0, end.line - pos.line);
+        self.folds.invalidate();
+        end
+    }
+
+    fn delete_text(&mut self, start: Position, end: Position) -> String {
+        self.touch();
+        let text = self.buffer.delete(start, end);
+        self.folds.shift(start.line + 1, end.line - start.line, 0);
+        self.folds.invalidate();
+        text
+    }
+
+    fn touch(&mut self) {
+        self.version += 1;
+        self.dirty = true;
+    }
+}
+
+/// 文档保存到磁盘后发出
+#[derive(Message, Clone, Debug)]
+pub struct DocumentSaved {
+    pub path: PathBuf,
+}
+
+/// 所有已打开的文档
+#[derive(Resource, Default)]
+pub struct Documents {
+    pub documents: Vec<Document>,
+}
+
+impl Documents {
+    /// 打开文档，已经打开过则直接返回
+    pub fn open(&mut self, path: &Path) -> std::io::Result<&mut Document> {
+        let index = match self.documents.iter().position(|doc| doc.path == path) {
+            Some(index) => index,
+            None => {
+                self.documents.push(Document::open(path)?);
+                self.documents.len() - 1
+            }
+        };
+        Ok(&mut self.documents[index])
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Document> {
+        self.documents.iter().find(|doc| doc.path == path)
+    }
+
+    pub fn get_mut(&mut self, path: &Path) -> Option<&mut Document> {
+        self.documents.iter_mut().find(|doc| doc.path == path)
+    }
+
+    pub fn close(&mut self, path: &Path) -> Option<Document> {
+        let index = self.documents.iter().position(|doc| doc.path == path)?;
+        Some(self.documents.remove(index))
+    }
+}
+
+/// 根据扩展名推断 LSP 的 languageId
+pub fn language_id(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    match extension {
+        "rs" => "rust",
+        "toml" => "toml",
+        "md" => "markdown",
+        "json" => "json",
+        "yml" | "yaml" => "yaml",
+        "js" => "javascript",
+        "ts" => "typescript",
+        "py" => "python",
+        "go" => "go",
+        "c" | "h" => "c",
+        "cpp" | "hpp" | "cc" => "cpp",
+        "sh" => "shellscript",
+        "sql" => "sql",
+        _ => "plaintext",
+    }
+}
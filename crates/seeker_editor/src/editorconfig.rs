@@ -0,0 +1,230 @@
+use crate::indent::IndentStyle;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = ".editorconfig";
+
+/// 文件适用的 .editorconfig 设置，没有设置的项为 None
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditorConfig {
+    pub indent_style: Option<IndentStyle>,
+    pub insert_final_newline: Option<bool>,
+    pub trim_trailing_whitespace: Option<bool>,
+}
+
+impl EditorConfig {
+    /// 从文件所在目录向上查找 .editorconfig，直到 root = true 的文件为止
+    pub fn for_path(path: &Path) -> Self {
+        let mut files = vec![];
+        for dir in path.ancestors().skip(1) {
+            let Ok(text) = std::fs::read_to_string(dir.join(FILE_NAME)) else {
+                continue;
+            };
+            let root = parse(&text).0;
+            files.push((dir.to_path_buf(), text));
+            if root {
+                break;
+            }
+        }
+        files.reverse();
+        Self::from_files(&files, path)
+    }
+
+    /// files 按从外到内的顺序，里面的设置覆盖外面的
+    fn from_files(files: &[(PathBuf, String)], path: &Path) -> Self {
+        let mut properties: HashMap<String, String> = HashMap::new();
+        for (dir, text) in files {
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            for (pattern, section) in parse(text).1 {
+                if section_matches(&pattern, &relative) {
+                    properties.extend(section);
+                }
+            }
+        }
+        let get = |key: &str| properties.get(key).map(String::as_str);
+        let bool = |key: &str| match get(key) {
+            Some("true") => Some(true),
+            Some("false") => Some(false),
+            _ => None,
+        };
+        let tab_width = get("tab_width").and_then(|value| value.parse().ok());
+        let size = match get("indent_size") {
+            Some("tab") => tab_width,
+            Some(value) => value.parse().ok().or(tab_width),
+            None => tab_width,
+        };
+        let indent_style = match get("indent_style") {
+            Some("tab") => Some(IndentStyle::Tabs),
+            Some("space") => Some(IndentStyle::Spaces(size.unwrap_or(4))),
+            _ => size.map(IndentStyle::Spaces),
+        };
+        Self {
+            indent_style,
+            insert_final_newline: bool("insert_final_newline"),
+            trim_trailing_whitespace: bool("trim_trailing_whitespace"),
+        }
+    }
+}
+
+type Section = Vec<(String, String)>;
+
+/// 解析文件，返回 (是否 root, 各节的模式和属性)，属性名和值都转成小写
+fn parse(text: &str) -> (bool, Vec<(String, Section)>) {
+    let mut root = false;
+    let mut sections: Vec<(String, Section)> = vec![];
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+        if let Some(pattern) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            sections.push((pattern.to_string(), vec![]));
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim().to_lowercase();
+        match sections.last_mut() {
+            Some((_, section)) => section.push((key, value)),
+            None if key == "root" => root = value == "true",
+            None => {}
+        }
+    }
+    (root, sections)
+}
+
+/// 不含 / 的模式匹配任意目录下的文件名，否则相对于 .editorconfig 所在目录匹配
+fn section_matches(pattern: &str, path: &str) -> bool {
+    let pattern = if pattern.contains('/') {
+        pattern.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{pattern}")
+    };
+    expand_braces(&pattern).iter().any(|pattern| {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let path: Vec<char> = path.chars().collect();
+        glob_match(&pattern, &path)
+    })
+}
+
+/// 展开 {a,b} 形式的备选项
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let Some(open) = chars.iter().position(|ch| *ch == '{') else {
+        return vec![pattern.to_string()];
+    };
+    let mut depth = 0;
+    let mut commas = vec![];
+    let mut close = None;
+    for (i, ch) in chars.iter().enumerate().skip(open) {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    break;
+                }
+            }
+            ',' if depth == 1 => commas.push(i),
+            _ => {}
+        }
+    }
+    let Some(close) = close.filter(|_| !commas.is_empty()) else {
+        return vec![pattern.to_string()];
+    };
+    let prefix: String = chars[..open].iter().collect();
+    let suffix: String = chars[close + 1..].iter().collect();
+    let mut bounds = vec![open];
+    bounds.extend(commas);
+    bounds.push(close);
+    bounds
+        .windows(2)
+        .flat_map(|window| {
+            let option: String = chars[window[0] + 1..window[1]].iter().collect();
+            expand_braces(&format!("{prefix}{option}{suffix}"))
+        })
+        .collect()
+}
+
+/// * 不匹配 /，** 匹配任意字符，支持 ? 和 [a-z] / [!a-z]
+fn glob_match(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            glob_match(rest, path)
+                || (0..path.len()).any(|i| path[i] == '/' && glob_match(rest, &path[i + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=path.len()).any(|i| glob_match(rest, &path[i..])),
+        ['*', rest @ ..] => {
+            for i in 0..=path.len() {
+                if glob_match(rest, &path[i..]) {
+                    return true;
+                }
+                if path.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        ['?', rest @ ..] => {
+            path.first().is_some_and(|ch| *ch != '/') && glob_match(rest, &path[1..])
+        }
+        ['[', rest @ ..] if rest.contains(&']') => {
+            let end = rest.iter().position(|ch| *ch == ']').unwrap_or_default();
+            let (negate, set) = match &rest[..end] {
+                ['!', set @ ..] => (true, set),
+                set => (false, set),
+            };
+            let Some(&ch) = path.first() else {
+                return false;
+            };
+            let mut matched = false;
+            let mut i = 0;
+            while i < set.len() {
+                if set.get(i + 1) == Some(&'-') && i + 2 < set.len() {
+                    matched |= (set[i]..=set[i + 2]).contains(&ch);
+                    i += 3;
+                } else {
+                    matched |= set[i] == ch;
+                    i += 1;
+                }
+            }
+            matched != negate && glob_match(&rest[end + 1..], &path[1..])
+        }
+        ['\\', ch, rest @ ..] | [ch, rest @ ..] => {
+            path.first() == Some(ch) && glob_match(rest, &path[1..])
+        }
+    }
+}
+
+#[test]
+fn test_editorconfig() {
+    let outer = "root = true\n\n[*]\nindent_style = space\nindent_size = 2\ninsert_final_newline = true\n\n[*.{rs,toml}]\nindent_size = 4\n";
+    let inner = "# 子目录\n[Makefile]\nindent_style = tab\n\n[src/**.rs]\ntrim_trailing_whitespace = true\n";
+    let files = vec![
+        (PathBuf::from("/p"), outer.to_string()),
+        (PathBuf::from("/p/a"), inner.to_string()),
+    ];
+    let config = EditorConfig::from_files(&files, Path::new("/p/a/src/x/main.rs"));
+    assert_eq!(config.indent_style, Some(IndentStyle::Spaces(4)));
+    assert_eq!(config.insert_final_newline, Some(true));
+    assert_eq!(config.trim_trailing_whitespace, Some(true));
+    let config = EditorConfig::from_files(&files, Path::new("/p/a/b/Makefile"));
+    assert_eq!(config.indent_style, Some(IndentStyle::Tabs));
+    assert_eq!(config.trim_trailing_whitespace, None);
+    let config = EditorConfig::from_files(&files, Path::new("/p/README.md"));
+    assert_eq!(config.indent_style, Some(IndentStyle::Spaces(2)));
+
+    assert!(section_matches("*.[ch]", "lib/a.h"));
+    assert!(!section_matches("/*.md", "docs/a.md"));
+    assert!(section_matches("{docs,lib}/**/*.md", "docs/a.md"));
+}
@@ -0,0 +1,464 @@
+use crate::buffer::TextBuffer;
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+/// 折叠区域的来源
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoldKind {
+    /// 语言服务器按语法结构给出的区域，例如函数、impl 和代码块
+    Block,
+    Comment,
+    Imports,
+    /// 没有语言服务器时按缩进折叠
+    Indent,
+}
+
+/// 可折叠的区域，折叠后 start 行仍然显示，隐藏 start 之后到 end（包含）的行
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FoldRange {
+    pub start: usize,
+    pub end: usize,
+    pub kind: FoldKind,
+    /// 嵌套层级，最外层为 1
+    pub level: usize,
+}
+
+/// 按缩进计算文档的折叠区域，没有语言服务器时使用
+pub fn folding_ranges(buffer: &TextBuffer) -> Vec<FoldRange> {
+    normalize(indent_ranges(buffer))
+}
+
+/// 按起始行排序，每行最多一个区域（取最大的），按嵌套关系计算层级
+fn normalize(mut ranges: Vec<FoldRange>) -> Vec<FoldRange> {
+    ranges.retain(|range| range.end > range.start);
+    ranges.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+    ranges.dedup_by_key(|range| range.start);
+    let mut ends: Vec<usize> = vec![];
+    for range in ranges.iter_mut() {
+        while ends.last().is_some_and(|end| *end < range.end) {
+            ends.pop();
+        }
+        range.level = ends.len() + 1;
+        ends.push(range.end);
+    }
+    ranges
+}
+
+/// 后面缩进更深的行（空行除外）属于同一个区域
+fn indent_ranges(buffer: &TextBuffer) -> Vec<FoldRange> {
+    let mut ranges = vec![];
+    let mut stack: Vec<(usize, usize)> = vec![];
+    let mut last = 0;
+    let mut close = |stack: &mut Vec<(usize, usize)>, last: usize, indent: usize| {
+        while let Some(&(top_indent, start)) = stack.last() {
+            if top_indent < indent {
+                break;
+            }
+            stack.pop();
+            if last > start {
+                ranges.push(FoldRange {
+                    start,
+                    end: last,
+                    kind: FoldKind::Indent,
+                    level: 0,
+                });
+            }
+        }
+    };
+    for (index, line) in buffer.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let indent = line
+            .chars()
+            .take_while(|ch| ch.is_whitespace())
+            .map(|ch| if ch == '\t' { 4 } else { 1 })
+            .sum();
+        close(&mut stack, last, indent);
+        stack.push((indent, index));
+        last = index;
+    }
+    close(&mut stack, last, 0);
+    ranges
+}
+
+/// 文档的折叠状态。编辑时折叠跟随所在的行移动
+#[derive(Clone, Debug, Default)]
+pub struct Folds {
+    ranges: Vec<FoldRange>,
+    /// 已折叠区域的起始行
+    folded: BTreeSet<usize>,
+    /// 隐藏的行，合并后的闭区间，按起始行排序
+    hidden: Vec<(usize, usize)>,
+    /// 折叠变化时自增，用于判断是否需要重新渲染
+    revision: u32,
+    /// 最后一次编辑的时间，区域还没有重新计算时为 Some
+    edited: Option<Instant>,
+    /// 区域来自语言服务器，编辑后只跟着移动，等待新的结果
+    syntax: bool,
+}
+
+impl Folds {
+    pub fn new(buffer: &TextBuffer) -> Self {
+        let mut folds = Self::default();
+        folds.refresh(buffer);
+        folds
+    }
+
+    pub fn ranges(&self) -> &[FoldRange] {
+        &self.ranges
+    }
+
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    /// 从 line 开始的区域
+    pub fn range_at(&self, line: usize) -> Option<&FoldRange> {
+        let index = self
+            .ranges
+            .binary_search_by_key(&line, |range| range.start)
+            .ok()?;
+        Some(&self.ranges[index])
+    }
+
+    pub fn is_folded(&self, line: usize) -> bool {
+        self.folded.contains(&line)
+    }
+
+    pub fn is_hidden(&self, line: usize) -> bool {
+        self.hidden
+            .iter()
+            .any(|(start, end)| (*start..=*end).contains(&line))
+    }
+
+    /// 折叠包含 line 的最内层未折叠区域
+    pub fn fold(&mut self, line: usize) -> bool {
+        let Some(start) = self
+            .ranges
+            .iter()
+            .rev()
+            .find(|range| {
+                range.start <= line && line <= range.end && !self.folded.contains(&range.start)
+            })
+            .map(|range| range.start)
+        else {
+            return false;
+        };
+        self.folded.insert(start);
+        self.update_hidden();
+        true
+    }
+
+    /// 展开从 line 开始或者包含 line 的最内层折叠
+    pub fn unfold(&mut self, line: usize) -> bool {
+        let Some(start) = self
+            .ranges
+            .iter()
+            .rev()
+            .find(|range| {
+                range.start <= line && line <= range.end && self.folded.contains(&range.start)
+            })
+            .map(|range| range.start)
+        else {
+            return false;
+        };
+        self.folded.remove(&start);
+        self.update_hidden();
+        true
+    }
+
+    /// 切换从 line 开始的区域
+    pub fn toggle(&mut self, line: usize) {
+        if !self.folded.remove(&line) && self.range_at(line).is_some() {
+            self.folded.insert(line);
+        }
+        self.update_hidden();
+    }
+
+    /// 折叠指定层级的所有区域，其他区域保持不变
+    pub fn fold_level(&mut self, level: usize) {
+        let starts = self
+            .ranges
+            .iter()
+            .filter(|range| range.level == level)
+            .map(|range| range.start);
+        self.folded.extend(starts);
+        self.update_hidden();
+    }
+
+    pub fn fold_all(&mut self) {
+        self.folded = self.ranges.iter().map(|range| range.start).collect();
+        self.update_hidden();
+    }
+
+    pub fn unfold_all(&mut self) {
+        self.folded.clear();
+        self.update_hidden();
+    }
+
+    /// 展开隐藏了 line 的折叠，例如光标跳转到被折叠的行
+    pub fn reveal(&mut self, line: usize) {
+        let before = self.folded.len();
+        let ranges = &self.ranges;
+        self.folded.retain(|start| {
+            ranges
+                .iter()
+                .find(|range| range.start == *start)
+                .is_none_or(|range| !(range.start < line && line <= range.end))
+        });
+        if self.folded.len() != before {
+            self.update_hidden();
+        }
+    }
+
+    /// 编辑后移动区域和折叠：from 开始的 removed 行被删除，原位置插入了 added 行。
+    /// 区域只是跟着移动，准确的区域等 refresh 重新计算
+    pub fn shift(&mut self, from: usize, removed: usize, added: usize) {
+        if removed == 0 && added == 0 {
+            return;
+        }
+        let moved = |line: usize| line - removed + added;
+        self.ranges.retain_mut(|range| {
+            if range.start >= from + removed {
+                range.start = moved(range.start);
+            } else if range.start >= from {
+                return false;
+            }
+            if range.end >= from + removed {
+                range.end = moved(range.end);
+            } else if range.end >= from {
+                // 删除的行合并到了 from 的上一行
+                range.end = from - 1;
+            }
+            range.end > range.start
+        });
+        self.folded = self
+            .folded
+            .iter()
+            .filter_map(|start| match *start {
+                start if start < from => Some(start),
+                start if start < from + removed => None,
+                start => Some(start - removed + added),
+            })
+            .collect();
+        let ranges = &self.ranges;
+        self.folded.retain(|start| {
+            ranges
+                .binary_search_by_key(start, |range| range.start)
+                .is_ok()
+        });
+        self.update_hidden();
+    }
+
+    /// 编辑后标记区域需要重新计算，连续输入时不必每次都扫描整个文档
+    pub fn invalidate(&mut self) {
+        self.edited = Some(Instant::now());
+    }
+
+    /// 是否还在等待编辑停下
+    pub fn is_editing(&self) -> bool {
+        self.edited.is_some()
+    }
+
+    /// 最后一次编辑超过 delay 后重新计算区域，还需要等待时返回 true。
+    /// 区域来自语言服务器时保留移动后的区域，由调用者重新请求
+    pub fn refresh_after(&mut self, buffer: &TextBuffer, delay: Duration) -> bool {
+        match self.edited {
+            Some(edited) if edited.elapsed() < delay => true,
+            Some(_) if self.syntax => {
+                self.edited = None;
+                false
+            }
+            Some(_) => {
+                self.refresh(buffer);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// 整个内容变化后按缩进重新计算区域，语言服务器的结果到达后再替换
+    pub fn refresh(&mut self, buffer: &TextBuffer) {
+        self.syntax = false;
+        self.set_ranges(folding_ranges(buffer));
+    }
+
+    /// 使用语言服务器按语法结构给出的区域
+    pub fn set_syntax_ranges(&mut self, ranges: Vec<FoldRange>) {
+        self.syntax = true;
+        self.set_ranges(normalize(ranges));
+    }
+
+    /// 起始行不再是区域的折叠被移除
+    fn set_ranges(&mut self, ranges: Vec<FoldRange>) {
+        self.edited = None;
+        self.ranges = ranges;
+        let ranges = &self.ranges;
+        self.folded.retain(|start| {
+            ranges
+                .binary_search_by_key(start, |range| range.start)
+                .is_ok()
+        });
+        self.update_hidden();
+    }
+
+    fn update_hidden(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+        self.hidden.clear();
+        for start in &self.folded {
+            let Some(range) = self.range_at(*start) else {
+                continue;
+            };
+            let (start, end) = (range.start + 1, range.end);
+            match self.hidden.last_mut() {
+                Some((_, last)) if start <= *last + 1 => *last = (*last).max(end),
+                _ => self.hidden.push((start, end)),
+            }
+        }
+    }
+
+    /// 显示的行数
+    pub fn row_count(&self, line_count: usize) -> usize {
+        line_count
+            - self
+                .hidden
+                .iter()
+                .map(|(start, end)| end - start + 1)
+                .sum::<usize>()
+    }
+
+    /// 行在显示中的序号，隐藏的行返回折叠起始行的序号
+    pub fn display_row(&self, line: usize) -> usize {
+        let mut row = line;
+        for (start, end) in &self.hidden {
+            if *end < line {
+                row -= end - start + 1;
+            } else {
+                if *start <= line {
+                    row -= line - start + 1;
+                }
+                break;
+            }
+        }
+        row
+    }
+
+    /// 显示序号对应的行
+    pub fn buffer_line(&self, row: usize) -> usize {
+        let mut line = row;
+        for (start, end) in &self.hidden {
+            if *start <= line {
+                line += end - start + 1;
+            } else {
+                break;
+            }
+        }
+        line
+    }
+}
+
+#[test]
+fn test_indent_ranges() {
+    let buffer = TextBuffer::new("a:\n  b:\n    c\n\n  d\ne\n");
+    let ranges = folding_ranges(&buffer);
+    let spans: Vec<_> = ranges
+        .iter()
+        .map(|range| (range.start, range.end, range.level))
+        .collect();
+    assert_eq!(spans, vec![(0, 4, 1), (1, 2, 2)]);
+}
+
+#[test]
+fn test_folds() {
+    let buffer = TextBuffer::new("fn a() {\n    1;\n    2;\n}\nfn b() {\n    3;\n    4;\n}\n");
+    let mut folds = Folds::new(&buffer);
+    assert!(folds.fold(1));
+    assert!(folds.is_folded(0));
+    assert!(folds.is_hidden(2) && !folds.is_hidden(3));
+    assert_eq!(folds.display_row(3), 1);
+    assert_eq!(folds.display_row(2), 0);
+    assert_eq!(folds.buffer_line(1), 3);
+    assert_eq!(folds.row_count(buffer.line_count()), 7);
+
+    // 在前面插入一行后折叠跟着移动
+    let mut buffer = buffer;
+    buffer.insert(crate::buffer::Position::new(0, 0), "// x\n");
+    folds.shift(0, 0, 1);
+    assert!(folds.is_folded(1) && folds.is_hidden(3));
+    folds.invalidate();
+    assert!(folds.refresh_after(&buffer, Duration::from_secs(60)));
+    assert!(!folds.refresh_after(&buffer, Duration::ZERO));
+    assert!(folds.is_folded(1) && folds.is_hidden(3));
+
+    // 删除折叠中的行后区域跟着缩短
+    folds.shift(3, 1, 0);
+    assert_eq!(folds.range_at(1).map(|range| range.end), Some(2));
+    assert_eq!(folds.range_at(4).map(|range| range.end), Some(6));
+    // 删除整个折叠后折叠被移除，后面的区域上移
+    folds.shift(1, 3, 0);
+    assert!(!folds.is_folded(1) && !folds.is_hidden(2));
+    assert_eq!(folds.range_at(1).map(|range| range.end), Some(3));
+    folds.refresh(&buffer);
+
+    folds.reveal(2);
+    assert!(!folds.is_folded(1));
+    folds.fold_level(1);
+    assert!(folds.is_folded(1) && folds.is_folded(5));
+    folds.unfold_all();
+    assert_eq!(folds.row_count(buffer.line_count()), buffer.line_count());
+}
+
+#[test]
+fn test_syntax_ranges() {
+    let buffer = TextBuffer::new("// a\n// b\nfn main() {\n    if x {\n        y();\n    }\n}\n");
+    let mut folds = Folds::new(&buffer);
+    assert!(folds
+        .ranges()
+        .iter()
+        .all(|range| range.kind == FoldKind::Indent));
+    let range = |start, end, kind| FoldRange {
+        start,
+        end,
+        kind,
+        level: 0,
+    };
+    folds.set_syntax_ranges(vec![
+        range(2, 5, FoldKind::Block),
+        range(0, 1, FoldKind::Comment),
+        range(3, 4, FoldKind::Block),
+        range(6, 6, FoldKind::Block),
+    ]);
+    let spans: Vec<_> = folds
+        .ranges()
+        .iter()
+        .map(|range| (range.start, range.end, range.kind, range.level))
+        .collect();
+    assert_eq!(
+        spans,
+        vec![
+            (0, 1, FoldKind::Comment, 1),
+            (2, 5, FoldKind::Block, 1),
+            (3, 4, FoldKind::Block, 2),
+        ]
+    );
+
+    // 编辑停下后保留移动后的区域，等语言服务器的新结果
+    assert!(folds.fold(3));
+    folds.shift(0, 0, 1);
+    folds.invalidate();
+    assert!(folds.is_editing());
+    assert!(!folds.refresh_after(&buffer, Duration::ZERO));
+    assert!(!folds.is_editing());
+    assert_eq!(
+        folds.range_at(3).map(|range| range.kind),
+        Some(FoldKind::Block)
+    );
+    assert!(folds.is_folded(4));
+    // 整个内容替换后退回到缩进
+    folds.refresh(&buffer);
+    assert_eq!(
+        folds.range_at(2).map(|range| range.kind),
+        Some(FoldKind::Indent)
+    );
+}
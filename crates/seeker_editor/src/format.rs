@@ -0,0 +1,257 @@
+use crate::buffer::Position;
+use crate::document::Document;
+use similar::{DiffTag, TextDiff};
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// 文件所属 crate 的 edition，和 cargo fmt 一样读取最近的 Cargo.toml，
+/// `edition.workspace = true` 时读取工作区的 Cargo.toml，都没有写时为 cargo 的默认值 2015
+pub fn manifest_edition(path: &Path) -> String {
+    let manifests: Vec<toml::Table> = path
+        .ancestors()
+        .skip(1)
+        .filter_map(|dir| std::fs::read_to_string(dir.join("Cargo.toml")).ok())
+        .filter_map(|text| text.parse().ok())
+        .collect();
+    let edition = |table: Option<&toml::Value>| {
+        table
+            .and_then(|table| table.get("edition"))
+            .and_then(toml::Value::as_str)
+            .map(str::to_string)
+    };
+    let Some(package) = manifests
+        .first()
+        .and_then(|manifest| manifest.get("package"))
+    else {
+        return "2015".to_string();
+    };
+    let inherited = package
+        .get("edition")
+        .and_then(|edition| edition.get("workspace"))
+        .and_then(toml::Value::as_bool)
+        .unwrap_or_default();
+    let found = match inherited {
+        true => manifests.iter().find_map(|manifest| {
+            edition(
+                manifest
+                    .get("workspace")
+                    .and_then(|workspace| workspace.get("package")),
+            )
+        }),
+        false => edition(Some(package)),
+    };
+    found.unwrap_or_else(|| "2015".to_string())
+}
+
+/// 把文本通过标准输入交给格式化工具，返回标准输出。
+/// 在文件所在的目录运行，这样 rustfmt 等工具能找到项目的配置，
+/// 参数中的 {file} 替换为文件路径，{edition} 替换为所属 crate 的 edition
+pub fn run_formatter(
+    command: &str,
+    args: &[String],
+    path: &Path,
+    text: &str,
+) -> std::io::Result<String> {
+    let file = path.to_string_lossy();
+    let edition = if args.iter().any(|arg| arg.contains("{edition}")) {
+        manifest_edition(path)
+    } else {
+        String::new()
+    };
+    let mut child = Command::new(command)
+        .args(
+            args.iter()
+                .map(|arg| arg.replace("{file}", &file).replace("{edition}", &edition)),
+        )
+        .current_dir(path.parent().unwrap_or(Path::new(".")))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // 输出较多时工具会等待读取，写入放到另一个线程
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = text.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(std::io::Error::other(match stderr.trim() {
+            "" => format!("{command} exited with {}", output.status),
+            stderr => stderr.to_string(),
+        }));
+    }
+    String::from_utf8(output.stdout).map_err(std::io::Error::other)
+}
+
+/// 按行比较得到的一处修改：旧文本的 old 行替换为新文本的 new 行
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineEdit {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+/// 相邻的删除和插入合并成一处修改
+pub fn line_edits(old: &str, new: &str) -> Vec<LineEdit> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut edits: Vec<LineEdit> = vec![];
+    for op in diff.ops() {
+        let (tag, old, new) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            continue;
+        }
+        match edits.last_mut() {
+            Some(last) if last.old.end == old.start && last.new.end == new.start => {
+                last.old.end = old.end;
+                last.new.end = new.end;
+            }
+            _ => edits.push(LineEdit { old, new }),
+        }
+    }
+    edits
+}
+
+/// 修改后位置所在的行：修改之后的行跟着移动，被修改的行保持在修改中的相对位置
+fn map_line(edits: &[LineEdit], line: usize) -> usize {
+    let mut delta = 0isize;
+    for edit in edits {
+        if line < edit.old.start {
+            break;
+        }
+        if line < edit.old.end {
+            let offset = (line - edit.old.start).min(edit.new.len().saturating_sub(1));
+            return edit.new.start + offset;
+        }
+        delta += edit.new.len() as isize - edit.old.len() as isize;
+    }
+    line.saturating_add_signed(delta)
+}
+
+/// 只修改和格式化结果不同的行，光标和折叠不会因为整体替换而丢失，全部修改作为一次撤销。
+/// lines 为 Some 时只应用和这些行相交的修改，positions 中的位置跟着修改移动，返回是否有修改
+pub fn apply_formatted(
+    doc: &mut Document,
+    formatted: &str,
+    lines: Option<Range<usize>>,
+    positions: &mut [Position],
+) -> bool {
+    let old = doc.buffer.lines().collect::<Vec<_>>().join("\n");
+    let new: Vec<&str> = formatted
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    let mut edits = line_edits(&old, &new.join("\n"));
+    if let Some(lines) = lines {
+        edits.retain(|edit| {
+            if edit.old.is_empty() {
+                lines.contains(&edit.old.start)
+            } else {
+                edit.old.start < lines.end && edit.old.end > lines.start
+            }
+        });
+    }
+    if edits.is_empty() {
+        return false;
+    }
+    let old_count = doc.buffer.line_count();
+    let new_count = new.len();
+    doc.seal_undo();
+    // 从后往前修改，前面的行号不变
+    for edit in edits.iter().rev() {
+        let start = Position::new(edit.old.start, 0);
+        let end = if edit.old.end < old_count {
+            Position::new(edit.old.end, 0)
+        } else {
+            doc.buffer.end()
+        };
+        let mut text = new[edit.new.clone()].join("\n");
+        if edit.new.end < new_count && !edit.new.is_empty() {
+            text.push('\n');
+        }
+        doc.delete(start, end);
+        doc.insert(start, &text);
+    }
+    doc.seal_undo();
+    for pos in positions.iter_mut() {
+        let line = map_line(&edits, pos.line);
+        *pos = doc.buffer.clamp(Position::new(line, pos.column));
+    }
+    true
+}
+
+#[test]
+fn test_apply_formatted() {
+    assert_eq!(
+        line_edits("a\nb\nc\nd", "a\nB\nc\nd\ne"),
+        vec![
+            LineEdit {
+                old: 1..2,
+                new: 1..2
+            },
+            LineEdit {
+                old: 3..4,
+                new: 3..5
+            },
+        ]
+    );
+    let mut doc = Document {
+        path: "a.rs".into(),
+        language_id: "rust".to_string(),
+        buffer: crate::buffer::TextBuffer::new("fn main(){\nlet x=1;\n}\n\n\n// end\n"),
+        version: 1,
+        dirty: false,
+        folds: Default::default(),
+        config: Default::default(),
+        history: Default::default(),
+    };
+    let formatted = "fn main() {\n    let x = 1;\n}\n\n// end\n";
+    let mut positions = [Position::new(1, 3), Position::new(5, 2)];
+    // 只格式化选中的空行
+    assert!(apply_formatted(
+        &mut doc,
+        formatted,
+        Some(3..5),
+        &mut positions
+    ));
+    assert_eq!(doc.buffer.text(), "fn main(){\nlet x=1;\n}\n\n// end\n");
+    assert_eq!(positions, [Position::new(1, 3), Position::new(4, 2)]);
+    assert!(apply_formatted(&mut doc, formatted, None, &mut positions));
+    assert_eq!(doc.buffer.text(), formatted);
+    assert_eq!(positions, [Position::new(1, 3), Position::new(4, 2)]);
+    assert!(!apply_formatted(&mut doc, formatted, None, &mut positions));
+    // 每次格式化一起撤销
+    assert!(doc.undo().is_some());
+    assert_eq!(doc.buffer.text(), "fn main(){\nlet x=1;\n}\n\n// end\n");
+    assert!(doc.redo().is_some());
+    assert_eq!(doc.buffer.text(), formatted);
+}
+
+#[test]
+fn test_manifest_edition() {
+    let root = std::env::temp_dir().join(format!("seeker_edition_{}", std::process::id()));
+    let write = |path: &str, text: &str| {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    };
+    write(
+        "Cargo.toml",
+        "[workspace]\nmembers = [\"a\", \"b\"]\n\n[workspace.package]\nedition = \"2021\"\n",
+    );
+    write(
+        "a/Cargo.toml",
+        "[package]\nname = \"a\"\nedition.workspace = true\n",
+    );
+    write(
+        "b/Cargo.toml",
+        "[package]\nname = \"b\"\nedition = \"2024\"\n",
+    );
+    write("c/Cargo.toml", "[package]\nname = \"c\"\n");
+    assert_eq!(manifest_edition(&root.join("a/src/lib.rs")), "2021");
+    assert_eq!(manifest_edition(&root.join("b/src/main.rs")), "2024");
+    assert_eq!(manifest_edition(&root.join("c/src/main.rs")), "2015");
+    assert_eq!(manifest_edition(&root.join("build.rs")), "2015");
+    std::fs::remove_dir_all(&root).unwrap();
+}
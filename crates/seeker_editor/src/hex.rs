@@ -0,0 +1,400 @@
+use memmap2::Mmap;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 十六进制转储每行的字节数
+pub const BYTES_PER_ROW: usize = 16;
+/// 搜索时每扫描这么多字节检查一次取消并报告进度
+const SEARCH_CHUNK: usize = 4 << 20;
+
+/// 含有 NUL 或者不是合法 UTF-8 的内容按二进制处理，末尾被截断的字符不算
+pub fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => false,
+        Err(err) => err.error_len().is_some(),
+    }
+}
+
+/// ASCII 列中显示的字符，不可见的字节显示为 .
+pub fn printable(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+/// 十六进制转储的一行：偏移、十六进制和 ASCII 三列，不满一行时补齐空格
+pub fn hex_row(offset: u64, bytes: &[u8]) -> String {
+    let mut hex = String::new();
+    for i in 0..BYTES_PER_ROW {
+        if i == BYTES_PER_ROW / 2 {
+            hex.push(' ');
+        }
+        match bytes.get(i) {
+            Some(byte) => hex.push_str(&format!("{byte:02x} ")),
+            None => hex.push_str("   "),
+        }
+    }
+    let ascii: String = bytes.iter().copied().map(printable).collect();
+    format!("{offset:08x}  {hex} |{ascii}|")
+}
+
+/// 读取文件开头判断是不是二进制文件
+pub fn is_binary_file(path: &Path) -> std::io::Result<bool> {
+    let mut head = Vec::with_capacity(8192);
+    File::open(path)?.take(8192).read_to_end(&mut head)?;
+    Ok(is_binary(&head))
+}
+
+/// 偏移列的宽度，至少 8 位
+pub fn offset_digits(len: u64) -> usize {
+    let bits = 64 - len.saturating_sub(1).leading_zeros() as usize;
+    bits.div_ceil(4).max(8)
+}
+
+/// 一行中第 i 个字节在十六进制列中的起始列
+pub fn hex_column(digits: usize, i: usize) -> usize {
+    digits + 2 + i * 3 + usize::from(i >= BYTES_PER_ROW / 2)
+}
+
+/// 一行中第 i 个字节在 ASCII 列中的列
+pub fn ascii_column(digits: usize, i: usize) -> usize {
+    hex_column(digits, BYTES_PER_ROW) + 2 + i
+}
+
+/// 点击的列对应的字节和是否在 ASCII 列
+pub fn column_byte(digits: usize, column: usize) -> Option<(usize, bool)> {
+    let ascii = ascii_column(digits, 0);
+    if (ascii..ascii + BYTES_PER_ROW).contains(&column) {
+        return Some((column - ascii, true));
+    }
+    (0..BYTES_PER_ROW)
+        .find(|i| (hex_column(digits, *i)..hex_column(digits, *i) + 3).contains(&column))
+        .map(|i| (i, false))
+}
+
+/// 跳转的偏移：0x 开头为十六进制，否则为十进制
+pub fn parse_offset(input: &str) -> Option<u64> {
+    let input = input.trim().replace('_', "");
+    match input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => input.parse().ok(),
+    }
+}
+
+/// 搜索的字节：用引号括起来的文本，或者可以用空格分隔的十六进制
+pub fn parse_pattern(input: &str) -> Option<Vec<u8>> {
+    let input = input.trim();
+    if let Some(text) = input
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+    {
+        return (!text.is_empty()).then(|| text.as_bytes().to_vec());
+    }
+    let digits: Vec<char> = input.chars().filter(|ch| !ch.is_whitespace()).collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(&pair.iter().collect::<String>(), 16).ok())
+        .collect()
+}
+
+/// 内存映射打开的二进制文件，修改记录在映射之外，保存时按偏移原地写回
+pub struct HexFile {
+    pub path: PathBuf,
+    /// 空文件不能映射，后台搜索时和副本共享
+    map: Option<Arc<Mmap>>,
+    edits: BTreeMap<u64, u8>,
+    /// 每次修改前这个偏移的修改记录
+    undo: Vec<(u64, Option<u8>)>,
+}
+
+impl HexFile {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        // 文件在外部被截断时访问映射会出错，和其他编辑器一样不做处理
+        let map = if file.metadata()?.len() == 0 {
+            None
+        } else {
+            Some(Arc::new(unsafe { Mmap::map(&file)? }))
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            map,
+            edits: BTreeMap::new(),
+            undo: vec![],
+        })
+    }
+
+    /// 在后台线程中搜索用的副本，共享映射，不包含撤销记录
+    pub fn snapshot(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            map: self.map.clone(),
+            edits: self.edits.clone(),
+            undo: vec![],
+        }
+    }
+
+    fn data(&self) -> &[u8] {
+        self.map.as_deref().map(|map| &map[..]).unwrap_or_default()
+    }
+
+    pub fn len(&self) -> u64 {
+        self.data().len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.edits.is_empty()
+    }
+
+    pub fn is_edited(&self, offset: u64) -> bool {
+        self.edits.contains_key(&offset)
+    }
+
+    pub fn byte(&self, offset: u64) -> Option<u8> {
+        match self.edits.get(&offset) {
+            Some(byte) => Some(*byte),
+            None => self.data().get(usize::try_from(offset).ok()?).copied(),
+        }
+    }
+
+    /// 从 offset 开始最多 len 个字节，包含修改
+    pub fn bytes(&self, offset: u64, len: usize) -> Vec<u8> {
+        let end = offset.saturating_add(len as u64).min(self.len());
+        (offset..end)
+            .filter_map(|offset| self.byte(offset))
+            .collect()
+    }
+
+    /// 修改一个字节，改回原来的值时去掉修改记录
+    pub fn set(&mut self, offset: u64, value: u8) {
+        let Some(original) = self.data().get(offset as usize).copied() else {
+            return;
+        };
+        let previous = self.edits.get(&offset).copied();
+        if previous.unwrap_or(original) == value {
+            return;
+        }
+        self.undo.push((offset, previous));
+        if value == original {
+            self.edits.remove(&offset);
+        } else {
+            self.edits.insert(offset, value);
+        }
+    }
+
+    /// 撤销最近一次修改，返回修改的偏移
+    pub fn undo(&mut self) -> Option<u64> {
+        let (offset, previous) = self.undo.pop()?;
+        match previous {
+            Some(byte) => self.edits.insert(offset, byte),
+            None => self.edits.remove(&offset),
+        };
+        Some(offset)
+    }
+
+    /// 只写回修改过的字节，文件长度不变。保存后仍然可以撤销之前的修改
+    pub fn save(&mut self) -> std::io::Result<()> {
+        // 映射会反映写入的内容，先记下撤销记录中原来的字节
+        let undo: Vec<(u64, Option<u8>)> = self
+            .undo
+            .iter()
+            .map(|(offset, previous)| {
+                let original = self.data().get(*offset as usize).copied();
+                (*offset, previous.or(original))
+            })
+            .collect();
+        let mut file = std::fs::OpenOptions::new().write(true).open(&self.path)?;
+        let mut edits = self.edits.iter().peekable();
+        while let Some((&start, &byte)) = edits.next() {
+            let mut run = vec![byte];
+            while let Some(&(&offset, &byte)) = edits.peek() {
+                if offset != start + run.len() as u64 {
+                    break;
+                }
+                run.push(byte);
+                edits.next();
+            }
+            file.seek(SeekFrom::Start(start))?;
+            file.write_all(&run)?;
+        }
+        file.flush()?;
+        let mut saved = Self::open(&self.path)?;
+        // 和保存后的内容相同的字节不再是修改
+        saved.undo = undo
+            .into_iter()
+            .map(|(offset, byte)| {
+                let current = saved.data().get(offset as usize).copied();
+                (offset, byte.filter(|byte| Some(*byte) != current))
+            })
+            .collect();
+        *self = saved;
+        Ok(())
+    }
+
+    fn matches_at(&self, offset: u64, pattern: &[u8]) -> bool {
+        pattern
+            .iter()
+            .enumerate()
+            .all(|(i, byte)| self.byte(offset + i as u64) == Some(*byte))
+    }
+
+    /// 从 from 开始向后查找，找不到时从头开始。
+    /// 大文件分段扫描，每段之后用已经扫描的字节数报告进度，取消后返回 None
+    pub fn find(
+        &self,
+        pattern: &[u8],
+        from: u64,
+        cancelled: &AtomicBool,
+        mut progress: impl FnMut(u64),
+    ) -> Option<u64> {
+        let from = from.min(self.len());
+        self.find_in(pattern, from, self.len(), cancelled, &mut |scanned| {
+            progress(scanned)
+        })
+        .or_else(|| {
+            let skipped = self.len() - from;
+            self.find_in(pattern, 0, from, cancelled, &mut |scanned| {
+                progress(skipped + scanned)
+            })
+        })
+    }
+
+    /// 在 [start, end) 中开始的第一个匹配
+    fn find_in(
+        &self,
+        pattern: &[u8],
+        start: u64,
+        end: u64,
+        cancelled: &AtomicBool,
+        progress: &mut dyn FnMut(u64),
+    ) -> Option<u64> {
+        if pattern.is_empty() {
+            return None;
+        }
+        let data = self.data();
+        let len = pattern.len() as u64;
+        // 映射中的匹配可能被修改破坏，修改附近也可能出现新的匹配
+        let mut found = None;
+        let (start_index, end_index) = (start as usize, end as usize);
+        let mut at = start_index;
+        'chunks: while at < end_index {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            // 匹配的开始位置在这一段中，内容可以延伸到下一段
+            let chunk_end = (at + SEARCH_CHUNK).min(end_index);
+            let haystack = &data[at..(chunk_end + pattern.len() - 1).min(data.len())];
+            let mut position = 0;
+            while let Some(i) = haystack.get(position..).and_then(|rest| {
+                rest.windows(pattern.len())
+                    .position(|window| window == pattern)
+            }) {
+                let offset = at + position + i;
+                if offset >= chunk_end {
+                    break;
+                }
+                if self.matches_at(offset as u64, pattern) {
+                    found = Some(offset as u64);
+                    break 'chunks;
+                }
+                position += i + 1;
+            }
+            at = chunk_end;
+            progress((at - start_index) as u64);
+        }
+        let edited = self
+            .edits
+            .range(start..found.unwrap_or(end).saturating_add(len))
+            .flat_map(|(offset, _)| offset.saturating_sub(len - 1)..=*offset)
+            .filter(|offset| (start..end).contains(offset))
+            .find(|offset| self.matches_at(*offset, pattern));
+        match (found, edited) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+#[test]
+fn test_hex_row() {
+    assert!(is_binary(b"\x7fELF\x00"));
+    assert!(!is_binary("中文".as_bytes()));
+    // 截断在多字节字符中间
+    assert!(!is_binary(&"中文".as_bytes()[..4]));
+    assert!(is_binary(b"\xff\xfe a"));
+    assert_eq!(
+        hex_row(0x10, b"Hi!\n"),
+        format!("00000010  48 69 21 0a {}  |Hi!.|", " ".repeat(36))
+    );
+    let row = hex_row(0, &[0; 16]);
+    assert_eq!(&row[hex_column(8, 9)..hex_column(8, 9) + 2], "00");
+    assert_eq!(&row[ascii_column(8, 0)..], "................|");
+    assert_eq!(column_byte(8, hex_column(8, 9) + 1), Some((9, false)));
+    assert_eq!(column_byte(8, ascii_column(8, 3)), Some((3, true)));
+    assert_eq!(offset_digits(0x1_0000_0001), 9);
+    assert_eq!(parse_offset("0x1F"), Some(31));
+    assert_eq!(parse_offset("1_024"), Some(1024));
+    assert_eq!(
+        parse_pattern("de ad BEEF"),
+        Some(vec![0xde, 0xad, 0xbe, 0xef])
+    );
+    assert_eq!(parse_pattern("\"PK\""), Some(b"PK".to_vec()));
+    assert_eq!(parse_pattern("abc"), None);
+}
+
+#[test]
+fn test_hex_file() {
+    let path = std::env::temp_dir().join(format!("seeker_hex_{}.bin", std::process::id()));
+    std::fs::write(&path, b"\x00abcabc\xff").unwrap();
+    let mut file = HexFile::open(&path).unwrap();
+    assert_eq!(file.len(), 8);
+    let running = AtomicBool::new(false);
+    let find = |file: &HexFile, pattern: &[u8], from| file.find(pattern, from, &running, |_| {});
+    assert_eq!(find(&file, b"abc", 2), Some(4));
+    assert_eq!(find(&file, b"abc", 5), Some(1));
+    let mut scanned = vec![];
+    file.find(b"zz", 3, &running, |bytes| scanned.push(bytes));
+    assert_eq!(scanned, vec![5, 8]);
+    assert_eq!(file.find(b"abc", 0, &AtomicBool::new(true), |_| {}), None);
+    file.set(1, b'x');
+    file.set(7, b'a');
+    assert_eq!(find(&file, b"abc", 0), Some(4));
+    assert_eq!(find(&file, b"ca", 0), Some(3));
+    assert_eq!(find(&file, b"ca", 4), Some(6));
+    assert_eq!(find(&file.snapshot(), b"ca", 4), Some(6));
+    assert_eq!(file.bytes(0, 3), b"\x00xb");
+    file.set(1, b'a');
+    assert!(!file.is_edited(1));
+    assert_eq!(file.undo(), Some(1));
+    assert!(file.is_edited(1));
+    file.save().unwrap();
+    assert!(!file.is_dirty());
+    assert_eq!(std::fs::read(&path).unwrap(), b"\x00xbcabca");
+    // 保存后撤销恢复保存前的字节
+    assert_eq!(file.undo(), Some(7));
+    assert_eq!(file.byte(7), Some(0xff));
+    assert!(file.is_dirty());
+    assert_eq!(file.undo(), Some(1));
+    assert_eq!(file.byte(1), Some(b'a'));
+    assert_eq!(file.undo(), None);
+    std::fs::remove_file(&path).unwrap();
+}
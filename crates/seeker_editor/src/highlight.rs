@@ -0,0 +1,242 @@
+/// 高亮的类别
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Plain,
+    Keyword,
+    String,
+    Number,
+    Comment,
+}
+
+/// 一种语言的简单语法：关键字和行注释
+struct Syntax {
+    keywords: &'static [&'static str],
+    comment: &'static str,
+}
+
+const RUST: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while",
+];
+const PYTHON: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "False", "finally", "for", "from", "if", "import", "in", "is", "lambda",
+    "None", "not", "or", "pass", "raise", "return", "True", "try", "while", "with", "yield",
+];
+const JAVASCRIPT: &[&str] = &[
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "else",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "return",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "undefined",
+    "var",
+    "while",
+    "yield",
+];
+const GO: &[&str] = &[
+    "break",
+    "case",
+    "chan",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "else",
+    "false",
+    "for",
+    "func",
+    "go",
+    "if",
+    "import",
+    "interface",
+    "map",
+    "nil",
+    "package",
+    "range",
+    "return",
+    "select",
+    "struct",
+    "switch",
+    "true",
+    "type",
+    "var",
+];
+const C: &[&str] = &[
+    "auto",
+    "break",
+    "case",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "if",
+    "include",
+    "int",
+    "long",
+    "namespace",
+    "new",
+    "nullptr",
+    "private",
+    "public",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "true",
+    "typedef",
+    "union",
+    "unsigned",
+    "using",
+    "void",
+    "while",
+];
+const SHELL: &[&str] = &[
+    "case", "do", "done", "echo", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+    "in", "local", "return", "then", "while",
+];
+const DATA: &[&str] = &["true", "false", "null"];
+
+/// 代码块标注的语言对应的语法
+fn syntax(language: &str) -> Option<Syntax> {
+    let (keywords, comment) = match language {
+        "rust" | "rs" => (RUST, "//"),
+        "python" | "py" => (PYTHON, "#"),
+        "javascript" | "js" | "typescript" | "ts" | "jsx" | "tsx" => (JAVASCRIPT, "//"),
+        "go" => (GO, "//"),
+        "c" | "cpp" | "c++" | "h" | "java" => (C, "//"),
+        "sh" | "bash" | "shell" | "shellscript" | "zsh" => (SHELL, "#"),
+        "toml" | "yaml" | "yml" => (DATA, "#"),
+        "json" => (DATA, ""),
+        _ => return None,
+    };
+    Some(Syntax { keywords, comment })
+}
+
+/// 按语言把一行代码拆成高亮的片段，不认识的语言整行都是 Plain
+pub fn highlight(line: &str, language: &str) -> Vec<(TokenKind, String)> {
+    let Some(syntax) = syntax(language) else {
+        return vec![(TokenKind::Plain, line.to_string())];
+    };
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens: Vec<(TokenKind, String)> = vec![];
+    let mut push = |kind: TokenKind, text: &[char]| match tokens.last_mut() {
+        Some((last, token)) if *last == kind => token.extend(text),
+        _ => tokens.push((kind, text.iter().collect())),
+    };
+    let comment: Vec<char> = syntax.comment.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let start = i;
+        let kind = if !comment.is_empty() && chars[i..].starts_with(&comment) {
+            i = chars.len();
+            TokenKind::Comment
+        } else if matches!(ch, '"' | '\'' | '`') {
+            // Rust 的单引号可能是生命周期
+            let lifetime = ch == '\''
+                && syntax.keywords == RUST
+                && chars.get(i + 2).is_some_and(|next| *next != '\'');
+            i += 1;
+            if lifetime {
+                TokenKind::Plain
+            } else {
+                while i < chars.len() && chars[i] != ch {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(chars.len());
+                TokenKind::String
+            }
+        } else if ch.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '.' | '_')) {
+                i += 1;
+            }
+            TokenKind::Number
+        } else if ch.is_alphabetic() || ch == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            if syntax.keywords.contains(&word.as_str()) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Plain
+            }
+        } else {
+            i += 1;
+            TokenKind::Plain
+        };
+        push(kind, &chars[start..i]);
+    }
+    tokens
+}
+
+#[test]
+fn test_highlight() {
+    assert_eq!(
+        highlight("let s = \"a\\\"b\"; // x", "rust"),
+        vec![
+            (TokenKind::Keyword, "let".to_string()),
+            (TokenKind::Plain, " s = ".to_string()),
+            (TokenKind::String, "\"a\\\"b\"".to_string()),
+            (TokenKind::Plain, "; ".to_string()),
+            (TokenKind::Comment, "// x".to_string()),
+        ]
+    );
+    assert_eq!(
+        highlight("fn f<'a>(x: &'a u8) -> 1", "rs")[1],
+        (TokenKind::Plain, " f<'a>(x: &'a u8) -> ".to_string())
+    );
+    assert_eq!(
+        highlight("x = 1 # c", "python")[1],
+        (TokenKind::Number, "1".to_string())
+    );
+    assert_eq!(
+        highlight("anything", "unknown"),
+        vec![(TokenKind::Plain, "anything".to_string())]
+    );
+}
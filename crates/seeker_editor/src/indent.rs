@@ -0,0 +1,120 @@
+use crate::buffer::{Position, TextBuffer};
+
+/// 缩进方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndentStyle {
+    Spaces(usize),
+    Tabs,
+}
+
+impl Default for IndentStyle {
+    fn default() -> Self {
+        Self::Spaces(4)
+    }
+}
+
+impl IndentStyle {
+    /// 一级缩进的文本
+    pub fn unit(&self) -> String {
+        match self {
+            Self::Spaces(size) => " ".repeat(*size),
+            Self::Tabs => "\t".to_string(),
+        }
+    }
+}
+
+/// 行首的空白
+pub fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// 块以冒号开头的语言
+fn opens_with_colon(language_id: &str) -> bool {
+    matches!(language_id, "python" | "yaml")
+}
+
+/// 在 pos 处换行时插入的文本，返回 (光标前的文本, 光标后的文本)。
+/// 光标在一对括号中间时括号各占一行，光标停在中间缩进后的空行上
+pub fn newline(
+    buffer: &TextBuffer,
+    pos: Position,
+    language_id: &str,
+    style: IndentStyle,
+) -> (String, String) {
+    let line = buffer.line(pos.line);
+    let chars: Vec<char> = line.chars().collect();
+    let column = pos.column.min(chars.len());
+    let base = leading_whitespace(line);
+    // 光标在行首的空白中时不要比光标更深
+    let base: String = base.chars().take(column).collect();
+    let before: String = chars[..column].iter().collect();
+    let before = before.trim_end();
+    let after = chars[column..].iter().find(|ch| !ch.is_whitespace());
+    let opens = match before.chars().last() {
+        Some(open @ ('{' | '[' | '(')) => Some(open),
+        Some(':') if opens_with_colon(language_id) => Some(':'),
+        _ => None,
+    };
+    let Some(open) = opens else {
+        return (format!("\n{base}"), String::new());
+    };
+    let inner = format!("\n{base}{}", style.unit());
+    if after.is_some_and(|close| crate::bracket::closing(open) == Some(*close)) {
+        (inner, format!("\n{base}"))
+    } else {
+        (inner, String::new())
+    }
+}
+
+/// 输入右括号时如果这一行只有空白，返回对齐到左括号所在行的缩进
+pub fn outdent_for_close(buffer: &TextBuffer, pos: Position, close: char) -> Option<String> {
+    let line = buffer.line(pos.line);
+    if !line.trim().is_empty() || pos.column != line.chars().count() {
+        return None;
+    }
+    let open = crate::bracket::matching_open(buffer, Position::new(pos.line, 0), close)?;
+    let indent = leading_whitespace(buffer.line(open.line)).to_string();
+    (indent != line).then_some(indent)
+}
+
+/// 去掉一级缩进后行首要删除的字符数
+pub fn outdent_width(line: &str, style: IndentStyle) -> usize {
+    let mut chars = line.chars();
+    match chars.next() {
+        Some('\t') => 1,
+        Some(' ') => {
+            let size = match style {
+                IndentStyle::Spaces(size) => size,
+                IndentStyle::Tabs => 4,
+            };
+            1 + chars.take(size - 1).take_while(|ch| *ch == ' ').count()
+        }
+        _ => 0,
+    }
+}
+
+#[test]
+fn test_newline() {
+    let style = IndentStyle::Spaces(4);
+    let buffer = TextBuffer::new("    fn main() {}\n    let a = 1;\ndef f():");
+    assert_eq!(
+        newline(&buffer, Position::new(0, 15), "rust", style),
+        ("\n        ".to_string(), "\n    ".to_string())
+    );
+    assert_eq!(
+        newline(&buffer, Position::new(1, 14), "rust", style),
+        ("\n    ".to_string(), String::new())
+    );
+    assert_eq!(
+        newline(&buffer, Position::new(2, 8), "python", IndentStyle::Tabs),
+        ("\n\t".to_string(), String::new())
+    );
+    assert_eq!(outdent_width("  \tx", style), 2);
+    assert_eq!(outdent_width("      x", style), 4);
+
+    let buffer = TextBuffer::new("    if x {\n        ");
+    assert_eq!(
+        outdent_for_close(&buffer, Position::new(1, 8), '}'),
+        Some("    ".to_string())
+    );
+}
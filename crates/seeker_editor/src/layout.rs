@@ -0,0 +1,135 @@
+use crate::buffer::Position;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 分屏方向
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitDirection {
+    /// 左右排列
+    Horizontal,
+    /// 上下排列
+    Vertical,
+}
+
+/// 一个窗格打开的标签
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaneState {
+    pub tabs: Vec<PathBuf>,
+    pub active: Option<PathBuf>,
+    #[serde(default)]
+    pub focused: bool,
+    /// 各标签的光标
+    #[serde(default)]
+    pub cursors: HashMap<PathBuf, Position>,
+    /// 当前标签的滚动位置
+    #[serde(default)]
+    pub scroll: (f32, f32),
+}
+
+/// 编辑区的分屏布局，每次分屏把一个窗格分成两部分
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LayoutNode {
+    Pane(PaneState),
+    Split {
+        direction: SplitDirection,
+        /// first 占的比例
+        ratio: f32,
+        first: Box<LayoutNode>,
+        second: Box<LayoutNode>,
+    },
+}
+
+impl Default for LayoutNode {
+    fn default() -> Self {
+        Self::Pane(PaneState::default())
+    }
+}
+
+impl LayoutNode {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// 只保留 keep 返回 true 的标签（例如文件仍然存在），没有标签的窗格被移除
+    pub fn retain_tabs(self, keep: &mut impl FnMut(&Path) -> bool) -> Option<Self> {
+        match self {
+            Self::Pane(mut pane) => {
+                pane.tabs.retain(|tab| keep(tab));
+                pane.cursors.retain(|tab, _| pane.tabs.contains(tab));
+                if pane
+                    .active
+                    .as_ref()
+                    .is_none_or(|active| !pane.tabs.contains(active))
+                {
+                    pane.active = pane.tabs.first().cloned();
+                    pane.scroll = (0., 0.);
+                }
+                (!pane.tabs.is_empty()).then_some(Self::Pane(pane))
+            }
+            Self::Split {
+                direction,
+                ratio,
+                first,
+                second,
+            } => match (first.retain_tabs(keep), second.retain_tabs(keep)) {
+                (Some(first), Some(second)) => Some(Self::Split {
+                    direction,
+                    ratio: ratio.clamp(0.1, 0.9),
+                    first: Box::new(first),
+                    second: Box::new(second),
+                }),
+                (Some(node), None) | (None, Some(node)) => Some(node),
+                (None, None) => None,
+            },
+        }
+    }
+}
+
+#[test]
+fn test_layout() {
+    let pane = |tabs: &[&str], focused: bool| {
+        LayoutNode::Pane(PaneState {
+            tabs: tabs.iter().map(PathBuf::from).collect(),
+            active: tabs.last().map(PathBuf::from),
+            focused,
+            cursors: tabs
+                .iter()
+                .map(|tab| (PathBuf::from(tab), Position::new(1, 2)))
+                .collect(),
+            scroll: (0., 20.),
+        })
+    };
+    let layout = LayoutNode::Split {
+        direction: SplitDirection::Horizontal,
+        ratio: 0.3,
+        first: Box::new(pane(&["a.rs", "b.rs"], false)),
+        second: Box::new(LayoutNode::Split {
+            direction: SplitDirection::Vertical,
+            ratio: 0.5,
+            first: Box::new(pane(&["b.rs"], true)),
+            second: Box::new(pane(&["c.rs"], false)),
+        }),
+    };
+    assert_eq!(LayoutNode::from_json(&layout.to_json()).unwrap(), layout);
+
+    // b.rs 和 c.rs 被删除后右边的窗格都消失，左边的窗格取代分屏
+    let retained = layout
+        .retain_tabs(&mut |path: &Path| path != Path::new("c.rs") && path != Path::new("b.rs"));
+    assert_eq!(
+        retained,
+        Some(LayoutNode::Pane(PaneState {
+            tabs: vec![PathBuf::from("a.rs")],
+            active: Some(PathBuf::from("a.rs")),
+            focused: false,
+            cursors: HashMap::from([(PathBuf::from("a.rs"), Position::new(1, 2))]),
+            scroll: (0., 0.),
+        }))
+    );
+}
@@ -0,0 +1,18 @@
+pub mod bracket;
+pub mod buffer;
+pub mod completion;
+pub mod diagnostic;
+pub mod diff;
+pub mod document;
+pub mod editorconfig;
+pub mod fold;
+pub mod format;
+pub mod hex;
+pub mod highlight;
+pub mod indent;
+pub mod layout;
+pub mod markdown;
+pub mod snippet;
+pub mod undo;
+pub mod width;
+pub mod wrap;
@@ -0,0 +1,586 @@
+/// 行内文本的一段，样式相同的文本合并在一起
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub strike: bool,
+    pub link: Option<String>,
+}
+
+impl Span {
+    fn same_style(&self, other: &Span) -> bool {
+        self.bold == other.bold
+            && self.italic == other.italic
+            && self.code == other.code
+            && self.strike == other.strike
+            && self.link == other.link
+    }
+}
+
+/// 表格列的对齐方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockKind {
+    Heading {
+        level: usize,
+        spans: Vec<Span>,
+    },
+    Paragraph(Vec<Span>),
+    Code {
+        language: String,
+        text: String,
+    },
+    Quote(Vec<Span>),
+    ListItem {
+        depth: usize,
+        marker: String,
+        /// 任务列表的勾选状态
+        checked: Option<bool>,
+        spans: Vec<Span>,
+    },
+    Table {
+        aligns: Vec<Align>,
+        header: Vec<Vec<Span>>,
+        rows: Vec<Vec<Vec<Span>>>,
+    },
+    Rule,
+    /// 单独占一行的图片
+    Image {
+        alt: String,
+        src: String,
+    },
+}
+
+/// 文档中的一个块和它在源文件中的起始行
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub line: usize,
+    pub kind: BlockKind,
+}
+
+/// 解析 CommonMark 的常用语法，不支持的语法按段落处理
+pub fn parse(text: &str) -> Vec<Block> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut blocks = vec![];
+    let mut i = 0;
+    while i < lines.len() {
+        let start = i;
+        let trimmed = lines[i].trim_start();
+        if trimmed.is_empty() {
+            i += 1;
+            continue;
+        }
+        let kind = if let Some((fence, language)) = fence(trimmed) {
+            let mut code = vec![];
+            i += 1;
+            while i < lines.len() && !closes_fence(lines[i], &fence) {
+                code.push(lines[i]);
+                i += 1;
+            }
+            i += 1;
+            BlockKind::Code {
+                language,
+                text: code.join("\n"),
+            }
+        } else if let Some((level, title)) = heading(trimmed) {
+            i += 1;
+            BlockKind::Heading {
+                level,
+                spans: parse_inline(title),
+            }
+        } else if is_rule(trimmed) {
+            i += 1;
+            BlockKind::Rule
+        } else if trimmed.starts_with('>') {
+            let mut quote = vec![];
+            while i < lines.len() {
+                let Some(line) = lines[i].trim_start().strip_prefix('>') else {
+                    break;
+                };
+                quote.push(line.trim());
+                i += 1;
+            }
+            BlockKind::Quote(parse_inline(&quote.join(" ")))
+        } else if let Some((depth, marker, checked, item)) = list_item(lines[i]) {
+            let mut text = vec![item];
+            i += 1;
+            while i < lines.len() && continues_paragraph(lines[i]) {
+                text.push(lines[i].trim());
+                i += 1;
+            }
+            BlockKind::ListItem {
+                depth,
+                marker,
+                checked,
+                spans: parse_inline(&text.join(" ")),
+            }
+        } else if let Some(aligns) = lines
+            .get(i + 1)
+            .filter(|_| trimmed.contains('|'))
+            .and_then(|line| table_aligns(line))
+            .filter(|aligns| aligns.len() == split_row(trimmed).len())
+        {
+            let header = table_row(trimmed);
+            i += 2;
+            let mut rows = vec![];
+            while i < lines.len() && lines[i].contains('|') && !lines[i].trim().is_empty() {
+                rows.push(table_row(lines[i]));
+                i += 1;
+            }
+            BlockKind::Table {
+                aligns,
+                header,
+                rows,
+            }
+        } else {
+            let mut text = vec![trimmed.trim_end()];
+            i += 1;
+            let mut level = None;
+            while i < lines.len() {
+                level = setext_level(lines[i]);
+                if level.is_some() {
+                    i += 1;
+                    break;
+                }
+                if !continues_paragraph(lines[i]) {
+                    break;
+                }
+                text.push(lines[i].trim());
+                i += 1;
+            }
+            match (level, image(&text.join(" "))) {
+                (Some(level), _) => BlockKind::Heading {
+                    level,
+                    spans: parse_inline(&text.join(" ")),
+                },
+                (None, Some((alt, src))) => BlockKind::Image { alt, src },
+                (None, None) => BlockKind::Paragraph(parse_inline(&text.join(" "))),
+            }
+        };
+        blocks.push(Block { line: start, kind });
+    }
+    blocks
+}
+
+/// 代码块的开始，返回 (围栏, 语言)
+fn fence(line: &str) -> Option<(String, String)> {
+    let ch = line.chars().next().filter(|ch| matches!(ch, '`' | '~'))?;
+    let len = line.chars().take_while(|c| *c == ch).count();
+    if len < 3 {
+        return None;
+    }
+    let info = line[len..].trim();
+    if ch == '`' && info.contains('`') {
+        return None;
+    }
+    let language = info.split_whitespace().next().unwrap_or_default();
+    Some((line[..len].to_string(), language.to_lowercase()))
+}
+
+fn closes_fence(line: &str, fence: &str) -> bool {
+    let line = line.trim();
+    let ch = fence.chars().next().unwrap_or('`');
+    line.len() >= fence.len() && line.chars().all(|c| c == ch)
+}
+
+/// ATX 标题，返回 (级别, 标题)
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|ch| *ch == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    // 去掉结尾的 #
+    let title = rest.trim();
+    let title = match title.trim_end_matches('#') {
+        stripped if stripped.is_empty() || stripped.ends_with(' ') => stripped.trim_end(),
+        _ => title,
+    };
+    Some((level, title))
+}
+
+/// 分隔线：三个以上相同的 - * _，中间可以有空格
+fn is_rule(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().filter(|ch| !ch.is_whitespace()).collect();
+    chars.len() >= 3
+        && matches!(chars[0], '-' | '*' | '_')
+        && chars.iter().all(|ch| *ch == chars[0])
+}
+
+/// 段落下面的 === 或 --- 把段落变成标题
+fn setext_level(line: &str) -> Option<usize> {
+    let line = line.trim();
+    if line.is_empty() {
+        None
+    } else if line.chars().all(|ch| ch == '=') {
+        Some(1)
+    } else if line.chars().all(|ch| ch == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// 列表项，返回 (层级, 标记, 勾选状态, 内容)
+fn list_item(line: &str) -> Option<(usize, String, Option<bool>, &str)> {
+    let indent: usize = line
+        .chars()
+        .take_while(|ch| ch.is_whitespace())
+        .map(|ch| if ch == '\t' { 4 } else { 1 })
+        .sum();
+    let trimmed = line.trim_start();
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    let (marker, rest) = if matches!(trimmed.chars().next(), Some('-' | '*' | '+')) {
+        ("•".to_string(), &trimmed[1..])
+    } else if (1..=9).contains(&digits) && trimmed[digits..].starts_with(['.', ')']) {
+        (trimmed[..=digits].to_string(), &trimmed[digits + 1..])
+    } else {
+        return None;
+    };
+    if !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let (checked, rest) = if let Some(rest) = rest.strip_prefix("[ ] ") {
+        (Some(false), rest)
+    } else if let Some(rest) = rest
+        .strip_prefix("[x] ")
+        .or_else(|| rest.strip_prefix("[X] "))
+    {
+        (Some(true), rest)
+    } else {
+        (None, rest)
+    };
+    Some((indent / 2, marker, checked, rest))
+}
+
+/// 非空行并且不是其他块的开始
+fn continues_paragraph(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty()
+        && fence(trimmed).is_none()
+        && heading(trimmed).is_none()
+        && !is_rule(trimmed)
+        && !trimmed.starts_with('>')
+        && list_item(line).is_none()
+}
+
+/// 表格的分隔行，返回每列的对齐方式
+fn table_aligns(line: &str) -> Option<Vec<Align>> {
+    let cells = split_row(line);
+    if cells.is_empty() {
+        return None;
+    }
+    cells
+        .iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|ch| ch == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Align::Center,
+                (false, true) => Align::Right,
+                _ => Align::Left,
+            })
+        })
+        .collect()
+}
+
+fn table_row(line: &str) -> Vec<Vec<Span>> {
+    split_row(line)
+        .iter()
+        .map(|cell| parse_inline(cell))
+        .collect()
+}
+
+/// 按没有转义的 | 拆分单元格，去掉两端的 |
+fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => line,
+    };
+    let mut cells = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some('|') => cells.last_mut().unwrap().push('|'),
+                Some(next) => cells.last_mut().unwrap().extend(['\\', next]),
+                None => cells.last_mut().unwrap().push('\\'),
+            },
+            '|' => cells.push(String::new()),
+            _ => cells.last_mut().unwrap().push(ch),
+        }
+    }
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+/// 整段只有一张图片时返回 (替代文本, 地址)
+fn image(text: &str) -> Option<(String, String)> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.first() != Some(&'!') {
+        return None;
+    }
+    let (alt, src, end) = link(&chars, 1)?;
+    (end == chars.len()).then(|| (alt.iter().collect(), src))
+}
+
+/// 从 [ 开始解析 [文本](地址)，返回 (文本, 地址, 结束位置)
+fn link(chars: &[char], start: usize) -> Option<(&[char], String, usize)> {
+    let close = matching(chars, start, '[', ']')?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let end = matching(chars, close + 1, '(', ')')?;
+    let target: String = chars[close + 2..end].iter().collect();
+    // 忽略标题
+    let target = target.trim();
+    let url = match target.strip_prefix('<') {
+        Some(rest) => rest.split('>').next().unwrap_or_default(),
+        None => target.split_whitespace().next().unwrap_or_default(),
+    };
+    Some((&chars[start + 1..close], url.to_string(), end + 1))
+}
+
+/// 配对的右括号位置
+fn matching(chars: &[char], start: usize, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            ch if ch == open => depth += 1,
+            ch if ch == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// 解析行内的强调、代码、删除线和链接
+pub fn parse_inline(text: &str) -> Vec<Span> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut inline = Inline::default();
+    inline.parse(&chars);
+    inline.spans
+}
+
+#[derive(Default)]
+struct Inline {
+    spans: Vec<Span>,
+    /// 当前的样式，text 不使用
+    style: Span,
+}
+
+impl Inline {
+    fn push(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if last.same_style(&self.style) => last.text.push_str(text),
+            _ => self.spans.push(Span {
+                text: text.to_string(),
+                ..self.style.clone()
+            }),
+        }
+    }
+
+    fn parse(&mut self, chars: &[char]) {
+        let mut i = 0;
+        while i < chars.len() {
+            let rest = &chars[i..];
+            i += match rest {
+                ['\\', ch, ..] if ch.is_ascii_punctuation() => {
+                    self.push(&ch.to_string());
+                    2
+                }
+                ['`', ..] => self.code(rest),
+                ['!', '[', ..] => match link(chars, i + 1) {
+                    Some((alt, src, end)) => {
+                        let link = self.style.link.replace(src);
+                        self.push(&alt.iter().collect::<String>());
+                        self.style.link = link;
+                        end - i
+                    }
+                    None => {
+                        self.push("!");
+                        1
+                    }
+                },
+                ['[', ..] => match link(chars, i) {
+                    Some((label, url, end)) => {
+                        let link = self.style.link.replace(url);
+                        self.parse(label);
+                        self.style.link = link;
+                        end - i
+                    }
+                    None => {
+                        self.push("[");
+                        1
+                    }
+                },
+                ['<', ..] => self.autolink(rest),
+                ['~', '~', ..] => self.delimiter(chars, i, 2, |style| &mut style.strike),
+                ['*', '*', ..] | ['_', '_', ..] => {
+                    self.delimiter(chars, i, 2, |style| &mut style.bold)
+                }
+                ['*', ..] | ['_', ..] => self.delimiter(chars, i, 1, |style| &mut style.italic),
+                [ch, ..] => {
+                    self.push(&ch.to_string());
+                    1
+                }
+                [] => 1,
+            };
+        }
+    }
+
+    /// 反引号包围的代码，返回消耗的字符数
+    fn code(&mut self, rest: &[char]) -> usize {
+        let len = rest.iter().take_while(|ch| **ch == '`').count();
+        let mut j = len;
+        while j < rest.len() {
+            let run = rest[j..].iter().take_while(|ch| **ch == '`').count();
+            if run == len {
+                let code: String = rest[len..j].iter().collect();
+                let code = match code.strip_prefix(' ').and_then(|c| c.strip_suffix(' ')) {
+                    Some(stripped) if !stripped.trim().is_empty() => stripped.to_string(),
+                    _ => code,
+                };
+                self.style.code = true;
+                self.push(&code);
+                self.style.code = false;
+                return j + len;
+            }
+            j += run.max(1);
+        }
+        self.push(&"`".repeat(len));
+        len
+    }
+
+    /// <https://...> 形式的链接
+    fn autolink(&mut self, rest: &[char]) -> usize {
+        let url: String = rest[1..].iter().take_while(|ch| **ch != '>').collect();
+        let closed = rest.get(url.chars().count() + 1) == Some(&'>');
+        let is_url = ["http://", "https://", "mailto:"]
+            .iter()
+            .any(|scheme| url.starts_with(scheme));
+        if !closed || !is_url || url.contains(char::is_whitespace) {
+            self.push("<");
+            return 1;
+        }
+        let link = self.style.link.replace(url.clone());
+        self.push(&url);
+        self.style.link = link;
+        url.chars().count() + 2
+    }
+
+    /// 强调的开始或结束。后面还有同样的分隔符时才开始，下划线不在单词中间开始
+    fn delimiter(
+        &mut self,
+        chars: &[char],
+        i: usize,
+        len: usize,
+        flag: fn(&mut Span) -> &mut bool,
+    ) -> usize {
+        let delimiter = &chars[i..i + len];
+        let active = *flag(&mut self.style);
+        let opens = || {
+            let next = chars.get(i + len).is_some_and(|ch| !ch.is_whitespace());
+            let intraword = delimiter[0] == '_'
+                && i.checked_sub(1)
+                    .is_some_and(|prev| chars[prev].is_alphanumeric());
+            let closed = chars[i + len..]
+                .windows(len)
+                .any(|window| window == delimiter);
+            next && !intraword && closed
+        };
+        let prev_space = i == 0 || chars[i - 1].is_whitespace();
+        if (active && !prev_space) || (!active && opens()) {
+            *flag(&mut self.style) = !active;
+        } else {
+            self.push(&delimiter.iter().collect::<String>());
+        }
+        len
+    }
+}
+
+/// 把块的文本连起来，用于测试和复制
+pub fn plain_text(spans: &[Span]) -> String {
+    spans.iter().map(|span| span.text.as_str()).collect()
+}
+
+#[test]
+fn test_parse_markdown() {
+    let text = "# Title #\n\nSome **bold** and *it* with `a*b` and [link](http://x.io \"t\").\n\n- [x] done\n  - nested\n1. first\n\n```rust\nfn main() {}\n```\n\n| a | b |\n|:-|--:|\n| 1 | 2 |\n\n![logo](img/logo.png)\n\nSetext\n---\n> quote\n> more\n***";
+    let blocks = parse(text);
+    let kinds: Vec<&BlockKind> = blocks.iter().map(|block| &block.kind).collect();
+    assert_eq!(blocks.len(), 11);
+    assert!(
+        matches!(kinds[0], BlockKind::Heading { level: 1, spans } if plain_text(spans) == "Title")
+    );
+    let BlockKind::Paragraph(spans) = kinds[1] else {
+        panic!("{:?}", kinds[1]);
+    };
+    assert_eq!(blocks[1].line, 2);
+    assert!(spans[1].bold && spans[1].text == "bold");
+    assert!(spans[3].italic && spans[3].text == "it");
+    assert!(spans[5].code && spans[5].text == "a*b");
+    assert_eq!(spans[7].link.as_deref(), Some("http://x.io"));
+    assert!(matches!(
+        kinds[2],
+        BlockKind::ListItem {
+            depth: 0,
+            checked: Some(true),
+            ..
+        }
+    ));
+    assert!(matches!(
+        kinds[3],
+        BlockKind::ListItem {
+            depth: 1,
+            checked: None,
+            ..
+        }
+    ));
+    assert!(matches!(kinds[4], BlockKind::ListItem { marker, .. } if marker == "1."));
+    assert_eq!(
+        kinds[5],
+        &BlockKind::Code {
+            language: "rust".to_string(),
+            text: "fn main() {}".to_string()
+        }
+    );
+    let BlockKind::Table { aligns, rows, .. } = kinds[6] else {
+        panic!("{:?}", kinds[6]);
+    };
+    assert_eq!(aligns, &[Align::Left, Align::Right]);
+    assert_eq!(plain_text(&rows[0][1]), "2");
+    assert!(matches!(kinds[7], BlockKind::Image { src, .. } if src == "img/logo.png"));
+    assert!(matches!(kinds[8], BlockKind::Heading { level: 2, .. }));
+    assert!(matches!(kinds[9], BlockKind::Quote(spans) if plain_text(spans) == "quote more"));
+    assert_eq!(kinds[10], &BlockKind::Rule);
+
+    assert_eq!(
+        plain_text(&parse_inline("a * b snake_case")),
+        "a * b snake_case"
+    );
+}
@@ -0,0 +1,467 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+
+/// 片段变量的值，例如 TM_FILENAME，没有值的变量使用默认值
+pub type SnippetVariables = HashMap<String, String>;
+
+/// 片段中的跳转点，start/end 为展开后文本的字符偏移
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TabStop {
+    pub index: u32,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 展开后的代码片段（LSP snippet 语法）
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Snippet {
+    pub text: String,
+    /// 按跳转顺序排列，$0 在最后，序号相同的跳转点互为镜像
+    pub tab_stops: Vec<TabStop>,
+    /// `${1|a,b|}` 形式的跳转点可以选择的值
+    pub choices: HashMap<u32, Vec<String>>,
+}
+
+impl Snippet {
+    /// 解析 `$1`、`${1:默认值}`、`${1|a,b|}`、`$0` 和变量，变量使用默认值
+    pub fn parse(source: &str) -> Self {
+        Self::parse_with(source, &SnippetVariables::new())
+    }
+
+    /// 和 parse 相同，有值的变量展开为变量的值
+    pub fn parse_with(source: &str, variables: &SnippetVariables) -> Self {
+        let mut parser = Parser {
+            chars: source.chars().peekable(),
+            snippet: Snippet::default(),
+            len: 0,
+            variables,
+        };
+        parser.parse(false);
+        let mut snippet = parser.snippet;
+        snippet
+            .tab_stops
+            .sort_by_key(|stop| (stop.index == 0, stop.index));
+        snippet
+    }
+
+    /// 第一个跳转点，没有跳转点时为 None
+    pub fn first_stop(&self) -> Option<&TabStop> {
+        self.tab_stops.first()
+    }
+
+    /// 多行片段的后续行加上插入处的缩进，Tab 换成文档的缩进单位
+    pub fn indented(&self, indent: &str, unit: &str) -> Self {
+        let mut text = String::new();
+        // 原来每个字符偏移在新文本中的偏移
+        let mut offsets = vec![];
+        let mut len = 0;
+        for ch in self.text.chars() {
+            offsets.push(len);
+            let start = text.len();
+            match ch {
+                '\n' => {
+                    text.push('\n');
+                    text.push_str(indent);
+                }
+                '\t' => text.push_str(unit),
+                _ => text.push(ch),
+            }
+            len += text[start..].chars().count();
+        }
+        offsets.push(len);
+        let tab_stops = self
+            .tab_stops
+            .iter()
+            .map(|stop| TabStop {
+                start: offsets[stop.start],
+                end: offsets[stop.end],
+                ..*stop
+            })
+            .collect();
+        Self {
+            text,
+            tab_stops,
+            choices: self.choices.clone(),
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    snippet: Snippet,
+    len: usize,
+    variables: &'a SnippetVariables,
+}
+
+impl Parser<'_> {
+    fn push(&mut self, ch: char) {
+        self.snippet.text.push(ch);
+        self.len += 1;
+    }
+
+    /// nested 为 true 时遇到 `}` 结束
+    fn parse(&mut self, nested: bool) {
+        while let Some(ch) = self.chars.next() {
+            match ch {
+                '\\' => match self.chars.next_if(|ch| matches!(ch, '$' | '}' | '\\')) {
+                    Some(escaped) => self.push(escaped),
+                    None => self.push('\\'),
+                },
+                '}' if nested => return,
+                '$' => self.parse_dollar(),
+                _ => self.push(ch),
+            }
+        }
+    }
+
+    fn parse_dollar(&mut self) {
+        match self.chars.peek() {
+            Some(ch) if ch.is_ascii_digit() => {
+                let index = self.number();
+                self.tab_stop(index, self.len);
+            }
+            Some('{') => {
+                self.chars.next();
+                self.parse_braced();
+            }
+            Some(ch) if ch.is_alphabetic() || *ch == '_' => {
+                // 没有值的变量展开为空
+                let name = self.name();
+                self.variable(&name);
+            }
+            _ => self.push('$'),
+        }
+    }
+
+    fn parse_braced(&mut self) {
+        let start = self.len;
+        if !self.chars.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+            let name = self.name();
+            match self.chars.next() {
+                Some(':') => {
+                    let stops = self.snippet.tab_stops.len();
+                    self.parse(true);
+                    // 变量有值时替换掉默认值
+                    if self.variables.contains_key(&name) {
+                        self.truncate(start);
+                        self.snippet.tab_stops.truncate(stops);
+                        self.variable(&name);
+                    }
+                }
+                // 不支持变换，直接使用变量的值
+                Some('/') => {
+                    while self.chars.next().is_some_and(|ch| ch != '}') {}
+                    self.variable(&name);
+                }
+                _ => self.variable(&name),
+            }
+            return;
+        }
+        let index = self.number();
+        match self.chars.next() {
+            Some(':') => self.parse(true),
+            Some('|') => {
+                let choices = self.choices();
+                if let Some(first) = choices.first() {
+                    first.chars().for_each(|ch| self.push(ch));
+                }
+                self.chars.next_if_eq(&'}');
+                self.snippet.choices.insert(index, choices);
+            }
+            _ => {}
+        }
+        self.tab_stop(index, start);
+    }
+
+    /// `a,b|` 形式的选项
+    fn choices(&mut self) -> Vec<String> {
+        let mut choices = vec![String::new()];
+        while let Some(ch) = self.chars.next() {
+            match ch {
+                '\\' => {
+                    if let Some(escaped) = self.chars.next() {
+                        choices.last_mut().unwrap().push(escaped);
+                    }
+                }
+                ',' => choices.push(String::new()),
+                '|' => break,
+                _ => choices.last_mut().unwrap().push(ch),
+            }
+        }
+        choices
+    }
+
+    fn number(&mut self) -> u32 {
+        let mut number = 0u32;
+        while let Some(digit) = self.chars.next_if(|ch| ch.is_ascii_digit()) {
+            number = number
+                .saturating_mul(10)
+                .saturating_add(digit.to_digit(10).unwrap_or_default());
+        }
+        number
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(ch) = self.chars.next_if(|ch| ch.is_alphanumeric() || *ch == '_') {
+            name.push(ch);
+        }
+        name
+    }
+
+    fn variable(&mut self, name: &str) {
+        if let Some(value) = self.variables.get(name) {
+            value.chars().for_each(|ch| self.push(ch));
+        }
+    }
+
+    /// 退回到第 len 个字符
+    fn truncate(&mut self, len: usize) {
+        let byte = self
+            .snippet
+            .text
+            .char_indices()
+            .nth(len)
+            .map(|(i, _)| i)
+            .unwrap_or(self.snippet.text.len());
+        self.snippet.text.truncate(byte);
+        self.len = len;
+    }
+
+    fn tab_stop(&mut self, index: u32, start: usize) {
+        self.snippet.tab_stops.push(TabStop {
+            index,
+            start,
+            end: self.len,
+        });
+    }
+}
+
+/// 时间相关的变量（UTC），例如 CURRENT_YEAR、CURRENT_DATE
+pub fn time_variables(unix_seconds: u64) -> SnippetVariables {
+    let days = (unix_seconds / 86400) as i64;
+    let seconds = unix_seconds % 86400;
+    // 从 1970-01-01 开始的天数转换为公历日期
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    [
+        ("CURRENT_YEAR", format!("{year}")),
+        ("CURRENT_YEAR_SHORT", format!("{:02}", year % 100)),
+        ("CURRENT_MONTH", format!("{month:02}")),
+        ("CURRENT_DATE", format!("{day:02}")),
+        ("CURRENT_HOUR", format!("{:02}", seconds / 3600)),
+        ("CURRENT_MINUTE", format!("{:02}", seconds / 60 % 60)),
+        ("CURRENT_SECOND", format!("{:02}", seconds % 60)),
+        ("CURRENT_SECONDS_UNIX", format!("{unix_seconds}")),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect()
+}
+
+/// 可以通过前缀触发的代码片段
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(from = "SnippetFile")]
+pub struct SnippetDefinition {
+    pub prefix: String,
+    pub body: String,
+    pub description: Option<String>,
+}
+
+/// 片段文件中的一项，body 可以是字符串或者每行一个字符串的数组
+#[derive(Deserialize)]
+struct SnippetFile {
+    prefix: String,
+    body: SnippetBody,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SnippetBody {
+    Text(String),
+    Lines(Vec<String>),
+}
+
+impl From<SnippetFile> for SnippetDefinition {
+    fn from(file: SnippetFile) -> Self {
+        Self {
+            prefix: file.prefix,
+            body: match file.body {
+                SnippetBody::Text(text) => text,
+                SnippetBody::Lines(lines) => lines.join("\n"),
+            },
+            description: file.description,
+        }
+    }
+}
+
+const RUST_SNIPPETS: &[(&str, &str, &str)] = &[
+    ("fn", "function", "fn ${1:name}($2) {\n\t$0\n}"),
+    ("pfn", "public function", "pub fn ${1:name}($2) -> ${3:()} {\n\t$0\n}"),
+    ("main", "main function", "fn main() {\n\t$0\n}"),
+    ("test", "test function", "#[test]\nfn ${1:name}() {\n\t$0\n}"),
+    (
+        "tests",
+        "test module",
+        "#[cfg(test)]\nmod tests {\n\tuse super::*;\n\n\t#[test]\n\tfn ${1:name}() {\n\t\t$0\n\t}\n}",
+    ),
+    ("struct", "struct", "#[derive(${1:Debug})]\npub struct ${2:Name} {\n\t$0\n}"),
+    ("enum", "enum", "#[derive(${1:Debug})]\npub enum ${2:Name} {\n\t$0\n}"),
+    ("impl", "impl block", "impl ${1:Type} {\n\t$0\n}"),
+    ("implt", "impl trait", "impl ${1:Trait} for ${2:Type} {\n\t$0\n}"),
+    ("match", "match expression", "match ${1:value} {\n\t${2:_} => $0,\n}"),
+    ("iflet", "if let", "if let ${1:Some(value)} = ${2:option} {\n\t$0\n}"),
+    ("for", "for loop", "for ${1:item} in ${2:iter} {\n\t$0\n}"),
+    ("while", "while loop", "while ${1:condition} {\n\t$0\n}"),
+    ("derive", "derive attribute", "#[derive(${1:Debug, Clone})]"),
+    ("println", "println!", "println!(\"${1:{\\}}\"$2);"),
+    ("res", "Result return type", "Result<${1:()}, ${2:Error}>"),
+];
+
+/// 内置片段和用户片段，key 为 languageId
+#[derive(Clone, Debug, Default)]
+pub struct SnippetLibrary {
+    snippets: HashMap<String, Vec<SnippetDefinition>>,
+}
+
+impl SnippetLibrary {
+    /// 对所有语言生效的片段文件名
+    pub const GLOBAL: &str = "global";
+
+    pub fn builtin() -> Self {
+        let mut library = Self::default();
+        library.snippets.insert(
+            "rust".to_string(),
+            RUST_SNIPPETS
+                .iter()
+                .map(|(prefix, description, body)| SnippetDefinition {
+                    prefix: prefix.to_string(),
+                    body: body.to_string(),
+                    description: Some(description.to_string()),
+                })
+                .collect(),
+        );
+        library
+    }
+
+    /// 内置片段加上目录中的 `<languageId>.toml`，前缀相同时用户的片段覆盖内置的
+    pub fn load(dir: &Path) -> Self {
+        let mut library = Self::builtin();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return library;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|extension| extension != "toml") {
+                continue;
+            }
+            let Some(language) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let result = std::fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|source| {
+                    library
+                        .add_toml(language, &source)
+                        .map_err(|err| err.to_string())
+                });
+            if let Err(err) = result {
+                warn!("load snippets {}: {err}", path.display());
+            }
+        }
+        library
+    }
+
+    /// 片段文件是名字到片段的表
+    pub fn add_toml(&mut self, language: &str, source: &str) -> Result<(), toml::de::Error> {
+        let file: BTreeMap<String, SnippetDefinition> = toml::from_str(source)?;
+        let snippets = self.snippets.entry(language.to_string()).or_default();
+        for (name, mut snippet) in file {
+            snippet.description.get_or_insert(name);
+            snippets.retain(|existing| existing.prefix != snippet.prefix);
+            snippets.push(snippet);
+        }
+        Ok(())
+    }
+
+    /// 语言的片段和全局片段
+    pub fn for_language<'a>(
+        &'a self,
+        language: &str,
+    ) -> impl Iterator<Item = &'a SnippetDefinition> + 'a {
+        let global = self.snippets.get(Self::GLOBAL).into_iter().flatten();
+        let language = self.snippets.get(language).into_iter().flatten();
+        language.chain(global)
+    }
+
+    pub fn find(&self, language: &str, prefix: &str) -> Option<&SnippetDefinition> {
+        self.for_language(language)
+            .find(|snippet| snippet.prefix == prefix)
+    }
+}
+
+#[test]
+fn test_snippet_parse() {
+    let snippet = Snippet::parse("fn ${1:name}(${2:arg}: ${3|u8,i32|}) {\n    $0\n}");
+    assert_eq!(snippet.text, "fn name(arg: u8) {\n    \n}");
+    let stops = snippet
+        .tab_stops
+        .iter()
+        .map(|stop| (stop.index, stop.start, stop.end))
+        .collect::<Vec<_>>();
+    assert_eq!(stops, vec![(1, 3, 7), (2, 8, 11), (3, 13, 15), (0, 23, 23)]);
+    let snippet = Snippet::parse("println!(\"\\$1 {}\", ${1:${TM_SELECTED_TEXT:value}})");
+    assert_eq!(snippet.text, "println!(\"$1 {}\", value)");
+    assert_eq!(snippet.first_stop(), Some(&TabStop { index: 1, start: 18, end: 23 }));
+}
+
+#[test]
+fn test_snippet_library() {
+    let variables = SnippetVariables::from([("TM_FILENAME".to_string(), "a.rs".to_string())]);
+    let snippet = Snippet::parse_with(
+        "// ${TM_FILENAME:x} ${1:$TM_FILENAME} ${2|a,b|}",
+        &variables,
+    );
+    assert_eq!(snippet.text, "// a.rs a.rs a");
+    assert_eq!(snippet.choices[&2], vec!["a".to_string(), "b".to_string()]);
+    let snippet = Snippet::parse("{\n\t$1\n}").indented("  ", "    ");
+    assert_eq!(snippet.text, "{\n      \n  }");
+    assert_eq!(
+        snippet.first_stop(),
+        Some(&TabStop {
+            index: 1,
+            start: 8,
+            end: 8
+        })
+    );
+    assert_eq!(time_variables(951_782_400)["CURRENT_DATE"], "29");
+    assert_eq!(time_variables(951_782_400)["CURRENT_MONTH"], "02");
+
+    let mut library = SnippetLibrary::builtin();
+    library
+        .add_toml(
+            "rust",
+            "[function]\nprefix = \"fn\"\nbody = [\"fn $1() {\", \"\\t$0\", \"}\"]",
+        )
+        .unwrap();
+    library
+        .add_toml("global", "[todo]\nprefix = \"todo\"\nbody = \"TODO: $0\"")
+        .unwrap();
+    let snippet = library.find("rust", "fn").unwrap();
+    assert_eq!(snippet.body, "fn $1() {\n\t$0\n}");
+    assert_eq!(snippet.description.as_deref(), Some("function"));
+    assert!(library.find("python", "todo").is_some());
+    assert!(library.find("python", "fn").is_none());
+}
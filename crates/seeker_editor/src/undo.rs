@@ -0,0 +1,114 @@
+use crate::buffer::{advance, Position};
+
+/// 最多保留的撤销组数
+const MAX_GROUPS: usize = 1000;
+
+/// 文档的一次修改，text 中的换行统一为 \n
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Edit {
+    Insert { at: Position, text: String },
+    Delete { at: Position, text: String },
+}
+
+impl Edit {
+    pub fn inverse(&self) -> Self {
+        match self {
+            Self::Insert { at, text } => Self::Delete {
+                at: *at,
+                text: text.clone(),
+            },
+            Self::Delete { at, text } => Self::Insert {
+                at: *at,
+                text: text.clone(),
+            },
+        }
+    }
+
+    /// 修改的文本在文档中结束的位置
+    pub fn end(&self) -> Position {
+        let (Self::Insert { at, text } | Self::Delete { at, text }) = self;
+        advance(*at, text, usize::MAX)
+    }
+}
+
+/// 撤销和重做记录，同一组中的修改一起撤销
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    undo: Vec<Vec<Edit>>,
+    redo: Vec<Vec<Edit>>,
+    /// 为 true 时下一次修改开始新的一组
+    sealed: bool,
+}
+
+impl History {
+    /// 记录新的修改，之前撤销的修改不能再重做
+    pub fn record(&mut self, edit: Edit) {
+        let (Edit::Insert { text, .. } | Edit::Delete { text, .. }) = &edit;
+        if text.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        match self.undo.last_mut() {
+            Some(group) if !self.sealed => group.push(edit),
+            _ => {
+                if self.undo.len() == MAX_GROUPS {
+                    self.undo.remove(0);
+                }
+                self.undo.push(vec![edit]);
+                self.sealed = false;
+            }
+        }
+    }
+
+    /// 结束当前的一组，之后的修改记录到新的一组
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// 取出最近一组修改，之后可以重做
+    pub fn undo(&mut self) -> Option<Vec<Edit>> {
+        self.sealed = true;
+        let group = self.undo.pop()?;
+        self.redo.push(group.clone());
+        Some(group)
+    }
+
+    /// 取出最近撤销的一组修改
+    pub fn redo(&mut self) -> Option<Vec<Edit>> {
+        self.sealed = true;
+        let group = self.redo.pop()?;
+        self.undo.push(group.clone());
+        Some(group)
+    }
+}
+
+#[test]
+fn test_undo() {
+    let mut doc = crate::document::Document {
+        path: "a.txt".into(),
+        language_id: "plaintext".to_string(),
+        buffer: crate::buffer::TextBuffer::new("a\nb"),
+        version: 1,
+        dirty: false,
+        folds: Default::default(),
+        config: Default::default(),
+        history: Default::default(),
+    };
+    let end = doc.insert(Position::new(0, 1), "x");
+    doc.insert(end, "y\nz");
+    doc.seal_undo();
+    doc.delete(Position::new(0, 0), Position::new(1, 1));
+    assert_eq!(doc.buffer.text(), "\nb");
+    assert_eq!(doc.undo(), Some(Position::new(1, 1)));
+    assert_eq!(doc.buffer.text(), "axy\nz\nb");
+    // 连续的修改在同一组
+    assert_eq!(doc.undo(), Some(Position::new(0, 1)));
+    assert_eq!(doc.buffer.text(), "a\nb");
+    assert_eq!(doc.undo(), None);
+    assert_eq!(doc.redo(), Some(Position::new(1, 1)));
+    assert_eq!(doc.buffer.text(), "axy\nz\nb");
+    // 新的修改之后不能再重做
+    doc.insert(Position::new(0, 0), "-");
+    assert_eq!(doc.redo(), None);
+    assert_eq!(doc.buffer.text(), "-axy\nz\nb");
+}
@@ -0,0 +1,31 @@
+// 估算显示宽度（中文等宽字符算作2个单位宽度）
+pub fn char_display_width(ch: char) -> usize {
+    if ch.len_utf8() > 1 {
+        2
+    } else {
+        1
+    }
+}
+
+/// 字符串的显示宽度
+pub fn str_display_width(text: &str) -> usize {
+    text.chars().map(char_display_width).sum()
+}
+
+/// 行内前 column 个字符的显示宽度
+pub fn column_display_width(line: &str, column: usize) -> usize {
+    line.chars().take(column).map(char_display_width).sum()
+}
+
+/// 显示宽度对应的字符列，落在宽字符右半边时取下一个字符
+pub fn column_at_display_width(line: &str, width: f32) -> usize {
+    let mut current = 0.;
+    for (column, ch) in line.chars().enumerate() {
+        let ch_width = char_display_width(ch) as f32;
+        if width < current + ch_width / 2. {
+            return column;
+        }
+        current += ch_width;
+    }
+    line.chars().count()
+}
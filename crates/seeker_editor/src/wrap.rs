@@ -0,0 +1,221 @@
+use crate::buffer::Position;
+use crate::document::Document;
+use crate::width::char_display_width;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// 中日韩文字，两个字之间可以换行
+pub fn is_cjk(ch: char) -> bool {
+    matches!(ch,
+        '\u{1100}'..='\u{11ff}'
+        | '\u{2e80}'..='\u{2fdf}'
+        | '\u{3000}'..='\u{30ff}'
+        | '\u{3100}'..='\u{31ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{ac00}'..='\u{d7af}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{ff00}'..='\u{ffef}'
+        | '\u{20000}'..='\u{2fa1f}')
+}
+
+/// 不能放在行首的标点
+fn is_closing_punctuation(ch: char) -> bool {
+    matches!(
+        ch,
+        '，' | '。'
+            | '、'
+            | '；'
+            | '：'
+            | '！'
+            | '？'
+            | '）'
+            | '】'
+            | '」'
+            | '』'
+            | '》'
+            | '〉'
+            | '”'
+            | '’'
+            | ','
+            | '.'
+            | ';'
+            | ':'
+            | '!'
+            | '?'
+            | ')'
+            | ']'
+            | '}'
+    )
+}
+
+/// 把一行按显示宽度拆成若干段，返回每段的起始列。
+/// 优先在空白后面换行，中日韩文字之间可以直接换行，行尾的空白不换到下一段
+pub fn wrap_line(line: &str, width: usize) -> Vec<usize> {
+    let width = width.max(1);
+    let chars: Vec<char> = line.chars().collect();
+    let mut starts = vec![0];
+    let mut start = 0;
+    let mut x = 0;
+    // 最近的可以开始新一段的列
+    let mut candidate: Option<usize> = None;
+    for (column, &ch) in chars.iter().enumerate() {
+        let ch_width = char_display_width(ch);
+        if x + ch_width > width && column > start && !ch.is_whitespace() {
+            let mut at = candidate.filter(|at| *at > start).unwrap_or(column);
+            while at > start + 1 && is_closing_punctuation(chars[at]) {
+                at -= 1;
+            }
+            starts.push(at);
+            start = at;
+            candidate = None;
+            x = chars[at..column]
+                .iter()
+                .copied()
+                .map(char_display_width)
+                .sum();
+        }
+        x += ch_width;
+        let next_cjk = chars.get(column + 1).is_some_and(|next| is_cjk(*next));
+        if ch.is_whitespace() || is_cjk(ch) || next_cjk {
+            candidate = Some(column + 1);
+        }
+    }
+    starts
+}
+
+/// 视图的软换行布局，显示行由折叠和换行共同决定。不换行时直接使用折叠的行
+#[derive(Clone, Debug, Default)]
+pub struct WrapLayout {
+    /// 换行的宽度（半角字符数）
+    width: Option<usize>,
+    /// 生成布局时的文档和版本
+    key: Option<(PathBuf, i32, u32)>,
+    /// 每个显示行的 (缓冲区行, 起始列)
+    rows: Vec<(usize, usize)>,
+    /// 每个缓冲区行的第一个显示行，折叠隐藏的行对应折叠的起始行
+    line_rows: Vec<usize>,
+}
+
+impl WrapLayout {
+    pub fn width(&self) -> Option<usize> {
+        self.width
+    }
+
+    /// 文档或者宽度变化后重新计算，返回是否有变化
+    pub fn update(&mut self, doc: &Document, width: Option<usize>) -> bool {
+        let key = Some((doc.path.clone(), doc.version, doc.folds.revision()));
+        if self.width == width && (width.is_none() || self.key == key) {
+            return false;
+        }
+        self.width = width;
+        self.rows.clear();
+        self.line_rows.clear();
+        let Some(width) = width else {
+            self.key = None;
+            return true;
+        };
+        self.key = key;
+        for line in 0..doc.buffer.line_count() {
+            if doc.folds.is_hidden(line) {
+                let row = self.line_rows.last().copied().unwrap_or_default();
+                self.line_rows.push(row);
+                continue;
+            }
+            self.line_rows.push(self.rows.len());
+            let starts = wrap_line(doc.buffer.line(line), width);
+            self.rows
+                .extend(starts.into_iter().map(|start| (line, start)));
+        }
+        true
+    }
+
+    fn wrapped(&self, doc: &Document) -> bool {
+        self.width.is_some() && self.line_rows.len() == doc.buffer.line_count()
+    }
+
+    pub fn row_count(&self, doc: &Document) -> usize {
+        if self.wrapped(doc) {
+            self.rows.len()
+        } else {
+            doc.folds.row_count(doc.buffer.line_count())
+        }
+    }
+
+    /// 位置所在的显示行，位于换行处的位置属于下一段
+    pub fn row(&self, doc: &Document, pos: Position) -> usize {
+        if !self.wrapped(doc) {
+            return doc.folds.display_row(pos.line);
+        }
+        let line = pos.line.min(self.line_rows.len() - 1);
+        let mut row = self.line_rows[line];
+        if doc.folds.is_hidden(line) {
+            return row;
+        }
+        while self
+            .rows
+            .get(row + 1)
+            .is_some_and(|(next_line, start)| *next_line == line && *start <= pos.column)
+        {
+            row += 1;
+        }
+        row
+    }
+
+    /// 显示行对应的缓冲区行和列范围
+    pub fn segment(&self, doc: &Document, row: usize) -> (usize, Range<usize>) {
+        if !self.wrapped(doc) {
+            let line = doc.folds.buffer_line(row).min(doc.buffer.line_count() - 1);
+            return (line, 0..doc.buffer.line_len(line));
+        }
+        let row = row.min(self.rows.len() - 1);
+        let (line, start) = self.rows[row];
+        let end = match self.rows.get(row + 1) {
+            Some((next_line, next)) if *next_line == line => *next,
+            _ => doc.buffer.line_len(line),
+        };
+        (line, start..end)
+    }
+
+    /// 显示行后面还有同一行的下一段
+    pub fn continues(&self, doc: &Document, row: usize) -> bool {
+        self.wrapped(doc)
+            && self
+                .rows
+                .get(row)
+                .zip(self.rows.get(row + 1))
+                .is_some_and(|((line, _), (next, _))| line == next)
+    }
+}
+
+#[test]
+fn test_wrap_line() {
+    assert_eq!(wrap_line("let value = compute(a);", 12), vec![0, 12]);
+    assert_eq!(wrap_line("abcdefghij", 4), vec![0, 4, 8]);
+    // 中文每个字两个单位宽，标点不放在行首
+    assert_eq!(wrap_line("中文软换行，测试", 10), vec![0, 4]);
+    assert_eq!(wrap_line("short", 80), vec![0]);
+
+    let mut doc = Document {
+        path: PathBuf::from("a.txt"),
+        language_id: "plaintext".to_string(),
+        buffer: crate::buffer::TextBuffer::new("aaaa bbbb cccc\nx"),
+        version: 1,
+        dirty: false,
+        folds: Default::default(),
+        config: Default::default(),
+        history: Default::default(),
+    };
+    let mut layout = WrapLayout::default();
+    assert!(layout.update(&doc, Some(10)));
+    assert!(!layout.update(&doc, Some(10)));
+    assert_eq!(layout.row_count(&doc), 3);
+    assert_eq!(layout.row(&doc, Position::new(0, 9)), 0);
+    assert_eq!(layout.row(&doc, Position::new(0, 10)), 1);
+    assert_eq!(layout.segment(&doc, 1), (0, 10..14));
+    assert!(layout.continues(&doc, 0) && !layout.continues(&doc, 1));
+    doc.version += 1;
+    assert!(layout.update(&doc, None));
+    assert_eq!(layout.row_count(&doc), 2);
+    assert_eq!(layout.segment(&doc, 1), (1, 0..1));
+}
@@ -0,0 +1,92 @@
+use crate::commit::{head_commit, relative_path, CommitInfo};
+use git2::{ErrorCode, Oid, Repository};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 文件每一行最后一次修改所在的提交
+#[derive(Clone, Debug, Default)]
+pub struct FileBlame {
+    /// 还没有提交的行为 None
+    pub lines: Vec<Option<Oid>>,
+    pub commits: HashMap<Oid, CommitInfo>,
+}
+
+impl FileBlame {
+    /// 按 text（编辑中的内容）计算，文件不在仓库中或者没有被提交过时返回 Ok(None)
+    pub fn open(path: &Path, text: &str) -> Result<Option<Self>, git2::Error> {
+        let repo = match Repository::discover(path) {
+            Ok(repo) => repo,
+            Err(err) if err.code() == ErrorCode::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        if head_commit(&repo)?.is_none() {
+            return Ok(None);
+        }
+        let relative = relative_path(&repo, path)?;
+        let blame = match repo.blame_file(&relative, None) {
+            Ok(blame) => blame,
+            Err(err) if err.code() == ErrorCode::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let blame = blame.blame_buffer(text.as_bytes())?;
+        let mut file = Self {
+            lines: vec![None; text.split('\n').count()],
+            commits: HashMap::new(),
+        };
+        for hunk in blame.iter() {
+            let id = hunk.final_commit_id();
+            if id.is_zero() {
+                continue;
+            }
+            if let Entry::Vacant(entry) = file.commits.entry(id) {
+                entry.insert(CommitInfo::new(&repo.find_commit(id)?));
+            }
+            // final_start_line 从 1 开始
+            let start = hunk.final_start_line().saturating_sub(1);
+            let end = (start + hunk.lines_in_hunk()).min(file.lines.len());
+            for line in file.lines.get_mut(start..end).into_iter().flatten() {
+                *line = Some(id);
+            }
+        }
+        Ok(Some(file))
+    }
+
+    /// 第 line 行所在的提交，没有提交时返回 None
+    pub fn line(&self, line: usize) -> Option<&CommitInfo> {
+        self.lines
+            .get(line)
+            .copied()
+            .flatten()
+            .and_then(|id| self.commits.get(&id))
+    }
+}
+
+#[test]
+fn test_file_blame() {
+    use crate::commit::{commit, stage};
+    use git2::RepositoryInitOptions;
+    let root = std::env::temp_dir().join(format!("seeker_git_blame_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let repo =
+        Repository::init_opts(&root, RepositoryInitOptions::new().initial_head("main")).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "seeker").unwrap();
+    config.set_str("user.email", "seeker@example.com").unwrap();
+    let root = repo.workdir().unwrap().to_path_buf();
+    let path = root.join("a.txt");
+    assert!(FileBlame::open(&path, "").unwrap().is_none());
+
+    std::fs::write(&path, "a\nb\n").unwrap();
+    stage(&path).unwrap();
+    let first = commit(&root, "first").unwrap();
+    std::fs::write(&path, "a\nB\n").unwrap();
+    stage(&path).unwrap();
+    let second = commit(&root, "second").unwrap();
+
+    let blame = FileBlame::open(&path, "a\nB\nc\n").unwrap().unwrap();
+    assert_eq!(blame.lines, vec![Some(first), Some(second), None, None]);
+    assert_eq!(blame.line(1).unwrap().summary, "second");
+    assert!(blame.line(2).is_none());
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,123 @@
+use crate::commit::head_commit;
+use git2::build::CheckoutBuilder;
+use git2::{BranchType, Repository, StashFlags};
+use std::path::Path;
+
+/// 本地分支
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BranchInfo {
+    pub name: String,
+    /// 是否是当前分支
+    pub is_head: bool,
+}
+
+/// 按名字排序的本地分支
+pub fn branches(path: &Path) -> Result<Vec<BranchInfo>, git2::Error> {
+    let repo = Repository::discover(path)?;
+    let mut branches = repo
+        .branches(Some(BranchType::Local))?
+        .map(|branch| {
+            let (branch, _) = branch?;
+            Ok(BranchInfo {
+                name: branch.name()?.unwrap_or_default().to_string(),
+                is_head: branch.is_head(),
+            })
+        })
+        .collect::<Result<Vec<_>, git2::Error>>()?;
+    branches.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(branches)
+}
+
+/// 从 HEAD 创建分支，不切换
+pub fn create_branch(path: &Path, name: &str) -> Result<(), git2::Error> {
+    let repo = Repository::discover(path)?;
+    let head = head_commit(&repo)?
+        .ok_or_else(|| git2::Error::from_str("cannot create a branch before the first commit"))?;
+    repo.branch(name, &head, false)?;
+    Ok(())
+}
+
+/// 切换到本地分支。工作区中会被覆盖的改动使切换失败，不会丢失
+pub fn checkout_branch(path: &Path, name: &str) -> Result<(), git2::Error> {
+    let repo = Repository::discover(path)?;
+    let branch = repo.find_branch(name, BranchType::Local)?;
+    let reference = branch.get();
+    let tree = reference.peel_to_tree()?;
+    repo.checkout_tree(tree.as_object(), Some(CheckoutBuilder::new().safe()))?;
+    let name = reference
+        .name()
+        .ok_or_else(|| git2::Error::from_str("branch name is not valid utf-8"))?;
+    repo.set_head(name)
+}
+
+/// 暂存工作区和暂存区中的改动（包括未跟踪的文件）
+pub fn stash(path: &Path) -> Result<(), git2::Error> {
+    let mut repo = Repository::discover(path)?;
+    let signature = repo.signature()?;
+    repo.stash_save(&signature, "seeker", Some(StashFlags::INCLUDE_UNTRACKED))?;
+    Ok(())
+}
+
+/// 恢复并删除最近一次暂存
+pub fn stash_pop(path: &Path) -> Result<(), git2::Error> {
+    let mut repo = Repository::discover(path)?;
+    repo.stash_pop(0, None)
+}
+
+pub fn stash_count(path: &Path) -> Result<usize, git2::Error> {
+    let mut repo = Repository::discover(path)?;
+    let mut count = 0;
+    repo.stash_foreach(|_, _, _| {
+        count += 1;
+        true
+    })?;
+    Ok(count)
+}
+
+#[test]
+fn test_branch_and_stash() {
+    use crate::commit::{commit, stage};
+    use git2::RepositoryInitOptions;
+    let root = std::env::temp_dir().join(format!("seeker_git_branch_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let repo =
+        Repository::init_opts(&root, RepositoryInitOptions::new().initial_head("main")).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "seeker").unwrap();
+    config.set_str("user.email", "seeker@example.com").unwrap();
+    let root = repo.workdir().unwrap().to_path_buf();
+    let path = root.join("a.txt");
+    std::fs::write(&path, "a\n").unwrap();
+    stage(&path).unwrap();
+    commit(&root, "first").unwrap();
+
+    create_branch(&root, "feature").unwrap();
+    checkout_branch(&root, "feature").unwrap();
+    std::fs::write(&path, "feature\n").unwrap();
+    stage(&path).unwrap();
+    commit(&root, "feature").unwrap();
+    let names: Vec<(String, bool)> = branches(&root)
+        .unwrap()
+        .into_iter()
+        .map(|branch| (branch.name, branch.is_head))
+        .collect();
+    assert_eq!(
+        names,
+        vec![("feature".to_string(), true), ("main".to_string(), false)]
+    );
+
+    // 有会被覆盖的改动时不能切换
+    std::fs::write(&path, "dirty\n").unwrap();
+    assert!(checkout_branch(&root, "main").is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "dirty\n");
+
+    stash(&root).unwrap();
+    assert_eq!(stash_count(&root).unwrap(), 1);
+    checkout_branch(&root, "main").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\n");
+    checkout_branch(&root, "feature").unwrap();
+    stash_pop(&root).unwrap();
+    assert_eq!(stash_count(&root).unwrap(), 0);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "dirty\n");
+    std::fs::remove_dir_all(&root).unwrap();
+}
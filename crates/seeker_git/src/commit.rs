@@ -0,0 +1,386 @@
+use crate::status::FileStatus;
+use git2::{
+    Delta, DiffOptions, ErrorCode, Oid, Repository, Sort, Status, StatusEntry, StatusOptions,
+};
+use std::path::{Path, PathBuf};
+
+/// 一个有改动的文件，路径是绝对路径
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangedFile {
+    pub path: PathBuf,
+    pub status: FileStatus,
+}
+
+/// 已暂存和未暂存的改动，同一个文件可能同时出现在两边
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Changes {
+    pub staged: Vec<ChangedFile>,
+    pub unstaged: Vec<ChangedFile>,
+}
+
+impl Changes {
+    pub fn open(path: &Path) -> Result<Self, git2::Error> {
+        let repo = Repository::discover(path)?;
+        let workdir = workdir(&repo)?;
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .exclude_submodules(true)
+            .renames_head_to_index(true);
+        let mut changes = Self::default();
+        for entry in repo.statuses(Some(&mut options))?.iter() {
+            let Some(path) = entry_path(&entry) else {
+                continue;
+            };
+            let path = workdir.join(path);
+            let status = entry.status();
+            if let Some(status) = index_status(status) {
+                changes.staged.push(ChangedFile {
+                    path: path.clone(),
+                    status,
+                });
+            }
+            if let Some(status) = workdir_status(status) {
+                changes.unstaged.push(ChangedFile { path, status });
+            }
+        }
+        Ok(changes)
+    }
+}
+
+/// 重命名时 `StatusEntry::path` 是原来的路径，取重命名后的路径
+fn entry_path<'a>(entry: &'a StatusEntry) -> Option<&'a Path> {
+    entry
+        .head_to_index()
+        .and_then(|delta| delta.new_file().path())
+        .or_else(|| {
+            entry
+                .index_to_workdir()
+                .and_then(|delta| delta.new_file().path())
+        })
+        .or_else(|| entry.path().map(Path::new))
+}
+
+fn index_status(status: Status) -> Option<FileStatus> {
+    if status.is_conflicted() {
+        None
+    } else if status.is_index_new() {
+        Some(FileStatus::Added)
+    } else if status.is_index_deleted() {
+        Some(FileStatus::Deleted)
+    } else if status.is_index_renamed() {
+        Some(FileStatus::Renamed)
+    } else if status.is_index_modified() || status.is_index_typechange() {
+        Some(FileStatus::Modified)
+    } else {
+        None
+    }
+}
+
+fn workdir_status(status: Status) -> Option<FileStatus> {
+    if status.is_conflicted() {
+        Some(FileStatus::Conflicted)
+    } else if status.is_wt_new() {
+        Some(FileStatus::Untracked)
+    } else if status.is_wt_deleted() {
+        Some(FileStatus::Deleted)
+    } else if status.is_wt_renamed() {
+        Some(FileStatus::Renamed)
+    } else if status.is_wt_modified() || status.is_wt_typechange() {
+        Some(FileStatus::Modified)
+    } else {
+        None
+    }
+}
+
+/// 把工作区中的文件写入暂存区，文件被删除时从暂存区移除
+pub fn stage(path: &Path) -> Result<(), git2::Error> {
+    let repo = discover(path)?;
+    let relative = relative_path(&repo, path)?;
+    let mut index = repo.index()?;
+    if path.exists() {
+        index.add_path(&relative)?;
+    } else {
+        index.remove_path(&relative)?;
+    }
+    index.write()
+}
+
+/// 暂存区中的文件恢复为 HEAD 中的版本，工作区不变。
+/// 已暂存的重命名同时恢复原来的路径
+pub fn unstage(path: &Path) -> Result<(), git2::Error> {
+    let repo = discover(path)?;
+    let relative = relative_path(&repo, path)?;
+    match head_commit(&repo)? {
+        Some(head) => {
+            let mut paths = vec![relative.clone()];
+            paths.extend(renamed_from(&repo, &relative)?);
+            repo.reset_default(Some(head.as_object()), paths)
+        }
+        None => {
+            let mut index = repo.index()?;
+            index.remove_path(&relative)?;
+            index.write()
+        }
+    }
+}
+
+/// 暂存区中重命名为 relative 的文件原来的路径
+fn renamed_from(repo: &Repository, relative: &Path) -> Result<Option<PathBuf>, git2::Error> {
+    let mut options = StatusOptions::new();
+    options
+        .exclude_submodules(true)
+        .renames_head_to_index(true);
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        let Some(delta) = entry.head_to_index() else {
+            continue;
+        };
+        if entry.status().is_index_renamed() && delta.new_file().path() == Some(relative) {
+            return Ok(delta.old_file().path().map(Path::to_path_buf));
+        }
+    }
+    Ok(None)
+}
+
+/// 提交暂存区，作者取仓库配置中的 user.name 和 user.email
+pub fn commit(path: &Path, message: &str) -> Result<Oid, git2::Error> {
+    let repo = Repository::discover(path)?;
+    let signature = repo.signature()?;
+    let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+    let parents: Vec<_> = head_commit(&repo)?.into_iter().collect();
+    let parents: Vec<_> = parents.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+}
+
+/// 提交历史中的一条
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitInfo {
+    pub id: Oid,
+    pub summary: String,
+    pub author: String,
+    /// 提交时间，Unix 秒
+    pub time: i64,
+}
+
+impl CommitInfo {
+    pub(crate) fn new(commit: &git2::Commit) -> Self {
+        Self {
+            id: commit.id(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            time: commit.time().seconds(),
+        }
+    }
+
+    pub fn short_id(&self) -> String {
+        self.id.to_string().chars().take(7).collect()
+    }
+}
+
+/// 从 HEAD 开始按时间倒序，跳过 skip 条后最多取 limit 条
+pub fn log(path: &Path, skip: usize, limit: usize) -> Result<Vec<CommitInfo>, git2::Error> {
+    let repo = Repository::discover(path)?;
+    if head_commit(&repo)?.is_none() {
+        return Ok(vec![]);
+    }
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TIME)?;
+    walk.push_head()?;
+    walk.skip(skip)
+        .take(limit)
+        .map(|id| Ok(CommitInfo::new(&repo.find_commit(id?)?)))
+        .collect()
+}
+
+/// 提交相对于第一个父提交改动的文件
+pub fn commit_files(path: &Path, id: Oid) -> Result<Vec<ChangedFile>, git2::Error> {
+    let repo = Repository::discover(path)?;
+    let workdir = workdir(&repo)?;
+    let commit = repo.find_commit(id)?;
+    let parent = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(err) if err.code() == ErrorCode::NotFound => None,
+        Err(err) => return Err(err),
+    };
+    let diff = repo.diff_tree_to_tree(
+        parent.as_ref(),
+        Some(&commit.tree()?),
+        Some(&mut DiffOptions::new()),
+    )?;
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| {
+            let status = match delta.status() {
+                Delta::Added => FileStatus::Added,
+                Delta::Deleted => FileStatus::Deleted,
+                Delta::Renamed => FileStatus::Renamed,
+                Delta::Modified | Delta::Typechange => FileStatus::Modified,
+                _ => return None,
+            };
+            let file = delta.new_file().path().or(delta.old_file().path())?;
+            Some(ChangedFile {
+                path: workdir.join(file),
+                status,
+            })
+        })
+        .collect())
+}
+
+/// 文件在提交的第一个父提交中和提交中的内容，不存在的一侧为 None
+pub fn commit_file_texts(
+    path: &Path,
+    id: Oid,
+) -> Result<(Option<String>, Option<String>), git2::Error> {
+    let repo = Repository::discover(path)?;
+    let relative = relative_path(&repo, path)?;
+    let commit = repo.find_commit(id)?;
+    let after = tree_text(&repo, &commit.tree()?, &relative)?;
+    let before = match commit.parent(0) {
+        Ok(parent) => tree_text(&repo, &parent.tree()?, &relative)?,
+        Err(err) if err.code() == ErrorCode::NotFound => None,
+        Err(err) => return Err(err),
+    };
+    Ok((before, after))
+}
+
+fn tree_text(
+    repo: &Repository,
+    tree: &git2::Tree,
+    path: &Path,
+) -> Result<Option<String>, git2::Error> {
+    let entry = match tree.get_path(path) {
+        Ok(entry) => entry,
+        Err(err) if err.code() == ErrorCode::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let blob = entry.to_object(repo)?.peel_to_blob()?;
+    Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+}
+
+pub(crate) fn head_commit(repo: &Repository) -> Result<Option<git2::Commit<'_>>, git2::Error> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_commit()?)),
+        Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// 从路径所在的仓库打开，路径已经被删除时从存在的上级目录查找
+fn discover(path: &Path) -> Result<Repository, git2::Error> {
+    let existing = path
+        .ancestors()
+        .find(|path| path.exists())
+        .unwrap_or(path);
+    Repository::discover(existing)
+}
+
+fn workdir(repo: &Repository) -> Result<PathBuf, git2::Error> {
+    repo.workdir()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| git2::Error::from_str("repository has no work tree"))
+}
+
+pub(crate) fn relative_path(repo: &Repository, path: &Path) -> Result<PathBuf, git2::Error> {
+    path.strip_prefix(workdir(repo)?)
+        .map(Path::to_path_buf)
+        .map_err(|_| git2::Error::from_str("file is outside of the work tree"))
+}
+
+#[test]
+fn test_commit_and_log() {
+    use git2::RepositoryInitOptions;
+    let root = std::env::temp_dir().join(format!("seeker_git_commit_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let repo =
+        Repository::init_opts(&root, RepositoryInitOptions::new().initial_head("main")).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "seeker").unwrap();
+    config.set_str("user.email", "seeker@example.com").unwrap();
+    let root = repo.workdir().unwrap().to_path_buf();
+    let (a, b) = (root.join("a.txt"), root.join("b.txt"));
+
+    std::fs::write(&a, "a\n").unwrap();
+    let changes = Changes::open(&root).unwrap();
+    assert!(changes.staged.is_empty());
+    assert_eq!(changes.unstaged[0].status, FileStatus::Untracked);
+    assert!(log(&root, 0, 10).unwrap().is_empty());
+
+    stage(&a).unwrap();
+    assert_eq!(
+        Changes::open(&root).unwrap().staged[0].status,
+        FileStatus::Added
+    );
+    unstage(&a).unwrap();
+    assert!(Changes::open(&root).unwrap().staged.is_empty());
+    stage(&a).unwrap();
+    let first = commit(&root, "add a").unwrap();
+    assert_eq!(Changes::open(&root).unwrap(), Changes::default());
+
+    std::fs::write(&a, "A\n").unwrap();
+    std::fs::write(&b, "b\n").unwrap();
+    stage(&a).unwrap();
+    stage(&b).unwrap();
+    unstage(&b).unwrap();
+    let changes = Changes::open(&root).unwrap();
+    assert_eq!(
+        changes.staged,
+        vec![ChangedFile {
+            path: a.clone(),
+            status: FileStatus::Modified
+        }]
+    );
+    assert_eq!(changes.unstaged[0].path, b);
+    let second = commit(&root, "change a\n\ndetails").unwrap();
+
+    let commits = log(&root, 0, 10).unwrap();
+    let ids: Vec<Oid> = commits.iter().map(|commit| commit.id).collect();
+    assert_eq!(ids, vec![second, first]);
+    assert_eq!(commits[0].summary, "change a");
+    assert_eq!(commits[0].author, "seeker");
+    assert_eq!(log(&root, 1, 10).unwrap()[0].id, first);
+
+    let files = commit_files(&root, second).unwrap();
+    assert_eq!(
+        files,
+        vec![ChangedFile {
+            path: a.clone(),
+            status: FileStatus::Modified
+        }]
+    );
+    assert_eq!(
+        commit_file_texts(&a, second).unwrap(),
+        (Some("a\n".to_string()), Some("A\n".to_string()))
+    );
+    assert_eq!(
+        commit_file_texts(&a, first).unwrap(),
+        (None, Some("a\n".to_string()))
+    );
+
+    // 重命名显示和暂存新的路径
+    let c = root.join("c.txt");
+    std::fs::rename(&a, &c).unwrap();
+    stage(&a).unwrap();
+    stage(&c).unwrap();
+    let changes = Changes::open(&root).unwrap();
+    assert_eq!(
+        changes.staged,
+        vec![ChangedFile {
+            path: c.clone(),
+            status: FileStatus::Renamed
+        }]
+    );
+    unstage(&c).unwrap();
+    let changes = Changes::open(&root).unwrap();
+    assert!(changes.staged.is_empty());
+    assert!(changes.unstaged.iter().any(|file| file.path == a));
+    assert!(changes.unstaged.iter().any(|file| file.path == c));
+    std::fs::remove_dir_all(&root).unwrap();
+}
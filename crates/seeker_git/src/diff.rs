@@ -0,0 +1,152 @@
+use git2::Repository;
+use similar::TextDiff;
+use std::ops::Range;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HunkKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// 一处改动，old 是基准中的行范围，new 是当前文本中的行范围
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hunk {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+impl Hunk {
+    pub fn kind(&self) -> HunkKind {
+        if self.old.is_empty() {
+            HunkKind::Added
+        } else if self.new.is_empty() {
+            HunkKind::Deleted
+        } else {
+            HunkKind::Modified
+        }
+    }
+
+    /// 删除的行显示在下一行的上边缘
+    pub fn contains_line(&self, line: usize) -> bool {
+        self.new.contains(&line) || (self.new.is_empty() && self.new.start == line)
+    }
+}
+
+/// 按行比较，不带上下文
+pub fn diff_lines(base: &str, current: &str) -> Vec<Hunk> {
+    TextDiff::from_lines(base, current)
+        .grouped_ops(0)
+        .iter()
+        .filter_map(|ops| {
+            let (first, last) = (ops.first()?, ops.last()?);
+            Some(Hunk {
+                old: first.old_range().start..last.old_range().end,
+                new: first.new_range().start..last.new_range().end,
+            })
+        })
+        .collect()
+}
+
+/// 行号所在的改动
+pub fn hunk_at_line(hunks: &[Hunk], line: usize) -> Option<usize> {
+    hunks.iter().position(|hunk| hunk.contains_line(line))
+}
+
+/// 取出文本中的若干行，保留换行符
+pub fn lines(text: &str, range: Range<usize>) -> String {
+    text.split_inclusive('\n')
+        .skip(range.start)
+        .take(range.len())
+        .collect()
+}
+
+/// 把 base 中 hunk.old 的行替换成 current 中 hunk.new 的行
+pub fn apply_hunk(base: &str, current: &str, hunk: &Hunk) -> String {
+    let mut text = lines(base, 0..hunk.old.start);
+    text.push_str(&lines(current, hunk.new.clone()));
+    text.push_str(&lines(base, hunk.old.end..usize::MAX));
+    text
+}
+
+/// 暂存区中的文件内容，文件没有被跟踪时返回 None
+pub fn index_text(path: &Path) -> Result<Option<String>, git2::Error> {
+    let repo = Repository::discover(path)?;
+    let Some(relative) = relative_path(&repo, path) else {
+        return Ok(None);
+    };
+    let index = repo.index()?;
+    let Some(entry) = index.get_path(relative, 0) else {
+        return Ok(None);
+    };
+    let blob = repo.find_blob(entry.id)?;
+    Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+}
+
+/// HEAD 中的文件内容，文件是新加的时返回 None
+pub fn head_text(path: &Path) -> Result<Option<String>, git2::Error> {
+    let repo = Repository::discover(path)?;
+    let Some(relative) = relative_path(&repo, path) else {
+        return Ok(None);
+    };
+    let tree = repo.head()?.peel_to_tree()?;
+    let entry = match tree.get_path(relative) {
+        Ok(entry) => entry,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let blob = entry.to_object(&repo)?.peel_to_blob()?;
+    Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+}
+
+/// 只把一处改动写入暂存区，base 是当前暂存区中的内容
+pub fn stage_hunk(path: &Path, base: &str, current: &str, hunk: &Hunk) -> Result<(), git2::Error> {
+    let repo = Repository::discover(path)?;
+    let relative = relative_path(&repo, path)
+        .ok_or_else(|| git2::Error::from_str("file is outside of the work tree"))?;
+    let mut index = repo.index()?;
+    let entry = index
+        .get_path(relative, 0)
+        .ok_or_else(|| git2::Error::from_str("file is not tracked"))?;
+    index.add_frombuffer(&entry, apply_hunk(base, current, hunk).as_bytes())?;
+    index.write()
+}
+
+fn relative_path<'a>(repo: &Repository, path: &'a Path) -> Option<&'a Path> {
+    path.strip_prefix(repo.workdir()?).ok()
+}
+
+#[test]
+fn test_diff_lines() {
+    let base = "a\nb\nc\nd\ne\n";
+    let current = "a\nB\nc\ne\nf\n";
+    let hunks = diff_lines(base, current);
+    assert_eq!(
+        hunks,
+        vec![
+            Hunk {
+                old: 1..2,
+                new: 1..2
+            },
+            Hunk {
+                old: 3..4,
+                new: 3..3
+            },
+            Hunk {
+                old: 5..5,
+                new: 4..5
+            },
+        ]
+    );
+    let kinds: Vec<HunkKind> = hunks.iter().map(Hunk::kind).collect();
+    assert_eq!(
+        kinds,
+        vec![HunkKind::Modified, HunkKind::Deleted, HunkKind::Added]
+    );
+    assert_eq!(hunk_at_line(&hunks, 3), Some(1));
+    assert_eq!(hunk_at_line(&hunks, 2), None);
+    assert_eq!(apply_hunk(base, current, &hunks[0]), "a\nB\nc\nd\ne\n");
+    assert_eq!(apply_hunk(base, current, &hunks[1]), "a\nb\nc\ne\n");
+    assert_eq!(apply_hunk(base, current, &hunks[2]), "a\nb\nc\nd\ne\nf\n");
+}
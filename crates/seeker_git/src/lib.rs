@@ -0,0 +1,10 @@
+pub mod blame;
+pub mod branch;
+pub mod commit;
+pub mod diff;
+pub mod plugin;
+pub mod status;
+pub mod watcher;
+
+pub use git2::{Error as GitError, Oid};
+pub use plugin::SeekerGitPlugin;
@@ -0,0 +1,200 @@
+use crate::status::RepoStatus;
+use crate::watcher::{is_git_internal, ProjectWatcher};
+use bevy::prelude::*;
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake};
+use seeker_resource::project_list::{CurrentProject, ProjectListResource};
+use seeker_state::{SeekerHomeSubLoadState, SeekerState};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::TryRecvError;
+use std::sync::Mutex;
+
+#[derive(Component)]
+pub struct SeekerGitPlugin;
+
+/// 项目中的文件发生了变化（不含被忽略的文件）
+#[derive(Message, Clone, Debug)]
+pub struct ProjectFilesChanged {
+    pub paths: Vec<PathBuf>,
+}
+
+/// 当前项目的 git 状态，文件变化后在后台重新计算
+#[derive(Resource, Default)]
+pub struct GitStatus {
+    pub status: Option<RepoStatus>,
+    root: Option<PathBuf>,
+    running: bool,
+    pending: bool,
+    receiver: Option<BackgroundReceiver<Option<RepoStatus>>>,
+    watcher: Option<Mutex<ProjectWatcher>>,
+}
+
+impl GitStatus {
+    pub fn refresh(&mut self, wake: &BackgroundWake) {
+        let Some(root) = self.root.clone() else {
+            return;
+        };
+        if self.running {
+            self.pending = true;
+            return;
+        }
+        self.running = true;
+        self.pending = false;
+        self.receiver = Some(wake.spawn(move || {
+            RepoStatus::open(&root)
+                .inspect_err(|err| error!("git status {}: {err}", root.display()))
+                .ok()
+                .flatten()
+        }));
+    }
+}
+
+/// 首页项目列表中显示的分支和是否有改动
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitSummary {
+    pub branch: Option<String>,
+    pub dirty: bool,
+}
+
+/// key 为 Project.path
+#[derive(Resource, Default)]
+pub struct ProjectGitSummaries {
+    pub summaries: HashMap<String, GitSummary>,
+    receiver: Option<BackgroundReceiver<HashMap<String, GitSummary>>>,
+}
+
+impl Plugin for SeekerGitPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BackgroundWake>()
+            .init_resource::<GitStatus>()
+            .init_resource::<ProjectGitSummaries>()
+            .add_message::<ProjectFilesChanged>()
+            .add_systems(OnEnter(SeekerState::Edit), Self::enter)
+            .add_systems(OnExit(SeekerState::Edit), Self::exit)
+            .add_systems(
+                Update,
+                (Self::receive_changes, Self::receive_status)
+                    .chain()
+                    .run_if(in_state(SeekerState::Edit)),
+            )
+            .add_systems(OnEnter(SeekerHomeSubLoadState::Loaded), Self::summarize)
+            .add_systems(
+                Update,
+                Self::receive_summaries.run_if(in_state(SeekerState::Home)),
+            );
+    }
+}
+
+impl SeekerGitPlugin {
+    fn enter(
+        mut git: ResMut<GitStatus>,
+        current_project: Res<CurrentProject>,
+        wake: Res<BackgroundWake>,
+    ) {
+        let Some(project) = current_project.project.as_ref() else {
+            return;
+        };
+        let root = PathBuf::from(&project.path);
+        git.watcher = ProjectWatcher::new(&root, &wake)
+            .inspect_err(|err| error!("watch {}: {err}", root.display()))
+            .ok()
+            .map(Mutex::new);
+        git.root = Some(root);
+        git.refresh(&wake);
+    }
+
+    fn exit(mut git: ResMut<GitStatus>) {
+        *git = GitStatus::default();
+    }
+
+    /// 被忽略文件的变化（例如 target 目录）不触发刷新
+    fn receive_changes(
+        mut git: ResMut<GitStatus>,
+        wake: Res<BackgroundWake>,
+        mut changed: MessageWriter<ProjectFilesChanged>,
+    ) {
+        let Some(watcher) = git.watcher.as_ref() else {
+            return;
+        };
+        let paths = watcher.lock().unwrap().changes();
+        if paths.is_empty() {
+            return;
+        }
+        let is_ignored = |path: &Path| {
+            git.status
+                .as_ref()
+                .is_some_and(|status| status.is_ignored(path))
+        };
+        let mut refresh = false;
+        let mut files = vec![];
+        for path in paths {
+            if is_git_internal(&path) {
+                refresh = true;
+            } else if !is_ignored(&path) {
+                refresh = true;
+                files.push(path);
+            }
+        }
+        if !files.is_empty() {
+            changed.write(ProjectFilesChanged { paths: files });
+        }
+        if refresh {
+            git.bypass_change_detection().refresh(&wake);
+        }
+    }
+
+    fn receive_status(mut git: ResMut<GitStatus>, wake: Res<BackgroundWake>) {
+        let Some(receiver) = git.receiver.as_ref() else {
+            return;
+        };
+        let status = match receiver.try_recv() {
+            Ok(status) => status,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => None,
+        };
+        git.status = status;
+        git.running = false;
+        git.receiver = None;
+        if git.pending {
+            git.refresh(&wake);
+        }
+    }
+
+    fn summarize(
+        mut summaries: ResMut<ProjectGitSummaries>,
+        project_list: Res<ProjectListResource>,
+        wake: Res<BackgroundWake>,
+    ) {
+        let paths: Vec<String> = project_list
+            .projects
+            .iter()
+            .map(|project| project.path.clone())
+            .collect();
+        summaries.receiver = Some(wake.spawn(move || {
+            paths
+                .into_iter()
+                .filter_map(|path| {
+                    let status = RepoStatus::open(Path::new(&path)).ok().flatten()?;
+                    let summary = GitSummary {
+                        dirty: status.is_dirty(),
+                        branch: status.branch,
+                    };
+                    Some((path, summary))
+                })
+                .collect()
+        }));
+    }
+
+    fn receive_summaries(mut summaries: ResMut<ProjectGitSummaries>) {
+        let Some(receiver) = summaries.receiver.as_ref() else {
+            return;
+        };
+        let result = receiver.try_recv();
+        match result {
+            Ok(result) => summaries.summaries = result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {}
+        }
+        summaries.receiver = None;
+    }
+}
@@ -0,0 +1,207 @@
+use git2::{ErrorCode, Repository, Status, StatusOptions};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 文件相对于 HEAD 和暂存区的状态，按显示优先级从低到高排列
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FileStatus {
+    Ignored,
+    Untracked,
+    Added,
+    Renamed,
+    Deleted,
+    Modified,
+    Conflicted,
+}
+
+impl FileStatus {
+    fn from_status(status: Status) -> Option<Self> {
+        if status.is_conflicted() {
+            Some(Self::Conflicted)
+        } else if status.is_ignored() {
+            Some(Self::Ignored)
+        } else if status.is_wt_new() && !status.is_index_new() {
+            Some(Self::Untracked)
+        } else if status.is_index_new() {
+            Some(Self::Added)
+        } else if status.is_index_deleted() || status.is_wt_deleted() {
+            Some(Self::Deleted)
+        } else if status.is_index_renamed() || status.is_wt_renamed() {
+            Some(Self::Renamed)
+        } else if status.is_index_modified()
+            || status.is_wt_modified()
+            || status.is_index_typechange()
+            || status.is_wt_typechange()
+        {
+            Some(Self::Modified)
+        } else {
+            None
+        }
+    }
+
+    /// 文件树中显示的字母
+    pub fn letter(&self) -> &'static str {
+        match self {
+            Self::Ignored => "",
+            Self::Untracked => "U",
+            Self::Added => "A",
+            Self::Renamed => "R",
+            Self::Deleted => "D",
+            Self::Modified => "M",
+            Self::Conflicted => "!",
+        }
+    }
+}
+
+/// 一个仓库工作区的状态，路径都是绝对路径
+#[derive(Clone, Debug, Default)]
+pub struct RepoStatus {
+    pub workdir: PathBuf,
+    /// 当前分支，分离 HEAD 时是提交的短哈希
+    pub branch: Option<String>,
+    pub files: HashMap<PathBuf, FileStatus>,
+    /// 目录取其中优先级最高的文件状态，忽略的文件不计入
+    dirs: HashMap<PathBuf, FileStatus>,
+}
+
+impl RepoStatus {
+    /// 计算 path 所在仓库的状态，path 不在仓库中时返回 Ok(None)
+    pub fn open(path: &Path) -> Result<Option<Self>, git2::Error> {
+        let repo = match Repository::discover(path) {
+            Ok(repo) => repo,
+            Err(err) if err.code() == ErrorCode::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let Some(workdir) = repo.workdir().map(Path::to_path_buf) else {
+            return Ok(None);
+        };
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(true)
+            .recurse_ignored_dirs(false)
+            .exclude_submodules(true)
+            .renames_head_to_index(true);
+        let mut status = Self {
+            branch: branch(&repo)?,
+            workdir,
+            ..Default::default()
+        };
+        for entry in repo.statuses(Some(&mut options))?.iter() {
+            let (Some(path), Some(file_status)) =
+                (entry.path(), FileStatus::from_status(entry.status()))
+            else {
+                continue;
+            };
+            // 忽略的目录以 `/` 结尾
+            let path = status.workdir.join(path.trim_end_matches('/'));
+            status.insert(path, file_status);
+        }
+        Ok(Some(status))
+    }
+
+    fn insert(&mut self, path: PathBuf, file_status: FileStatus) {
+        if file_status != FileStatus::Ignored {
+            for dir in path.ancestors().skip(1) {
+                if !dir.starts_with(&self.workdir) {
+                    break;
+                }
+                let entry = self.dirs.entry(dir.to_path_buf()).or_insert(file_status);
+                *entry = (*entry).max(file_status);
+            }
+        }
+        self.files.insert(path, file_status);
+    }
+
+    /// 文件或者目录的状态，被忽略目录中的文件也返回 Ignored
+    pub fn status_of(&self, path: &Path) -> Option<FileStatus> {
+        if let Some(status) = self.files.get(path).or_else(|| self.dirs.get(path)) {
+            return Some(*status);
+        }
+        self.is_ignored(path).then_some(FileStatus::Ignored)
+    }
+
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        path.ancestors()
+            .take_while(|dir| dir.starts_with(&self.workdir))
+            .any(|dir| self.files.get(dir) == Some(&FileStatus::Ignored))
+    }
+
+    /// 是否有未提交的改动
+    pub fn is_dirty(&self) -> bool {
+        self.files
+            .values()
+            .any(|status| *status != FileStatus::Ignored)
+    }
+}
+
+fn branch(repo: &Repository) -> Result<Option<String>, git2::Error> {
+    let head = match repo.head() {
+        Ok(head) => head,
+        // 还没有提交时 HEAD 指向一个不存在的分支
+        Err(err) if err.code() == ErrorCode::UnbornBranch => {
+            let head = repo.find_reference("HEAD")?;
+            return Ok(head
+                .symbolic_target()
+                .map(|target| target.trim_start_matches("refs/heads/").to_string()));
+        }
+        Err(err) => return Err(err),
+    };
+    if head.is_branch() {
+        return Ok(head.shorthand().map(str::to_string));
+    }
+    Ok(head
+        .target()
+        .map(|oid| oid.to_string().chars().take(7).collect()))
+}
+
+#[test]
+fn test_repo_status() {
+    use git2::{RepositoryInitOptions, Signature};
+    let root = std::env::temp_dir().join(format!("seeker_git_status_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let repo =
+        Repository::init_opts(&root, RepositoryInitOptions::new().initial_head("main")).unwrap();
+    let root = repo.workdir().unwrap().to_path_buf();
+    std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(".gitignore")).unwrap();
+    index.add_path(Path::new("a.txt")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = Signature::now("seeker", "seeker@example.com").unwrap();
+    repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+        .unwrap();
+
+    let status = RepoStatus::open(&root).unwrap().unwrap();
+    assert_eq!(status.branch.as_deref(), Some("main"));
+    assert!(!status.is_dirty());
+
+    std::fs::write(root.join("a.txt"), "b\n").unwrap();
+    std::fs::create_dir_all(root.join("src/nested")).unwrap();
+    std::fs::write(root.join("src/nested/c.rs"), "").unwrap();
+    std::fs::create_dir_all(root.join("target/debug")).unwrap();
+    std::fs::write(root.join("target/debug/x"), "").unwrap();
+    let status = RepoStatus::open(&root).unwrap().unwrap();
+    assert!(status.is_dirty());
+    assert_eq!(
+        status.status_of(&root.join("a.txt")),
+        Some(FileStatus::Modified)
+    );
+    assert_eq!(
+        status.status_of(&root.join("src/nested/c.rs")),
+        Some(FileStatus::Untracked)
+    );
+    assert_eq!(
+        status.status_of(&root.join("src")),
+        Some(FileStatus::Untracked)
+    );
+    assert_eq!(
+        status.status_of(&root.join("target/debug/x")),
+        Some(FileStatus::Ignored)
+    );
+    assert_eq!(status.status_of(&root.join(".gitignore")), None);
+    std::fs::remove_dir_all(&root).unwrap();
+}
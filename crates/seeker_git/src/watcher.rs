@@ -0,0 +1,169 @@
+use git2::Repository;
+use notify::event::EventKind;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake};
+use std::path::{Component, Path, PathBuf};
+
+/// 监听项目目录，收到变化时唤醒事件循环。
+/// 逐个目录监听以跳过被忽略的目录（例如构建时大量变化的 target）
+pub struct ProjectWatcher {
+    watcher: RecommendedWatcher,
+    /// 判断目录是否被忽略，不是 git 仓库时为 None
+    repo: Option<Repository>,
+    receiver: BackgroundReceiver<Vec<PathBuf>>,
+}
+
+impl ProjectWatcher {
+    pub fn new(root: &Path, wake: &BackgroundWake) -> notify::Result<Self> {
+        let (sender, receiver) = wake.channel();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            let paths: Vec<PathBuf> = event
+                .paths
+                .into_iter()
+                .filter(|path| is_relevant(path))
+                .collect();
+            if !paths.is_empty() {
+                sender.send(paths);
+            }
+        })?;
+        let mut watcher = Self {
+            watcher,
+            repo: Repository::discover(root).ok(),
+            receiver,
+        };
+        watcher.watch_tree(root)?;
+        // .git 中的暂存区、HEAD 和引用
+        let git = root.join(".git");
+        if git.is_dir() {
+            watcher.watcher.watch(&git, RecursiveMode::NonRecursive)?;
+            let refs = git.join("refs");
+            if refs.is_dir() {
+                watcher.watcher.watch(&refs, RecursiveMode::Recursive)?;
+            }
+        }
+        Ok(watcher)
+    }
+
+    /// 监听 dir 和其中没有被忽略的子目录
+    fn watch_tree(&mut self, dir: &Path) -> notify::Result<()> {
+        for dir in watched_dirs(dir, self.repo.as_ref()) {
+            self.watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        }
+        Ok(())
+    }
+
+    /// 取出目前为止变化的路径，新建的目录开始监听
+    pub fn changes(&mut self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.receiver.drain().0.concat();
+        paths.sort();
+        paths.dedup();
+        for path in &paths {
+            if path.is_dir()
+                && !is_git_internal(path)
+                && let Err(err) = self.watch_tree(path)
+            {
+                bevy::log::warn!("watch {}: {err}", path.display());
+            }
+        }
+        paths
+    }
+}
+
+/// dir 和其中需要监听的子目录，跳过 `.git` 和被忽略的目录
+fn watched_dirs(dir: &Path, repo: Option<&Repository>) -> Vec<PathBuf> {
+    let is_ignored = |path: &Path| {
+        let Some(repo) = repo else {
+            return false;
+        };
+        let Some(relative) = repo
+            .workdir()
+            .and_then(|workdir| path.strip_prefix(workdir).ok())
+        else {
+            return false;
+        };
+        // 以 / 结尾才能匹配 `target/` 这样只针对目录的规则
+        let relative = format!("{}/", relative.to_string_lossy());
+        repo.is_path_ignored(relative).unwrap_or_default()
+    };
+    let mut dirs = vec![];
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        if is_git_internal(&dir) || is_ignored(&dir) {
+            continue;
+        }
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                // 不跟随符号链接，避免循环
+                if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                    stack.push(entry.path());
+                }
+            }
+        }
+        dirs.push(dir);
+    }
+    dirs.sort();
+    dirs
+}
+
+/// `.git` 目录下只关心暂存区、HEAD 和引用（提交、重置时分支指向的提交会变化）
+fn is_relevant(path: &Path) -> bool {
+    let mut components = path.components();
+    if !components.any(|component| component == Component::Normal(".git".as_ref())) {
+        return true;
+    }
+    let rest: Vec<&str> = components
+        .filter_map(|component| component.as_os_str().to_str())
+        .collect();
+    matches!(
+        rest.as_slice(),
+        ["index" | "HEAD" | "packed-refs"] | ["refs", ..]
+    )
+}
+
+/// 路径是否在 `.git` 目录中
+pub fn is_git_internal(path: &Path) -> bool {
+    path.components()
+        .any(|component| component == Component::Normal(".git".as_ref()))
+}
+
+#[test]
+fn test_watched_dirs() {
+    let root = std::env::temp_dir().join(format!("seeker_git_watcher_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let repo = Repository::init(&root).unwrap();
+    let root = repo.workdir().unwrap().to_path_buf();
+    std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+    for dir in ["src/nested", "target/debug", "assets"] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    let relative = |dirs: Vec<PathBuf>| -> Vec<String> {
+        dirs.iter()
+            .map(|dir| {
+                dir.strip_prefix(&root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    };
+    assert_eq!(
+        relative(watched_dirs(&root, Some(&repo))),
+        ["", "assets", "src", "src/nested"]
+    );
+    assert_eq!(relative(watched_dirs(&root, None)).len(), 6);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert!(is_relevant(Path::new("/p/src/main.rs")));
+    assert!(is_relevant(Path::new("/p/.git/index")));
+    assert!(is_relevant(Path::new("/p/.git/HEAD")));
+    assert!(is_relevant(Path::new("/p/.git/packed-refs")));
+    assert!(is_relevant(Path::new("/p/.git/refs/heads/feature/x")));
+    assert!(!is_relevant(Path::new("/p/.git/objects/ab/cdef")));
+    assert!(!is_relevant(Path::new("/p/.git/logs/HEAD")));
+}
@@ -6,13 +6,82 @@ use bevy::prelude::*;
 use bevy::ui::FocusPolicy;
 use bevy::window::{WindowRef, WindowResolution};
 use seeker_config::SEEKER_CONFIG;
-use seeker_resource::file::{get_files, CurrentFile, File, Level};
-use seeker_resource::fonts::MAPLE_MONO_BOLD_ITALIC;
+use seeker_editor::document::language_id;
+use seeker_editor::hex::{hex_row, is_binary, BYTES_PER_ROW};
+use seeker_editor::highlight::{highlight, TokenKind};
+use seeker_resource::assets::local_asset_path;
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake};
+use seeker_resource::clipboard::SeekerClipboard;
+use seeker_resource::file::{
+    get_files, CurrentFile, File, FileDialogPicked, FileDialogPurpose, Level,
+};
+use seeker_resource::fonts::{MAPLE_MONO_BOLD, MAPLE_MONO_BOLD_ITALIC};
 use seeker_resource::SeekerResource;
 use seeker_sqlite::seeker::project::Project;
 use seeker_state::{SeekerFileDialogFnState, SeekerNewFolderState, SeekerState};
 use seeker_trait::SeekerTrait;
+use std::io::Read;
 use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::mpsc::TryRecvError;
+
+/// 文本文件预览的行数
+const PREVIEW_LINES: usize = 40;
+/// 二进制文件预览的字节数
+const PREVIEW_BYTES: usize = 256;
+/// 预览最多读取的字节数
+const PREVIEW_READ: u64 = 64 * 1024;
+
+/// 后台读取的文件信息和预览
+struct LoadedPreview {
+    metadata: Option<std::fs::Metadata>,
+    preview: std::io::Result<FilePreview>,
+}
+
+/// 正在后台读取预览的文件
+#[derive(Component)]
+struct PendingPreview(BackgroundReceiver<LoadedPreview>);
+
+/// 选中文件的预览内容
+enum FilePreview {
+    Text(String),
+    /// 图片由资源加载器在后台线程读取和解码
+    Image,
+    Binary(Vec<u8>),
+}
+
+impl FilePreview {
+    fn read(path: &Path) -> std::io::Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if matches!(extension.as_str(), "png" | "jpg" | "jpeg") {
+            return Ok(Self::Image);
+        }
+        let mut bytes = vec![];
+        std::fs::File::open(path)?
+            .take(PREVIEW_READ)
+            .read_to_end(&mut bytes)?;
+        if is_binary(&bytes) {
+            bytes.truncate(PREVIEW_BYTES);
+            return Ok(Self::Binary(bytes));
+        }
+        let text = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = text.lines().take(PREVIEW_LINES).collect();
+        Ok(Self::Text(lines.join("\n")))
+    }
+
+    /// 预览所在列的宽度，十六进制转储一行需要 80 个字符左右
+    fn width(&self) -> f32 {
+        match self {
+            Self::Text(_) => 560.,
+            Self::Image => 400.,
+            Self::Binary(_) => 620.,
+        }
+    }
+}
 
 #[derive(Component)]
 pub struct FileDialogPlugin;
@@ -35,6 +104,9 @@ pub struct FileDialogFnButton;
 impl Plugin for FileDialogPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(CurrentFile::default())
+            .init_resource::<FileDialogPurpose>()
+            .init_resource::<BackgroundWake>()
+            .add_message::<FileDialogPicked>()
             .insert_state(SeekerNewFolderState::None)
             .add_plugins(NewFolderPlugin)
             .add_systems(OnEnter(SeekerFileDialogFnState::Open), Self::enter)
@@ -43,6 +115,7 @@ impl Plugin for FileDialogPlugin {
                 (
                     Self::button_on_pressed_changed_color::<FileDialogButton>,
                     Self::update_file,
+                    Self::receive_preview,
                     Self::update_fn,
                 )
                     .run_if(in_state(SeekerFileDialogFnState::Open)),
@@ -138,6 +211,7 @@ impl FileDialogPlugin {
                                 display: Display::Flex,
                                 flex_direction: FlexDirection::Row,
                                 width: Val::Percent(70.),
+                                column_gap: Val::Px(10.),
                                 ..default()
                             }),
                         )
@@ -149,6 +223,14 @@ impl FileDialogPlugin {
                                 &res,
                                 &assets,
                             );
+                            Self::ui_button(
+                                parent,
+                                FileDialogFnButton,
+                                "CopyPath",
+                                "Copy path",
+                                &res,
+                                &assets,
+                            );
                         });
                 })
                 .with_children(|parent| {
@@ -176,6 +258,7 @@ impl FileDialogPlugin {
     fn update_file(
         mut commands: Commands,
         asset: Res<AssetServer>,
+        wake: Res<BackgroundWake>,
         mut current_file: ResMut<CurrentFile>,
         mut query: ParamSet<(
             Query<(&mut File, &Interaction), (Changed<Interaction>, With<FileDialogButton>)>,
@@ -213,16 +296,20 @@ impl FileDialogPlugin {
             } else {
                 if let (Some(file), Some(entity)) = (file_, entity) {
                     commands.entity(entity).with_children(|parent| {
-                        Self::render_file(file, parent, &asset, dialog_level);
+                        Self::render_file(file, parent, &wake, dialog_level);
                     });
                 }
             }
         }
     }
+    #[allow(clippy::too_many_arguments)]
     fn update_fn(
         mut commands: Commands,
         current_file: Res<CurrentFile>,
+        mut clipboard: ResMut<SeekerClipboard>,
         mut project_list: ResMut<ProjectListResource>,
+        mut purpose: ResMut<FileDialogPurpose>,
+        mut picked: MessageWriter<FileDialogPicked>,
         mut state: ResMut<NextState<SeekerState>>,
         mut dialog_file_state: ResMut<NextState<SeekerFileDialogFnState>>,
         mut new_folder_state: ResMut<NextState<SeekerNewFolderState>>,
@@ -239,8 +326,16 @@ impl FileDialogPlugin {
                         window.visible = true;
                         // state.set(SeekerState::Edit);
                         dialog_file_state.set(SeekerFileDialogFnState::None);
+                        let purpose = std::mem::take(&mut *purpose);
                         if let Some(file) = current_file.file.as_ref() {
-                            if file.is_dir {
+                            if purpose != FileDialogPurpose::AddProject {
+                                if !file.is_dir {
+                                    picked.write(FileDialogPicked {
+                                        purpose,
+                                        path: file.path.clone(),
+                                    });
+                                }
+                            } else if file.is_dir {
                                 let data = Project {
                                     project_name: file.filename.to_string(),
                                     path: file.path.to_string_lossy().to_string(),
@@ -255,10 +350,16 @@ impl FileDialogPlugin {
                     "Cancel" => {
                         window.visible = true;
                         dialog_file_state.set(SeekerFileDialogFnState::None);
+                        *purpose = FileDialogPurpose::default();
                     }
                     "NewFolder" => {
                         new_folder_state.set(SeekerNewFolderState::Open);
                     }
+                    "CopyPath" => {
+                        if let Some(file) = current_file.file.as_ref() {
+                            clipboard.copy(file.path.to_string_lossy());
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -332,18 +433,25 @@ impl FileDialogPlugin {
             });
     }
 
+    /// 文件信息和预览在后台读取，避免在慢速或者网络文件系统上卡住界面
     fn render_file(
         file: File,
         parent: &mut RelatedSpawnerCommands<ChildOf>,
-        asset: &Res<AssetServer>,
+        wake: &BackgroundWake,
         level: Level,
     ) {
+        let path = file.path.clone();
+        let receiver = wake.spawn(move || LoadedPreview {
+            metadata: std::fs::metadata(&path).ok(),
+            preview: FilePreview::read(&path),
+        });
         parent
             .spawn((
                 level,
                 Node {
                     display: Display::Flex,
                     flex_direction: FlexDirection::Column,
+                    width: Val::Px(400.),
                     min_width: Val::Px(400.),
                     overflow: Overflow::scroll(),
                     padding: UiRect::new(Val::Px(10.), Val::Px(10.), Val::Px(0.), Val::Px(0.)),
@@ -353,76 +461,184 @@ impl FileDialogPlugin {
                 BorderColor::all(Color::srgb_u8(78, 81, 87)),
             ))
             .with_children(|parent| {
-                parent
-                    .spawn((
-                        file.clone(),
-                        Node {
-                            width: Val::Percent(100.),
-                            height: Val::Percent(100.),
-                            display: Display::Flex,
-                            flex_direction: FlexDirection::Column,
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
+                parent.spawn((
+                    file,
+                    PendingPreview(receiver),
+                    Node {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(2.),
+                        ..default()
+                    },
+                ));
+            });
+    }
+
+    /// 读取完成后显示文件信息和预览，预览所在列按内容调整宽度
+    fn receive_preview(
+        mut commands: Commands,
+        query: Query<(Entity, &File, &PendingPreview, &ChildOf)>,
+        mut columns: Query<&mut Node>,
+        asset: Res<AssetServer>,
+        res: Res<SeekerResource>,
+    ) {
+        for (entity, file, pending, child_of) in query.iter() {
+            let loaded = match pending.0.try_recv() {
+                Ok(loaded) => loaded,
+                Err(TryRecvError::Empty) => continue,
+                Err(TryRecvError::Disconnected) => {
+                    commands.entity(entity).remove::<PendingPreview>();
+                    continue;
+                }
+            };
+            if let (Ok(preview), Ok(mut column)) =
+                (loaded.preview.as_ref(), columns.get_mut(child_of.parent()))
+            {
+                column.width = Val::Px(preview.width());
+                column.min_width = Val::Px(preview.width());
+            }
+            let mut entity = commands.entity(entity);
+            entity.remove::<PendingPreview>();
+            entity.with_children(|parent| {
+                if let Some(metadata) = loaded.metadata {
+                    parent.spawn((
+                        Text::new(format!("filename: {}", file.filename)),
+                        TextFont {
+                            font: asset.load(MAPLE_MONO_BOLD_ITALIC),
+                            font_size: 14.,
                             ..default()
                         },
-                    ))
-                    .with_children(|parent| {
-                        if let Ok(metadata) = std::fs::metadata(&file.path) {
-                            parent.spawn((
-                                Text::new(format!("filename: {}", file.filename)),
-                                TextFont {
-                                    font: asset.load(MAPLE_MONO_BOLD_ITALIC),
-                                    font_size: 14.,
-                                    ..default()
-                                },
-                            ));
-                            parent.spawn((
-                                Text::new(format!("size: {}", metadata.size())),
-                                TextFont {
-                                    font: asset.load(MAPLE_MONO_BOLD_ITALIC),
-                                    font_size: 14.,
-                                    ..default()
-                                },
-                            ));
-                            parent.spawn((
-                                Text::new(format!(
-                                    "created_time: {}",
-                                    system_time_to_datetime_string(metadata.created().unwrap())
-                                )),
-                                TextFont {
-                                    font: asset.load(MAPLE_MONO_BOLD_ITALIC),
-                                    font_size: 14.,
-                                    ..default()
-                                },
-                            ));
-                            parent.spawn((
-                                Text::new(format!(
-                                    "modified_time: {}",
-                                    system_time_to_datetime_string(metadata.modified().unwrap())
-                                )),
-                                TextFont {
-                                    font: asset.load(MAPLE_MONO_BOLD_ITALIC),
-                                    font_size: 14.,
-                                    ..default()
-                                },
-                            ));
-                        }
-                    });
+                    ));
+                    parent.spawn((
+                        Text::new(format!("size: {}", metadata.size())),
+                        TextFont {
+                            font: asset.load(MAPLE_MONO_BOLD_ITALIC),
+                            font_size: 14.,
+                            ..default()
+                        },
+                    ));
+                    parent.spawn((
+                        Text::new(format!(
+                            "created_time: {}",
+                            system_time_to_datetime_string(metadata.created().unwrap())
+                        )),
+                        TextFont {
+                            font: asset.load(MAPLE_MONO_BOLD_ITALIC),
+                            font_size: 14.,
+                            ..default()
+                        },
+                    ));
+                    parent.spawn((
+                        Text::new(format!(
+                            "modified_time: {}",
+                            system_time_to_datetime_string(metadata.modified().unwrap())
+                        )),
+                        TextFont {
+                            font: asset.load(MAPLE_MONO_BOLD_ITALIC),
+                            font_size: 14.,
+                            ..default()
+                        },
+                    ));
+                }
+                if let Ok(preview) = loaded.preview {
+                    Self::render_preview(&file.path, preview, parent, &asset, &res);
+                }
             });
+        }
     }
-}
 
-// 估算显示宽度（中文等宽字符算作2个单位宽度）
-fn char_display_width(ch: char) -> usize {
-    if ch.len_utf8() > 1 {
-        2
-    } else {
-        1
+    fn render_preview(
+        path: &Path,
+        preview: FilePreview,
+        parent: &mut RelatedSpawnerCommands<ChildOf>,
+        asset: &Res<AssetServer>,
+        res: &Res<SeekerResource>,
+    ) {
+        let font = TextFont {
+            font: asset.load(MAPLE_MONO_BOLD),
+            font_size: 12.,
+            ..default()
+        };
+        let background = match preview {
+            FilePreview::Image => Color::NONE,
+            _ => res.colors.home_menu,
+        };
+        let mut node = parent.spawn((
+            Node {
+                margin: UiRect::top(Val::Px(10.)),
+                padding: UiRect::all(Val::Px(8.)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BorderRadius::all(Val::Px(3.)),
+            BackgroundColor(background),
+        ));
+        match preview {
+            FilePreview::Image => {
+                node.with_child((
+                    ImageNode::new(asset.load(local_asset_path(path))),
+                    Node {
+                        max_width: Val::Percent(100.),
+                        max_height: Val::Px(260.),
+                        ..default()
+                    },
+                ));
+            }
+            FilePreview::Text(text) => {
+                let language = language_id(path);
+                node.with_children(|parent| {
+                    parent
+                        .spawn((
+                            Text::default(),
+                            font.clone(),
+                            TextLayout::new_with_no_wrap(),
+                            TextColor(res.colors.home_font_color),
+                        ))
+                        .with_children(|parent| {
+                            for (index, line) in text.lines().enumerate() {
+                                if index > 0 {
+                                    parent.spawn((TextSpan::new("\n"), font.clone()));
+                                }
+                                for (kind, token) in highlight(line, language) {
+                                    let color = match kind {
+                                        TokenKind::Plain => res.colors.home_font_color,
+                                        TokenKind::Keyword => res.colors.syntax_keyword,
+                                        TokenKind::String => res.colors.syntax_string,
+                                        TokenKind::Number => res.colors.syntax_number,
+                                        TokenKind::Comment => res.colors.syntax_comment,
+                                    };
+                                    parent.spawn((
+                                        TextSpan::new(token),
+                                        font.clone(),
+                                        TextColor(color),
+                                    ));
+                                }
+                            }
+                        });
+                });
+            }
+            FilePreview::Binary(bytes) => {
+                let dump: Vec<String> = bytes
+                    .chunks(BYTES_PER_ROW)
+                    .enumerate()
+                    .map(|(row, chunk)| hex_row((row * BYTES_PER_ROW) as u64, chunk))
+                    .collect();
+                node.with_child((
+                    Text::new(dump.join("\n")),
+                    font,
+                    TextLayout::new_with_no_wrap(),
+                    TextColor(res.colors.home_font_grey_color),
+                ));
+            }
+        }
     }
 }
 
 use bevy::tasks::IoTaskPool;
 use chrono::{DateTime, Utc};
+use seeker_editor::width::char_display_width;
 use std::time::SystemTime;
 use seeker_resource::project_list::ProjectListResource;
 
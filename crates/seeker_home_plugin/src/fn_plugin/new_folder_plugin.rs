@@ -1,6 +1,5 @@
 use crate::fn_plugin::file_dialog_plugin::FileDialogWindow;
 use bevy::camera::RenderTarget;
-use bevy::input::keyboard::{Key, KeyboardInput};
 use bevy::picking::hover::Hovered;
 use bevy::prelude::*;
 use bevy::ui::FocusPolicy;
@@ -10,8 +9,12 @@ use seeker_resource::file::CurrentFile;
 use seeker_resource::fonts::MAPLE_MONO_BOLD_ITALIC;
 use seeker_resource::SeekerResource;
 use seeker_state::SeekerNewFolderState;
+use seeker_trait::text_input::{SeekerTextInput, TextInputCancel, TextInputSubmit};
 use seeker_trait::SeekerTrait;
-use std::mem;
+use std::path::Path;
+
+/// 文件夹名的最大字符数
+const FOLDER_NAME_MAX_CHARS: usize = 255;
 
 #[derive(Component)]
 pub struct NewFolderPlugin;
@@ -28,24 +31,12 @@ pub struct NewFolderInput;
 #[require(Node, FocusPolicy::Block, Interaction)]
 pub struct NewFolderFnButton;
 
-#[derive(Component)]
-struct Bubble {
-    timer: Timer,
-}
-
 impl Plugin for NewFolderPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(SeekerNewFolderState::Open), Self::on_enter)
             .add_systems(
                 Update,
-                (
-                    Self::toggle_ime,
-                    Self::listen_ime_events,
-                    Self::listen_keyboard_input_events,
-                    Self::bubbling_text,
-                    Self::update_fn,
-                )
-                    .run_if(in_state(SeekerNewFolderState::Open)),
+                (Self::input_events, Self::update_fn).run_if(in_state(SeekerNewFolderState::Open)),
             )
             .add_observer(
                 Self::button_on_hovered_changed_color::<Insert, Hovered, NewFolderFnButton>,
@@ -116,6 +107,17 @@ impl NewFolderPlugin {
                 .with_children(|parent| {
                     parent
                         .spawn((
+                            NewFolderInput,
+                            SeekerTextInput::new("Folder name")
+                                .with_max_chars(FOLDER_NAME_MAX_CHARS)
+                                .with_filter(|ch| !matches!(ch, '/' | '\\'))
+                                .with_validator(Self::validate_name)
+                                .with_focus(),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: SEEKER_CONFIG.font_size,
+                                ..default()
+                            },
                             Node {
                                 padding: UiRect::new(
                                     Val::Px(15.),
@@ -125,8 +127,8 @@ impl NewFolderPlugin {
                                 ),
                                 box_sizing: BoxSizing::BorderBox,
                                 display: Display::Flex,
-                                justify_content: JustifyContent::Center,
                                 align_items: AlignItems::Center,
+                                overflow: Overflow::clip(),
                                 width: Val::Percent(98.),
                                 height: Val::Px(32.),
                                 border: UiRect::all(Val::Px(1.)),
@@ -135,18 +137,7 @@ impl NewFolderPlugin {
                             BorderRadius::all(Val::Px(3.)),
                             Hovered::default(),
                             BorderColor::all(res.colors.button_border),
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn((
-                                NewFolderInput,
-                                Text::new(""),
-                                TextFont {
-                                    font: font.clone(),
-                                    font_size: SEEKER_CONFIG.font_size,
-                                    ..default()
-                                },
-                            ));
-                        });
+                        ));
                 });
         });
 
@@ -167,101 +158,41 @@ impl NewFolderPlugin {
         });
     }
 
-    fn toggle_ime(
-        input: Res<ButtonInput<MouseButton>>,
-        mut window: Single<&mut Window, With<NewFolderWindow>>,
-    ) {
-        if input.just_pressed(MouseButton::Left) {
-            window.ime_position = window.cursor_position().unwrap();
-            window.ime_enabled = !window.ime_enabled;
-        }
-    }
-
-    fn bubbling_text(
-        mut commands: Commands,
-        mut bubbles: Query<(Entity, &mut Transform, &mut Bubble), With<NewFolderInput>>,
-        time: Res<Time>,
-    ) {
-        for (entity, mut transform, mut bubble) in bubbles.iter_mut() {
-            if bubble.timer.tick(time.delta()).just_finished() {
-                commands.entity(entity).despawn();
-            }
-            transform.translation.y += time.delta_secs() * 100.0;
+    /// 文件夹名不能为空，也不能是 `.` 或 `..`
+    fn validate_name(name: &str) -> Result<(), String> {
+        match name.trim() {
+            "" => Err("Folder name is empty".to_string()),
+            "." | ".." => Err(format!("\"{name}\" is not a valid folder name")),
+            _ => Ok(()),
         }
     }
 
-    fn listen_ime_events(
-        mut ime_reader: MessageReader<Ime>,
-        mut edit_text: Single<&mut Text, With<NewFolderInput>>,
-    ) {
-        for ime in ime_reader.read() {
-            match ime {
-                Ime::Preedit { cursor, .. } if !cursor.is_none() => {}
-                Ime::Preedit { cursor, .. } if cursor.is_none() => {}
-                Ime::Commit { value, .. } => {
-                    if edit_text.len() < 32 {
-                        edit_text.push_str(value);
-                    }
-                }
-                Ime::Enabled { .. } => {}
-                Ime::Disabled { .. } => {}
-                _ => (),
-            }
+    fn create(dir: &Path, name: &str) {
+        if let Err(e) = std::fs::create_dir(dir.join(name)) {
+            error!("create_dir: {e}");
         }
     }
 
-    fn listen_keyboard_input_events(
-        mut commands: Commands,
-        mut keyboard_input_reader: MessageReader<KeyboardInput>,
-        edit_text: Single<(&mut Text, &TextFont), With<NewFolderInput>>,
+    /// Enter 创建，Esc 取消
+    fn input_events(
+        mut submit: MessageReader<TextInputSubmit>,
+        mut cancel: MessageReader<TextInputCancel>,
+        mut state: ResMut<NextState<SeekerNewFolderState>>,
+        res: Res<CurrentFile>,
+        mut window: Single<&mut Window, With<FileDialogWindow>>,
+        input: Single<Entity, With<NewFolderInput>>,
     ) {
-        let (mut text, style) = edit_text.into_inner();
-        for keyboard_input in keyboard_input_reader.read() {
-            // Only trigger changes when the key is first pressed.
-            if !keyboard_input.state.is_pressed() {
-                continue;
-            }
-
-            match (&keyboard_input.logical_key, &keyboard_input.text) {
-                (Key::Enter, _) => {
-                    if text.is_empty() {
-                        continue;
-                    }
-                    let old_value = mem::take(&mut **text);
-
-                    commands.spawn((
-                        Text::new(old_value),
-                        style.clone(),
-                        Bubble {
-                            timer: Timer::from_seconds(5.0, TimerMode::Once),
-                        },
-                    ));
-                }
-                (Key::Backspace, _) => {
-                    text.pop();
-                }
-                (_, Some(inserted_text)) => {
-                    // Make sure the text doesn't have any control characters,
-                    // which can happen when keys like Escape are pressed
-                    if inserted_text.chars().all(Self::is_printable_char) {
-                        if text.len() < 32 {
-                            text.push_str(inserted_text);
-                        }
-                    }
-                }
-                _ => continue,
+        let submitted = submit.read().find(|submit| submit.entity == *input);
+        let cancelled = cancel.read().any(|cancel| cancel.entity == *input);
+        if let Some(submit) = submitted {
+            if let Some(file) = &res.file {
+                Self::create(&file.path, &submit.value);
             }
+        } else if !cancelled {
+            return;
         }
-    }
-
-    // this logic is taken from egui-winit:
-    // https://github.com/emilk/egui/blob/adfc0bebfc6be14cee2068dee758412a5e0648dc/crates/egui-winit/src/lib.rs#L1014-L1024
-    fn is_printable_char(chr: char) -> bool {
-        let is_in_private_use_area = ('\u{e000}'..='\u{f8ff}').contains(&chr)
-            || ('\u{f0000}'..='\u{ffffd}').contains(&chr)
-            || ('\u{100000}'..='\u{10fffd}').contains(&chr);
-
-        !is_in_private_use_area && !chr.is_ascii_control()
+        window.visible = true;
+        state.set(SeekerNewFolderState::None);
     }
 
     fn update_fn(
@@ -269,7 +200,7 @@ impl NewFolderPlugin {
         res: Res<CurrentFile>,
         mut query: Query<(&Name, &Interaction), (Changed<Interaction>, With<NewFolderFnButton>)>,
         mut window: Single<&mut Window, With<FileDialogWindow>>,
-        text: Single<&Text, With<NewFolderInput>>,
+        input: Single<&SeekerTextInput, With<NewFolderInput>>,
     ) {
         for (name, interaction) in query.iter_mut() {
             if *interaction == Interaction::Pressed {
@@ -280,12 +211,14 @@ impl NewFolderPlugin {
                         state.set(SeekerNewFolderState::None);
                     }
                     "Create" => {
+                        if let Some(error) = input.error() {
+                            warn!("{error}");
+                            continue;
+                        }
                         window.visible = true;
                         state.set(SeekerNewFolderState::None);
                         if let Some(file) = &res.file {
-                            if let Err(e) = std::fs::create_dir(file.path.join(text.as_str())) {
-                                error!("create_dir: {e}");
-                            };
+                            Self::create(&file.path, input.value());
                         }
                     }
                     _ => {}
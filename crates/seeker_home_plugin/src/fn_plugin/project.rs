@@ -3,10 +3,15 @@ use bevy::picking::hover::Hovered;
 use bevy::prelude::*;
 use bevy::text::LineHeight;
 use bevy::ui::FocusPolicy;
+use seeker_git::plugin::ProjectGitSummaries;
+use seeker_resource::clipboard::SeekerClipboard;
 use seeker_resource::fonts::MAPLE_MONO_BOLD_ITALIC;
-use seeker_resource::project_list::ProjectListResource;
+use seeker_resource::project_list::{CurrentProject, ProjectListResource};
 use seeker_resource::SeekerResource;
-use seeker_state::{SeekerFileDialogFnState, SeekerHomeSubFnState, SeekerHomeSubLoadState};
+use seeker_sqlite::seeker::project::Project;
+use seeker_state::{
+    SeekerFileDialogFnState, SeekerHomeSubFnState, SeekerHomeSubLoadState, SeekerState,
+};
 use seeker_trait::SeekerTrait;
 
 #[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
@@ -19,6 +24,25 @@ pub struct ProjectItemButton;
 #[require(Node, FocusPolicy::Block, Interaction)]
 pub struct FileDialogButton;
 
+/// 项目列表中的一项，点击后进入编辑界面
+#[derive(Component, Clone)]
+pub struct ProjectListItem {
+    pub project: Project,
+}
+
+/// 项目的分支和改动标记，后台计算完成后更新
+#[derive(Component, Clone)]
+pub struct ProjectGitText {
+    pub path: String,
+}
+
+/// 复制项目路径，点击时不打开项目
+#[derive(Component, Clone)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct ProjectCopyPathButton {
+    pub path: String,
+}
+
 #[derive(Component)]
 pub struct ProjectPlugin;
 
@@ -45,6 +69,24 @@ impl Plugin for ProjectPlugin {
                 Self::update_color_state::<FileDialogButton, SeekerFileDialogFnState>
                     .run_if(in_state(SeekerHomeSubFnState::Project)),
             )
+            .add_systems(
+                Update,
+                Self::open_project_item.run_if(in_state(SeekerHomeSubFnState::Project)),
+            )
+            .add_systems(
+                Update,
+                Self::copy_project_path.run_if(in_state(SeekerHomeSubFnState::Project)),
+            )
+            .add_systems(
+                Update,
+                Self::update_git_text.run_if(
+                    in_state(SeekerHomeSubFnState::Project)
+                        .and(
+                            resource_changed::<ProjectGitSummaries>
+                                .or(any_match_filter::<Added<ProjectGitText>>),
+                        ),
+                ),
+            )
             .add_observer(
                 Self::button_on_hovered_changed_color::<Insert, Hovered, ProjectItemButton>,
             )
@@ -55,6 +97,47 @@ impl Plugin for ProjectPlugin {
 }
 
 impl ProjectPlugin {
+    fn open_project_item(
+        query: Query<(&Interaction, &ProjectListItem), Changed<Interaction>>,
+        mut current_project: ResMut<CurrentProject>,
+        mut state: ResMut<NextState<SeekerState>>,
+    ) {
+        for (interaction, item) in query.iter() {
+            if *interaction == Interaction::Pressed {
+                current_project.project = Some(item.project.clone());
+                state.set(SeekerState::Edit);
+            }
+        }
+    }
+
+    fn copy_project_path(
+        query: Query<(&Interaction, &ProjectCopyPathButton), Changed<Interaction>>,
+        mut clipboard: ResMut<SeekerClipboard>,
+    ) {
+        for (interaction, button) in query.iter() {
+            if *interaction == Interaction::Pressed {
+                clipboard.copy(button.path.clone());
+            }
+        }
+    }
+
+    fn update_git_text(
+        summaries: Res<ProjectGitSummaries>,
+        query: Query<(Entity, &ProjectGitText)>,
+        mut writer: TextUiWriter,
+    ) {
+        for (entity, git) in query.iter() {
+            let summary = summaries.summaries.get(&git.path);
+            *writer.text(entity, 0) = summary
+                .and_then(|summary| summary.branch.clone())
+                .unwrap_or_default();
+            *writer.text(entity, 1) = match summary {
+                Some(summary) if summary.dirty => " ●".to_string(),
+                _ => String::new(),
+            };
+        }
+    }
+
     fn new_project(
         mut commands: Commands,
         query: Query<Entity, With<FnUi>>,
@@ -204,6 +287,9 @@ impl ProjectPlugin {
                                     parent
                                         .spawn((
                                             ProjectItemButton,
+                                            ProjectListItem {
+                                                project: project.clone(),
+                                            },
                                             Hovered::default(),
                                             Name::new("ProjectListItem"),
                                             Node {
@@ -289,6 +375,53 @@ impl ProjectPlugin {
                                                         TextColor(res.colors.home_font_grey_color),
                                                     ));
                                                 });
+                                            parent
+                                                .spawn((
+                                                    ProjectGitText {
+                                                        path: project.path.clone(),
+                                                    },
+                                                    Text::new(""),
+                                                    TextFont {
+                                                        font: assets.load(MAPLE_MONO_BOLD_ITALIC),
+                                                        font_size: 14.0,
+                                                        ..default()
+                                                    },
+                                                    TextLayout::new_with_no_wrap(),
+                                                    TextColor(res.colors.home_font_grey_color),
+                                                    Node {
+                                                        flex_shrink: 0.,
+                                                        margin: UiRect::right(Val::Px(20.0)),
+                                                        ..default()
+                                                    },
+                                                ))
+                                                .with_child((
+                                                    TextSpan::new(""),
+                                                    TextFont {
+                                                        font: assets.load(MAPLE_MONO_BOLD_ITALIC),
+                                                        font_size: 14.0,
+                                                        ..default()
+                                                    },
+                                                    TextColor(res.colors.warning),
+                                                ));
+                                            parent.spawn((
+                                                ProjectCopyPathButton {
+                                                    path: project.path.clone(),
+                                                },
+                                                Name::new("ProjectCopyPathButton"),
+                                                Text::new("Copy path"),
+                                                TextFont {
+                                                    font: assets.load(MAPLE_MONO_BOLD_ITALIC),
+                                                    font_size: 14.0,
+                                                    ..default()
+                                                },
+                                                TextLayout::new_with_no_wrap(),
+                                                TextColor(res.colors.home_font_grey_color),
+                                                Node {
+                                                    flex_shrink: 0.,
+                                                    margin: UiRect::right(Val::Px(20.0)),
+                                                    ..default()
+                                                },
+                                            ));
                                         });
                                 }
                             });
@@ -0,0 +1,445 @@
+use crate::position::PositionEncoding;
+use crate::transport::{read_message, write_message};
+use crate::{LspError, Result};
+use bevy::log::warn;
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument, Exit,
+    Initialized, Notification, PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, DocumentSymbolRequest, FoldingRangeRequest, GotoDefinition, HoverRequest,
+    Initialize, References, Request, Shutdown, WorkspaceSymbolRequest,
+};
+use lsp_types::*;
+use seeker_editor::buffer::Position;
+use seeker_editor::document::Document;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::BufReader;
+use tokio::process::{Child, ChildStdout, Command};
+use tokio::sync::{mpsc, oneshot};
+
+/// 语言服务器主动推送给客户端的消息
+#[derive(Debug)]
+pub enum LspEvent {
+    Diagnostics(PublishDiagnosticsParams),
+    Exited,
+}
+
+type Pending = Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value>>>>>;
+
+/// 通过 stdio 与语言服务器通信的客户端
+#[derive(Clone)]
+pub struct LspClient {
+    next_id: Arc<AtomicI64>,
+    outgoing: mpsc::UnboundedSender<Value>,
+    pending: Pending,
+    child: Arc<Mutex<Option<Child>>>,
+    encoding: Arc<Mutex<PositionEncoding>>,
+    /// 服务器是否支持 foldingRange
+    folding_range: Arc<AtomicBool>,
+}
+
+impl LspClient {
+    /// 启动语言服务器进程，必须在 tokio 运行时中调用
+    pub fn spawn(
+        command: &str,
+        args: &[String],
+        root: &Path,
+        events: mpsc::UnboundedSender<LspEvent>,
+    ) -> Result<Self> {
+        let mut child = Command::new(command)
+            .args(args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let mut stdin = child.stdin.take().ok_or(LspError::Exited)?;
+        let stdout = child.stdout.take().ok_or(LspError::Exited)?;
+
+        let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<Value>();
+        tokio::spawn(async move {
+            while let Some(message) = outgoing_rx.recv().await {
+                if write_message(&mut stdin, &message).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let client = Self {
+            next_id: Arc::new(AtomicI64::new(1)),
+            outgoing,
+            pending: Arc::default(),
+            child: Arc::new(Mutex::new(Some(child))),
+            encoding: Arc::default(),
+            folding_range: Arc::default(),
+        };
+        tokio::spawn(Self::read_loop(
+            stdout,
+            client.pending.clone(),
+            client.outgoing.clone(),
+            events,
+        ));
+        Ok(client)
+    }
+
+    async fn read_loop(
+        stdout: ChildStdout,
+        pending: Pending,
+        outgoing: mpsc::UnboundedSender<Value>,
+        events: mpsc::UnboundedSender<LspEvent>,
+    ) {
+        let mut reader = BufReader::new(stdout);
+        loop {
+            let message = match read_message(&mut reader).await {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                // 单条消息有问题时跳过，只在读取失败或者流结束时停下
+                Err(err @ (LspError::Json(_) | LspError::Protocol(_))) => {
+                    warn!("language server sent an invalid message: {err}");
+                    continue;
+                }
+                Err(err) => {
+                    warn!("language server read failed: {err}");
+                    break;
+                }
+            };
+            let method = message.get("method").and_then(Value::as_str);
+            let id = message.get("id").cloned();
+            match (method, id) {
+                // 响应
+                (None, Some(id)) => {
+                    let Some(id) = id.as_i64() else {
+                        continue;
+                    };
+                    let Some(sender) = pending.lock().unwrap().remove(&id) else {
+                        continue;
+                    };
+                    let result = match message.get("error") {
+                        Some(error) => Err(LspError::Server {
+                            code: error["code"].as_i64().unwrap_or_default(),
+                            message: error["message"].as_str().unwrap_or_default().to_string(),
+                        }),
+                        None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                    };
+                    let _ = sender.send(result);
+                }
+                // 服务器发起的请求，不支持的一律回 null
+                (Some(method), Some(id)) => {
+                    let result = match method {
+                        "workspace/configuration" => {
+                            let count = message["params"]["items"]
+                                .as_array()
+                                .map(Vec::len)
+                                .unwrap_or_default();
+                            Value::Array(vec![Value::Null; count])
+                        }
+                        _ => Value::Null,
+                    };
+                    let _ = outgoing.send(json!({"jsonrpc": "2.0", "id": id, "result": result}));
+                }
+                (Some(PublishDiagnostics::METHOD), None) => {
+                    if let Ok(params) = serde_json::from_value(message["params"].clone()) {
+                        let _ = events.send(LspEvent::Diagnostics(params));
+                    }
+                }
+                _ => {}
+            }
+        }
+        for (_, sender) in pending.lock().unwrap().drain() {
+            let _ = sender.send(Err(LspError::Exited));
+        }
+        let _ = events.send(LspEvent::Exited);
+    }
+
+    pub async fn request<R: Request>(&self, params: R::Params) -> Result<R::Result> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, sender);
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": R::METHOD, "params": params});
+        if self.outgoing.send(message).is_err() {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(LspError::Exited);
+        }
+        let value = receiver.await.map_err(|_| LspError::Exited)??;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub fn notify<N: Notification>(&self, params: N::Params) -> Result<()> {
+        let message = json!({"jsonrpc": "2.0", "method": N::METHOD, "params": params});
+        self.outgoing.send(message).map_err(|_| LspError::Exited)
+    }
+
+    pub fn encoding(&self) -> PositionEncoding {
+        *self.encoding.lock().unwrap()
+    }
+
+    pub fn supports_folding_range(&self) -> bool {
+        self.folding_range.load(Ordering::Relaxed)
+    }
+
+    #[allow(deprecated)]
+    pub async fn initialize(&self, root: &Path) -> Result<InitializeResult> {
+        let root_uri = file_url(root)?;
+        let params = InitializeParams {
+            process_id: Some(std::process::id()),
+            root_uri: Some(root_uri.clone()),
+            workspace_folders: Some(vec![WorkspaceFolder {
+                uri: root_uri,
+                name: root
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            }]),
+            capabilities: ClientCapabilities {
+                general: Some(GeneralClientCapabilities {
+                    position_encodings: Some(vec![
+                        PositionEncodingKind::UTF32,
+                        PositionEncodingKind::UTF16,
+                    ]),
+                    ..Default::default()
+                }),
+                text_document: Some(TextDocumentClientCapabilities {
+                    synchronization: Some(TextDocumentSyncClientCapabilities {
+                        did_save: Some(true),
+                        ..Default::default()
+                    }),
+                    hover: Some(HoverClientCapabilities {
+                        content_format: Some(vec![MarkupKind::PlainText, MarkupKind::Markdown]),
+                        ..Default::default()
+                    }),
+                    completion: Some(CompletionClientCapabilities {
+                        completion_item: Some(CompletionItemCapability {
+                            snippet_support: Some(true),
+                            documentation_format: Some(vec![
+                                MarkupKind::PlainText,
+                                MarkupKind::Markdown,
+                            ]),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                    publish_diagnostics: Some(PublishDiagnosticsClientCapabilities {
+                        version_support: Some(true),
+                        ..Default::default()
+                    }),
+                    document_symbol: Some(DocumentSymbolClientCapabilities {
+                        hierarchical_document_symbol_support: Some(true),
+                        ..Default::default()
+                    }),
+                    // 编辑器按整行折叠，区域的最后一行是要隐藏的最后一行
+                    folding_range: Some(FoldingRangeClientCapabilities {
+                        line_folding_only: Some(true),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                workspace: Some(WorkspaceClientCapabilities {
+                    symbol: Some(WorkspaceSymbolClientCapabilities::default()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            client_info: Some(ClientInfo {
+                name: "Seeker".to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+            ..Default::default()
+        };
+        let result = self.request::<Initialize>(params).await?;
+        *self.encoding.lock().unwrap() =
+            PositionEncoding::from(result.capabilities.position_encoding.as_ref());
+        let folding_range = !matches!(
+            result.capabilities.folding_range_provider,
+            None | Some(FoldingRangeProviderCapability::Simple(false))
+        );
+        self.folding_range.store(folding_range, Ordering::Relaxed);
+        self.notify::<Initialized>(InitializedParams {})?;
+        Ok(result)
+    }
+
+    pub fn did_open(&self, doc: &Document) -> Result<()> {
+        self.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: file_url(&doc.path)?,
+                language_id: doc.language_id.clone(),
+                version: doc.version,
+                text: doc.buffer.text(),
+            },
+        })
+    }
+
+    /// 全量同步
+    pub fn did_change(&self, doc: &Document) -> Result<()> {
+        self.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: file_url(&doc.path)?,
+                version: doc.version,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: doc.buffer.text(),
+            }],
+        })
+    }
+
+    pub fn did_save(&self, path: &Path) -> Result<()> {
+        self.notify::<DidSaveTextDocument>(DidSaveTextDocumentParams {
+            text_document: TextDocumentIdentifier::new(file_url(path)?),
+            text: None,
+        })
+    }
+
+    pub fn did_close(&self, path: &Path) -> Result<()> {
+        self.notify::<DidCloseTextDocument>(DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier::new(file_url(path)?),
+        })
+    }
+
+    pub async fn hover(&self, position: TextDocumentPositionParams) -> Result<Option<Hover>> {
+        self.request::<HoverRequest>(HoverParams {
+            text_document_position_params: position,
+            work_done_progress_params: Default::default(),
+        })
+        .await
+    }
+
+    pub async fn goto_definition(
+        &self,
+        position: TextDocumentPositionParams,
+    ) -> Result<Vec<Location>> {
+        let response = self
+            .request::<GotoDefinition>(GotoDefinitionParams {
+                text_document_position_params: position,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await?;
+        Ok(match response {
+            None => vec![],
+            Some(GotoDefinitionResponse::Scalar(location)) => vec![location],
+            Some(GotoDefinitionResponse::Array(locations)) => locations,
+            Some(GotoDefinitionResponse::Link(links)) => links
+                .into_iter()
+                .map(|link| Location::new(link.target_uri, link.target_selection_range))
+                .collect(),
+        })
+    }
+
+    pub async fn references(&self, position: TextDocumentPositionParams) -> Result<Vec<Location>> {
+        let response = self
+            .request::<References>(ReferenceParams {
+                text_document_position: position,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: ReferenceContext {
+                    include_declaration: true,
+                },
+            })
+            .await?;
+        Ok(response.unwrap_or_default())
+    }
+
+    pub async fn completion(
+        &self,
+        position: TextDocumentPositionParams,
+    ) -> Result<Vec<CompletionItem>> {
+        let response = self
+            .request::<Completion>(CompletionParams {
+                text_document_position: position,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: None,
+            })
+            .await?;
+        Ok(match response {
+            None => vec![],
+            Some(CompletionResponse::Array(items)) => items,
+            Some(CompletionResponse::List(list)) => list.items,
+        })
+    }
+
+    /// 文档中的符号，旧的服务器返回没有层级的列表
+    pub async fn document_symbol(&self, path: &Path) -> Result<DocumentSymbolResponse> {
+        let response = self
+            .request::<DocumentSymbolRequest>(DocumentSymbolParams {
+                text_document: TextDocumentIdentifier::new(file_url(path)?),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await?;
+        Ok(response.unwrap_or(DocumentSymbolResponse::Nested(vec![])))
+    }
+
+    /// 文档中可以折叠的区域，按行计算
+    pub async fn folding_range(&self, path: &Path) -> Result<Vec<FoldingRange>> {
+        let response = self
+            .request::<FoldingRangeRequest>(FoldingRangeParams {
+                text_document: TextDocumentIdentifier::new(file_url(path)?),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await?;
+        Ok(response.unwrap_or_default())
+    }
+
+    /// 按名称搜索整个工作区的符号
+    pub async fn workspace_symbol(&self, query: &str) -> Result<Vec<WorkspaceSymbol>> {
+        let response = self
+            .request::<WorkspaceSymbolRequest>(WorkspaceSymbolParams {
+                query: query.to_string(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await?;
+        Ok(match response {
+            None => vec![],
+            Some(WorkspaceSymbolResponse::Nested(symbols)) => symbols,
+            Some(WorkspaceSymbolResponse::Flat(symbols)) => symbols
+                .into_iter()
+                .map(|symbol| WorkspaceSymbol {
+                    name: symbol.name,
+                    kind: symbol.kind,
+                    tags: symbol.tags,
+                    container_name: symbol.container_name,
+                    location: OneOf::Left(symbol.location),
+                    data: None,
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn shutdown(&self) -> Result<()> {
+        self.request::<Shutdown>(()).await?;
+        self.notify::<Exit>(())?;
+        let child = self.child.lock().unwrap().take();
+        if let Some(mut child) = child {
+            let _ = child.wait().await;
+        }
+        Ok(())
+    }
+
+    /// 把编辑器位置转换为请求参数
+    pub fn text_document_position(
+        &self,
+        doc: &Document,
+        pos: Position,
+    ) -> Result<TextDocumentPositionParams> {
+        Ok(TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier::new(file_url(&doc.path)?),
+            position: self.encoding().to_lsp(doc.buffer.line(pos.line), pos),
+        })
+    }
+}
+
+pub fn file_url(path: &Path) -> Result<Url> {
+    Url::from_file_path(path)
+        .map_err(|_| LspError::Protocol(format!("not an absolute path: {}", path.display())))
+}
@@ -0,0 +1,23 @@
+pub mod client;
+pub mod plugin;
+pub mod position;
+pub mod transport;
+
+pub use client::{LspClient, LspEvent};
+pub use plugin::SeekerLspPlugin;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LspError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("language server error {code}: {message}")]
+    Server { code: i64, message: String },
+    #[error("invalid message: {0}")]
+    Protocol(String),
+    #[error("language server exited")]
+    Exited,
+}
+
+pub type Result<T> = std::result::Result<T, LspError>;
@@ -0,0 +1,712 @@
+use crate::client::{LspClient, LspEvent};
+use crate::position::PositionEncoding;
+use crate::LspError;
+use bevy::prelude::*;
+use lsp_types::{
+    CompletionItem, DiagnosticSeverity, DocumentSymbol, DocumentSymbolResponse, FoldingRange,
+    FoldingRangeKind, HoverContents, Location, MarkedString, OneOf, PublishDiagnosticsParams,
+    SymbolKind, TextDocumentPositionParams, WorkspaceSymbol,
+};
+use seeker_config::SEEKER_CONFIG;
+use seeker_editor::buffer::Position;
+use seeker_editor::diagnostic::{Diagnostic, Diagnostics, Severity};
+use seeker_editor::document::{DocumentSaved, Documents};
+use seeker_editor::fold::{FoldKind, FoldRange};
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake, WakeSender};
+use seeker_resource::project_list::CurrentProject;
+use seeker_state::SeekerState;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+pub const LSP_DIAGNOSTIC_SOURCE: &str = "lsp";
+
+#[derive(Component)]
+pub struct SeekerLspPlugin;
+
+/// 编辑界面发起的语言服务请求
+#[derive(Message, Clone, Debug)]
+pub struct LspRequest {
+    pub path: PathBuf,
+    pub position: Position,
+    pub kind: LspRequestKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LspRequestKind {
+    Hover,
+    Definition,
+    References,
+    Completion,
+    /// 文档中的符号，忽略请求的位置
+    DocumentSymbol,
+    /// 按语法结构折叠的区域，忽略请求的位置
+    FoldingRange,
+}
+
+/// 在所有已启动的语言服务器中搜索符号
+#[derive(Message, Clone, Debug)]
+pub struct WorkspaceSymbolRequest {
+    pub query: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LspLocation {
+    pub path: PathBuf,
+    pub start: Position,
+    pub end: Position,
+}
+
+/// 大纲或者工作区符号搜索中的一项
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LspSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// 文档符号的签名，工作区符号所在的容器
+    pub detail: Option<String>,
+    /// 嵌套的层级，顶层为 0
+    pub depth: usize,
+    /// 整个符号的范围
+    pub location: LspLocation,
+    /// 符号名称的位置，跳转时使用
+    pub selection: Position,
+}
+
+#[derive(Message, Clone, Debug)]
+pub enum LspResponse {
+    Hover {
+        path: PathBuf,
+        position: Position,
+        contents: String,
+    },
+    Definition(Vec<LspLocation>),
+    References(Vec<LspLocation>),
+    Completion {
+        path: PathBuf,
+        position: Position,
+        items: Vec<CompletionItem>,
+    },
+    DocumentSymbols {
+        path: PathBuf,
+        /// 请求时的文档版本
+        version: i32,
+        symbols: Vec<LspSymbol>,
+    },
+    WorkspaceSymbols {
+        query: String,
+        symbols: Vec<LspSymbol>,
+    },
+    FoldingRanges {
+        path: PathBuf,
+        /// 请求时的文档版本
+        version: i32,
+        ranges: Vec<FoldRange>,
+    },
+}
+
+enum Server {
+    Starting,
+    Running(LspClient),
+    Failed,
+}
+
+/// 异步任务回传给主线程的消息
+enum ServerMessage {
+    Started(String, LspClient),
+    Failed(String, LspError),
+    Event(String, LspEvent),
+    Hover(LspRequest, Option<String>),
+    Locations(LspRequest, PositionEncoding, Vec<Location>),
+    Completion(LspRequest, Vec<CompletionItem>),
+    DocumentSymbols(LspRequest, i32, PositionEncoding, DocumentSymbolResponse),
+    WorkspaceSymbols(String, PositionEncoding, Vec<WorkspaceSymbol>),
+    FoldingRanges(LspRequest, i32, Vec<FoldingRange>),
+}
+
+/// 消息带上发送者的代数，退出编辑界面后收到的旧消息会被丢弃
+#[derive(Clone)]
+struct ServerSender {
+    sender: WakeSender<(u64, ServerMessage)>,
+    generation: u64,
+}
+
+impl ServerSender {
+    fn send(&self, message: ServerMessage) {
+        self.sender.send((self.generation, message));
+    }
+}
+
+/// 每种语言一个语言服务器进程
+#[derive(Resource)]
+pub struct LspServers {
+    runtime: Runtime,
+    servers: HashMap<String, Server>,
+    /// 已经 didOpen 的文档及同步过的版本
+    synced: HashMap<PathBuf, i32>,
+    sender: ServerSender,
+    receiver: BackgroundReceiver<(u64, ServerMessage)>,
+}
+
+impl Default for LspServers {
+    fn default() -> Self {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("seeker-lsp")
+            .enable_all()
+            .build()
+            .expect("Failed to start lsp runtime");
+        let (sender, receiver) = BackgroundWake::default().channel();
+        Self {
+            runtime,
+            servers: HashMap::new(),
+            synced: HashMap::new(),
+            sender: ServerSender {
+                sender,
+                generation: 0,
+            },
+            receiver,
+        }
+    }
+}
+
+impl LspServers {
+    /// 文档所属语言的语言服务器是否已就绪
+    pub fn is_running(&self, language_id: &str) -> bool {
+        matches!(self.servers.get(language_id), Some(Server::Running(_)))
+    }
+
+    fn client(&self, language_id: &str) -> Option<&LspClient> {
+        match self.servers.get(language_id) {
+            Some(Server::Running(client)) => Some(client),
+            _ => None,
+        }
+    }
+}
+
+impl Plugin for SeekerLspPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LspServers>()
+            .init_resource::<BackgroundWake>()
+            .init_resource::<Diagnostics>()
+            .add_message::<LspRequest>()
+            .add_message::<WorkspaceSymbolRequest>()
+            .add_message::<LspResponse>()
+            .add_message::<DocumentSaved>()
+            .add_systems(
+                Update,
+                (
+                    Self::start_servers,
+                    Self::receive_messages,
+                    Self::sync_documents,
+                    Self::send_requests,
+                )
+                    .chain()
+                    .run_if(in_state(SeekerState::Edit)),
+            )
+            .add_systems(Startup, Self::init_wake)
+            .add_systems(OnExit(SeekerState::Edit), Self::shutdown_servers);
+    }
+}
+
+impl SeekerLspPlugin {
+    /// 换成能唤醒事件循环的通道
+    fn init_wake(mut servers: ResMut<LspServers>, wake: Res<BackgroundWake>) {
+        (servers.sender.sender, servers.receiver) = wake.channel();
+    }
+
+    fn start_servers(
+        mut servers: ResMut<LspServers>,
+        documents: Res<Documents>,
+        current_project: Res<CurrentProject>,
+    ) {
+        if !documents.is_changed() {
+            return;
+        }
+        let Some(project) = current_project.project.as_ref() else {
+            return;
+        };
+        let root = PathBuf::from(&project.path);
+        for doc in &documents.documents {
+            let language_id = doc.language_id.clone();
+            if servers.servers.contains_key(&language_id) {
+                continue;
+            }
+            let Some(config) = SEEKER_CONFIG.lsp.get(&language_id).cloned() else {
+                continue;
+            };
+            info!("starting {} for {}", config.command, language_id);
+            servers.servers.insert(language_id.clone(), Server::Starting);
+            let sender = servers.sender.clone();
+            let root = root.clone();
+            servers.runtime.spawn(async move {
+                let (events, mut events_rx) = mpsc::unbounded_channel();
+                let started = async {
+                    let client = LspClient::spawn(&config.command, &config.args, &root, events)?;
+                    client.initialize(&root).await?;
+                    Ok::<_, LspError>(client)
+                };
+                match started.await {
+                    Ok(client) => {
+                        sender.send(ServerMessage::Started(language_id.clone(), client));
+                    }
+                    Err(err) => {
+                        sender.send(ServerMessage::Failed(language_id, err));
+                        return;
+                    }
+                }
+                while let Some(event) = events_rx.recv().await {
+                    sender.send(ServerMessage::Event(language_id.clone(), event));
+                }
+            });
+        }
+    }
+
+    fn sync_documents(mut servers: ResMut<LspServers>, documents: Res<Documents>) {
+        let servers = &mut *servers;
+        for doc in &documents.documents {
+            let Some(client) = servers.client(&doc.language_id) else {
+                continue;
+            };
+            let result = match servers.synced.get(&doc.path) {
+                None => client.did_open(doc),
+                Some(version) if *version != doc.version => client.did_change(doc),
+                Some(_) => continue,
+            };
+            // 失败时不记录，下一帧重新同步
+            match result {
+                Ok(()) => {
+                    servers.synced.insert(doc.path.clone(), doc.version);
+                }
+                Err(err) => error!("lsp sync {}: {err}", doc.path.display()),
+            }
+        }
+
+        let closed = servers
+            .synced
+            .keys()
+            .filter(|path| documents.get(path).is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in closed {
+            servers.synced.remove(&path);
+            let language_id = seeker_editor::document::language_id(&path);
+            if let Some(client) = servers.client(language_id) {
+                let _ = client.did_close(&path);
+            }
+        }
+    }
+
+    fn send_requests(
+        servers: Res<LspServers>,
+        documents: Res<Documents>,
+        mut requests: MessageReader<LspRequest>,
+        mut symbols: MessageReader<WorkspaceSymbolRequest>,
+        mut saved: MessageReader<DocumentSaved>,
+    ) {
+        for DocumentSaved { path } in saved.read() {
+            let language_id = seeker_editor::document::language_id(path);
+            if let Some(client) = servers.client(language_id) {
+                let _ = client.did_save(path);
+            }
+        }
+
+        for request in requests.read() {
+            let Some(doc) = documents.get(&request.path) else {
+                continue;
+            };
+            let Some(client) = servers.client(&doc.language_id).cloned() else {
+                continue;
+            };
+            if request.kind == LspRequestKind::FoldingRange && !client.supports_folding_range() {
+                continue;
+            }
+            let position: TextDocumentPositionParams =
+                match client.text_document_position(doc, request.position) {
+                    Ok(position) => position,
+                    Err(err) => {
+                        error!("{err}");
+                        continue;
+                    }
+                };
+            let request = request.clone();
+            let version = doc.version;
+            let sender = servers.sender.clone();
+            servers.runtime.spawn(async move {
+                let message = match request.kind {
+                    LspRequestKind::Hover => client
+                        .hover(position)
+                        .await
+                        .map(|hover| ServerMessage::Hover(request, hover.map(|h| hover_text(h.contents)))),
+                    LspRequestKind::Definition => client
+                        .goto_definition(position)
+                        .await
+                        .map(|locations| ServerMessage::Locations(request, client.encoding(), locations)),
+                    LspRequestKind::References => client
+                        .references(position)
+                        .await
+                        .map(|locations| ServerMessage::Locations(request, client.encoding(), locations)),
+                    LspRequestKind::Completion => client
+                        .completion(position)
+                        .await
+                        .map(|items| ServerMessage::Completion(request, items)),
+                    LspRequestKind::DocumentSymbol => {
+                        client.document_symbol(&request.path).await.map(|symbols| {
+                            ServerMessage::DocumentSymbols(
+                                request,
+                                version,
+                                client.encoding(),
+                                symbols,
+                            )
+                        })
+                    }
+                    LspRequestKind::FoldingRange => client
+                        .folding_range(&request.path)
+                        .await
+                        .map(|ranges| ServerMessage::FoldingRanges(request, version, ranges)),
+                };
+                match message {
+                    Ok(message) => {
+                        sender.send(message);
+                    }
+                    Err(err) => warn!("lsp request: {err}"),
+                }
+            });
+        }
+
+        for WorkspaceSymbolRequest { query } in symbols.read() {
+            for server in servers.servers.values() {
+                let Server::Running(client) = server else {
+                    continue;
+                };
+                let (client, query) = (client.clone(), query.clone());
+                let sender = servers.sender.clone();
+                servers.runtime.spawn(async move {
+                    match client.workspace_symbol(&query).await {
+                        Ok(symbols) => {
+                            sender.send(ServerMessage::WorkspaceSymbols(
+                                query,
+                                client.encoding(),
+                                symbols,
+                            ));
+                        }
+                        Err(err) => warn!("lsp workspace symbol: {err}"),
+                    }
+                });
+            }
+        }
+    }
+
+    fn receive_messages(
+        mut servers: ResMut<LspServers>,
+        documents: Res<Documents>,
+        mut diagnostics: ResMut<Diagnostics>,
+        mut responses: MessageWriter<LspResponse>,
+    ) {
+        let (messages, _) = servers.receiver.drain();
+        let lines = LineCache::new(&documents);
+        for (generation, message) in messages {
+            if generation != servers.sender.generation {
+                continue;
+            }
+            match message {
+                ServerMessage::Started(language_id, client) => {
+                    info!("language server for {language_id} started");
+                    servers.servers.insert(language_id, Server::Running(client));
+                }
+                ServerMessage::Failed(language_id, err) => {
+                    error!("language server for {language_id}: {err}");
+                    servers.servers.insert(language_id, Server::Failed);
+                }
+                ServerMessage::Event(language_id, LspEvent::Exited) => {
+                    warn!("language server for {language_id} exited");
+                    servers.servers.insert(language_id, Server::Failed);
+                }
+                ServerMessage::Event(language_id, LspEvent::Diagnostics(params)) => {
+                    let Some(client) = servers.client(&language_id) else {
+                        continue;
+                    };
+                    let encoding = client.encoding();
+                    if let Some((path, items)) = convert_diagnostics(&lines, encoding, params) {
+                        diagnostics.set(LSP_DIAGNOSTIC_SOURCE, path, items);
+                    }
+                }
+                ServerMessage::Hover(request, contents) => {
+                    if let Some(contents) = contents.filter(|contents| !contents.is_empty()) {
+                        responses.write(LspResponse::Hover {
+                            path: request.path,
+                            position: request.position,
+                            contents,
+                        });
+                    }
+                }
+                ServerMessage::Locations(request, encoding, locations) => {
+                    let locations = locations
+                        .iter()
+                        .filter_map(|location| convert_location(&lines, encoding, location))
+                        .collect();
+                    responses.write(match request.kind {
+                        LspRequestKind::Definition => LspResponse::Definition(locations),
+                        _ => LspResponse::References(locations),
+                    });
+                }
+                ServerMessage::Completion(request, items) => {
+                    responses.write(LspResponse::Completion {
+                        path: request.path,
+                        position: request.position,
+                        items,
+                    });
+                }
+                ServerMessage::DocumentSymbols(request, version, encoding, response) => {
+                    let symbols =
+                        convert_document_symbols(&lines, encoding, &request.path, response);
+                    responses.write(LspResponse::DocumentSymbols {
+                        path: request.path,
+                        version,
+                        symbols,
+                    });
+                }
+                ServerMessage::WorkspaceSymbols(query, encoding, symbols) => {
+                    let symbols = symbols
+                        .iter()
+                        .filter_map(|symbol| convert_workspace_symbol(&lines, encoding, symbol))
+                        .collect();
+                    responses.write(LspResponse::WorkspaceSymbols { query, symbols });
+                }
+                ServerMessage::FoldingRanges(request, version, ranges) => {
+                    responses.write(LspResponse::FoldingRanges {
+                        path: request.path,
+                        version,
+                        ranges: ranges.into_iter().map(convert_folding_range).collect(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn shutdown_servers(mut servers: ResMut<LspServers>, mut diagnostics: ResMut<Diagnostics>) {
+        let servers = &mut *servers;
+        for (_, server) in servers.servers.drain() {
+            if let Server::Running(client) = server {
+                servers.runtime.spawn(async move {
+                    let _ = client.shutdown().await;
+                });
+            }
+        }
+        servers.synced.clear();
+        // 还在启动中的服务器稍后发来的消息属于上一个项目
+        servers.sender.generation += 1;
+        servers.receiver.drain();
+        diagnostics.clear_source(LSP_DIAGNOSTIC_SOURCE);
+    }
+}
+
+fn hover_text(contents: HoverContents) -> String {
+    fn marked(text: MarkedString) -> String {
+        match text {
+            MarkedString::String(text) => text,
+            MarkedString::LanguageString(code) => code.value,
+        }
+    }
+    match contents {
+        HoverContents::Scalar(text) => marked(text),
+        HoverContents::Array(texts) => texts.into_iter().map(marked).collect::<Vec<_>>().join("\n"),
+        HoverContents::Markup(markup) => markup.value,
+    }
+}
+
+/// 转换位置时读取行文本，优先使用编辑器中未保存的内容，
+/// 没有打开的文件每批消息只读一次
+struct LineCache<'a> {
+    documents: &'a Documents,
+    files: RefCell<HashMap<PathBuf, Vec<String>>>,
+}
+
+impl<'a> LineCache<'a> {
+    fn new(documents: &'a Documents) -> Self {
+        Self {
+            documents,
+            files: RefCell::default(),
+        }
+    }
+
+    fn line(&self, path: &Path, line: usize) -> String {
+        if let Some(doc) = self.documents.get(path) {
+            return doc.buffer.line(line).to_string();
+        }
+        let mut files = self.files.borrow_mut();
+        let lines = files.entry(path.to_path_buf()).or_insert_with(|| {
+            std::fs::read_to_string(path)
+                .map(|text| text.lines().map(str::to_string).collect())
+                .unwrap_or_default()
+        });
+        lines.get(line).cloned().unwrap_or_default()
+    }
+}
+
+fn convert_range(
+    lines: &LineCache,
+    encoding: PositionEncoding,
+    path: &Path,
+    range: lsp_types::Range,
+) -> (Position, Position) {
+    let start = lines.line(path, range.start.line as usize);
+    let end = if range.end.line == range.start.line {
+        start.clone()
+    } else {
+        lines.line(path, range.end.line as usize)
+    };
+    (
+        encoding.from_lsp(&start, range.start),
+        encoding.from_lsp(&end, range.end),
+    )
+}
+
+fn convert_location(
+    lines: &LineCache,
+    encoding: PositionEncoding,
+    location: &Location,
+) -> Option<LspLocation> {
+    let path = location.uri.to_file_path().ok()?;
+    let (start, end) = convert_range(lines, encoding, &path, location.range);
+    Some(LspLocation { path, start, end })
+}
+
+/// 按位置排序并展开嵌套的符号，没有层级的列表按范围的包含关系计算层级
+fn convert_document_symbols(
+    lines: &LineCache,
+    encoding: PositionEncoding,
+    path: &Path,
+    response: DocumentSymbolResponse,
+) -> Vec<LspSymbol> {
+    fn nested(
+        mut symbols: Vec<DocumentSymbol>,
+        depth: usize,
+        convert: &impl Fn(lsp_types::Range) -> (Position, Position),
+        path: &Path,
+        result: &mut Vec<LspSymbol>,
+    ) {
+        symbols.sort_by_key(|symbol| (symbol.range.start.line, symbol.range.start.character));
+        for symbol in symbols {
+            let (start, end) = convert(symbol.range);
+            result.push(LspSymbol {
+                name: symbol.name,
+                kind: symbol.kind,
+                detail: symbol.detail,
+                depth,
+                location: LspLocation {
+                    path: path.to_path_buf(),
+                    start,
+                    end,
+                },
+                selection: convert(symbol.selection_range).0,
+            });
+            nested(
+                symbol.children.unwrap_or_default(),
+                depth + 1,
+                convert,
+                path,
+                result,
+            );
+        }
+    }
+
+    let convert = |range| convert_range(lines, encoding, path, range);
+    let mut result = Vec::new();
+    match response {
+        DocumentSymbolResponse::Nested(symbols) => nested(symbols, 0, &convert, path, &mut result),
+        DocumentSymbolResponse::Flat(mut symbols) => {
+            symbols.sort_by_key(|symbol| {
+                let range = symbol.location.range;
+                (range.start.line, range.start.character)
+            });
+            // 还没有结束的外层符号
+            let mut parents: Vec<Position> = Vec::new();
+            for symbol in symbols {
+                let (start, end) = convert(symbol.location.range);
+                while parents.last().is_some_and(|parent| *parent < end) {
+                    parents.pop();
+                }
+                result.push(LspSymbol {
+                    name: symbol.name,
+                    kind: symbol.kind,
+                    detail: None,
+                    depth: parents.len(),
+                    location: LspLocation {
+                        path: path.to_path_buf(),
+                        start,
+                        end,
+                    },
+                    selection: start,
+                });
+                parents.push(end);
+            }
+        }
+    }
+    result
+}
+
+fn convert_workspace_symbol(
+    lines: &LineCache,
+    encoding: PositionEncoding,
+    symbol: &WorkspaceSymbol,
+) -> Option<LspSymbol> {
+    let location = match &symbol.location {
+        OneOf::Left(location) => convert_location(lines, encoding, location)?,
+        OneOf::Right(location) => LspLocation {
+            path: location.uri.to_file_path().ok()?,
+            start: Position::default(),
+            end: Position::default(),
+        },
+    };
+    Some(LspSymbol {
+        name: symbol.name.clone(),
+        kind: symbol.kind,
+        detail: symbol.container_name.clone(),
+        depth: 0,
+        selection: location.start,
+        location,
+    })
+}
+
+/// 区域按整行请求，结束行是要隐藏的最后一行
+fn convert_folding_range(range: FoldingRange) -> FoldRange {
+    FoldRange {
+        start: range.start_line as usize,
+        end: range.end_line as usize,
+        kind: match range.kind {
+            Some(FoldingRangeKind::Comment) => FoldKind::Comment,
+            Some(FoldingRangeKind::Imports) => FoldKind::Imports,
+            _ => FoldKind::Block,
+        },
+        level: 0,
+    }
+}
+
+fn convert_diagnostics(
+    lines: &LineCache,
+    encoding: PositionEncoding,
+    params: PublishDiagnosticsParams,
+) -> Option<(PathBuf, Vec<Diagnostic>)> {
+    let path = params.uri.to_file_path().ok()?;
+    let items = params
+        .diagnostics
+        .into_iter()
+        .map(|diagnostic| {
+            let (start, end) = convert_range(lines, encoding, &path, diagnostic.range);
+            Diagnostic {
+                start,
+                end,
+                severity: match diagnostic.severity {
+                    Some(DiagnosticSeverity::WARNING) => Severity::Warning,
+                    Some(DiagnosticSeverity::INFORMATION) => Severity::Information,
+                    Some(DiagnosticSeverity::HINT) => Severity::Hint,
+                    _ => Severity::Error,
+                },
+                message: diagnostic.message,
+            }
+        })
+        .collect();
+    Some((path, items))
+}
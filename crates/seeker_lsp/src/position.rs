@@ -0,0 +1,63 @@
+use lsp_types::PositionEncodingKind;
+use seeker_editor::buffer::Position;
+
+/// 编辑器按字符计列，LSP 按协商好的编码计列
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+    Utf32,
+}
+
+impl From<Option<&PositionEncodingKind>> for PositionEncoding {
+    fn from(kind: Option<&PositionEncodingKind>) -> Self {
+        match kind.map(PositionEncodingKind::as_str) {
+            Some("utf-8") => PositionEncoding::Utf8,
+            Some("utf-32") => PositionEncoding::Utf32,
+            _ => PositionEncoding::Utf16,
+        }
+    }
+}
+
+impl PositionEncoding {
+    pub fn to_lsp(self, line: &str, pos: Position) -> lsp_types::Position {
+        let character = line
+            .chars()
+            .take(pos.column)
+            .map(|ch| self.unit_len(ch))
+            .sum::<usize>();
+        lsp_types::Position::new(pos.line as u32, character as u32)
+    }
+
+    pub fn from_lsp(self, line: &str, pos: lsp_types::Position) -> Position {
+        let mut units = 0;
+        let mut column = 0;
+        for ch in line.chars() {
+            if units >= pos.character as usize {
+                break;
+            }
+            units += self.unit_len(ch);
+            column += 1;
+        }
+        Position::new(pos.line as usize, column)
+    }
+
+    fn unit_len(self, ch: char) -> usize {
+        match self {
+            PositionEncoding::Utf8 => ch.len_utf8(),
+            PositionEncoding::Utf16 => ch.len_utf16(),
+            PositionEncoding::Utf32 => 1,
+        }
+    }
+}
+
+#[test]
+fn test_position_encoding() {
+    let line = "let 名字 = \"🦀\";";
+    let pos = Position::new(3, 11);
+    let utf16 = PositionEncoding::Utf16.to_lsp(line, pos);
+    assert_eq!(utf16.character, 12);
+    assert_eq!(PositionEncoding::Utf16.from_lsp(line, utf16), pos);
+    assert_eq!(PositionEncoding::Utf32.to_lsp(line, pos).character, 11);
+}
@@ -0,0 +1,94 @@
+use lsp_types::DocumentSymbolResponse;
+use seeker_editor::buffer::Position;
+use seeker_editor::document::Document;
+use seeker_lsp::{LspClient, LspEvent};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+const MOCK_SERVER: &str = env!("CARGO_BIN_EXE_seeker_mock_lsp");
+
+async fn next_diagnostics(events: &mut mpsc::UnboundedReceiver<LspEvent>) -> Vec<String> {
+    loop {
+        let event = timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("timed out waiting for diagnostics")
+            .expect("server closed");
+        if let LspEvent::Diagnostics(params) = event {
+            return params
+                .diagnostics
+                .into_iter()
+                .map(|diagnostic| format!("{}:{}", diagnostic.range.start.line, diagnostic.message))
+                .collect();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_mock_server_session() {
+    let root = std::env::temp_dir().join(format!("seeker_lsp_session_{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    let path = root.join("main.rs");
+    std::fs::write(&path, "fn main() {\n    let value = 1;\n    let 名字 = value;\n}\n").unwrap();
+
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let client = LspClient::spawn(MOCK_SERVER, &[], &root, sender).unwrap();
+    let result = client.initialize(&root).await.unwrap();
+    assert!(result.capabilities.hover_provider.is_some());
+
+    let mut doc = Document::open(&path).unwrap();
+    client.did_open(&doc).unwrap();
+    assert!(next_diagnostics(&mut events).await.is_empty());
+
+    doc.insert(Position::new(2, 19), " // error");
+    client.did_change(&doc).unwrap();
+    assert_eq!(next_diagnostics(&mut events).await, vec!["2:mock error"]);
+
+    let position = client
+        .text_document_position(&doc, Position::new(1, 9))
+        .unwrap();
+
+    let hover = client.hover(position.clone()).await.unwrap().unwrap();
+    assert_eq!(
+        serde_json::to_value(hover.contents).unwrap()["value"],
+        "hover 1:9"
+    );
+
+    let definition = client.goto_definition(position.clone()).await.unwrap();
+    assert_eq!(definition.len(), 1);
+    assert_eq!(definition[0].uri.to_file_path().unwrap(), path);
+
+    let references = client.references(position.clone()).await.unwrap();
+    let lines = references
+        .iter()
+        .map(|location| location.range.start.line)
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![1, 2]);
+
+    let completion = client.completion(position).await.unwrap();
+    let labels = completion.iter().map(|item| item.label.as_str()).collect::<Vec<_>>();
+    assert_eq!(labels, vec!["mock_function", "MockStruct"]);
+
+    let DocumentSymbolResponse::Nested(symbols) = client.document_symbol(&path).await.unwrap()
+    else {
+        panic!("expected nested document symbols");
+    };
+    assert_eq!(symbols[0].name, "main");
+    assert_eq!(symbols[0].children.as_ref().unwrap()[0].name, "value");
+
+    assert!(client.supports_folding_range());
+    let ranges = client.folding_range(&path).await.unwrap();
+    let lines = ranges
+        .iter()
+        .map(|range| (range.start_line, range.end_line))
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![(0, 2)]);
+
+    let symbols = client.workspace_symbol("mai").await.unwrap();
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].container_name.as_deref(), Some("mock"));
+    assert!(client.workspace_symbol("other").await.unwrap().is_empty());
+
+    client.shutdown().await.unwrap();
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,231 @@
+//! 测试用的最小语言服务器，通过 stdio 收发 JSON-RPC
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+
+fn main() {
+    let stdin = std::io::stdin();
+    let mut reader = BufReader::new(stdin.lock());
+    let mut documents: HashMap<String, String> = HashMap::new();
+
+    while let Some(message) = read_message(&mut reader) {
+        let method = message["method"].as_str().unwrap_or_default();
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+        match method {
+            "initialize" => respond(
+                id,
+                json!({
+                    "capabilities": {
+                        "positionEncoding": "utf-16",
+                        "textDocumentSync": 1,
+                        "hoverProvider": true,
+                        "definitionProvider": true,
+                        "referencesProvider": true,
+                        "completionProvider": {},
+                        "documentSymbolProvider": true,
+                        "foldingRangeProvider": true,
+                        "workspaceSymbolProvider": true
+                    },
+                    "serverInfo": { "name": "seeker_mock_lsp" }
+                }),
+            ),
+            "textDocument/didOpen" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                documents.insert(uri.to_string(), text.to_string());
+                publish_diagnostics(uri, text);
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = params["contentChanges"][0]["text"].as_str().unwrap_or_default();
+                documents.insert(uri.to_string(), text.to_string());
+                publish_diagnostics(uri, text);
+            }
+            "textDocument/hover" => {
+                // 先发一条无法解析的消息，客户端应该跳过它继续工作
+                send_body("{invalid json");
+                let (line, character) = position(params);
+                respond(
+                    id,
+                    json!({ "contents": { "kind": "plaintext", "value": format!("hover {line}:{character}") } }),
+                );
+            }
+            "textDocument/definition" => {
+                let uri = &params["textDocument"]["uri"];
+                respond(id, json!({ "uri": uri, "range": range(0, 0, 2) }));
+            }
+            "textDocument/references" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = documents.get(uri).map(String::as_str).unwrap_or_default();
+                let (line, character) = position(params);
+                let word = word_at(text, line, character);
+                let locations = occurrences(text, &word)
+                    .into_iter()
+                    .map(|(line, start)| {
+                        json!({ "uri": uri, "range": range(line, start, start + word.len()) })
+                    })
+                    .collect::<Vec<_>>();
+                respond(id, Value::Array(locations));
+            }
+            "textDocument/completion" => respond(
+                id,
+                json!({
+                    "isIncomplete": false,
+                    "items": [
+                        { "label": "mock_function", "kind": 3, "detail": "fn mock_function()" },
+                        { "label": "MockStruct", "kind": 22, "documentation": "A mock struct" }
+                    ]
+                }),
+            ),
+            "textDocument/documentSymbol" => respond(
+                id,
+                json!([{
+                    "name": "main",
+                    "kind": 12,
+                    "range": range(0, 0, 1),
+                    "selectionRange": range(0, 3, 7),
+                    "children": [{
+                        "name": "value",
+                        "kind": 13,
+                        "range": range(1, 4, 18),
+                        "selectionRange": range(1, 8, 13)
+                    }]
+                }]),
+            ),
+            "workspace/symbol" => {
+                let query = params["query"].as_str().unwrap_or_default();
+                let symbols = documents
+                    .keys()
+                    .filter(|_| "main".contains(query))
+                    .map(|uri| {
+                        json!({
+                            "name": "main",
+                            "kind": 12,
+                            "location": { "uri": uri, "range": range(0, 3, 7) },
+                            "containerName": "mock"
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                respond(id, Value::Array(symbols));
+            }
+            "textDocument/foldingRange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = documents.get(uri).map(String::as_str).unwrap_or_default();
+                respond(id, Value::Array(folding_ranges(text)));
+            }
+            "shutdown" => respond(id, Value::Null),
+            "exit" => break,
+            _ => {
+                if id.is_some() {
+                    respond(id, Value::Null);
+                }
+            }
+        }
+    }
+}
+
+fn position(params: &Value) -> (usize, usize) {
+    let position = &params["position"];
+    (
+        position["line"].as_u64().unwrap_or_default() as usize,
+        position["character"].as_u64().unwrap_or_default() as usize,
+    )
+}
+
+fn range(line: usize, start: usize, end: usize) -> Value {
+    json!({
+        "start": { "line": line, "character": start },
+        "end": { "line": line, "character": end }
+    })
+}
+
+fn word_at(text: &str, line: usize, character: usize) -> String {
+    let line = text.lines().nth(line).unwrap_or_default();
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    let start = line[..character.min(line.len())]
+        .rfind(|ch| !is_word(ch))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let end = line[start..]
+        .find(|ch| !is_word(ch))
+        .map(|i| start + i)
+        .unwrap_or(line.len());
+    line[start..end].to_string()
+}
+
+fn occurrences(text: &str, word: &str) -> Vec<(usize, usize)> {
+    if word.is_empty() {
+        return vec![];
+    }
+    text.lines()
+        .enumerate()
+        .flat_map(|(line, content)| content.match_indices(word).map(move |(i, _)| (line, i)))
+        .collect()
+}
+
+/// 以 `{` 结尾的行到对应的 `}` 的上一行
+fn folding_ranges(text: &str) -> Vec<Value> {
+    let mut starts = vec![];
+    let mut ranges = vec![];
+    for (line, content) in text.lines().enumerate() {
+        if content.trim_start().starts_with('}')
+            && let Some(start) = starts.pop()
+        {
+            ranges.push(json!({ "startLine": start, "endLine": line - 1 }));
+        }
+        if content.trim_end().ends_with('{') {
+            starts.push(line);
+        }
+    }
+    ranges
+}
+
+/// 每个出现 `error` 的位置报告一个错误
+fn publish_diagnostics(uri: &str, text: &str) {
+    let diagnostics = occurrences(text, "error")
+        .into_iter()
+        .map(|(line, start)| {
+            json!({ "range": range(line, start, start + 5), "severity": 1, "message": "mock error" })
+        })
+        .collect::<Vec<_>>();
+    send(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics }
+    }));
+}
+
+fn respond(id: Option<Value>, result: Value) {
+    send(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+}
+
+fn send(message: &Value) {
+    send_body(&message.to_string());
+}
+
+fn send_body(body: &str) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body);
+    let _ = stdout.flush();
+}
+
+fn read_message(reader: &mut impl BufRead) -> Option<Value> {
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse().ok()?;
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
@@ -0,0 +1,98 @@
+use bevy::asset::io::{
+    AssetReader, AssetReaderError, AssetSourceBuilder, PathStream, Reader, VecReader,
+};
+use bevy::asset::AssetPath;
+use std::path::{Component, Path, PathBuf, Prefix};
+
+/// 读取项目中本地文件（例如 Markdown 引用的图片）的资源来源，需要在 DefaultPlugins 之前注册
+pub const LOCAL_ASSET_SOURCE: &str = "local";
+
+/// 按绝对路径读取本地文件的只读来源，不监听文件变化
+pub fn local_asset_source() -> AssetSourceBuilder {
+    AssetSourceBuilder::default().with_reader(|| Box::new(LocalAssetReader))
+}
+
+/// 本地文件的绝对路径对应的资源路径，路径中的 .. 在这里展开。
+/// 资源路径只能是相对路径，Windows 的盘符保存为第一段，读取时再还原
+pub fn local_asset_path(path: &Path) -> AssetPath<'static> {
+    let mut relative = PathBuf::new();
+    let mut root = 0;
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                if let Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) = prefix.kind() {
+                    relative.push(char::from(drive).to_string());
+                    root = 1;
+                }
+            }
+            Component::Normal(name) => relative.push(name),
+            Component::ParentDir => {
+                if relative.components().count() > root {
+                    relative.pop();
+                }
+            }
+            Component::RootDir | Component::CurDir => {}
+        }
+    }
+    AssetPath::from_path_buf(relative).with_source(LOCAL_ASSET_SOURCE)
+}
+
+/// 资源路径还原成本地文件的绝对路径
+fn local_file_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        let mut components = path.components();
+        if let Some(drive) = components.next() {
+            let mut root = drive.as_os_str().to_owned();
+            root.push(":\\");
+            return PathBuf::from(root).join(components.as_path());
+        }
+    }
+    Path::new("/").join(path)
+}
+
+struct LocalAssetReader;
+
+impl AssetReader for LocalAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        let path = local_file_path(path);
+        match std::fs::read(&path) {
+            Ok(bytes) => Ok(VecReader::new(bytes)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                Err(AssetReaderError::NotFound(path))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// 本地文件没有 .meta，使用默认的加载设置
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        Err::<VecReader, _>(AssetReaderError::NotFound(local_file_path(path)))
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        Err(AssetReaderError::NotFound(local_file_path(path)))
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(local_file_path(path).is_dir())
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_local_asset_path() {
+    let asset = local_asset_path(Path::new("/home/seeker/docs/../images/a.png"));
+    assert_eq!(asset.path(), Path::new("home/seeker/images/a.png"));
+    assert!(!asset.is_unapproved());
+    assert_eq!(
+        local_file_path(asset.path()),
+        Path::new("/home/seeker/images/a.png")
+    );
+    // 不会越过根目录
+    let asset = local_asset_path(Path::new("/../a.png"));
+    assert_eq!(local_file_path(asset.path()), Path::new("/a.png"));
+}
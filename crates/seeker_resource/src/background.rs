@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use bevy::winit::{EventLoopProxy, EventLoopProxyWrapper, WakeUp};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Mutex;
+
+/// 后台线程发送结果后唤醒低功耗模式下的事件循环，没有窗口时（例如测试）不唤醒
+#[derive(Resource, Default, Clone)]
+pub struct BackgroundWake(Option<EventLoopProxy<WakeUp>>);
+
+impl BackgroundWake {
+    pub fn wake(&self) {
+        if let Some(proxy) = &self.0 {
+            let _ = proxy.send_event(WakeUp);
+        }
+    }
+
+    /// 发送后唤醒事件循环的通道
+    pub fn channel<T>(&self) -> (WakeSender<T>, BackgroundReceiver<T>) {
+        let (sender, receiver) = mpsc::channel();
+        let sender = WakeSender {
+            sender,
+            wake: self.clone(),
+        };
+        (sender, BackgroundReceiver(Mutex::new(receiver)))
+    }
+
+    /// 在后台线程中运行，完成后唤醒事件循环
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        f: impl FnOnce() -> T + Send + 'static,
+    ) -> BackgroundReceiver<T> {
+        let (sender, receiver) = self.channel();
+        std::thread::spawn(move || sender.send(f()));
+        receiver
+    }
+}
+
+/// 发送的一端，drop 时也唤醒一次让界面发现后台已经结束
+pub struct WakeSender<T> {
+    sender: Sender<T>,
+    wake: BackgroundWake,
+}
+
+impl<T> Clone for WakeSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            wake: self.wake.clone(),
+        }
+    }
+}
+
+impl<T> WakeSender<T> {
+    /// 接收的一端已经 drop 时返回 false
+    pub fn send(&self, value: T) -> bool {
+        let sent = self.sender.send(value).is_ok();
+        if sent {
+            self.wake.wake();
+        }
+        sent
+    }
+}
+
+impl<T> Drop for WakeSender<T> {
+    fn drop(&mut self) {
+        self.wake.wake();
+    }
+}
+
+/// 接收的一端，资源需要 Sync 所以放在 Mutex 中
+pub struct BackgroundReceiver<T>(Mutex<Receiver<T>>);
+
+impl<T> BackgroundReceiver<T> {
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.0.lock().unwrap().try_recv()
+    }
+
+    /// 取出目前为止收到的全部消息，所有发送端都已 drop 时 bool 为 true
+    pub fn drain(&self) -> (Vec<T>, bool) {
+        let receiver = self.0.lock().unwrap();
+        let mut values = vec![];
+        loop {
+            match receiver.try_recv() {
+                Ok(value) => values.push(value),
+                Err(TryRecvError::Empty) => return (values, false),
+                Err(TryRecvError::Disconnected) => return (values, true),
+            }
+        }
+    }
+}
+
+/// 启动时取得事件循环的代理
+#[derive(Component)]
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BackgroundWake>()
+            .add_systems(PreStartup, Self::init_wake);
+    }
+}
+
+impl BackgroundPlugin {
+    fn init_wake(
+        mut wake: ResMut<BackgroundWake>,
+        proxy: Option<Res<EventLoopProxyWrapper<WakeUp>>>,
+    ) {
+        wake.0 = proxy.map(|proxy| (**proxy).clone());
+    }
+}
+
+#[test]
+fn test_background() {
+    let wake = BackgroundWake::default();
+    let receiver = wake.spawn(|| 1 + 1);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    let value = loop {
+        match receiver.try_recv() {
+            Ok(value) => break value,
+            Err(TryRecvError::Empty) => assert!(std::time::Instant::now() < deadline),
+            Err(TryRecvError::Disconnected) => panic!("sender dropped"),
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    };
+    assert_eq!(value, 2);
+
+    let (sender, receiver) = wake.channel();
+    assert!(sender.send("a"));
+    sender.clone().send("b");
+    assert_eq!(receiver.drain(), (vec!["a", "b"], false));
+    drop(sender);
+    assert_eq!(receiver.drain(), (vec![], true));
+    drop(receiver);
+    let (sender, receiver) = wake.channel::<u8>();
+    drop(receiver);
+    assert!(!sender.send(1));
+}
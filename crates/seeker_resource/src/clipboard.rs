@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::sync::{Mutex, PoisonError};
+
+/// 剪贴板历史最多保留的条数
+pub const CLIPBOARD_HISTORY: usize = 20;
+
+/// 一次复制的内容
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClipboardEntry {
+    pub text: String,
+}
+
+enum Backend {
+    System(Mutex<arboard::Clipboard>),
+    /// 没有窗口系统时（例如测试）只在进程内保存
+    Memory(Option<String>),
+}
+
+/// 系统剪贴板和复制历史，输入框、编辑器、终端和文件对话框共用
+#[derive(Resource)]
+pub struct SeekerClipboard {
+    backend: Backend,
+    /// 最新的在前面
+    history: VecDeque<ClipboardEntry>,
+}
+
+impl Default for SeekerClipboard {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+impl SeekerClipboard {
+    /// 连接系统剪贴板，失败时退回到进程内的剪贴板
+    pub fn system() -> Self {
+        match arboard::Clipboard::new() {
+            Ok(clipboard) => Self {
+                backend: Backend::System(Mutex::new(clipboard)),
+                history: VecDeque::new(),
+            },
+            Err(err) => {
+                warn!("system clipboard unavailable: {err}");
+                Self::memory()
+            }
+        }
+    }
+
+    pub fn memory() -> Self {
+        Self {
+            backend: Backend::Memory(None),
+            history: VecDeque::new(),
+        }
+    }
+
+    fn set_text(&mut self, text: &str) {
+        match &mut self.backend {
+            Backend::System(clipboard) => {
+                let clipboard = clipboard.get_mut().unwrap_or_else(PoisonError::into_inner);
+                if let Err(err) = clipboard.set_text(text) {
+                    error!("copy: {err}");
+                }
+            }
+            Backend::Memory(memory) => *memory = Some(text.to_string()),
+        }
+    }
+
+    fn get_text(&self) -> Option<String> {
+        match &self.backend {
+            Backend::System(clipboard) => {
+                let mut clipboard = clipboard.lock().unwrap_or_else(PoisonError::into_inner);
+                match clipboard.get_text() {
+                    Ok(text) => Some(text),
+                    // 剪贴板是空的或者不是文本
+                    Err(arboard::Error::ContentNotAvailable) => None,
+                    Err(err) => {
+                        error!("paste: {err}");
+                        None
+                    }
+                }
+            }
+            Backend::Memory(memory) => memory.clone(),
+        }
+    }
+
+    fn remember(&mut self, entry: ClipboardEntry) {
+        self.history.retain(|existing| existing.text != entry.text);
+        self.history.push_front(entry);
+        self.history.truncate(CLIPBOARD_HISTORY);
+    }
+
+    pub fn copy(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        if text.is_empty() {
+            return;
+        }
+        self.set_text(&text);
+        self.remember(ClipboardEntry { text });
+    }
+
+    /// 在其他程序中复制的内容也记入历史
+    pub fn paste(&mut self) -> Option<String> {
+        let text = self.get_text().filter(|text| !text.is_empty())?;
+        if self.history.front().is_none_or(|entry| entry.text != text) {
+            self.remember(ClipboardEntry { text: text.clone() });
+        }
+        Some(text)
+    }
+
+    pub fn history(&self) -> impl Iterator<Item = &ClipboardEntry> {
+        self.history.iter()
+    }
+
+    /// 轮换历史：最新的一条移到最后，下一条写回系统剪贴板，返回它
+    pub fn cycle(&mut self) -> Option<&ClipboardEntry> {
+        if self.history.len() < 2 {
+            return None;
+        }
+        self.history.rotate_left(1);
+        let text = self.history.front()?.text.clone();
+        self.set_text(&text);
+        self.history.front()
+    }
+}
+
+#[test]
+fn test_clipboard() {
+    let mut clipboard = SeekerClipboard::memory();
+    assert_eq!(clipboard.paste(), None);
+    clipboard.copy("");
+    assert_eq!(clipboard.paste(), None);
+    clipboard.copy("a\nb");
+    assert_eq!(clipboard.paste().as_deref(), Some("a\nb"));
+    clipboard.copy("c");
+    clipboard.copy("a\nb");
+    let history: Vec<&str> = clipboard
+        .history()
+        .map(|entry| entry.text.as_str())
+        .collect();
+    assert_eq!(history, ["a\nb", "c"]);
+    assert_eq!(
+        clipboard.cycle().map(|entry| entry.text.as_str()),
+        Some("c")
+    );
+    assert_eq!(clipboard.paste().as_deref(), Some("c"));
+}
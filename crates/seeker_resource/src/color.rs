@@ -9,6 +9,23 @@ pub struct SeekerColors {
     pub home_font_color: Color,
     /// 灰色
     pub home_font_grey_color: Color,
+    pub editor_background: Color,
+    pub editor_cursor: Color,
+    pub editor_selection: Color,
+    pub editor_current_line: Color,
+    /// 配对的括号
+    pub editor_bracket: Color,
+    pub error: Color,
+    pub warning: Color,
+    pub info: Color,
+    pub git_added: Color,
+    pub git_modified: Color,
+    pub git_untracked: Color,
+    /// 代码高亮
+    pub syntax_keyword: Color,
+    pub syntax_string: Color,
+    pub syntax_number: Color,
+    pub syntax_comment: Color,
 }
 
 impl SeekerColors {
@@ -20,6 +37,21 @@ impl SeekerColors {
             button_border: Color::srgb_u8(79, 81, 86),
             home_font_color: Color::srgb_u8(218, 220, 224),
             home_font_grey_color: Color::srgb_u8(100, 100, 100),
+            editor_background: Color::srgb_u8(30, 31, 34),
+            editor_cursor: Color::srgb_u8(206, 208, 214),
+            editor_selection: Color::srgb_u8(33, 66, 131),
+            editor_current_line: Color::srgb_u8(38, 40, 46),
+            editor_bracket: Color::srgb_u8(67, 80, 80),
+            error: Color::srgb_u8(247, 84, 100),
+            warning: Color::srgb_u8(232, 169, 71),
+            info: Color::srgb_u8(84, 138, 247),
+            git_added: Color::srgb_u8(98, 181, 67),
+            git_modified: Color::srgb_u8(108, 149, 235),
+            git_untracked: Color::srgb_u8(208, 128, 96),
+            syntax_keyword: Color::srgb_u8(207, 142, 109),
+            syntax_string: Color::srgb_u8(106, 171, 115),
+            syntax_number: Color::srgb_u8(42, 172, 184),
+            syntax_comment: Color::srgb_u8(122, 126, 133),
         }
     }
 }
@@ -17,6 +17,22 @@ pub struct CurrentFile {
     pub file: Option<File>,
 }
 
+/// 文件对话框打开的用途，关闭后恢复为添加项目
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileDialogPurpose {
+    #[default]
+    AddProject,
+    /// 选择一个文件和当前文档比较
+    CompareWithFocused,
+}
+
+/// 在文件对话框中选择了文件
+#[derive(Message, Clone, Debug)]
+pub struct FileDialogPicked {
+    pub purpose: FileDialogPurpose,
+    pub path: PathBuf,
+}
+
 #[derive(Component, Clone, Default)]
 pub struct Level {
     pub level: usize,
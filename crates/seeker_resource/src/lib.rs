@@ -1,6 +1,9 @@
 use crate::color::SeekerColors;
 use bevy::prelude::*;
 
+pub mod assets;
+pub mod background;
+pub mod clipboard;
 pub mod color;
 pub mod file;
 pub mod fonts;
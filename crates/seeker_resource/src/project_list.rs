@@ -13,3 +13,9 @@ impl Default for ProjectListResource {
         }
     }
 }
+
+/// 当前在编辑界面中打开的项目
+#[derive(Resource, Default)]
+pub struct CurrentProject {
+    pub project: Option<Project>,
+}
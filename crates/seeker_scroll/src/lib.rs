@@ -1,6 +1,8 @@
 use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::picking::hover::HoverMap;
 use bevy::prelude::*;
+use bevy::ui::{FocusPolicy, RelativeCursorPosition};
+use std::collections::HashMap;
 
 #[derive(Component)]
 pub struct SeekerScrollPlugin;
@@ -9,8 +11,19 @@ const LINE_HEIGHT: f32 = 20.;
 
 impl Plugin for SeekerScrollPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, send_scroll_events)
-            .add_observer(on_scroll_handler);
+        app.add_systems(
+            Update,
+            (
+                send_scroll_events,
+                scrollbar_input,
+                sync_scroll,
+                spawn_thumbs,
+                update_thumbs,
+                render_markers,
+            )
+                .chain(),
+        )
+        .add_observer(on_scroll_handler);
     }
 }
 
@@ -41,6 +54,235 @@ fn send_scroll_events(
     }
 }
 
+/// Scroll containers with the same group id share one scroll position.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ScrollSync(pub u32);
+
+/// Copies the position of the container that scrolled to the rest of its group.
+fn sync_scroll(
+    mut query: Query<(&ScrollSync, &mut ScrollPosition)>,
+    mut synced: Local<HashMap<ScrollSync, Vec2>>,
+) {
+    let mut moved = HashMap::new();
+    for (group, scroll) in query.iter_mut() {
+        if scroll.is_changed() && synced.get(group) != Some(&scroll.0) {
+            moved.insert(*group, scroll.0);
+        }
+    }
+    for (group, mut scroll) in query.iter_mut() {
+        if let Some(position) = moved.get(group)
+            && scroll.0 != *position
+        {
+            scroll.0 = *position;
+        }
+    }
+    synced.extend(moved);
+}
+
+/// Wheel events over this node scroll the target container instead.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScrollProxy(pub Entity);
+
+/// Vertical scrollbar of the target container.
+/// Clicking the track jumps to that part of the content, the thumb can be dragged.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+#[require(Node, FocusPolicy::Block, Interaction, RelativeCursorPosition)]
+pub struct Scrollbar {
+    pub target: Entity,
+}
+
+#[derive(Component, Default)]
+#[require(Node, FocusPolicy::Block, Interaction)]
+pub struct ScrollbarThumb {
+    /// Where the thumb was grabbed, as a fraction of the content above the scroll position.
+    grab: Option<f32>,
+}
+
+const THUMB_COLOR: Color = Color::srgba(1., 1., 1., 0.12);
+const THUMB_ACTIVE_COLOR: Color = Color::srgba(1., 1., 1., 0.24);
+const MIN_THUMB_HEIGHT: f32 = 16.;
+const MIN_MARKER_HEIGHT: f32 = 2.;
+
+/// Column of the track a marker is drawn in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkerLane {
+    Left,
+    Center,
+    Right,
+    Full,
+}
+
+/// A range of the content highlighted on the track.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollbarMarker {
+    /// Start and end as fractions of the content height.
+    pub start: f32,
+    pub end: f32,
+    pub lane: MarkerLane,
+    pub color: Color,
+}
+
+/// Markers shown on a scrollbar, set by whoever owns the content.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct ScrollbarMarkers(pub Vec<ScrollbarMarker>);
+
+#[derive(Component)]
+struct ScrollbarMarkerNode;
+
+/// Content height, viewport height and maximum offset of a scroll container in logical pixels.
+fn scroll_extent(computed: &ComputedNode) -> (f32, f32, f32) {
+    let content = computed.content_size().y * computed.inverse_scale_factor();
+    let viewport = computed.size().y * computed.inverse_scale_factor();
+    (content, viewport, (content - viewport).max(0.))
+}
+
+/// Top and height of the thumb as fractions of the track, None when everything fits.
+fn thumb_span(content: f32, viewport: f32, offset: f32) -> Option<(f32, f32)> {
+    (content > viewport + 0.5).then(|| (offset / content, viewport / content))
+}
+
+/// Scroll offset for the pointer at `fraction` of the track.
+/// A dragged thumb keeps the point it was grabbed at under the pointer,
+/// pressing the track centers that part of the content.
+fn track_offset(fraction: f32, grab: Option<f32>, content: f32, viewport: f32) -> f32 {
+    let offset = match grab {
+        Some(grab) => (fraction - grab) * content,
+        None => fraction * content - viewport / 2.,
+    };
+    offset.clamp(0., (content - viewport).max(0.))
+}
+
+fn scrollbar_input(
+    scrollbars: Query<(&Scrollbar, &Interaction, &RelativeCursorPosition, &Children)>,
+    mut thumbs: Query<(&Interaction, &mut ScrollbarThumb)>,
+    mut targets: Query<(&mut ScrollPosition, &ComputedNode)>,
+) {
+    for (scrollbar, interaction, relative, children) in scrollbars.iter() {
+        let Some(thumb) = children.iter().find(|child| thumbs.contains(*child)) else {
+            continue;
+        };
+        let Ok((thumb_interaction, mut thumb)) = thumbs.get_mut(thumb) else {
+            continue;
+        };
+        if *thumb_interaction != Interaction::Pressed {
+            thumb.grab = None;
+        }
+        let Ok((mut scroll, computed)) = targets.get_mut(scrollbar.target) else {
+            continue;
+        };
+        let (content, viewport, _) = scroll_extent(computed);
+        let Some(normalized) = relative.normalized else {
+            continue;
+        };
+        if content <= 0. {
+            continue;
+        }
+        let fraction = normalized.y + 0.5;
+        let grab = if *thumb_interaction == Interaction::Pressed {
+            Some(*thumb.grab.get_or_insert(fraction - scroll.y / content))
+        } else if *interaction == Interaction::Pressed {
+            // Keep following the pointer while the track stays pressed
+            None
+        } else {
+            continue;
+        };
+        let offset = track_offset(fraction, grab, content, viewport);
+        if scroll.y != offset {
+            scroll.y = offset;
+        }
+    }
+}
+
+fn spawn_thumbs(mut commands: Commands, scrollbars: Query<Entity, Added<Scrollbar>>) {
+    for entity in scrollbars.iter() {
+        commands.entity(entity).with_child((
+            ScrollbarThumb::default(),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                min_height: Val::Px(MIN_THUMB_HEIGHT),
+                ..default()
+            },
+            ZIndex(1),
+            BackgroundColor(THUMB_COLOR),
+        ));
+    }
+}
+
+/// Hides the thumb when everything fits.
+fn update_thumbs(
+    scrollbars: Query<(&Scrollbar, &Children)>,
+    mut thumbs: Query<(&Interaction, &mut Node, &mut BackgroundColor), With<ScrollbarThumb>>,
+    targets: Query<(&ScrollPosition, &ComputedNode)>,
+) {
+    for (scrollbar, children) in scrollbars.iter() {
+        let Some(thumb) = children.iter().find(|child| thumbs.contains(*child)) else {
+            continue;
+        };
+        let (Ok((interaction, mut node, mut background)), Ok((scroll, computed))) =
+            (thumbs.get_mut(thumb), targets.get(scrollbar.target))
+        else {
+            continue;
+        };
+        let (content, viewport, _) = scroll_extent(computed);
+        let (display, top, height) = match thumb_span(content, viewport, scroll.y) {
+            Some((top, height)) => (
+                Display::Flex,
+                Val::Percent(top * 100.),
+                Val::Percent(height * 100.),
+            ),
+            None => (Display::None, Val::ZERO, Val::ZERO),
+        };
+        if node.display != display || node.top != top || node.height != height {
+            node.display = display;
+            node.top = top;
+            node.height = height;
+        }
+        background.set_if_neq(BackgroundColor(if *interaction == Interaction::None {
+            THUMB_COLOR
+        } else {
+            THUMB_ACTIVE_COLOR
+        }));
+    }
+}
+
+fn render_markers(
+    mut commands: Commands,
+    scrollbars: Query<(Entity, &ScrollbarMarkers, Option<&Children>), Changed<ScrollbarMarkers>>,
+    nodes: Query<(), With<ScrollbarMarkerNode>>,
+) {
+    for (entity, markers, children) in scrollbars.iter() {
+        for child in children.into_iter().flatten() {
+            if nodes.contains(*child) {
+                commands.entity(*child).despawn();
+            }
+        }
+        commands.entity(entity).with_children(|parent| {
+            for marker in &markers.0 {
+                let (left, width) = match marker.lane {
+                    MarkerLane::Left => (0., 100. / 3.),
+                    MarkerLane::Center => (100. / 3., 100. / 3.),
+                    MarkerLane::Right => (200. / 3., 100. / 3.),
+                    MarkerLane::Full => (0., 100.),
+                };
+                parent.spawn((
+                    ScrollbarMarkerNode,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(left),
+                        width: Val::Percent(width),
+                        top: Val::Percent(marker.start * 100.),
+                        height: Val::Percent((marker.end - marker.start).max(0.) * 100.),
+                        min_height: Val::Px(MIN_MARKER_HEIGHT),
+                        ..default()
+                    },
+                    BackgroundColor(marker.color),
+                ));
+            }
+        });
+    }
+}
+
 /// UI scrolling event.
 #[derive(EntityEvent, Debug)]
 #[entity_event(propagate, auto_propagate)]
@@ -53,9 +295,16 @@ struct Scroll {
 fn on_scroll_handler(
     mut scroll: On<Scroll>,
     mut query: Query<(&mut ScrollPosition, &Node, &ComputedNode)>,
+    proxies: Query<&ScrollProxy>,
+    scrollbars: Query<&Scrollbar>,
 ) {
     // info!("on_scroll_handler");
-    let Ok((mut scroll_position, node, computed)) = query.get_mut(scroll.entity) else {
+    let entity = match (proxies.get(scroll.entity), scrollbars.get(scroll.entity)) {
+        (Ok(proxy), _) => proxy.0,
+        (_, Ok(scrollbar)) => scrollbar.target,
+        _ => scroll.entity,
+    };
+    let Ok((mut scroll_position, node, computed)) = query.get_mut(entity) else {
         return;
     };
 
@@ -97,3 +346,53 @@ fn on_scroll_handler(
         scroll.propagate(false);
     }
 }
+
+#[test]
+fn test_scrollbar_mapping() {
+    // 1000px of content in a 250px viewport
+    assert_eq!(thumb_span(1000., 250., 0.), Some((0., 0.25)));
+    assert_eq!(thumb_span(1000., 250., 750.), Some((0.75, 0.25)));
+    assert_eq!(thumb_span(250., 250., 0.), None);
+
+    // Pressing the track centers the pointer, clamped to the scroll range
+    assert_eq!(track_offset(0.5, None, 1000., 250.), 375.);
+    assert_eq!(track_offset(0.05, None, 1000., 250.), 0.);
+    assert_eq!(track_offset(0.99, None, 1000., 250.), 750.);
+    // The thumb grabbed at its middle follows the pointer
+    assert_eq!(track_offset(0.625, Some(0.125), 1000., 250.), 500.);
+    assert_eq!(track_offset(0.1, Some(0.125), 1000., 250.), 0.);
+    assert_eq!(track_offset(0.5, None, 100., 250.), 0.);
+}
+
+#[test]
+fn test_scroll_sync() {
+    let mut app = App::new();
+    app.add_systems(Update, sync_scroll);
+    let left = app
+        .world_mut()
+        .spawn((ScrollSync(1), ScrollPosition::default()))
+        .id();
+    let right = app
+        .world_mut()
+        .spawn((ScrollSync(1), ScrollPosition::default()))
+        .id();
+    let other = app
+        .world_mut()
+        .spawn((ScrollSync(2), ScrollPosition::default()))
+        .id();
+    app.update();
+
+    let position = |app: &App, entity| app.world().get::<ScrollPosition>(entity).unwrap().0;
+    app.world_mut().get_mut::<ScrollPosition>(left).unwrap().0 = Vec2::new(0., 120.);
+    app.update();
+    assert_eq!(position(&app, right), Vec2::new(0., 120.));
+    assert_eq!(position(&app, other), Vec2::ZERO);
+
+    // Either side can lead
+    app.world_mut().get_mut::<ScrollPosition>(right).unwrap().0 = Vec2::new(0., 40.);
+    app.update();
+    assert_eq!(position(&app, left), Vec2::new(0., 40.));
+    app.update();
+    assert_eq!(position(&app, left), Vec2::new(0., 40.));
+    assert_eq!(position(&app, right), Vec2::new(0., 40.));
+}
@@ -2,7 +2,9 @@ use rusqlite::Connection;
 use std::rc::Rc;
 use std::sync::LazyLock;
 
+pub mod pane_layout;
 pub mod project;
+pub mod session;
 
 pub type Result<T> = std::result::Result<T, rusqlite::Error>;
 
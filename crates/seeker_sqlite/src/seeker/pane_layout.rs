@@ -0,0 +1,84 @@
+use super::{get_conn, Result};
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+
+pub const TABLE_NAME: &str = "pane_layout";
+
+pub const FIELDS: &str = "project_path,layout";
+
+/// 项目的编辑器分屏布局，layout 为 JSON
+/// Unique：[project_path]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaneLayout {
+    pub project_path: String,
+    pub layout: String,
+}
+
+impl PaneLayout {
+    pub fn upsert(&self) -> Result<usize> {
+        let sql = format!(
+            "INSERT INTO {TABLE_NAME} ({FIELDS}) VALUES(?1,?2) \
+             ON CONFLICT(project_path) DO UPDATE SET layout = excluded.layout"
+        );
+        let data = get_conn().execute(&sql, (&self.project_path, &self.layout))?;
+        Ok(data)
+    }
+
+    pub fn select_optional_by_project_path(project_path: &str) -> Result<Option<Self>> {
+        let sql = format!("SELECT {FIELDS} FROM {TABLE_NAME} WHERE project_path = ?1");
+        let conn = get_conn();
+        let data = conn
+            .query_row(&sql, [project_path], |row| {
+                Ok(Self {
+                    project_path: row.get(0)?,
+                    layout: row.get(1)?,
+                })
+            })
+            .optional()?;
+        Ok(data)
+    }
+}
+
+impl PaneLayout {
+    pub fn create_table() -> Result<()> {
+        let sql = r#"
+            create table if not exists pane_layout
+            (
+                project_path text    not null primary key,
+                layout       text    not null
+            );
+        "#;
+        let conn = get_conn();
+        conn.execute(sql, ())?;
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        Self::create_table()?;
+        self.upsert()?;
+        Ok(())
+    }
+
+    pub fn load(project_path: &str) -> Result<Option<Self>> {
+        Self::create_table()?;
+        Self::select_optional_by_project_path(project_path)
+    }
+}
+
+#[test]
+fn test_pane_layout() {
+    let project_path = "/tmp/seeker-test-pane-layout";
+    let data = PaneLayout {
+        project_path: project_path.to_string(),
+        layout: "{}".to_string(),
+    };
+    data.save().unwrap();
+    let data = PaneLayout {
+        layout: "[]".to_string(),
+        ..data
+    };
+    data.save().unwrap();
+    let loaded = PaneLayout::load(project_path).unwrap().unwrap();
+    assert_eq!(loaded.layout, "[]");
+    assert!(PaneLayout::load("/tmp/seeker-test-missing").unwrap().is_none());
+}
@@ -0,0 +1,125 @@
+use super::{get_conn, Result};
+use rusqlite::{OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+pub const TABLE_NAME: &str = "session";
+
+pub const FIELDS: &str = "project_path,expanded,sidebar_width,panel_height,opened_at";
+
+/// 项目上次的会话：展开的目录（JSON 数组）、侧边栏宽度、底部面板高度和打开时间
+/// Unique：[project_path]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub project_path: String,
+    pub expanded: String,
+    pub sidebar_width: f64,
+    pub panel_height: f64,
+    /// 秒级时间戳
+    pub opened_at: i64,
+}
+
+impl Session {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            project_path: row.get(0)?,
+            expanded: row.get(1)?,
+            sidebar_width: row.get(2)?,
+            panel_height: row.get(3)?,
+            opened_at: row.get(4)?,
+        })
+    }
+
+    pub fn upsert(&self) -> Result<usize> {
+        let sql = format!(
+            "INSERT INTO {TABLE_NAME} ({FIELDS}) VALUES(?1,?2,?3,?4,?5) \
+             ON CONFLICT(project_path) DO UPDATE SET expanded = excluded.expanded, \
+             sidebar_width = excluded.sidebar_width, panel_height = excluded.panel_height, \
+             opened_at = excluded.opened_at"
+        );
+        let data = get_conn().execute(
+            &sql,
+            (
+                &self.project_path,
+                &self.expanded,
+                self.sidebar_width,
+                self.panel_height,
+                self.opened_at,
+            ),
+        )?;
+        Ok(data)
+    }
+
+    pub fn select_optional_by_project_path(project_path: &str) -> Result<Option<Self>> {
+        let sql = format!("SELECT {FIELDS} FROM {TABLE_NAME} WHERE project_path = ?1");
+        let data = get_conn()
+            .query_row(&sql, [project_path], Self::from_row)
+            .optional()?;
+        Ok(data)
+    }
+
+    /// 最近打开的项目
+    pub fn select_optional_last() -> Result<Option<Self>> {
+        let sql = format!("SELECT {FIELDS} FROM {TABLE_NAME} ORDER BY opened_at DESC LIMIT 1");
+        let data = get_conn().query_row(&sql, [], Self::from_row).optional()?;
+        Ok(data)
+    }
+}
+
+impl Session {
+    pub fn create_table() -> Result<()> {
+        let sql = r#"
+            create table if not exists session
+            (
+                project_path  text    not null primary key,
+                expanded      text    not null,
+                sidebar_width real    not null,
+                panel_height  real    not null,
+                opened_at     integer not null
+            );
+        "#;
+        let conn = get_conn();
+        conn.execute(sql, ())?;
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        Self::create_table()?;
+        self.upsert()?;
+        Ok(())
+    }
+
+    pub fn load(project_path: &str) -> Result<Option<Self>> {
+        Self::create_table()?;
+        Self::select_optional_by_project_path(project_path)
+    }
+
+    pub fn last() -> Result<Option<Self>> {
+        Self::create_table()?;
+        Self::select_optional_last()
+    }
+}
+
+#[test]
+fn test_session() {
+    let data = Session {
+        project_path: "/tmp/seeker-test-session".to_string(),
+        expanded: "[]".to_string(),
+        sidebar_width: 250.,
+        panel_height: 200.,
+        opened_at: 1,
+    };
+    data.save().unwrap();
+    let data = Session {
+        sidebar_width: 300.,
+        opened_at: 2,
+        ..data
+    };
+    data.save().unwrap();
+    assert_eq!(
+        Session::load(&data.project_path).unwrap(),
+        Some(data.clone())
+    );
+    assert!(Session::last()
+        .unwrap()
+        .is_some_and(|last| last.opened_at >= data.opened_at));
+}
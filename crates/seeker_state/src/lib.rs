@@ -32,9 +32,8 @@ fn test() {
     );
 }
 
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, SubStates, enum_from_derive::From)]
-#[source(SeekerState = SeekerState::Home)]
-#[states(scoped_entities)]
+/// 文件对话框，首页添加项目和编辑界面选择比较的文件都会打开
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States, enum_from_derive::From)]
 pub enum SeekerFileDialogFnState {
     #[default]
     None,
@@ -64,3 +63,44 @@ pub enum SeekerHomeSubLoadState {
     Loading,
     Loaded,
 }
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, SubStates)]
+#[source(SeekerState = SeekerState::Edit)]
+#[states(scoped_entities)]
+pub enum SeekerEditSubLoadState {
+    #[default]
+    Loading,
+    Loaded,
+}
+
+/// 编辑区显示编辑器、文本比较还是十六进制视图
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, SubStates)]
+#[source(SeekerState = SeekerState::Edit)]
+#[states(scoped_entities)]
+pub enum SeekerEditViewState {
+    #[default]
+    Editor,
+    Diff,
+    Hex,
+}
+
+/// 编辑界面底部面板
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, SubStates, enum_from_derive::From)]
+#[source(SeekerState = SeekerState::Edit)]
+#[states(scoped_entities)]
+pub enum SeekerEditPanelState {
+    #[default]
+    None,
+    References,
+    Problems,
+    SourceControl,
+    Terminal,
+    Tasks,
+    Outline,
+}
+
+impl From<String> for SeekerEditPanelState {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
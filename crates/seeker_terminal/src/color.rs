@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+/// xterm 默认的 16 色
+const ANSI: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x31, 0x31),
+    (0x0d, 0xbc, 0x79),
+    (0xe5, 0xe5, 0x10),
+    (0x24, 0x72, 0xc8),
+    (0xbc, 0x3f, 0xbc),
+    (0x11, 0xa8, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x66, 0x66, 0x66),
+    (0xf1, 0x4c, 0x4c),
+    (0x23, 0xd1, 0x8b),
+    (0xf5, 0xf5, 0x43),
+    (0x3b, 0x8e, 0xea),
+    (0xd6, 0x70, 0xd6),
+    (0x29, 0xb8, 0xdb),
+    (0xff, 0xff, 0xff),
+];
+
+/// 256 色表中的颜色：16 色、6x6x6 色块和 24 级灰度
+pub fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..16 => ANSI[index as usize],
+        16..232 => {
+            let index = index - 16;
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// 终端颜色转换为界面颜色，默认颜色使用 default
+pub fn terminal_color(color: vt100::Color, default: Color) -> Color {
+    match color {
+        vt100::Color::Default => default,
+        vt100::Color::Idx(index) => {
+            let (r, g, b) = indexed_rgb(index);
+            Color::srgb_u8(r, g, b)
+        }
+        vt100::Color::Rgb(r, g, b) => Color::srgb_u8(r, g, b),
+    }
+}
+
+#[test]
+fn test_indexed_rgb() {
+    assert_eq!(indexed_rgb(1), (0xcd, 0x31, 0x31));
+    assert_eq!(indexed_rgb(16), (0, 0, 0));
+    assert_eq!(indexed_rgb(196), (255, 0, 0));
+    assert_eq!(indexed_rgb(231), (255, 255, 255));
+    assert_eq!(indexed_rgb(232), (8, 8, 8));
+    assert_eq!(indexed_rgb(255), (238, 238, 238));
+}
@@ -0,0 +1,160 @@
+use bevy::input::keyboard::Key;
+
+/// 按下的修饰键
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    /// xterm 修饰键参数，没有修饰键时为 None
+    fn param(&self) -> Option<u8> {
+        let value = 1 + self.shift as u8 + self.alt as u8 * 2 + self.ctrl as u8 * 4;
+        (value > 1).then_some(value)
+    }
+}
+
+/// 按键发送给终端的字节，application_cursor 为程序请求的光标键模式
+pub fn key_to_bytes(
+    key: &Key,
+    text: Option<&str>,
+    modifiers: Modifiers,
+    application_cursor: bool,
+) -> Option<Vec<u8>> {
+    let cursor = |code: char| {
+        let bytes = match modifiers.param() {
+            Some(param) => format!("\x1b[1;{param}{code}"),
+            None if application_cursor => format!("\x1bO{code}"),
+            None => format!("\x1b[{code}"),
+        };
+        Some(bytes.into_bytes())
+    };
+    let tilde = |code: u8| {
+        let bytes = match modifiers.param() {
+            Some(param) => format!("\x1b[{code};{param}~"),
+            None => format!("\x1b[{code}~"),
+        };
+        Some(bytes.into_bytes())
+    };
+    let function = |code: char| {
+        let bytes = match modifiers.param() {
+            Some(param) => format!("\x1b[1;{param}{code}"),
+            None => format!("\x1bO{code}"),
+        };
+        Some(bytes.into_bytes())
+    };
+    let alt = |bytes: Vec<u8>| {
+        if modifiers.alt {
+            [vec![0x1b], bytes].concat()
+        } else {
+            bytes
+        }
+    };
+    match key {
+        Key::Enter => Some(alt(vec![b'\r'])),
+        Key::Tab if modifiers.shift => Some(b"\x1b[Z".to_vec()),
+        Key::Tab => Some(alt(vec![b'\t'])),
+        Key::Backspace if modifiers.ctrl => Some(alt(vec![0x08])),
+        Key::Backspace => Some(alt(vec![0x7f])),
+        Key::Escape => Some(vec![0x1b]),
+        Key::Space if modifiers.ctrl => Some(vec![0]),
+        Key::ArrowUp => cursor('A'),
+        Key::ArrowDown => cursor('B'),
+        Key::ArrowRight => cursor('C'),
+        Key::ArrowLeft => cursor('D'),
+        Key::Home => cursor('H'),
+        Key::End => cursor('F'),
+        Key::Insert => tilde(2),
+        Key::Delete => tilde(3),
+        Key::PageUp => tilde(5),
+        Key::PageDown => tilde(6),
+        Key::F1 => function('P'),
+        Key::F2 => function('Q'),
+        Key::F3 => function('R'),
+        Key::F4 => function('S'),
+        Key::F5 => tilde(15),
+        Key::F6 => tilde(17),
+        Key::F7 => tilde(18),
+        Key::F8 => tilde(19),
+        Key::F9 => tilde(20),
+        Key::F10 => tilde(21),
+        Key::F11 => tilde(23),
+        Key::F12 => tilde(24),
+        Key::Character(ch) if modifiers.ctrl => {
+            let mut chars = ch.chars();
+            let (Some(ch), None) = (chars.next(), chars.next()) else {
+                return None;
+            };
+            control_byte(ch).map(|byte| alt(vec![byte]))
+        }
+        _ => text
+            .filter(|text| !text.chars().any(char::is_control))
+            .map(|text| alt(text.as_bytes().to_vec())),
+    }
+}
+
+/// Ctrl+字母等对应的控制字符
+fn control_byte(ch: char) -> Option<u8> {
+    match ch.to_ascii_lowercase() {
+        ch @ 'a'..='z' => Some(ch as u8 - b'a' + 1),
+        '@' | '2' => Some(0),
+        '[' | '3' => Some(0x1b),
+        '\\' | '4' => Some(0x1c),
+        ']' | '5' => Some(0x1d),
+        '^' | '6' => Some(0x1e),
+        '_' | '7' | '-' => Some(0x1f),
+        '8' | '?' => Some(0x7f),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_key_to_bytes() {
+    let none = Modifiers::default();
+    let ctrl = Modifiers {
+        ctrl: true,
+        ..Modifiers::default()
+    };
+    let bytes = |key: Key, text: Option<&str>, modifiers: Modifiers, application: bool| {
+        key_to_bytes(&key, text, modifiers, application)
+    };
+    assert_eq!(
+        bytes(Key::Character("a".into()), Some("a"), none, false),
+        Some(b"a".to_vec())
+    );
+    assert_eq!(
+        bytes(Key::Character("c".into()), Some("c"), ctrl, false),
+        Some(vec![3])
+    );
+    assert_eq!(
+        bytes(Key::Enter, Some("\r"), none, false),
+        Some(b"\r".to_vec())
+    );
+    assert_eq!(
+        bytes(Key::ArrowUp, None, none, false),
+        Some(b"\x1b[A".to_vec())
+    );
+    assert_eq!(
+        bytes(Key::ArrowUp, None, none, true),
+        Some(b"\x1bOA".to_vec())
+    );
+    assert_eq!(
+        bytes(Key::ArrowLeft, None, ctrl, true),
+        Some(b"\x1b[1;5D".to_vec())
+    );
+    assert_eq!(
+        bytes(Key::Delete, None, none, false),
+        Some(b"\x1b[3~".to_vec())
+    );
+    let alt = Modifiers {
+        alt: true,
+        ..Modifiers::default()
+    };
+    assert_eq!(
+        bytes(Key::Character("b".into()), Some("b"), alt, false),
+        Some(b"\x1bb".to_vec())
+    );
+    assert_eq!(bytes(Key::Shift, None, none, false), None);
+}
@@ -0,0 +1,6 @@
+pub mod color;
+pub mod input;
+pub mod plugin;
+pub mod session;
+
+pub use plugin::SeekerTerminalPlugin;
@@ -0,0 +1,86 @@
+use crate::session::TerminalSession;
+use bevy::prelude::*;
+use seeker_resource::background::BackgroundWake;
+use seeker_state::SeekerState;
+use std::io;
+use std::path::Path;
+
+#[derive(Component)]
+pub struct SeekerTerminalPlugin;
+
+/// 编辑界面中打开的终端，每个对应一个标签
+#[derive(Resource, Default)]
+pub struct Terminals {
+    pub sessions: Vec<TerminalSession>,
+    pub active: usize,
+}
+
+impl Terminals {
+    /// 打开新的终端并切换过去
+    pub fn open(
+        &mut self,
+        cwd: &Path,
+        rows: u16,
+        cols: u16,
+        wake: &BackgroundWake,
+    ) -> io::Result<()> {
+        let session = TerminalSession::spawn(cwd, rows, cols, wake)?;
+        self.sessions.push(session);
+        self.active = self.sessions.len() - 1;
+        Ok(())
+    }
+
+    pub fn close(&mut self, index: usize) {
+        if index < self.sessions.len() {
+            self.sessions.remove(index);
+        }
+        self.active = self.active.min(self.sessions.len().saturating_sub(1));
+    }
+
+    pub fn active(&self) -> Option<&TerminalSession> {
+        self.sessions.get(self.active)
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut TerminalSession> {
+        self.sessions.get_mut(self.active)
+    }
+}
+
+impl Plugin for SeekerTerminalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Terminals>()
+            .init_resource::<BackgroundWake>()
+            .add_systems(Update, Self::poll.run_if(in_state(SeekerState::Edit)))
+            .add_systems(OnExit(SeekerState::Edit), Self::exit);
+    }
+}
+
+impl SeekerTerminalPlugin {
+    /// 读取终端输出，shell 退出后关闭它的标签
+    fn poll(mut terminals: ResMut<Terminals>) {
+        let mut changed = false;
+        for session in terminals.bypass_change_detection().sessions.iter_mut() {
+            changed |= session.poll();
+        }
+        if !changed {
+            return;
+        }
+        let exited: Vec<usize> = terminals
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, session)| session.is_exited())
+            .map(|(index, _)| index)
+            .collect();
+        for index in exited.into_iter().rev() {
+            terminals.close(index);
+        }
+        terminals.set_changed();
+    }
+
+    /// 离开编辑界面时结束所有终端
+    fn exit(mut terminals: ResMut<Terminals>) {
+        terminals.sessions.clear();
+        terminals.active = 0;
+    }
+}
@@ -0,0 +1,224 @@
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::mpsc::TryRecvError;
+use std::sync::Mutex;
+
+/// 每个终端保留的历史行数
+pub const SCROLLBACK: usize = 5000;
+
+/// 运行在 PTY 中的 shell 和它的屏幕内容
+pub struct TerminalSession {
+    parser: vt100::Parser,
+    /// 资源需要 Sync，这里只在 &mut self 中使用
+    master: Mutex<Box<dyn MasterPty + Send>>,
+    writer: Mutex<Box<dyn Write + Send>>,
+    child: Box<dyn Child + Send + Sync>,
+    /// 后台线程读到的输出
+    receiver: BackgroundReceiver<Vec<u8>>,
+    exited: bool,
+}
+
+impl TerminalSession {
+    /// 在 cwd 中启动用户的 shell
+    pub fn spawn(cwd: &Path, rows: u16, cols: u16, wake: &BackgroundWake) -> io::Result<Self> {
+        let mut command = CommandBuilder::new_default_prog();
+        command.cwd(cwd);
+        command.env("TERM", "xterm-256color");
+        command.env("COLORTERM", "truecolor");
+        Self::spawn_command(command, rows, cols, wake)
+    }
+
+    pub fn spawn_command(
+        command: CommandBuilder,
+        rows: u16,
+        cols: u16,
+        wake: &BackgroundWake,
+    ) -> io::Result<Self> {
+        let pair = native_pty_system()
+            .openpty(pty_size(rows, cols))
+            .map_err(io::Error::other)?;
+        let child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(io::Error::other)?;
+        // 子进程持有 slave，这里关闭自己的一份，子进程退出后读取才会结束
+        drop(pair.slave);
+        let mut reader = pair.master.try_clone_reader().map_err(io::Error::other)?;
+        let writer = pair.master.take_writer().map_err(io::Error::other)?;
+        // 线程结束时 sender 被 drop，唤醒一次让界面发现终端已经退出
+        let (sender, receiver) = wake.channel();
+        std::thread::spawn(move || {
+            let mut buf = [0; 8192];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if !sender.send(buf[..n].to_vec()) {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+        Ok(Self {
+            parser: vt100::Parser::new(rows, cols, SCROLLBACK),
+            master: Mutex::new(pair.master),
+            writer: Mutex::new(writer),
+            child,
+            receiver,
+            exited: false,
+        })
+    }
+
+    /// 处理后台读到的输出，屏幕有变化时返回 true
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        loop {
+            let received = self.receiver.try_recv();
+            match received {
+                Ok(bytes) => {
+                    self.parser.process(&bytes);
+                    changed = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    changed |= !self.exited;
+                    self.exited = true;
+                    break;
+                }
+            }
+        }
+        changed
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let writer = self.writer.get_mut().unwrap();
+        writer.write_all(bytes)?;
+        writer.flush()
+    }
+
+    /// 程序开启了 bracketed paste 时包裹粘贴的内容，避免被当作按键执行
+    pub fn paste(&mut self, text: &str) -> io::Result<()> {
+        let text = text.replace("\r\n", "\r").replace('\n', "\r");
+        if self.parser.screen().bracketed_paste() {
+            self.write(format!("\x1b[200~{text}\x1b[201~").as_bytes())
+        } else {
+            self.write(text.as_bytes())
+        }
+    }
+
+    pub fn resize(&mut self, rows: u16, cols: u16) -> io::Result<()> {
+        let rows = rows.max(1);
+        let cols = cols.max(1);
+        if self.parser.screen().size() == (rows, cols) {
+            return Ok(());
+        }
+        self.parser.screen_mut().set_size(rows, cols);
+        self.master
+            .get_mut()
+            .unwrap()
+            .resize(pty_size(rows, cols))
+            .map_err(io::Error::other)
+    }
+
+    /// 当前显示的内容，受 scroll_to 影响
+    pub fn screen(&self) -> &vt100::Screen {
+        self.parser.screen()
+    }
+
+    /// 历史中的行数
+    pub fn scrollback_len(&mut self) -> usize {
+        let screen = self.parser.screen_mut();
+        let offset = screen.scrollback();
+        screen.set_scrollback(usize::MAX);
+        let len = screen.scrollback();
+        screen.set_scrollback(offset);
+        len
+    }
+
+    /// 向上滚动 offset 行，0 表示显示最新的内容
+    pub fn scroll_to(&mut self, offset: usize) {
+        self.parser.screen_mut().set_scrollback(offset);
+    }
+
+    /// 两个位置之间的文本，位置为（行，列），行从最早的历史开始计算
+    pub fn text_between(&mut self, start: (usize, u16), end: (usize, u16)) -> String {
+        let len = self.scrollback_len();
+        let offset = self.screen().scrollback();
+        let (_, cols) = self.screen().size();
+        let mut text = String::new();
+        for line in start.0..=end.0 {
+            // 历史中的行滚动到屏幕第一行，其余的行在最新的屏幕中
+            let back = len.saturating_sub(line);
+            self.scroll_to(back);
+            let row = (line + back - len) as u16;
+            let from = if line == start.0 { start.1 } else { 0 };
+            let to = if line == end.0 { end.1 } else { cols };
+            let screen = self.screen();
+            if from < to {
+                text.push_str(
+                    &screen
+                        .rows(from, to - from)
+                        .nth(row as usize)
+                        .unwrap_or_default(),
+                );
+            }
+            if line != end.0 && !screen.row_wrapped(row) {
+                text.push('\n');
+            }
+        }
+        self.scroll_to(offset);
+        text
+    }
+
+    pub fn is_exited(&self) -> bool {
+        self.exited
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+fn pty_size(rows: u16, cols: u16) -> PtySize {
+    PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_session_output() {
+    let run = |script: &str| {
+        let mut command = CommandBuilder::new("sh");
+        command.args(["-c", script]);
+        let mut session =
+            TerminalSession::spawn_command(command, 4, 20, &BackgroundWake::default()).unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !session.is_exited() && std::time::Instant::now() < deadline {
+            session.poll();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        session
+    };
+    let mut session = run("printf 'hello\\r\\n\\033[31mred\\033[0m'");
+    let screen = session.screen();
+    assert_eq!(screen.contents(), "hello\nred");
+    assert_eq!(screen.cell(1, 0).unwrap().fgcolor(), vt100::Color::Idx(1));
+    assert_eq!(session.text_between((0, 2), (1, 2)), "llo\nre");
+    session.resize(2, 10).unwrap();
+    assert_eq!(session.screen().size(), (2, 10));
+
+    // 超出屏幕的行进入历史
+    let mut session = run("printf '1\\r\\n2\\r\\n3\\r\\n4\\r\\n5\\r\\n6'");
+    assert_eq!(session.scrollback_len(), 2);
+    assert_eq!(session.text_between((1, 0), (3, 1)), "2\n3\n4");
+    assert_eq!(session.screen().scrollback(), 0);
+}
@@ -1,3 +1,5 @@
+pub mod text_input;
+
 use bevy::color::Color;
 use bevy::ecs::relationship::RelatedSpawnerCommands;
 use bevy::picking::hover::Hovered;
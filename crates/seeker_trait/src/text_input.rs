@@ -0,0 +1,698 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use bevy::ui::{FocusPolicy, UiGlobalTransform};
+use seeker_resource::background::{BackgroundReceiver, BackgroundWake};
+use seeker_resource::clipboard::SeekerClipboard;
+use seeker_resource::SeekerResource;
+use std::ops::Range;
+use std::sync::mpsc::TryRecvError;
+
+/// 光标闪烁的间隔（秒）
+const CARET_BLINK: f32 = 0.53;
+
+/// 检查输入的内容，返回错误信息
+pub type TextInputValidator = fn(&str) -> Result<(), String>;
+
+/// 可复用的输入框，光标和选区都是字符下标。
+/// 实体上的 TextFont 决定文字样式，内容由插件渲染为子节点
+#[derive(Component, Clone, Debug, Default)]
+#[require(Node, FocusPolicy::Block, Interaction, TextFont)]
+pub struct SeekerTextInput {
+    value: String,
+    cursor: usize,
+    anchor: Option<usize>,
+    /// 输入法正在组合的文本，显示在光标处
+    preedit: String,
+    pub placeholder: String,
+    /// 最多输入的字符数
+    pub max_chars: Option<usize>,
+    pub focused: bool,
+    /// 允许输入的字符，例如文件名中不能有 `/`
+    pub filter: Option<fn(char) -> bool>,
+    /// 内容变化时检查，有错误时不能提交
+    pub validator: Option<TextInputValidator>,
+    error: Option<String>,
+    /// 多行时 Enter 换行，Ctrl+Enter 提交；单行时 Enter 提交
+    multiline: bool,
+}
+
+/// 按 Enter 提交，内容没有通过检查时不发送
+#[derive(Message, Clone, Debug)]
+pub struct TextInputSubmit {
+    pub entity: Entity,
+    pub value: String,
+}
+
+/// 按 Esc 取消，输入框同时失去焦点
+#[derive(Message, Clone, Debug)]
+pub struct TextInputCancel {
+    pub entity: Entity,
+}
+
+/// 处理按键和渲染输入框，读取输入框内容的系统在它之后运行
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextInputSystems;
+
+/// 渲染时的一段文本
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextInputSegment {
+    Text(String),
+    Selected(String),
+    Preedit(String),
+    Placeholder(String),
+    Caret,
+}
+
+impl SeekerTextInput {
+    pub fn new(placeholder: impl Into<String>) -> Self {
+        Self {
+            placeholder: placeholder.into(),
+            ..default()
+        }
+    }
+
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+
+    pub fn with_filter(mut self, filter: fn(char) -> bool) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn with_validator(mut self, validator: TextInputValidator) -> Self {
+        self.validator = Some(validator);
+        self.validate();
+        self
+    }
+
+    pub fn with_multiline(mut self) -> Self {
+        self.multiline = true;
+        self
+    }
+
+    pub fn with_focus(mut self) -> Self {
+        self.focused = true;
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// 替换全部内容，光标移到末尾
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value.clear();
+        self.cursor = 0;
+        self.anchor = None;
+        self.insert(&value.into());
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+
+    fn validate(&mut self) {
+        self.error = self
+            .validator
+            .and_then(|validator| validator(&self.value).err());
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    fn byte(&self, index: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(index)
+            .map_or(self.value.len(), |(byte, _)| byte)
+    }
+
+    /// index 所在行的范围，不包含换行符，单行时是全部内容
+    fn line_range(&self, index: usize) -> Range<usize> {
+        let chars: Vec<char> = self.value.chars().collect();
+        let start = chars[..index]
+            .iter()
+            .rposition(|ch| *ch == '\n')
+            .map_or(0, |newline| newline + 1);
+        let end = chars[index..]
+            .iter()
+            .position(|ch| *ch == '\n')
+            .map_or(chars.len(), |newline| index + newline);
+        start..end
+    }
+
+    /// 多行时移动到上一行或者下一行的同一列，没有更多行时不动
+    fn move_line(&mut self, down: bool, select: bool) {
+        let line = self.line_range(self.cursor);
+        let column = self.cursor - line.start;
+        let target = match down {
+            true if line.end < self.len() => self.line_range(line.end + 1),
+            false if line.start > 0 => self.line_range(line.start - 1),
+            _ => return,
+        };
+        self.move_to(target.start + column.min(target.len()), select);
+    }
+
+    pub fn selection(&self) -> Option<Range<usize>> {
+        self.anchor
+            .filter(|anchor| *anchor != self.cursor)
+            .map(|anchor| anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection()
+            .map(|range| &self.value[self.byte(range.start)..self.byte(range.end)])
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.len();
+    }
+
+    /// 移动光标，select 为 true 时扩展选区
+    pub fn move_to(&mut self, index: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = index.min(self.len());
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        let Some(range) = self.selection() else {
+            self.anchor = None;
+            return false;
+        };
+        let bytes = self.byte(range.start)..self.byte(range.end);
+        self.value.replace_range(bytes, "");
+        self.cursor = range.start;
+        self.anchor = None;
+        true
+    }
+
+    /// 在光标处插入，替换选区，去掉不允许的字符（单行时包括换行），超出长度的部分丢弃
+    pub fn insert(&mut self, text: &str) {
+        self.delete_selection();
+        let room = self
+            .max_chars
+            .map_or(usize::MAX, |max| max.saturating_sub(self.len()));
+        let text: String = text
+            .chars()
+            .filter(|ch| is_printable_char(*ch) || self.multiline && *ch == '\n')
+            .filter(|ch| self.filter.is_none_or(|filter| filter(*ch)))
+            .take(room)
+            .collect();
+        let byte = self.byte(self.cursor);
+        self.value.insert_str(byte, &text);
+        self.cursor += text.chars().count();
+        self.validate();
+    }
+
+    pub fn backspace(&mut self) {
+        if !self.delete_selection() && self.cursor > 0 {
+            self.anchor = Some(self.cursor - 1);
+            self.delete_selection();
+        }
+        self.validate();
+    }
+
+    pub fn delete(&mut self) {
+        if !self.delete_selection() && self.cursor < self.len() {
+            self.anchor = Some(self.cursor + 1);
+            self.delete_selection();
+        }
+        self.validate();
+    }
+
+    /// 选区、输入法组合文本、光标和占位文本按显示顺序排列
+    pub fn segments(&self) -> Vec<TextInputSegment> {
+        let range = self.selection().unwrap_or(self.cursor..self.cursor);
+        let slice = |range: Range<usize>| {
+            self.value[self.byte(range.start)..self.byte(range.end)].to_string()
+        };
+        let mut segments = vec![TextInputSegment::Text(slice(0..range.start))];
+        let caret = |segments: &mut Vec<TextInputSegment>| {
+            if self.focused {
+                if !self.preedit.is_empty() {
+                    segments.push(TextInputSegment::Preedit(self.preedit.clone()));
+                }
+                segments.push(TextInputSegment::Caret);
+            }
+        };
+        if self.cursor == range.start {
+            caret(&mut segments);
+        }
+        segments.push(TextInputSegment::Selected(slice(range.clone())));
+        if self.cursor != range.start {
+            caret(&mut segments);
+        }
+        segments.push(TextInputSegment::Text(slice(range.end..self.len())));
+        if self.value.is_empty() && self.preedit.is_empty() {
+            segments.push(TextInputSegment::Placeholder(self.placeholder.clone()));
+        }
+        segments.retain(|segment| {
+            !matches!(
+                segment,
+                TextInputSegment::Text(text) | TextInputSegment::Selected(text) if text.is_empty()
+            )
+        });
+        segments
+    }
+
+    /// 处理一个按键，返回是否提交或取消
+    fn key(
+        &mut self,
+        key: &Key,
+        text: Option<&str>,
+        command: bool,
+        shift: bool,
+        clipboard: &mut SeekerClipboard,
+    ) -> Option<bool> {
+        let line = self.line_range(self.cursor);
+        match key {
+            Key::Enter if self.multiline && !command => self.insert("\n"),
+            Key::Enter => return self.is_valid().then_some(true),
+            Key::Escape => {
+                self.focused = false;
+                return Some(false);
+            }
+            Key::ArrowLeft if !shift && self.selection().is_some() => {
+                let start = self.selection().map_or(self.cursor, |range| range.start);
+                self.move_to(start, false);
+            }
+            Key::ArrowRight if !shift && self.selection().is_some() => {
+                let end = self.selection().map_or(self.cursor, |range| range.end);
+                self.move_to(end, false);
+            }
+            Key::ArrowLeft => self.move_to(self.cursor.saturating_sub(1), shift),
+            Key::ArrowRight => self.move_to(self.cursor + 1, shift),
+            Key::ArrowUp => self.move_line(false, shift),
+            Key::ArrowDown => self.move_line(true, shift),
+            Key::Home => self.move_to(line.start, shift),
+            Key::End => self.move_to(line.end, shift),
+            Key::Backspace => self.backspace(),
+            Key::Delete => self.delete(),
+            Key::Character(ch) if command => match ch.to_lowercase().as_str() {
+                "a" => self.select_all(),
+                "c" => {
+                    if let Some(text) = self.selected_text() {
+                        clipboard.copy(text);
+                    }
+                }
+                "x" => {
+                    if let Some(text) = self.selected_text() {
+                        clipboard.copy(text);
+                        self.delete_selection();
+                        self.validate();
+                    }
+                }
+                "v" => {
+                    if let Some(text) = clipboard.paste() {
+                        self.insert(&text);
+                    }
+                }
+                _ => {}
+            },
+            _ => {
+                if let Some(text) = text
+                    && !command
+                {
+                    self.insert(text);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// 光标片段，闪烁时只修改它的颜色
+#[derive(Component)]
+struct TextInputCaret;
+
+#[derive(Resource)]
+struct CaretBlink {
+    timer: Timer,
+    visible: bool,
+    /// 在下一次切换时唤醒事件循环的后台线程
+    pending: Option<BackgroundReceiver<()>>,
+}
+
+impl Default for CaretBlink {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(CARET_BLINK, TimerMode::Repeating),
+            visible: true,
+            pending: None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SeekerTextInputPlugin;
+
+impl Plugin for SeekerTextInputPlugin {
+    fn build(&self, app: &mut App) {
+        // 所有输入框和编辑器、终端共用剪贴板和复制历史
+        app.init_resource::<CaretBlink>()
+            .init_resource::<BackgroundWake>()
+            .init_resource::<SeekerClipboard>()
+            .add_message::<TextInputSubmit>()
+            .add_message::<TextInputCancel>()
+            .add_systems(
+                Update,
+                (
+                    Self::focus_on_click,
+                    Self::keyboard_input,
+                    Self::ime_input,
+                    Self::update_ime,
+                    Self::render,
+                    Self::blink,
+                )
+                    .chain()
+                    .in_set(TextInputSystems),
+            );
+    }
+}
+
+impl SeekerTextInputPlugin {
+    /// 点击输入框获得焦点，其他输入框失去焦点
+    #[allow(clippy::type_complexity)]
+    fn focus_on_click(
+        clicked: Query<(Entity, &Interaction), (Changed<Interaction>, With<SeekerTextInput>)>,
+        mut inputs: Query<(Entity, &mut SeekerTextInput)>,
+    ) {
+        let Some((focused, _)) = clicked
+            .iter()
+            .find(|(_, interaction)| **interaction == Interaction::Pressed)
+        else {
+            return;
+        };
+        for (entity, mut input) in inputs.iter_mut() {
+            if input.focused != (entity == focused) {
+                input.focused = entity == focused;
+                input.preedit.clear();
+            }
+        }
+    }
+
+    fn keyboard_input(
+        mut reader: MessageReader<KeyboardInput>,
+        keys: Res<ButtonInput<KeyCode>>,
+        mut inputs: Query<(Entity, &mut SeekerTextInput)>,
+        mut clipboard: ResMut<SeekerClipboard>,
+        mut submit: MessageWriter<TextInputSubmit>,
+        mut cancel: MessageWriter<TextInputCancel>,
+    ) {
+        let Some((entity, mut input)) = inputs.iter_mut().find(|(_, input)| input.focused) else {
+            // 没有焦点时的按键不留到获得焦点之后
+            reader.clear();
+            return;
+        };
+        let command = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::SuperLeft,
+            KeyCode::SuperRight,
+        ]);
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        for event in reader.read() {
+            if !event.state.is_pressed() {
+                continue;
+            }
+            let key = &event.logical_key;
+            match input.key(key, event.text.as_deref(), command, shift, &mut clipboard) {
+                Some(true) => {
+                    submit.write(TextInputSubmit {
+                        entity,
+                        value: input.value.clone(),
+                    });
+                }
+                Some(false) => {
+                    cancel.write(TextInputCancel { entity });
+                    break;
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn ime_input(mut reader: MessageReader<Ime>, mut inputs: Query<&mut SeekerTextInput>) {
+        let Some(mut input) = inputs.iter_mut().find(|input| input.focused) else {
+            reader.clear();
+            return;
+        };
+        for ime in reader.read() {
+            match ime {
+                Ime::Preedit { value, .. } => input.preedit = value.clone(),
+                Ime::Commit { value, .. } => {
+                    input.preedit.clear();
+                    input.insert(value);
+                }
+                Ime::Disabled { .. } => input.preedit.clear(),
+                Ime::Enabled { .. } => {}
+            }
+        }
+    }
+
+    /// 获得焦点的输入框所在的窗口打开输入法，候选框显示在输入框下方。
+    /// 所有输入框失去焦点后恢复原来的设置（编辑器自己打开输入法）
+    fn update_ime(
+        inputs: Query<(&SeekerTextInput, &UiGlobalTransform, &ComputedNode)>,
+        mut windows: Query<&mut Window>,
+        mut restore: Local<Option<bool>>,
+    ) {
+        let Some(mut window) = windows.iter_mut().find(|window| window.focused) else {
+            return;
+        };
+        let Some((_, transform, computed)) = inputs.iter().find(|(input, ..)| input.focused) else {
+            if let Some(enabled) = restore.take()
+                && window.ime_enabled != enabled
+            {
+                window.ime_enabled = enabled;
+            }
+            return;
+        };
+        restore.get_or_insert(window.ime_enabled);
+        let scale = computed.inverse_scale_factor();
+        let size = computed.size();
+        let position = (transform.translation + Vec2::new(-size.x, size.y) / 2.) * scale;
+        if !window.ime_enabled {
+            window.ime_enabled = true;
+        }
+        if window.ime_position != position {
+            window.ime_position = position;
+        }
+    }
+
+    fn render(
+        mut commands: Commands,
+        mut inputs: Query<
+            (
+                Entity,
+                &SeekerTextInput,
+                &TextFont,
+                Option<&mut BorderColor>,
+            ),
+            Changed<SeekerTextInput>,
+        >,
+        mut blink: ResMut<CaretBlink>,
+        res: Res<SeekerResource>,
+    ) {
+        for (entity, input, font, border) in inputs.iter_mut() {
+            // 编辑后光标立即显示
+            blink.timer.reset();
+            blink.visible = true;
+            if let Some(mut border) = border {
+                *border = BorderColor::all(match input.error {
+                    Some(_) => res.colors.error,
+                    None => res.colors.button_border,
+                });
+            }
+            commands
+                .entity(entity)
+                .despawn_children()
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            Text::default(),
+                            font.clone(),
+                            TextLayout::new_with_no_wrap(),
+                            TextColor(res.colors.home_font_color),
+                        ))
+                        .with_children(|parent| {
+                            for segment in input.segments() {
+                                match segment {
+                                    TextInputSegment::Text(text) => {
+                                        parent.spawn((TextSpan::new(text), font.clone()));
+                                    }
+                                    TextInputSegment::Selected(text) => {
+                                        parent.spawn((
+                                            TextSpan::new(text),
+                                            font.clone(),
+                                            TextBackgroundColor(res.colors.editor_selection),
+                                        ));
+                                    }
+                                    TextInputSegment::Preedit(text) => {
+                                        parent.spawn((
+                                            TextSpan::new(text),
+                                            font.clone(),
+                                            TextColor(res.colors.info),
+                                            TextBackgroundColor(res.colors.editor_current_line),
+                                        ));
+                                    }
+                                    TextInputSegment::Placeholder(text) => {
+                                        parent.spawn((
+                                            TextSpan::new(text),
+                                            font.clone(),
+                                            TextColor(res.colors.home_font_grey_color),
+                                        ));
+                                    }
+                                    TextInputSegment::Caret => {
+                                        parent.spawn((
+                                            TextInputCaret,
+                                            TextSpan::new("│"),
+                                            font.clone(),
+                                            TextColor(res.colors.editor_cursor),
+                                        ));
+                                    }
+                                }
+                            }
+                        });
+                });
+        }
+    }
+
+    /// 有输入框获得焦点时闪烁光标。低功耗模式下只在下一次切换时唤醒事件循环，
+    /// 编辑后计时重新开始，提前醒来时再等剩下的时间
+    fn blink(
+        inputs: Query<&SeekerTextInput>,
+        mut carets: Query<&mut TextColor, With<TextInputCaret>>,
+        mut blink: ResMut<CaretBlink>,
+        time: Res<Time>,
+        wake: Res<BackgroundWake>,
+        res: Res<SeekerResource>,
+    ) {
+        if !inputs.iter().any(|input| input.focused) {
+            blink.pending = None;
+            return;
+        }
+        if blink.timer.tick(time.delta()).just_finished() {
+            blink.visible = !blink.visible;
+            let color = match blink.visible {
+                true => res.colors.editor_cursor,
+                false => Color::NONE,
+            };
+            for mut caret in carets.iter_mut() {
+                caret.0 = color;
+            }
+        }
+        let waiting = blink
+            .pending
+            .as_ref()
+            .is_some_and(|pending| matches!(pending.try_recv(), Err(TryRecvError::Empty)));
+        if !waiting {
+            let remaining = blink.timer.remaining();
+            blink.pending = Some(wake.spawn(move || std::thread::sleep(remaining)));
+        }
+    }
+}
+
+// this logic is taken from egui-winit:
+// https://github.com/emilk/egui/blob/adfc0bebfc6be14cee2068dee758412a5e0648dc/crates/egui-winit/src/lib.rs#L1014-L1024
+pub fn is_printable_char(chr: char) -> bool {
+    let is_in_private_use_area = ('\u{e000}'..='\u{f8ff}').contains(&chr)
+        || ('\u{f0000}'..='\u{ffffd}').contains(&chr)
+        || ('\u{100000}'..='\u{10fffd}').contains(&chr);
+
+    !is_in_private_use_area && !chr.is_ascii_control()
+}
+
+#[test]
+fn test_text_input() {
+    let mut input = SeekerTextInput::new("Name")
+        .with_max_chars(5)
+        .with_filter(|ch| ch != '/')
+        .with_validator(|value| match value.is_empty() {
+            true => Err("empty".to_string()),
+            false => Ok(()),
+        });
+    assert_eq!(input.error(), Some("empty"));
+    input.focused = true;
+    assert_eq!(
+        input.segments(),
+        vec![
+            TextInputSegment::Caret,
+            TextInputSegment::Placeholder("Name".to_string())
+        ]
+    );
+    input.insert("a/中文\nbcd");
+    assert_eq!(input.value(), "a中文bc");
+    assert!(input.is_valid());
+    input.move_to(1, false);
+    input.move_to(3, true);
+    assert_eq!(input.selected_text(), Some("中文"));
+    assert_eq!(
+        input.segments(),
+        vec![
+            TextInputSegment::Text("a".to_string()),
+            TextInputSegment::Selected("中文".to_string()),
+            TextInputSegment::Caret,
+            TextInputSegment::Text("bc".to_string()),
+        ]
+    );
+    let mut clipboard = SeekerClipboard::memory();
+    let (cut, paste) = (Key::Character("x".into()), Key::Character("v".into()));
+    input.key(&cut, None, true, false, &mut clipboard);
+    assert_eq!(input.value(), "abc");
+    assert_eq!(
+        clipboard.history().next().map(|entry| &*entry.text),
+        Some("中文")
+    );
+    input.key(&Key::End, None, false, false, &mut clipboard);
+    input.key(&paste, None, true, false, &mut clipboard);
+    assert_eq!(input.value(), "abc中文");
+    input.key(&Key::Home, None, false, true, &mut clipboard);
+    input.backspace();
+    assert_eq!(input.value(), "");
+    assert_eq!(
+        input.key(&Key::Enter, None, false, false, &mut clipboard),
+        None
+    );
+    input.insert("bc");
+    assert_eq!(
+        input.key(&Key::Enter, None, false, false, &mut clipboard),
+        Some(true)
+    );
+
+    let mut message = SeekerTextInput::default().with_multiline();
+    message.insert("ab\r\ncde");
+    assert_eq!(message.value(), "ab\ncde");
+    message.key(&Key::ArrowUp, None, false, false, &mut clipboard);
+    assert_eq!(message.cursor, 2);
+    message.key(&Key::Home, None, false, false, &mut clipboard);
+    message.key(&Key::ArrowDown, None, false, true, &mut clipboard);
+    assert_eq!(message.selected_text(), Some("ab\n"));
+    message.key(&Key::End, None, false, false, &mut clipboard);
+    assert_eq!(
+        message.key(&Key::Enter, None, false, false, &mut clipboard),
+        None
+    );
+    assert_eq!(message.value(), "ab\ncde\n");
+    assert_eq!(
+        message.key(&Key::Enter, None, true, false, &mut clipboard),
+        Some(true)
+    );
+}
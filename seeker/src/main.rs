@@ -3,12 +3,21 @@ use bevy::prelude::*;
 use bevy::ui_widgets::UiWidgetsPlugins;
 use bevy::window::{PresentMode, WindowResolution};
 use bevy::winit::{UpdateMode, WinitSettings};
+use seeker_cargo::SeekerCargoPlugin;
 use seeker_config::SEEKER_CONFIG;
+use seeker_edit_plugin::SeekerEditPlugin;
 use seeker_embed_plugin::SeekerEmbedPlugin;
+use seeker_git::SeekerGitPlugin;
 use seeker_home_plugin::SeekerHomePlugin;
+use seeker_lsp::SeekerLspPlugin;
+use seeker_resource::assets::{local_asset_source, LOCAL_ASSET_SOURCE};
+use seeker_resource::background::BackgroundPlugin;
+use seeker_resource::clipboard::SeekerClipboard;
 use seeker_resource::SeekerResource;
 use seeker_scroll::SeekerScrollPlugin;
 use seeker_state::SeekerState;
+use seeker_terminal::SeekerTerminalPlugin;
+use seeker_trait::text_input::SeekerTextInputPlugin;
 use std::time::Duration;
 
 fn main() {
@@ -19,7 +28,10 @@ fn main() {
         })
         .insert_resource(SeekerResource::new())
         .init_resource::<InputFocus>()
+        .init_resource::<SeekerClipboard>()
         .add_plugins(UiWidgetsPlugins)
+        // 必须在 AssetPlugin 之前注册
+        .register_asset_source(LOCAL_ASSET_SOURCE, local_asset_source())
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 present_mode: PresentMode::AutoVsync,
@@ -34,9 +46,16 @@ fn main() {
         }))
         .insert_state(SeekerState::default())
         .add_plugins(SeekerEmbedPlugin)
+        .add_plugins(BackgroundPlugin)
         .add_plugins(SeekerScrollPlugin)
+        .add_plugins(SeekerTextInputPlugin)
         .add_systems(Startup, setup)
         .add_plugins(SeekerHomePlugin)
+        .add_plugins(SeekerEditPlugin)
+        .add_plugins(SeekerLspPlugin)
+        .add_plugins(SeekerCargoPlugin)
+        .add_plugins(SeekerGitPlugin)
+        .add_plugins(SeekerTerminalPlugin)
         .run();
 }
 